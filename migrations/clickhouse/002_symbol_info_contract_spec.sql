@@ -0,0 +1,5 @@
+ALTER TABLE trading.symbol_info
+    ADD COLUMN IF NOT EXISTS asset_class String DEFAULT '' AFTER exchange,
+    ADD COLUMN IF NOT EXISTS tick_size Float32 DEFAULT 0 AFTER asset_class,
+    ADD COLUMN IF NOT EXISTS lot_size Float32 DEFAULT 1 AFTER tick_size,
+    ADD COLUMN IF NOT EXISTS contract_multiplier Float32 DEFAULT 1 AFTER lot_size;
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
+use crate::data_model::meta::MetaRegistry;
 use crate::metrics::BacktestReport;
-use crate::position::{PositionBook, PositionManager};
+use crate::position::{ExecutionReport, PositionBook, PositionManager};
 use crate::risk::RiskManager;
 use crate::strategy::base::Strategy;
 use crate::strategy::context::StrategyContext;
-use crate::strategy::types::StrategyDecision;
+use crate::strategy::types::{StopSignal, StopSignalKind, StrategyDecision};
 
 use crate::metrics::BacktestAnalytics;
 
@@ -99,6 +102,30 @@ impl BacktestOrchestrator {
         }
     }
 
+    /// Подключает торговый календарь: ряды для условий и правила сессий.
+    /// Часовой пояс берется из справочника для инструмента основного таймфрейма.
+    pub fn prepare_session(
+        session_manager: &mut SessionManager,
+        feed_manager: &FeedManager,
+        meta_registry: Option<&MetaRegistry>,
+        context: &mut StrategyContext,
+    ) -> Result<(), BacktestError> {
+        let primary_tf = feed_manager.primary_timeframe();
+        let timezone = primary_tf
+            .and_then(|tf| context.timeframe(tf).ok())
+            .and_then(|data| data.symbol())
+            .and_then(|symbol| meta_registry.and_then(|registry| registry.get(symbol)))
+            .and_then(|meta| meta.timezone())
+            .map(str::to_string);
+        session_manager.prepare(
+            feed_manager.frames(),
+            primary_tf,
+            timezone.as_deref(),
+            context,
+        )?;
+        Ok(())
+    }
+
     pub fn process_decision(
         decision: StrategyDecision,
         position_manager: &mut PositionManager,
@@ -127,14 +154,12 @@ impl BacktestOrchestrator {
                 .filtered_entries
                 .reserve(validated_decision.entries.len());
             for entry in &validated_decision.entries {
+                let side = crate::position::TradeSide::for_entry(&entry.direction);
                 let entry_price = context
                     .timeframe(&entry.timeframe)
                     .ok()
-                    .and_then(|tf| {
-                        tf.price_series_slice(&crate::strategy::types::PriceField::Close)
-                            .and_then(|series| series.get(tf.index()).copied())
-                            .map(|p| p as f64)
-                    })
+                    .and_then(|tf| position_manager.fill_policy().resolve(tf, tf.index(), side))
+                    .map(|point| point.price)
                     .unwrap_or(0.0);
 
                 if entry_price <= 0.0 {
@@ -155,7 +180,25 @@ impl BacktestOrchestrator {
                     continue;
                 }
 
-                buffers.filtered_entries.push(entry.clone());
+                let mut entry = entry.clone();
+                if entry.quantity.is_none() && position_manager.position_sizer().is_some() {
+                    let price = entry
+                        .order
+                        .as_ref()
+                        .map(|request| request.price)
+                        .unwrap_or(entry_price);
+                    let stop_level = risk_manager.initial_stop_level(
+                        context,
+                        &entry.direction,
+                        price,
+                        &entry.timeframe,
+                    );
+                    match position_manager.entry_quantity(context, &entry, price, stop_level) {
+                        Some(quantity) => entry.quantity = Some(quantity),
+                        None => continue,
+                    }
+                }
+                buffers.filtered_entries.push(entry);
             }
             std::mem::swap(
                 &mut validated_decision.entries,
@@ -163,11 +206,14 @@ impl BacktestOrchestrator {
             );
         }
 
+        let intrabar = risk_manager.resolve_take_conflicts(context, &mut validated_decision);
+
         let mut report = position_manager
             .process_decision(context, &validated_decision)
             .map_err(BacktestError::Position)?;
 
         for trade in &mut report.closed_trades {
+            trade.intrabar_resolution = intrabar.get(&trade.position_id).copied();
             let history = risk_manager.take_stop_history(&trade.position_id);
             trade.stop_history = history
                 .into_iter()
@@ -187,11 +233,151 @@ impl BacktestOrchestrator {
         if had_new_entries && has_positions {
             risk_manager.sync_with_positions(context);
             risk_manager.on_new_bar(context);
+            risk_manager.publish_state(context);
         }
 
         Ok(true)
     }
 
+    /// Исполняет отложенные заявки по ценам текущего бара до оценки стратегии
+    pub fn process_pending_orders(
+        position_manager: &mut PositionManager,
+        risk_manager: &mut RiskManager,
+        context: &mut StrategyContext,
+        metrics_collector: &mut BacktestAnalytics,
+        equity_calculator: &mut EquityCalculator,
+    ) -> Result<bool, BacktestError> {
+        if !position_manager.has_pending_orders() {
+            return Ok(false);
+        }
+        let report = position_manager
+            .process_pending_orders(context)
+            .map_err(BacktestError::Position)?;
+        if report.opened_positions.is_empty() && report.closed_trades.is_empty() {
+            return Ok(false);
+        }
+        metrics_collector.absorb_execution_report(&report);
+        equity_calculator.reset();
+        if !report.opened_positions.is_empty() {
+            risk_manager.sync_with_positions(context);
+            risk_manager.on_new_bar(context);
+            risk_manager.publish_state(context);
+        }
+        Ok(true)
+    }
+
+    /// Начисляет плату за перенос позиций и закрывает позиции по маржин-коллу
+    pub fn process_margin(
+        position_manager: &mut PositionManager,
+        risk_manager: &mut RiskManager,
+        context: &mut StrategyContext,
+        metrics_collector: &mut BacktestAnalytics,
+        equity_calculator: &mut EquityCalculator,
+    ) -> Result<bool, BacktestError> {
+        let financed = Self::process_financing(position_manager, context, equity_calculator);
+        if position_manager.open_position_count() == 0 {
+            return Ok(financed);
+        }
+        let margin_calls = position_manager.margin_call_signals(context);
+        let liquidated = Self::process_liquidation(
+            position_manager,
+            risk_manager,
+            context,
+            margin_calls,
+            metrics_collector,
+            equity_calculator,
+        )?;
+        Ok(financed || liquidated)
+    }
+
+    /// Начисляет плату за перенос открытых позиций контекста
+    pub fn process_financing(
+        position_manager: &mut PositionManager,
+        context: &StrategyContext,
+        equity_calculator: &mut EquityCalculator,
+    ) -> bool {
+        if position_manager.margin_model().is_none() || position_manager.open_position_count() == 0
+        {
+            return false;
+        }
+        let financed = position_manager.accrue_financing(context);
+        if financed {
+            equity_calculator.reset();
+        }
+        financed
+    }
+
+    /// Проверяет портфельные ограничения риска и закрывает позиции при срабатывании
+    /// лимита убытка или kill switch
+    pub fn process_risk_guards(
+        position_manager: &mut PositionManager,
+        risk_manager: &mut RiskManager,
+        context: &mut StrategyContext,
+        metrics_collector: &mut BacktestAnalytics,
+        equity_calculator: &mut EquityCalculator,
+    ) -> Result<bool, BacktestError> {
+        let signals = position_manager.risk_guard_signals(context);
+        Self::process_liquidation(
+            position_manager,
+            risk_manager,
+            context,
+            signals,
+            metrics_collector,
+            equity_calculator,
+        )
+    }
+
+    /// Закрывает позиции контекста по сигналам принудительного выхода
+    pub fn process_liquidation(
+        position_manager: &mut PositionManager,
+        risk_manager: &mut RiskManager,
+        context: &mut StrategyContext,
+        signals: Vec<StopSignal>,
+        metrics_collector: &mut BacktestAnalytics,
+        equity_calculator: &mut EquityCalculator,
+    ) -> Result<bool, BacktestError> {
+        if signals.is_empty() {
+            return Ok(false);
+        }
+        let mut decision = StrategyDecision::empty();
+        decision.stop_signals = signals;
+        let mut report = position_manager
+            .process_decision(context, &decision)
+            .map_err(BacktestError::Position)?;
+        Self::attach_stop_history(risk_manager, &mut report);
+        metrics_collector.absorb_execution_report(&report);
+        equity_calculator.reset();
+        Ok(true)
+    }
+
+    /// Закрывает все позиции перед окончанием торгового дня или выходными
+    pub fn process_session_close(
+        session_manager: &SessionManager,
+        position_manager: &mut PositionManager,
+        risk_manager: &mut RiskManager,
+        context: &mut StrategyContext,
+        metrics_collector: &mut BacktestAnalytics,
+        equity_calculator: &mut EquityCalculator,
+    ) -> Result<bool, BacktestError> {
+        if position_manager.open_position_count() == 0 || !session_manager.should_flatten(context) {
+            return Ok(false);
+        }
+        let signals = position_manager.liquidation_signals(
+            context,
+            StopSignalKind::SessionClose,
+            "session_close",
+            &HashMap::new(),
+        );
+        Self::process_liquidation(
+            position_manager,
+            risk_manager,
+            context,
+            signals,
+            metrics_collector,
+            equity_calculator,
+        )
+    }
+
     pub fn process_stop_checks(
         risk_manager: &mut RiskManager,
         position_manager: &mut PositionManager,
@@ -212,19 +398,7 @@ impl BacktestOrchestrator {
             let mut report = position_manager
                 .process_decision(context, &buffers.stop_decision)
                 .map_err(BacktestError::Position)?;
-
-            for trade in &mut report.closed_trades {
-                let history = risk_manager.take_stop_history(&trade.position_id);
-                trade.stop_history = history
-                    .into_iter()
-                    .map(|h| crate::position::StopHistoryEntry {
-                        bar_index: h.bar_index,
-                        stop_level: h.stop_level,
-                        max_high: h.max_high,
-                        min_low: h.min_low,
-                    })
-                    .collect();
-            }
+            Self::attach_stop_history(risk_manager, &mut report);
 
             metrics_collector.absorb_execution_report(&report);
             equity_calculator.reset();
@@ -232,4 +406,19 @@ impl BacktestOrchestrator {
 
         Ok(())
     }
+
+    fn attach_stop_history(risk_manager: &mut RiskManager, report: &mut ExecutionReport) {
+        for trade in &mut report.closed_trades {
+            let history = risk_manager.take_stop_history(&trade.position_id);
+            trade.stop_history = history
+                .into_iter()
+                .map(|h| crate::position::StopHistoryEntry {
+                    bar_index: h.bar_index,
+                    stop_level: h.stop_level,
+                    max_high: h.max_high,
+                    min_low: h.min_low,
+                })
+                .collect();
+        }
+    }
 }
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
+
 use crate::candles::aggregator::TimeFrameAggregator;
+use crate::data_model::meta::MetaRegistry;
 use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::di::ServiceContainer;
-use crate::metrics::{BacktestAnalytics, BacktestReport};
+use crate::metrics::{BacktestAnalytics, BacktestReport, BenchmarkSource};
 use crate::position::{PositionBook, PositionManager};
 use crate::risk::RiskManager;
 use crate::strategy::base::Strategy;
@@ -26,14 +29,14 @@ pub(crate) struct BacktestBuffers {
 }
 
 impl BacktestBuffers {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             filtered_entries: Vec::new(),
             stop_decision: StrategyDecision::empty(),
         }
     }
 
-    fn reset(&mut self) {
+    pub(crate) fn reset(&mut self) {
         self.filtered_entries.clear();
         self.stop_decision.entries.clear();
         self.stop_decision.exits.clear();
@@ -55,8 +58,11 @@ pub struct BacktestEngine {
     strategy: Box<dyn Strategy>,
     context: StrategyContext,
     warmup_bars: usize,
+    /// Бары до этого момента только прогревают индикаторы
+    trading_start: Option<DateTime<Utc>>,
     initial_capital: f64,
     config: BacktestConfig,
+    meta_registry: Option<Arc<MetaRegistry>>,
     buffers: BacktestBuffers,
 }
 
@@ -72,6 +78,15 @@ impl BacktestEngine {
         strategy: Box<dyn Strategy>,
         frames: HashMap<TimeFrame, QuoteFrame>,
         container: Option<Arc<ServiceContainer>>,
+    ) -> Result<Self, BacktestError> {
+        Self::with_shared_frames(strategy, Self::share_frames(frames), container)
+    }
+
+    /// Создает движок над общими котировками без их копирования
+    pub fn with_shared_frames(
+        strategy: Box<dyn Strategy>,
+        frames: HashMap<TimeFrame, Arc<QuoteFrame>>,
+        container: Option<Arc<ServiceContainer>>,
     ) -> Result<Self, BacktestError> {
         if frames.is_empty() {
             return Err(BacktestError::Feed(
@@ -79,7 +94,8 @@ impl BacktestEngine {
             ));
         }
 
-        let (feed_manager, context) = Self::prepare_feed_and_context(strategy.as_ref(), frames)?;
+        let (feed_manager, context) =
+            Self::prepare_shared_feed_and_context(strategy.as_ref(), frames)?;
         let config = BacktestConfig::default();
         let position_manager =
             Self::create_position_manager(strategy.as_ref(), &config, container.as_ref());
@@ -98,28 +114,38 @@ impl BacktestEngine {
             strategy,
             context,
             warmup_bars: 0,
+            trading_start: None,
             initial_capital: config.initial_capital,
             config,
+            meta_registry: None,
             buffers: BacktestBuffers::new(),
         })
     }
 
-    fn prepare_feed_and_context(
+    fn share_frames(frames: HashMap<TimeFrame, QuoteFrame>) -> HashMap<TimeFrame, Arc<QuoteFrame>> {
+        frames
+            .into_iter()
+            .map(|(tf, frame)| (tf, Arc::new(frame)))
+            .collect()
+    }
+
+    pub(crate) fn prepare_feed_and_context(
         strategy: &dyn Strategy,
         frames: HashMap<TimeFrame, QuoteFrame>,
+    ) -> Result<(FeedManager, StrategyContext), BacktestError> {
+        Self::prepare_shared_feed_and_context(strategy, Self::share_frames(frames))
+    }
+
+    pub(crate) fn prepare_shared_feed_and_context(
+        strategy: &dyn Strategy,
+        frames: HashMap<TimeFrame, Arc<QuoteFrame>>,
     ) -> Result<(FeedManager, StrategyContext), BacktestError> {
         let aggregation_service = TimeFrameAggregationService::new();
         let required_timeframes =
             TimeFrameAggregationService::collect_required_timeframes(strategy);
 
-        let mut arc_frames: HashMap<TimeFrame, Arc<QuoteFrame>> =
-            HashMap::with_capacity(frames.len());
-        let mut base_timeframes: Vec<TimeFrame> = Vec::new();
-
-        for (tf, frame) in frames {
-            arc_frames.insert(tf.clone(), Arc::new(frame));
-            base_timeframes.push(tf);
-        }
+        let base_timeframes: Vec<TimeFrame> = frames.keys().cloned().collect();
+        let mut arc_frames = frames;
 
         let aggregated_frames = aggregation_service.aggregate_required_timeframes(
             &arc_frames,
@@ -176,20 +202,42 @@ impl BacktestEngine {
             container
                 .resolve::<PositionManager>()
                 .and_then(|pm_arc| Arc::try_unwrap(pm_arc).ok())
-                .unwrap_or_else(|| {
-                    PositionManager::new(strategy.id().to_string()).with_capital(
-                        config.initial_capital,
-                        config.use_full_capital,
-                        config.reinvest_profits,
-                    )
-                })
+                .unwrap_or_else(|| Self::build_position_manager(strategy, config, None))
         } else {
-            PositionManager::new(strategy.id().to_string()).with_capital(
+            Self::build_position_manager(strategy, config, None)
+        }
+    }
+
+    pub(crate) fn build_position_manager(
+        strategy: &dyn Strategy,
+        config: &BacktestConfig,
+        meta_registry: Option<Arc<MetaRegistry>>,
+    ) -> PositionManager {
+        let mut position_manager = PositionManager::new(strategy.id().to_string())
+            .with_capital(
                 config.initial_capital,
                 config.use_full_capital,
                 config.reinvest_profits,
             )
-        }
+            .with_cost_model(config.costs.clone())
+            .with_fill_policy(config.fill_policy);
+        position_manager.set_meta_registry(meta_registry);
+        position_manager.set_margin_model(config.margin.clone());
+        position_manager.set_risk_guards(config.risk_guards.clone());
+        position_manager.set_calendar(
+            config
+                .session
+                .as_ref()
+                .map(|policy| policy.calendar.clone()),
+        );
+        let sizer = strategy.position_sizer().cloned().or_else(|| {
+            config
+                .position_sizer
+                .as_ref()
+                .map(|sizer| sizer.with_parameters(strategy.parameters()))
+        });
+        position_manager.set_position_sizer(sizer.map(|sizer| sizer.build()));
+        position_manager
     }
 
     fn create_risk_manager(
@@ -206,7 +254,9 @@ impl BacktestEngine {
         }
     }
 
-    fn calculate_session_duration(feed_manager: &FeedManager) -> Option<chrono::Duration> {
+    pub(crate) fn calculate_session_duration(
+        feed_manager: &FeedManager,
+    ) -> Option<chrono::Duration> {
         feed_manager.primary_timeframe().and_then(|tf| match tf {
             TimeFrame::Minutes(m) => Some(chrono::Duration::minutes(*m as i64)),
             TimeFrame::Hours(h) => Some(chrono::Duration::hours(*h as i64)),
@@ -221,16 +271,41 @@ impl BacktestEngine {
 
     pub fn with_config(mut self, config: BacktestConfig) -> Self {
         self.initial_capital = config.initial_capital;
-        self.position_manager = PositionManager::new(self.strategy.id().to_string()).with_capital(
-            config.initial_capital,
-            config.use_full_capital,
-            config.reinvest_profits,
+        self.position_manager = Self::build_position_manager(
+            self.strategy.as_ref(),
+            &config,
+            self.meta_registry.clone(),
         );
-        self.equity_calculator = EquityCalculator::new(config.initial_capital);
+        self.equity_calculator =
+            EquityCalculator::new(config.initial_capital).with_mode(config.equity_mode);
         self.config = config;
         self
     }
 
+    /// Торговля, кривая капитала и число баров считаются с `start`;
+    /// более ранние бары только прогревают индикаторы
+    pub fn with_trading_start(mut self, start: DateTime<Utc>) -> Self {
+        self.trading_start = Some(start);
+        self
+    }
+
+    /// Число баров основного таймфрейма для прогрева индикаторов стратегии
+    pub fn warmup_bars(&self) -> usize {
+        self.warmup_bars
+    }
+
+    /// Подключает справочник инструментов (индивидуальные издержки и параметры инструментов)
+    pub fn with_meta_registry(mut self, registry: Arc<MetaRegistry>) -> Self {
+        self.position_manager
+            .set_meta_registry(Some(Arc::clone(&registry)));
+        self.meta_registry = Some(registry);
+        self
+    }
+
+    pub fn meta_registry(&self) -> Option<&Arc<MetaRegistry>> {
+        self.meta_registry.as_ref()
+    }
+
     pub fn config(&self) -> &BacktestConfig {
         &self.config
     }
@@ -251,6 +326,24 @@ impl BacktestEngine {
         Ok(engine)
     }
 
+    /// Как `from_definition`, но над общими котировками, например при параллельной
+    /// оценке множества стратегий на одних данных
+    pub fn from_definition_shared(
+        definition: StrategyDefinition,
+        parameter_overrides: Option<StrategyParameterMap>,
+        frames: HashMap<TimeFrame, Arc<QuoteFrame>>,
+    ) -> Result<Self, BacktestError> {
+        let mut builder = StrategyBuilder::new(definition);
+        if let Some(overrides) = parameter_overrides {
+            builder = builder.with_parameters(overrides);
+        }
+        let strategy = builder.build().map_err(BacktestError::Strategy)?;
+
+        let mut engine = Self::with_shared_frames(Box::new(strategy), frames, None)?;
+        engine.warmup_bars = engine.compute_warmup_bars();
+        Ok(engine)
+    }
+
     pub fn context(&self) -> &StrategyContext {
         &self.context
     }
@@ -267,7 +360,7 @@ impl BacktestEngine {
         &mut self.position_manager
     }
 
-    fn build_risk_manager(strategy: &dyn Strategy) -> RiskManager {
+    pub(crate) fn build_risk_manager(strategy: &dyn Strategy) -> RiskManager {
         let mut risk_manager = RiskManager::new();
 
         use crate::risk::factory::StopHandlerFactory;
@@ -291,13 +384,17 @@ impl BacktestEngine {
     }
 
     fn compute_warmup_bars(&self) -> usize {
+        Self::warmup_bars_for(self.strategy.as_ref(), &self.feed_manager)
+    }
+
+    pub(crate) fn warmup_bars_for(strategy: &dyn Strategy, feed_manager: &FeedManager) -> usize {
         let mut max_warmup_bars = 0usize;
-        let primary_tf = match self.feed_manager.primary_timeframe() {
+        let primary_tf = match feed_manager.primary_timeframe() {
             Some(tf) => tf,
             None => return constants::MIN_WARMUP_BARS,
         };
 
-        for binding in self.strategy.indicator_bindings() {
+        for binding in strategy.indicator_bindings() {
             let crate::strategy::types::IndicatorSourceSpec::Registry { parameters, .. } =
                 &binding.source
             else {
@@ -353,8 +450,19 @@ impl BacktestEngine {
 
         self.position_manager.reset();
         self.risk_manager.reset();
+        self.risk_manager
+            .set_intrabar_config(self.config.intrabar.clone());
+        self.risk_manager
+            .set_intrabar_frames(self.feed_manager.frames().clone());
         self.context.set_active_positions(PositionBook::default());
         self.metrics_collector.reset();
+        let mut equity_risk = self.config.risk_metrics.clone();
+        if let Some(timeframe) = self.feed_manager.primary_timeframe() {
+            if equity_risk.periods_per_year.is_none() {
+                equity_risk = equity_risk.with_timeframe(timeframe);
+            }
+        }
+        self.metrics_collector.set_equity_risk_config(equity_risk);
         self.equity_calculator.reset();
         self.buffers.reset();
 
@@ -371,6 +479,14 @@ impl BacktestEngine {
         self.context =
             BacktestOrchestrator::initialize_context(&mut self.feed_manager, &timeframe_order);
 
+        self.session_manager.set_policy(self.config.session.clone());
+        BacktestOrchestrator::prepare_session(
+            &mut self.session_manager,
+            &self.feed_manager,
+            self.meta_registry.as_deref(),
+            &mut self.context,
+        )?;
+
         BacktestOrchestrator::populate_indicators_and_conditions(
             &mut self.indicator_engine,
             &self.condition_evaluator,
@@ -383,6 +499,16 @@ impl BacktestEngine {
             .push_equity_point(self.initial_capital);
 
         let mut processed_bars = 0usize;
+        if let Some(start) = self.trading_start {
+            while self
+                .feed_manager
+                .next_timestamp_millis()
+                .is_some_and(|time| time < start.timestamp_millis())
+                && self.feed_manager.step(&mut self.context)
+            {
+                processed_bars += 1;
+            }
+        }
         let mut needs_session_check = true;
 
         while BacktestOrchestrator::process_bar(
@@ -401,6 +527,14 @@ impl BacktestEngine {
                 &mut needs_session_check,
             );
 
+            let orders_filled = BacktestOrchestrator::process_pending_orders(
+                &mut self.position_manager,
+                &mut self.risk_manager,
+                &mut self.context,
+                &mut self.metrics_collector,
+                &mut self.equity_calculator,
+            )?;
+
             let has_open_positions = self.position_manager.open_position_count() > 0;
             self.metrics_collector
                 .increment_bars_in_positions_if_has_positions(has_open_positions);
@@ -413,6 +547,9 @@ impl BacktestEngine {
                 .strategy
                 .evaluate(&self.context)
                 .map_err(BacktestError::Strategy)?;
+            if self.session_manager.entries_blocked(&self.context) {
+                decision.entries.clear();
+            }
 
             let equity_changed = BacktestOrchestrator::process_decision(
                 decision,
@@ -435,13 +572,54 @@ impl BacktestEngine {
                 )?;
             }
 
-            let equity = self.equity_calculator.calculate(
+            let session_closed = BacktestOrchestrator::process_session_close(
+                &self.session_manager,
+                &mut self.position_manager,
+                &mut self.risk_manager,
+                &mut self.context,
+                &mut self.metrics_collector,
+                &mut self.equity_calculator,
+            )?;
+
+            let guard_fired = BacktestOrchestrator::process_risk_guards(
+                &mut self.position_manager,
+                &mut self.risk_manager,
+                &mut self.context,
+                &mut self.metrics_collector,
+                &mut self.equity_calculator,
+            )?;
+
+            let margin_changed = BacktestOrchestrator::process_margin(
+                &mut self.position_manager,
+                &mut self.risk_manager,
+                &mut self.context,
+                &mut self.metrics_collector,
+                &mut self.equity_calculator,
+            )?;
+
+            let point = self.equity_calculator.evaluate(
                 &self.position_manager,
+                &[&self.context],
                 has_open_positions,
-                equity_changed,
+                equity_changed || orders_filled || session_closed || guard_fired || margin_changed,
                 processed_bars,
             );
-            self.metrics_collector.push_equity_point(equity);
+            self.metrics_collector.push_equity_point(point.equity);
+            if let Some(worst) = point.intrabar_worst {
+                self.metrics_collector.push_intrabar_equity_point(worst);
+            }
+            if let Some(timestamp) = self
+                .feed_manager
+                .primary_timeframe()
+                .and_then(|timeframe| self.context.timeframe(timeframe).ok())
+                .and_then(|data| data.current_timestamp())
+            {
+                self.metrics_collector.push_equity_timestamp(timestamp);
+            }
+            if let Some(status) = self.position_manager.margin_status(&self.context) {
+                self.metrics_collector
+                    .push_margin_point(status.initial_margin);
+            }
         }
 
         self.build_report()
@@ -450,6 +628,7 @@ impl BacktestEngine {
     fn update_trailing_stops(&mut self) {
         self.risk_manager.sync_with_positions(&self.context);
         self.risk_manager.on_new_bar(&self.context);
+        self.risk_manager.publish_state(&mut self.context);
     }
 
     fn build_report(&mut self) -> Result<BacktestReport, BacktestError> {
@@ -461,27 +640,51 @@ impl BacktestEngine {
             .unwrap_or(0.0);
 
         let primary_tf = self.feed_manager.primary_timeframe();
-        let primary_frame = primary_tf.and_then(|tf| self.feed_manager.get_frame(tf));
+        let primary_frame = primary_tf
+            .and_then(|tf| self.feed_manager.get_frame(tf))
+            .map(|frame| match self.trading_start {
+                Some(start) => {
+                    let mut traded = frame.as_ref().clone();
+                    traded.truncate_before(start);
+                    Arc::new(traded)
+                }
+                None => Arc::clone(frame),
+            });
 
         let start_date = primary_frame
+            .as_ref()
             .and_then(|frame| frame.first())
             .map(|quote| quote.timestamp());
 
         let end_date = primary_frame
+            .as_ref()
             .and_then(|frame| frame.latest())
             .map(|quote| quote.timestamp());
 
-        let total_bars = primary_frame.map(|frame| frame.len()).unwrap_or(0);
+        let total_bars = primary_frame.as_ref().map(|frame| frame.len()).unwrap_or(0);
 
         let bars_in_positions = self.metrics_collector.bars_in_positions();
 
-        Ok(self.metrics_collector.build_report(
+        let mut report = self.metrics_collector.build_report(
             initial_capital,
             start_date,
             end_date,
             total_bars,
             bars_in_positions,
             None,
-        ))
+        );
+        report.metrics.risk_guards = self.position_manager.risk_guard_stats();
+        let benchmark_frame = match &self.config.benchmark {
+            Some(BenchmarkSource::PrimaryFrame) => primary_frame.clone(),
+            Some(BenchmarkSource::Frame(frame)) => Some(frame.clone()),
+            None => None,
+        };
+        if let Some(frame) = benchmark_frame {
+            report = report.with_benchmark(
+                &frame,
+                self.metrics_collector.equity_risk_config().periods_per_year,
+            );
+        }
+        Ok(report)
     }
 }
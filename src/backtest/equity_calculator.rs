@@ -1,11 +1,34 @@
+use serde::{Deserialize, Serialize};
+
 use crate::position::PositionManager;
+use crate::strategy::context::StrategyContext;
 
 use super::constants;
 
+/// Способ оценки капитала на каждом баре
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquityMode {
+    /// Кэшированный капитал по ценам последних исполнений: быстро, для отбора в GA
+    #[default]
+    Approximate,
+    /// Переоценка открытых позиций по закрытию каждого бара
+    Exact,
+    /// Как `Exact`, плюс худший внутрибарный капитал по минимуму/максимуму бара
+    ExactIntrabar,
+}
+
+/// Капитал на закрытии бара и худший капитал внутри бара
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EquityPoint {
+    pub equity: f64,
+    pub intrabar_worst: Option<f64>,
+}
+
 pub struct EquityCalculator {
     cached_equity: Option<f64>,
     last_equity_bar: usize,
     initial_capital: f64,
+    mode: EquityMode,
 }
 
 impl EquityCalculator {
@@ -14,6 +37,50 @@ impl EquityCalculator {
             cached_equity: None,
             last_equity_bar: 0,
             initial_capital,
+            mode: EquityMode::default(),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: EquityMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> EquityMode {
+        self.mode
+    }
+
+    /// Капитал бара в выбранном режиме. Контексты нужны точным режимам
+    /// для переоценки позиций по ценам текущих баров.
+    pub fn evaluate(
+        &mut self,
+        position_manager: &PositionManager,
+        contexts: &[&StrategyContext],
+        has_open_positions: bool,
+        equity_changed: bool,
+        processed_bars: usize,
+    ) -> EquityPoint {
+        match self.mode {
+            EquityMode::Approximate => EquityPoint {
+                equity: self.calculate(
+                    position_manager,
+                    has_open_positions,
+                    equity_changed,
+                    processed_bars,
+                ),
+                intrabar_worst: None,
+            },
+            EquityMode::Exact | EquityMode::ExactIntrabar => {
+                let equity = self.initial_capital + position_manager.marked_pnl(contexts, false);
+                let intrabar_worst = (self.mode == EquityMode::ExactIntrabar).then(|| {
+                    let worst = self.initial_capital + position_manager.marked_pnl(contexts, true);
+                    worst.min(equity)
+                });
+                EquityPoint {
+                    equity,
+                    intrabar_worst,
+                }
+            }
         }
     }
 
@@ -59,6 +59,20 @@ impl FeedManager {
         self.cached_aligned_timestamps.clear();
     }
 
+    /// Время бара основного таймфрейма, который будет обработан следующим `step`
+    pub fn next_timestamp_millis(&self) -> Option<i64> {
+        let primary_tf = self.primary_timeframe.as_ref()?;
+        let index = self
+            .indices
+            .get(primary_tf)
+            .copied()
+            .unwrap_or(constants::INITIAL_INDEX);
+        self.frames
+            .get(primary_tf)?
+            .get(index)
+            .map(|bar| bar.timestamp_millis())
+    }
+
     pub fn step(&mut self, context: &mut StrategyContext) -> bool {
         let Some(primary_tf) = &self.primary_timeframe else {
             return false;
@@ -9,7 +9,7 @@ use crate::strategy::base::Strategy;
 use crate::strategy::context::StrategyContext;
 use crate::strategy::types::{IndicatorBindingSpec, IndicatorSourceSpec, StrategyError};
 
-use super::{constants, helpers, traits::IndicatorComputer, BacktestError};
+use super::{constants, helpers, traits::IndicatorComputer, BacktestError, FeedManager};
 
 pub struct IndicatorEngine {
     runtime: IndicatorRuntimeEngine,
@@ -103,9 +103,11 @@ impl IndicatorEngine {
             return Ok(());
         }
 
+        // Служебные индикаторы считаются на базовом (наименьшем) таймфрейме,
+        // на котором оцениваются стопы; первый ключ HashMap случаен
         let timeframe = frames
             .keys()
-            .next()
+            .min_by_key(|tf| FeedManager::timeframe_to_minutes(tf).unwrap_or(u32::MAX))
             .cloned()
             .ok_or_else(|| BacktestError::Feed("No frames available".to_string()))?;
 
@@ -6,6 +6,7 @@ mod equity_calculator;
 mod feed_manager;
 mod helpers;
 mod indicator_engine;
+mod portfolio;
 mod session_manager;
 mod timeframe_aggregation_service;
 mod traits;
@@ -16,16 +17,23 @@ mod tests;
 pub use backtest_orchestrator::BacktestOrchestrator;
 pub use condition_evaluator::ConditionEvaluator;
 pub use engine::BacktestEngine;
-pub use equity_calculator::EquityCalculator;
+pub use equity_calculator::{EquityCalculator, EquityMode, EquityPoint};
 pub use feed_manager::FeedManager;
 pub use indicator_engine::IndicatorEngine;
-pub use session_manager::{SessionManager, SessionState};
+pub use portfolio::{
+    PortfolioBacktestEngine, PortfolioBacktestReport, PortfolioConfig, SymbolBacktestReport,
+};
+pub use session_manager::{SessionManager, SessionPolicy, SessionState};
 pub use timeframe_aggregation_service::TimeFrameAggregationService;
 pub use traits::{ConditionEvaluatorTrait, FeedProvider, IndicatorComputer};
 
 use thiserror::Error;
 
-use crate::position::PositionError;
+use crate::data_model::calendar::UnsupportedTimezone;
+use crate::data_model::CostModel;
+use crate::metrics::{BenchmarkSource, EquityRiskConfig};
+use crate::position::{FillPolicy, MarginModel, PositionError, PositionSizerSpec};
+use crate::risk::{IntrabarConfig, RiskGuardConfig};
 use crate::strategy::types::StrategyError;
 
 #[derive(Debug, Error)]
@@ -36,6 +44,8 @@ pub enum BacktestError {
     Position(#[from] PositionError),
     #[error("feed error: {0}")]
     Feed(String),
+    #[error("session calendar error: {0}")]
+    Calendar(#[from] UnsupportedTimezone),
 }
 
 #[derive(Clone, Debug)]
@@ -43,6 +53,26 @@ pub struct BacktestConfig {
     pub initial_capital: f64,
     pub use_full_capital: bool,
     pub reinvest_profits: bool,
+    /// Модель комиссий и проскальзывания для всех инструментов
+    pub costs: CostModel,
+    /// Цена и бар исполнения рыночных входов, выходов по правилам и разворотов
+    pub fill_policy: FillPolicy,
+    /// Разрешение конфликтов стоп/тейк внутри бара
+    pub intrabar: IntrabarConfig,
+    /// Расчет объема входа, если стратегия не задает свой
+    pub position_sizer: Option<PositionSizerSpec>,
+    /// Маржа, плечо и плата за перенос; без модели покупательная способность не проверяется
+    pub margin: Option<MarginModel>,
+    /// Торговый календарь и правила входов/закрытия позиций по сессиям
+    pub session: Option<SessionPolicy>,
+    /// Лимиты убытка, экспозиции и kill switch уровня счета
+    pub risk_guards: Option<RiskGuardConfig>,
+    /// Доверительный уровень VaR/CVaR, порог Omega и аннуализация метрик риска
+    pub risk_metrics: EquityRiskConfig,
+    /// Бенчмарк покупки и удержания; `None` отключает сравнение
+    pub benchmark: Option<BenchmarkSource>,
+    /// Точность кривой капитала: приближенная для GA или переоценка на каждом баре
+    pub equity_mode: EquityMode,
 }
 
 impl Default for BacktestConfig {
@@ -51,6 +81,16 @@ impl Default for BacktestConfig {
             initial_capital: 0.0,
             use_full_capital: false,
             reinvest_profits: false,
+            costs: CostModel::default(),
+            fill_policy: FillPolicy::default(),
+            intrabar: IntrabarConfig::default(),
+            position_sizer: None,
+            margin: None,
+            session: None,
+            risk_guards: None,
+            risk_metrics: EquityRiskConfig::default(),
+            benchmark: Some(BenchmarkSource::PrimaryFrame),
+            equity_mode: EquityMode::default(),
         }
     }
 }
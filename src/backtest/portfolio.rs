@@ -0,0 +1,642 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::data_model::meta::MetaRegistry;
+use crate::data_model::quote_frame::QuoteFrame;
+use crate::data_model::types::{timestamp_from_millis, Symbol, TimeFrame};
+use crate::metrics::{
+    BacktestAnalytics, BacktestMetrics, BacktestReport, BenchmarkSource, EquityRiskConfig,
+    StrategyTrade,
+};
+use crate::position::sizing::round_units;
+use crate::position::{PositionBook, PositionManager, TradeSide};
+use crate::risk::RiskManager;
+use crate::strategy::base::Strategy;
+use crate::strategy::builder::StrategyBuilder;
+use crate::strategy::context::StrategyContext;
+use crate::strategy::types::{StrategyDecision, StrategyDefinition, StrategyParameterMap};
+
+use super::engine::BacktestBuffers;
+use super::{
+    BacktestConfig, BacktestEngine, BacktestError, BacktestOrchestrator, ConditionEvaluator,
+    EquityCalculator, FeedManager, IndicatorEngine, SessionManager,
+};
+
+/// Ограничения портфеля при совместном использовании капитала
+#[derive(Clone, Debug, Default)]
+pub struct PortfolioConfig {
+    /// Максимум одновременно открытых позиций (вместе с отложенными заявками)
+    pub max_open_positions: Option<usize>,
+    /// Доля капитала на один инструмент в процентах; `None` — поровну между инструментами
+    pub allocation_pct: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SymbolBacktestReport {
+    pub symbol: Symbol,
+    pub report: BacktestReport,
+}
+
+#[derive(Clone, Debug)]
+pub struct PortfolioBacktestReport {
+    /// Итог по портфелю: общие сделки и кривая капитала на общей временной шкале
+    pub combined: BacktestReport,
+    pub per_symbol: Vec<SymbolBacktestReport>,
+}
+
+/// Состояние прогона одного инструмента портфеля
+struct PortfolioLane {
+    symbol: Symbol,
+    feed_manager: FeedManager,
+    indicator_engine: IndicatorEngine,
+    condition_evaluator: ConditionEvaluator,
+    session_manager: SessionManager,
+    risk_manager: RiskManager,
+    strategy: Box<dyn Strategy>,
+    context: StrategyContext,
+    buffers: BacktestBuffers,
+    warmup_bars: usize,
+    processed_bars: usize,
+    bars_in_positions: usize,
+    needs_session_check: bool,
+}
+
+/// Прогон одной стратегии по нескольким инструментам с общим капиталом.
+///
+/// Инструменты синхронизируются по времени баров основного таймфрейма:
+/// на каждом шаге обрабатываются только те инструменты, у которых есть бар
+/// с этим временем, поэтому пропуски торгов у разных инструментов допустимы.
+pub struct PortfolioBacktestEngine {
+    lanes: Vec<PortfolioLane>,
+    position_manager: PositionManager,
+    metrics_collector: BacktestAnalytics,
+    equity_calculator: EquityCalculator,
+    config: BacktestConfig,
+    portfolio_config: PortfolioConfig,
+    meta_registry: Option<Arc<MetaRegistry>>,
+}
+
+impl PortfolioBacktestEngine {
+    pub fn from_definition(
+        definition: StrategyDefinition,
+        parameter_overrides: Option<StrategyParameterMap>,
+        frames: HashMap<Symbol, HashMap<TimeFrame, QuoteFrame>>,
+    ) -> Result<Self, BacktestError> {
+        if frames.is_empty() {
+            return Err(BacktestError::Feed(
+                "portfolio symbols collection is empty".to_string(),
+            ));
+        }
+
+        let mut symbols: Vec<(Symbol, HashMap<TimeFrame, QuoteFrame>)> =
+            frames.into_iter().collect();
+        symbols.sort_by_key(|(symbol, _)| symbol.descriptor());
+
+        let mut lanes = Vec::with_capacity(symbols.len());
+        for (symbol, symbol_frames) in symbols {
+            if symbol_frames.is_empty() {
+                return Err(BacktestError::Feed(format!(
+                    "frames collection for {} is empty",
+                    symbol
+                )));
+            }
+            let mut builder = StrategyBuilder::new(definition.clone());
+            if let Some(overrides) = parameter_overrides.clone() {
+                builder = builder.with_parameters(overrides);
+            }
+            let strategy: Box<dyn Strategy> = Box::new(builder.build()?);
+            let (feed_manager, context) =
+                BacktestEngine::prepare_feed_and_context(strategy.as_ref(), symbol_frames)?;
+            let session_duration = BacktestEngine::calculate_session_duration(&feed_manager);
+            let warmup_bars = BacktestEngine::warmup_bars_for(strategy.as_ref(), &feed_manager);
+            lanes.push(PortfolioLane {
+                symbol,
+                feed_manager,
+                indicator_engine: IndicatorEngine::new(),
+                condition_evaluator: ConditionEvaluator::new(),
+                session_manager: SessionManager::new(session_duration),
+                risk_manager: BacktestEngine::build_risk_manager(strategy.as_ref()),
+                strategy,
+                context,
+                buffers: BacktestBuffers::new(),
+                warmup_bars,
+                processed_bars: 0,
+                bars_in_positions: 0,
+                needs_session_check: true,
+            });
+        }
+
+        let config = BacktestConfig::default();
+        let position_manager =
+            BacktestEngine::build_position_manager(lanes[0].strategy.as_ref(), &config, None);
+
+        Ok(Self {
+            lanes,
+            position_manager,
+            metrics_collector: BacktestAnalytics::new(),
+            equity_calculator: EquityCalculator::new(config.initial_capital),
+            config,
+            portfolio_config: PortfolioConfig::default(),
+            meta_registry: None,
+        })
+    }
+
+    pub fn with_config(mut self, config: BacktestConfig) -> Self {
+        self.position_manager = BacktestEngine::build_position_manager(
+            self.lanes[0].strategy.as_ref(),
+            &config,
+            self.meta_registry.clone(),
+        );
+        self.equity_calculator =
+            EquityCalculator::new(config.initial_capital).with_mode(config.equity_mode);
+        self.config = config;
+        self
+    }
+
+    pub fn with_portfolio_config(mut self, portfolio_config: PortfolioConfig) -> Self {
+        self.portfolio_config = portfolio_config;
+        self
+    }
+
+    pub fn with_meta_registry(mut self, registry: Arc<MetaRegistry>) -> Self {
+        self.position_manager
+            .set_meta_registry(Some(Arc::clone(&registry)));
+        self.meta_registry = Some(registry);
+        self
+    }
+
+    pub fn config(&self) -> &BacktestConfig {
+        &self.config
+    }
+
+    pub fn portfolio_config(&self) -> &PortfolioConfig {
+        &self.portfolio_config
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.lanes.iter().map(|lane| &lane.symbol)
+    }
+
+    pub fn position_manager(&self) -> &PositionManager {
+        &self.position_manager
+    }
+
+    pub fn run(&mut self) -> Result<PortfolioBacktestReport, BacktestError> {
+        self.position_manager.reset();
+        self.metrics_collector.reset();
+        self.equity_calculator.reset();
+
+        for lane in &mut self.lanes {
+            lane.reset(&self.config, self.meta_registry.as_deref())?;
+        }
+        let mut equity_risk = self.config.risk_metrics.clone();
+        if let Some(timeframe) = self
+            .lanes
+            .first()
+            .and_then(|lane| lane.feed_manager.primary_timeframe())
+        {
+            if equity_risk.periods_per_year.is_none() {
+                equity_risk = equity_risk.with_timeframe(timeframe);
+            }
+        }
+        self.metrics_collector.set_equity_risk_config(equity_risk);
+
+        self.metrics_collector
+            .push_equity_point(self.config.initial_capital);
+
+        let allocation = self.allocation_share();
+        let mut ticks = 0usize;
+
+        while let Some(timestamp) = self
+            .lanes
+            .iter()
+            .filter_map(|lane| lane.feed_manager.next_timestamp_millis())
+            .min()
+        {
+            ticks += 1;
+            let mut equity_changed = false;
+
+            for lane in &mut self.lanes {
+                if lane.feed_manager.next_timestamp_millis() != Some(timestamp) {
+                    continue;
+                }
+                if !lane.feed_manager.step(&mut lane.context) {
+                    continue;
+                }
+                lane.processed_bars += 1;
+
+                BacktestOrchestrator::check_session(
+                    &lane.session_manager,
+                    &lane.feed_manager,
+                    &mut lane.context,
+                    lane.processed_bars,
+                    &mut lane.needs_session_check,
+                );
+
+                equity_changed |= BacktestOrchestrator::process_pending_orders(
+                    &mut self.position_manager,
+                    &mut lane.risk_manager,
+                    &mut lane.context,
+                    &mut self.metrics_collector,
+                    &mut self.equity_calculator,
+                )?;
+
+                let has_open_positions = !lane.context.active_positions().is_empty();
+                if has_open_positions {
+                    lane.bars_in_positions += 1;
+                    lane.risk_manager.sync_with_positions(&lane.context);
+                    lane.risk_manager.on_new_bar(&lane.context);
+                    lane.risk_manager.publish_state(&mut lane.context);
+                }
+
+                if lane.processed_bars >= lane.warmup_bars {
+                    let mut decision = lane
+                        .strategy
+                        .evaluate(&lane.context)
+                        .map_err(BacktestError::Strategy)?;
+                    if lane.session_manager.entries_blocked(&lane.context) {
+                        decision.entries.clear();
+                    }
+
+                    allocate_entries(
+                        &mut decision,
+                        &self.position_manager,
+                        &lane.risk_manager,
+                        &lane.context,
+                        &self.config,
+                        &self.portfolio_config,
+                        allocation,
+                    );
+
+                    equity_changed |= BacktestOrchestrator::process_decision(
+                        decision,
+                        &mut self.position_manager,
+                        &mut lane.risk_manager,
+                        &mut lane.context,
+                        &mut self.metrics_collector,
+                        &mut self.equity_calculator,
+                        &mut lane.buffers,
+                    )?;
+                }
+
+                if has_open_positions {
+                    BacktestOrchestrator::process_stop_checks(
+                        &mut lane.risk_manager,
+                        &mut self.position_manager,
+                        &mut lane.context,
+                        &mut self.metrics_collector,
+                        &mut self.equity_calculator,
+                        &mut lane.buffers,
+                    )?;
+                }
+
+                equity_changed |= BacktestOrchestrator::process_session_close(
+                    &lane.session_manager,
+                    &mut self.position_manager,
+                    &mut lane.risk_manager,
+                    &mut lane.context,
+                    &mut self.metrics_collector,
+                    &mut self.equity_calculator,
+                )?;
+
+                equity_changed |= BacktestOrchestrator::process_financing(
+                    &mut self.position_manager,
+                    &lane.context,
+                    &mut self.equity_calculator,
+                );
+            }
+
+            equity_changed |= self.process_account_limits()?;
+
+            let has_open_positions = self.position_manager.open_position_count() > 0;
+            self.metrics_collector
+                .increment_bars_in_positions_if_has_positions(has_open_positions);
+            let contexts: Vec<&StrategyContext> =
+                self.lanes.iter().map(|lane| &lane.context).collect();
+            let point = self.equity_calculator.evaluate(
+                &self.position_manager,
+                &contexts,
+                has_open_positions,
+                equity_changed,
+                ticks,
+            );
+            self.metrics_collector.push_equity_point(point.equity);
+            if let Some(worst) = point.intrabar_worst {
+                self.metrics_collector.push_intrabar_equity_point(worst);
+            }
+            if let Some(time) = timestamp_from_millis(timestamp) {
+                self.metrics_collector.push_equity_timestamp(time);
+            }
+            if let Some(status) = self.position_manager.portfolio_margin_status(&contexts) {
+                self.metrics_collector
+                    .push_margin_point(status.initial_margin);
+            }
+        }
+
+        Ok(self.build_report(ticks, allocation))
+    }
+
+    /// Проверяет лимиты убытка и маржу один раз за бар портфеля по капиталу всех
+    /// инструментов и при срабатывании закрывает позиции во всех инструментах
+    fn process_account_limits(&mut self) -> Result<bool, BacktestError> {
+        let contexts: Vec<&StrategyContext> = self.lanes.iter().map(|lane| &lane.context).collect();
+        let breach = self.position_manager.check_risk_guards(&contexts);
+        let margin_call = match breach {
+            Some(_) => None,
+            None => self.position_manager.margin_call(&contexts),
+        };
+        if breach.is_none() && margin_call.is_none() {
+            return Ok(false);
+        }
+
+        let mut changed = false;
+        for lane in &mut self.lanes {
+            let signals = match (breach, &margin_call) {
+                (Some((kind, equity)), _) => {
+                    self.position_manager
+                        .risk_guard_liquidation(&lane.context, kind, equity)
+                }
+                (None, Some(status)) => self
+                    .position_manager
+                    .margin_call_liquidation(&lane.context, status),
+                (None, None) => Vec::new(),
+            };
+            changed |= BacktestOrchestrator::process_liquidation(
+                &mut self.position_manager,
+                &mut lane.risk_manager,
+                &mut lane.context,
+                signals,
+                &mut self.metrics_collector,
+                &mut self.equity_calculator,
+            )?;
+        }
+        Ok(changed)
+    }
+
+    /// Доля капитала одного инструмента
+    fn allocation_share(&self) -> f64 {
+        match self.portfolio_config.allocation_pct {
+            Some(pct) => (pct / 100.0).clamp(0.0, 1.0),
+            None => 1.0 / self.lanes.len() as f64,
+        }
+    }
+
+    fn build_report(&self, ticks: usize, allocation: f64) -> PortfolioBacktestReport {
+        let start_date = self.lanes.iter().filter_map(|lane| lane.first_time()).min();
+        let end_date = self.lanes.iter().filter_map(|lane| lane.last_time()).max();
+
+        let mut combined = self.metrics_collector.build_report(
+            self.config.initial_capital,
+            start_date,
+            end_date,
+            ticks,
+            self.metrics_collector.bars_in_positions(),
+            None,
+        );
+        combined.metrics.risk_guards = self.position_manager.risk_guard_stats();
+        // У портфеля нет единого основного инструмента: общий отчет сравнивается
+        // только с явно заданным бенчмарком, отчеты инструментов — с самим инструментом
+        let periods_per_year = self.metrics_collector.equity_risk_config().periods_per_year;
+        if let Some(BenchmarkSource::Frame(frame)) = &self.config.benchmark {
+            combined = combined.with_benchmark(frame, periods_per_year);
+        }
+
+        let symbol_capital = self.config.initial_capital * allocation;
+        let per_symbol = self
+            .lanes
+            .iter()
+            .map(|lane| {
+                let trades: Vec<StrategyTrade> = self
+                    .metrics_collector
+                    .trades()
+                    .iter()
+                    .filter(|trade| trade.symbol == lane.symbol)
+                    .cloned()
+                    .collect();
+                // Кривая капитала инструмента строится по закрытым сделкам от выделенной доли
+                let mut equity_curve = Vec::with_capacity(trades.len() + 1);
+                equity_curve.push(symbol_capital);
+                let mut equity = symbol_capital;
+                for trade in &trades {
+                    equity += trade.pnl;
+                    equity_curve.push(equity);
+                }
+                let metrics = BacktestMetrics::from_data(
+                    &trades,
+                    &equity_curve,
+                    symbol_capital,
+                    lane.first_time(),
+                    lane.last_time(),
+                    lane.total_bars(),
+                    lane.bars_in_positions,
+                    None,
+                )
+                // Кривая построена по сделкам, поэтому аннуализация оценивается по датам
+                .with_equity_risk(
+                    &equity_curve,
+                    &EquityRiskConfig {
+                        periods_per_year: None,
+                        ..self.config.risk_metrics.clone()
+                    },
+                );
+                // Точки кривой датируются началом выборки и временем выхода сделок
+                let equity_timestamps: Vec<DateTime<Utc>> = std::iter::once(lane.first_time())
+                    .chain(trades.iter().map(|trade| trade.exit_time))
+                    .collect::<Option<_>>()
+                    .unwrap_or_default();
+                let mut report = BacktestReport::new(trades, metrics, equity_curve)
+                    .with_equity_timestamps(equity_timestamps);
+                let benchmark_frame = match &self.config.benchmark {
+                    Some(BenchmarkSource::PrimaryFrame) => lane.primary_frame(),
+                    Some(BenchmarkSource::Frame(frame)) => Some(frame),
+                    None => None,
+                };
+                if let Some(frame) = benchmark_frame {
+                    report = report.with_benchmark(frame, None);
+                }
+                SymbolBacktestReport {
+                    symbol: lane.symbol.clone(),
+                    report,
+                }
+            })
+            .collect();
+
+        PortfolioBacktestReport {
+            combined,
+            per_symbol,
+        }
+    }
+}
+
+impl PortfolioLane {
+    fn reset(
+        &mut self,
+        config: &BacktestConfig,
+        meta_registry: Option<&MetaRegistry>,
+    ) -> Result<(), BacktestError> {
+        self.risk_manager.reset();
+        self.risk_manager
+            .set_intrabar_config(config.intrabar.clone());
+        self.risk_manager
+            .set_intrabar_frames(self.feed_manager.frames().clone());
+        self.buffers.reset();
+        self.processed_bars = 0;
+        self.bars_in_positions = 0;
+        self.needs_session_check = true;
+
+        let mut timeframe_order: Vec<TimeFrame> =
+            self.feed_manager.frames().keys().cloned().collect();
+        timeframe_order.sort_by_key(|tf| FeedManager::timeframe_to_minutes(tf).unwrap_or(0));
+        self.context =
+            BacktestOrchestrator::initialize_context(&mut self.feed_manager, &timeframe_order);
+        self.context.set_active_positions(PositionBook::default());
+
+        self.session_manager.set_policy(config.session.clone());
+        BacktestOrchestrator::prepare_session(
+            &mut self.session_manager,
+            &self.feed_manager,
+            meta_registry,
+            &mut self.context,
+        )?;
+
+        BacktestOrchestrator::populate_indicators_and_conditions(
+            &mut self.indicator_engine,
+            &self.condition_evaluator,
+            self.strategy.as_ref(),
+            self.feed_manager.frames(),
+            &mut self.context,
+        )
+    }
+
+    fn primary_frame(&self) -> Option<&Arc<QuoteFrame>> {
+        self.feed_manager
+            .primary_timeframe()
+            .and_then(|tf| self.feed_manager.get_frame(tf))
+    }
+
+    fn first_time(&self) -> Option<DateTime<Utc>> {
+        self.primary_frame()
+            .and_then(|frame| frame.first())
+            .map(|quote| quote.timestamp())
+    }
+
+    fn last_time(&self) -> Option<DateTime<Utc>> {
+        self.primary_frame()
+            .and_then(|frame| frame.latest())
+            .map(|quote| quote.timestamp())
+    }
+
+    fn total_bars(&self) -> usize {
+        self.primary_frame().map(|frame| frame.len()).unwrap_or(0)
+    }
+}
+
+/// Отбрасывает входы сверх лимита позиций и ограничивает объем долей капитала инструмента
+pub(super) fn allocate_entries(
+    decision: &mut StrategyDecision,
+    position_manager: &PositionManager,
+    risk_manager: &RiskManager,
+    context: &StrategyContext,
+    config: &BacktestConfig,
+    portfolio_config: &PortfolioConfig,
+    allocation: f64,
+) {
+    if decision.entries.is_empty() {
+        return;
+    }
+    let snapshot = position_manager.portfolio_snapshot();
+    let capital = if config.reinvest_profits {
+        config.initial_capital + snapshot.total_equity
+    } else {
+        config.initial_capital
+    };
+    let mut open = position_manager.open_position_count() + position_manager.pending_orders().len();
+    let mut exposure = snapshot.exposure;
+    // Объем, уже выделенный инструменту входами этого решения
+    let mut allocated = 0.0;
+
+    decision.entries.retain_mut(|entry| {
+        // Разворот закрывает встречную позицию, число позиций не растет
+        let reverses = context.active_positions().values().any(|position| {
+            position.timeframe == entry.timeframe && position.direction != entry.direction
+        });
+        if !reverses
+            && portfolio_config
+                .max_open_positions
+                .is_some_and(|max| open >= max)
+        {
+            return false;
+        }
+
+        if capital > 0.0 {
+            let price = entry
+                .order
+                .as_ref()
+                .map(|request| request.price)
+                .or_else(|| {
+                    let data = context.timeframe(&entry.timeframe).ok()?;
+                    let side = TradeSide::for_entry(&entry.direction);
+                    position_manager
+                        .fill_policy()
+                        .resolve(data, data.index(), side)
+                        .map(|point| point.price)
+                })
+                .unwrap_or(0.0);
+            let symbol = context
+                .timeframe(&entry.timeframe)
+                .ok()
+                .and_then(|data| data.symbol())
+                .filter(|_| price > 0.0);
+            if let Some(symbol) = symbol {
+                let multiplier = position_manager.contract_multiplier(symbol);
+                let unit_value = price * multiplier;
+                // Встречную позицию закроет разворот, в доле инструмента она не учитывается
+                let held: f64 = context
+                    .active_positions()
+                    .values()
+                    .filter(|position| &position.symbol == symbol)
+                    .filter(|position| {
+                        position.timeframe != entry.timeframe
+                            || position.direction == entry.direction
+                    })
+                    .map(|position| {
+                        position.quantity.abs()
+                            * position.last_price.unwrap_or(position.entry_price)
+                            * multiplier
+                    })
+                    .sum();
+                let budget = (capital * allocation - held - allocated).min(capital - exposure);
+                if budget <= 0.0 {
+                    return false;
+                }
+                let Some(max_quantity) =
+                    round_units(position_manager.instrument(symbol), budget / unit_value)
+                else {
+                    return false;
+                };
+                let stop_level = risk_manager.initial_stop_level(
+                    context,
+                    &entry.direction,
+                    price,
+                    &entry.timeframe,
+                );
+                let Some(quantity) =
+                    position_manager.entry_quantity(context, entry, price, stop_level)
+                else {
+                    return false;
+                };
+                let quantity = quantity.min(max_quantity);
+                entry.quantity = Some(quantity);
+                exposure += quantity * unit_value;
+                allocated += quantity * unit_value;
+            }
+        }
+
+        if !reverses {
+            open += 1;
+        }
+        true
+    });
+}
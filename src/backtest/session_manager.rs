@@ -1,22 +1,199 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data_model::calendar::{
+    TimeWindow, TradingCalendar, UnsupportedTimezone, DAY_OF_WEEK_SERIES, FIRST_BAR_OF_DAY_SERIES,
+    MINUTES_TO_CLOSE_SERIES,
+};
 use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::strategy::context::StrategyContext;
 
+use super::FeedManager;
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct SessionState {
     pub is_session_start: bool,
     pub is_session_end: bool,
 }
 
+/// Правила исполнения по торговому календарю
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SessionPolicy {
+    pub calendar: TradingCalendar,
+    /// Окна местного времени, в которые новые входы запрещены
+    pub no_entry_windows: Vec<TimeWindow>,
+    /// Запрет входов за указанное число минут до закрытия торгового дня
+    pub no_entry_before_close: Option<u32>,
+    /// Закрытие всех позиций за указанное число минут до закрытия торгового дня
+    pub flat_before_close: Option<u32>,
+    /// Закрытие всех позиций на последнем баре перед выходными и праздниками
+    pub flat_before_weekend: bool,
+}
+
+impl SessionPolicy {
+    pub fn new(calendar: TradingCalendar) -> Self {
+        Self {
+            calendar,
+            no_entry_windows: Vec::new(),
+            no_entry_before_close: None,
+            flat_before_close: None,
+            flat_before_weekend: false,
+        }
+    }
+
+    pub fn with_no_entry_window(mut self, window: TimeWindow) -> Self {
+        self.no_entry_windows.push(window);
+        self
+    }
+
+    pub fn with_no_entry_before_close(mut self, minutes: u32) -> Self {
+        self.no_entry_before_close = Some(minutes);
+        self
+    }
+
+    pub fn with_flat_before_close(mut self, minutes: u32) -> Self {
+        self.flat_before_close = Some(minutes);
+        self
+    }
+
+    pub fn with_flat_before_weekend(mut self, enabled: bool) -> Self {
+        self.flat_before_weekend = enabled;
+        self
+    }
+}
+
+/// Флаги правил сессии по барам основного таймфрейма
+#[derive(Clone, Debug, Default)]
+struct SessionSchedule {
+    timeframe: Option<TimeFrame>,
+    entries_blocked: Vec<bool>,
+    flatten: Vec<bool>,
+}
+
 pub struct SessionManager {
     cached_duration: Option<chrono::Duration>,
+    policy: Option<SessionPolicy>,
+    schedule: SessionSchedule,
 }
 
 impl SessionManager {
     pub fn new(cached_duration: Option<chrono::Duration>) -> Self {
-        Self { cached_duration }
+        Self {
+            cached_duration,
+            policy: None,
+            schedule: SessionSchedule::default(),
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: Option<SessionPolicy>) {
+        self.policy = policy;
+        self.schedule = SessionSchedule::default();
+    }
+
+    pub fn policy(&self) -> Option<&SessionPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Добавляет ряды календаря во все таймфреймы контекста и рассчитывает правила
+    /// входов и закрытия позиций по основному таймфрейму.
+    /// `timezone` инструмента заменяет часовой пояс календаря.
+    pub fn prepare(
+        &mut self,
+        frames: &HashMap<TimeFrame, Arc<QuoteFrame>>,
+        primary_tf: Option<&TimeFrame>,
+        timezone: Option<&str>,
+        context: &mut StrategyContext,
+    ) -> Result<(), UnsupportedTimezone> {
+        self.schedule = SessionSchedule::default();
+        let Some(policy) = self.policy.as_ref() else {
+            return Ok(());
+        };
+        let mut calendar = policy.calendar.clone();
+        if let Some(timezone) = timezone {
+            calendar.timezone = timezone.parse()?;
+        }
+        for (timeframe, frame) in frames {
+            let Ok(data) = context.timeframe_mut(timeframe) else {
+                continue;
+            };
+            let timestamps: Vec<i64> = frame
+                .iter()
+                .map(|quote| quote.timestamp().timestamp_millis())
+                .collect();
+            let series = calendar.bar_series(&timestamps, bar_duration(timeframe));
+            if Some(timeframe) == primary_tf {
+                self.schedule = Self::build_schedule(policy, &calendar, &timestamps, &series);
+                self.schedule.timeframe = Some(timeframe.clone());
+            }
+            data.insert_custom_series(MINUTES_TO_CLOSE_SERIES, series.minutes_to_close);
+            data.insert_custom_series(FIRST_BAR_OF_DAY_SERIES, series.first_bar_of_day);
+            data.insert_custom_series(DAY_OF_WEEK_SERIES, series.day_of_week);
+        }
+        Ok(())
+    }
+
+    fn build_schedule(
+        policy: &SessionPolicy,
+        calendar: &TradingCalendar,
+        timestamps: &[i64],
+        series: &crate::data_model::calendar::CalendarSeries,
+    ) -> SessionSchedule {
+        let len = timestamps.len();
+        let mut schedule = SessionSchedule {
+            timeframe: None,
+            entries_blocked: vec![false; len],
+            flatten: vec![false; len],
+        };
+        let within =
+            |limit: Option<u32>, minutes: f32| limit.is_some_and(|limit| minutes <= limit as f32);
+        for (index, &millis) in timestamps.iter().enumerate() {
+            let Some(open_time) = DateTime::<Utc>::from_timestamp_millis(millis) else {
+                continue;
+            };
+            let local = calendar.local(open_time);
+            let minutes = series.minutes_to_close[index];
+            // Данные следующего бара показывают, что торговый день закончился
+            let last_bar_of_day = index + 1 < len && series.is_first_bar_of_day(index + 1);
+            let break_ahead = last_bar_of_day
+                && calendar.next_trading_day(local.date()) != local.date().succ_opt();
+            let flatten = within(policy.flat_before_close, minutes)
+                || (policy.flat_before_close.is_some() && last_bar_of_day)
+                || (policy.flat_before_weekend && break_ahead);
+            let blocked = flatten
+                || within(policy.no_entry_before_close, minutes)
+                || policy
+                    .no_entry_windows
+                    .iter()
+                    .any(|window| window.contains(local.time()));
+            schedule.flatten[index] = flatten;
+            schedule.entries_blocked[index] = blocked;
+        }
+        schedule
+    }
+
+    fn schedule_flag(&self, flags: &[bool], context: &StrategyContext) -> bool {
+        let Some(timeframe) = self.schedule.timeframe.as_ref() else {
+            return false;
+        };
+        context
+            .timeframe(timeframe)
+            .ok()
+            .and_then(|data| flags.get(data.index()).copied())
+            .unwrap_or(false)
+    }
+
+    /// Запрещены ли новые входы на текущем баре
+    pub fn entries_blocked(&self, context: &StrategyContext) -> bool {
+        self.schedule_flag(&self.schedule.entries_blocked, context)
+    }
+
+    /// Нужно ли закрыть все позиции на текущем баре
+    pub fn should_flatten(&self, context: &StrategyContext) -> bool {
+        self.schedule_flag(&self.schedule.flatten, context)
     }
 
     pub fn session_state(
@@ -76,3 +253,9 @@ impl SessionManager {
         }
     }
 }
+
+fn bar_duration(timeframe: &TimeFrame) -> Duration {
+    FeedManager::timeframe_to_minutes(timeframe)
+        .map(|minutes| Duration::minutes(minutes as i64))
+        .unwrap_or_else(Duration::zero)
+}
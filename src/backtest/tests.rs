@@ -3,16 +3,22 @@ mod tests {
     use super::*;
     use crate::backtest::{
         BacktestConfig, BacktestEngine, BacktestError, ConditionEvaluator, EquityCalculator,
-        FeedManager, IndicatorEngine, SessionManager, SessionState, TimeFrameAggregationService,
+        EquityMode, FeedManager, IndicatorEngine, PortfolioBacktestEngine, PortfolioConfig,
+        SessionManager, SessionPolicy, SessionState, TimeFrameAggregationService,
     };
+    use crate::data_model::calendar::{TimeWindow, TradingCalendar, DAY_OF_WEEK_SERIES};
     use crate::data_model::quote::Quote;
     use crate::data_model::quote_frame::QuoteFrame;
     use crate::data_model::types::{Symbol, TimeFrame};
+    use crate::metrics::{BacktestReport, BreakdownConfig};
+    use crate::position::{MarginModel, PositionSizerSpec};
+    use crate::risk::guards::{LossLimit, RiskGuardConfig};
     use crate::strategy::base::Strategy;
     use crate::strategy::presets::default_strategy_definitions;
     use crate::strategy::types::{
         StrategyCategory, StrategyDecision, StrategyDefinition, StrategyError, StrategyId,
-        StrategyMetadata, StrategyParameterMap, StrategyRuleSpec,
+        StrategyMetadata, StrategyParamValue, StrategyParameterMap, StrategyRuleSpec,
+        TakeHandlerSpec,
     };
     use chrono::{DateTime, Duration, Utc};
     use std::collections::HashMap;
@@ -205,6 +211,7 @@ mod tests {
             initial_capital: 5000.0,
             use_full_capital: true,
             reinvest_profits: true,
+            ..BacktestConfig::default()
         };
         let engine_with_config = engine.with_config(custom_config.clone());
         assert_eq!(engine_with_config.config().initial_capital, 5000.0);
@@ -336,6 +343,46 @@ mod tests {
         assert!(report.metrics.total_bars > 0);
     }
 
+    #[test]
+    fn test_shared_frames_match_owned_frames() {
+        let definitions = default_strategy_definitions();
+        if definitions.is_empty() {
+            return;
+        }
+
+        let definition = definitions.first().unwrap();
+        let timeframe = TimeFrame::Minutes(60);
+        let frame = create_test_quote_frame(
+            Symbol::from_descriptor("TEST"),
+            timeframe.clone(),
+            500,
+            Utc::now() - Duration::days(30),
+            Duration::hours(1),
+        );
+        let shared = Arc::new(frame.clone());
+
+        let owned = BacktestEngine::from_definition(
+            definition.clone(),
+            None,
+            HashMap::from([(timeframe.clone(), frame)]),
+        )
+        .unwrap()
+        .run()
+        .unwrap();
+        let from_shared = BacktestEngine::from_definition_shared(
+            definition.clone(),
+            None,
+            HashMap::from([(timeframe, Arc::clone(&shared))]),
+        )
+        .unwrap()
+        .run()
+        .unwrap();
+
+        assert_eq!(owned.equity_curve, from_shared.equity_curve);
+        assert_eq!(owned.trades.len(), from_shared.trades.len());
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
     #[test]
     fn test_backtest_engine_warmup_bars() {
         let strategy = create_simple_test_strategy();
@@ -394,6 +441,7 @@ mod tests {
             initial_capital: 5000.0,
             use_full_capital: true,
             reinvest_profits: true,
+            ..BacktestConfig::default()
         };
         let cloned = config.clone();
         assert_eq!(cloned.initial_capital, config.initial_capital);
@@ -702,4 +750,656 @@ mod tests {
         let aggregated = result.unwrap();
         assert!(aggregated.is_empty());
     }
+
+    fn create_wave_quote_frame(
+        symbol: Symbol,
+        timeframe: TimeFrame,
+        start_time: DateTime<Utc>,
+        count: usize,
+        skip_every: usize,
+    ) -> QuoteFrame {
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for i in 0..count {
+            if skip_every > 0 && i % skip_every == skip_every - 1 {
+                continue;
+            }
+            let close = 100.0 + 10.0 * (i as f32 / 12.0).sin();
+            let quote = create_test_quote(
+                symbol.clone(),
+                timeframe.clone(),
+                start_time + Duration::hours(i as i64),
+                close,
+            );
+            frame.push(quote).unwrap();
+        }
+        frame
+    }
+
+    #[test]
+    fn test_portfolio_engine_shares_capital_across_symbols() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let first = Symbol::from_descriptor("AAA");
+        let second = Symbol::from_descriptor("BBB");
+
+        let mut frames = HashMap::new();
+        frames.insert(
+            first.clone(),
+            HashMap::from([(
+                timeframe.clone(),
+                create_wave_quote_frame(first.clone(), timeframe.clone(), start_time, 400, 0),
+            )]),
+        );
+        // У второго инструмента пропущен каждый пятый бар
+        frames.insert(
+            second.clone(),
+            HashMap::from([(
+                timeframe.clone(),
+                create_wave_quote_frame(second.clone(), timeframe.clone(), start_time, 400, 5),
+            )]),
+        );
+
+        let mut engine = PortfolioBacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .with_config(BacktestConfig {
+                initial_capital: 10_000.0,
+                use_full_capital: true,
+                ..BacktestConfig::default()
+            })
+            .with_portfolio_config(PortfolioConfig {
+                max_open_positions: Some(1),
+                allocation_pct: None,
+            });
+        let report = engine.run().unwrap();
+
+        assert_eq!(report.combined.metrics.total_bars, 400);
+        assert_eq!(report.combined.equity_curve.len(), 401);
+        assert_eq!(report.combined.equity_timestamps.len(), 401);
+        assert_eq!(report.per_symbol.len(), 2);
+        assert_eq!(report.per_symbol[0].symbol, first);
+        assert_eq!(report.per_symbol[1].report.metrics.total_bars, 320);
+
+        let trades = &report.combined.trades;
+        assert!(!trades.is_empty());
+        let per_symbol_trades: usize = report
+            .per_symbol
+            .iter()
+            .map(|entry| entry.report.trades.len())
+            .sum();
+        assert_eq!(per_symbol_trades, trades.len());
+
+        for trade in trades {
+            // Не больше половины капитала на инструмент
+            assert!(trade.quantity * trade.entry_price <= 5_000.0 + 1e-6);
+        }
+        // Лимит в одну позицию: сделки не пересекаются по времени
+        let mut intervals: Vec<_> = trades
+            .iter()
+            .map(|trade| (trade.entry_time.unwrap(), trade.exit_time.unwrap()))
+            .collect();
+        intervals.sort();
+        for pair in intervals.windows(2) {
+            assert!(pair[0].1 <= pair[1].0);
+        }
+    }
+
+    #[test]
+    fn test_portfolio_kill_switch_checks_combined_equity_once_per_tick() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames: HashMap<_, _> = ["AAA", "BBB"]
+            .into_iter()
+            .map(|descriptor| {
+                let symbol = Symbol::from_descriptor(descriptor);
+                let frame =
+                    create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0);
+                (symbol, HashMap::from([(timeframe.clone(), frame)]))
+            })
+            .collect();
+
+        let report = PortfolioBacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .with_config(BacktestConfig {
+                initial_capital: 10_000.0,
+                use_full_capital: true,
+                risk_guards: Some(RiskGuardConfig::default().with_kill_switch(0.01)),
+                ..BacktestConfig::default()
+            })
+            .run()
+            .unwrap();
+
+        let stats = report.combined.metrics.risk_guards;
+        assert_eq!(stats.kill_switch, 1);
+        let killed: Vec<_> = report
+            .combined
+            .trades
+            .iter()
+            .filter(|trade| trade.exit_reason.as_deref() == Some("risk_guard:kill_switch"))
+            .collect();
+        assert!(!killed.is_empty());
+        let kill_time = killed[0].exit_time;
+        assert!(killed.iter().all(|trade| trade.exit_time == kill_time));
+        assert!(report
+            .combined
+            .trades
+            .iter()
+            .all(|trade| trade.entry_time < kill_time));
+    }
+
+    #[test]
+    fn test_portfolio_allocation_counts_held_symbol_notional() {
+        use crate::backtest::portfolio::allocate_entries;
+        use crate::condition::types::SignalStrength;
+        use crate::position::PositionManager;
+        use crate::risk::RiskManager;
+        use crate::strategy::context::{StrategyContext, TimeframeData};
+        use crate::strategy::types::{PositionDirection, StrategySignal, StrategySignalType};
+
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(1);
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for i in 0..2 {
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start_time + Duration::hours(i),
+                100.0,
+                100.0,
+                100.0,
+                100.0,
+                1000.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let mut context = StrategyContext::new();
+        context.insert_timeframe(
+            timeframe.clone(),
+            TimeframeData::with_quote_frame(&frame, 0),
+        );
+        let config = BacktestConfig {
+            initial_capital: 10_000.0,
+            ..BacktestConfig::default()
+        };
+        let portfolio_config = PortfolioConfig {
+            max_open_positions: None,
+            allocation_pct: Some(50.0),
+        };
+        let mut position_manager =
+            PositionManager::new("portfolio").with_capital(10_000.0, false, false);
+        let risk_manager = RiskManager::new();
+        let entry = |rule_id: &str, quantity: f64| {
+            let mut decision = StrategyDecision::empty();
+            decision.entries.push(StrategySignal {
+                rule_id: rule_id.to_string(),
+                signal_type: StrategySignalType::Entry,
+                direction: PositionDirection::Long,
+                timeframe: timeframe.clone(),
+                strength: SignalStrength::Strong,
+                quantity: Some(quantity),
+                entry_rule_id: Some(rule_id.to_string()),
+                tags: Vec::new(),
+                position_group: Some(rule_id.to_string()),
+                target_entry_ids: Vec::new(),
+                order: None,
+                close_fraction: None,
+            });
+            decision
+        };
+
+        position_manager
+            .process_decision(&mut context, &entry("first", 30.0))
+            .unwrap();
+        // Из 5 000 на инструмент 3 000 уже заняты позицией
+        let mut decision = entry("second", 50.0);
+        allocate_entries(
+            &mut decision,
+            &position_manager,
+            &risk_manager,
+            &context,
+            &config,
+            &portfolio_config,
+            0.5,
+        );
+        assert_eq!(decision.entries[0].quantity, Some(20.0));
+
+        position_manager
+            .process_decision(&mut context, &decision)
+            .unwrap();
+        let mut decision = entry("third", 10.0);
+        allocate_entries(
+            &mut decision,
+            &position_manager,
+            &risk_manager,
+            &context,
+            &config,
+            &portfolio_config,
+            0.5,
+        );
+        assert!(decision.entries.is_empty());
+    }
+
+    #[test]
+    fn test_position_sizer_from_config_and_definition() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames = || {
+            HashMap::from([(
+                timeframe.clone(),
+                create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+            )])
+        };
+        let config = BacktestConfig {
+            initial_capital: 10_000.0,
+            position_sizer: Some(PositionSizerSpec::FixedQuantity { quantity: 3.0 }),
+            ..BacktestConfig::default()
+        };
+
+        let report = BacktestEngine::from_definition(definition.clone(), None, frames())
+            .unwrap()
+            .with_config(config.clone())
+            .run()
+            .unwrap();
+        assert!(!report.trades.is_empty());
+        assert!(report.trades.iter().all(|trade| trade.quantity == 3.0));
+
+        // Сайзер стратегии важнее настроек бэктеста, его параметры переопределяются
+        let definition =
+            definition.with_position_sizer(PositionSizerSpec::RiskPerTrade { risk_pct: 0.5 });
+        assert!(definition
+            .parameters
+            .iter()
+            .any(|param| param.name == "sizer_risk_pct"));
+        let first_quantity = |overrides: Option<StrategyParameterMap>| {
+            BacktestEngine::from_definition(definition.clone(), overrides, frames())
+                .unwrap()
+                .with_config(config.clone())
+                .run()
+                .unwrap()
+                .trades
+                .first()
+                .map(|trade| trade.quantity)
+                .expect("trade")
+        };
+        let base = first_quantity(None);
+        let doubled = first_quantity(Some(HashMap::from([(
+            "sizer_risk_pct".to_string(),
+            StrategyParamValue::Number(1.0),
+        )])));
+        assert_ne!(base, 3.0);
+        assert!(doubled > base);
+    }
+
+    #[test]
+    fn test_margin_model_limits_buying_power_and_reports_usage() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames = HashMap::from([(
+            timeframe.clone(),
+            create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+        )]);
+        let config = BacktestConfig {
+            initial_capital: 1_000.0,
+            position_sizer: Some(PositionSizerSpec::FixedQuantity { quantity: 1_000.0 }),
+            margin: Some(
+                MarginModel::default()
+                    .with_leverage(2.0)
+                    .with_financing(5.0, 5.0),
+            ),
+            ..BacktestConfig::default()
+        };
+
+        let report = BacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .with_config(config)
+            .run()
+            .unwrap();
+        assert!(!report.trades.is_empty());
+        assert_eq!(report.margin_usage.len(), report.equity_curve.len());
+        assert_eq!(report.equity_timestamps.len(), report.equity_curve.len());
+        let breakdown = report.periodic_breakdown(&BreakdownConfig::default());
+        let traded: usize = breakdown.day_of_week.iter().map(|day| day.trades).sum();
+        assert_eq!(traded, report.trades.len());
+        let attribution = report.attribution();
+        let by_side: usize = attribution
+            .by_direction
+            .iter()
+            .map(|slice| slice.metrics.total_trades)
+            .sum();
+        assert_eq!(by_side, report.trades.len());
+        let by_exit: f64 = attribution
+            .by_exit_reason
+            .iter()
+            .map(|slice| slice.metrics.total_profit)
+            .sum();
+        let closed: f64 = report.trades.iter().map(|trade| trade.pnl).sum();
+        assert!((by_exit - closed).abs() < 1e-6);
+        for trade in &report.trades {
+            assert!(trade.quantity < 1_000.0);
+            assert!(trade.quantity * trade.entry_price <= 2_000.0 + 1e-6);
+        }
+        assert!(report.margin_usage.iter().all(|margin| *margin <= 1_100.0));
+        assert!(report.metrics.total_financing >= 0.0);
+    }
+
+    #[test]
+    fn test_session_policy_flattens_before_weekend_and_exposes_calendar_series() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let calendar = TradingCalendar::moex_equity();
+        let start_time = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let wave =
+            create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 24 * 42, 0);
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for quote in wave.iter() {
+            if calendar.session_at(quote.timestamp()).is_some()
+                && calendar.local(quote.timestamp()).time()
+                    < chrono::NaiveTime::from_hms_opt(18, 0, 0).unwrap()
+            {
+                frame.push(quote.clone()).unwrap();
+            }
+        }
+        let run = |session: Option<SessionPolicy>| {
+            let config = BacktestConfig {
+                session,
+                ..BacktestConfig::default()
+            };
+            let mut engine = BacktestEngine::from_definition(
+                definition.clone(),
+                None,
+                HashMap::from([(timeframe.clone(), frame.clone())]),
+            )
+            .unwrap()
+            .with_config(config);
+            let report = engine.run().unwrap();
+            let has_series = engine
+                .context()
+                .timeframe(&timeframe)
+                .unwrap()
+                .custom_series_slice(DAY_OF_WEEK_SERIES)
+                .is_some();
+            (report, has_series)
+        };
+        let crosses_weekend = |trade: &crate::metrics::StrategyTrade| {
+            let week = |time: Option<DateTime<Utc>>| {
+                chrono::Datelike::iso_week(&calendar.local(time.unwrap()).date())
+            };
+            week(trade.entry_time) != week(trade.exit_time)
+        };
+
+        let (baseline, baseline_series) = run(None);
+        assert!(!baseline_series);
+        assert!(baseline.trades.iter().any(|trade| crosses_weekend(trade)));
+
+        let policy = SessionPolicy::new(calendar.clone())
+            .with_flat_before_weekend(true)
+            .with_no_entry_window(TimeWindow::hm((10, 0), (12, 0)));
+        let (report, has_series) = run(Some(policy));
+        assert!(has_series);
+        assert!(!report.trades.is_empty());
+        assert!(!report.trades.iter().any(|trade| crosses_weekend(trade)));
+        let blocked = TimeWindow::hm((10, 0), (12, 0));
+        assert!(report
+            .trades
+            .iter()
+            .all(|trade| !blocked.contains(calendar.local(trade.entry_time.unwrap()).time())));
+    }
+
+    #[test]
+    fn test_risk_guards_block_entries_and_kill_switch_flattens() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames = HashMap::from([(
+            timeframe.clone(),
+            create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+        )]);
+        let run = |guards: RiskGuardConfig| {
+            let config = BacktestConfig {
+                initial_capital: 10_000.0,
+                position_sizer: Some(PositionSizerSpec::FixedQuantity { quantity: 10.0 }),
+                risk_guards: Some(guards),
+                ..BacktestConfig::default()
+            };
+            BacktestEngine::from_definition(definition.clone(), None, frames.clone())
+                .unwrap()
+                .with_config(config)
+                .run()
+                .unwrap()
+        };
+
+        let baseline = run(RiskGuardConfig::default());
+        assert!(baseline.trades.len() > 1);
+        assert_eq!(baseline.metrics.risk_guards.triggers(), 0);
+
+        let capped = run(RiskGuardConfig::default().with_exposure(Some(0.1), None));
+        assert!(capped.trades.is_empty());
+        assert!(capped.metrics.risk_guards.gross_exposure > 0);
+        assert_eq!(
+            capped.metrics.risk_guards.blocked_entries,
+            capped.metrics.risk_guards.gross_exposure
+        );
+
+        let killed = run(RiskGuardConfig::default()
+            .with_daily_loss(LossLimit::Absolute(1e9))
+            .with_kill_switch(0.01));
+        assert_eq!(killed.metrics.risk_guards.kill_switch, 1);
+        let last = killed.trades.last().expect("trade closed by kill switch");
+        assert_eq!(last.exit_reason.as_deref(), Some("risk_guard:kill_switch"));
+        assert!(killed.trades.len() < baseline.trades.len());
+    }
+
+    #[test]
+    fn test_time_exit_stop_closes_positions_after_bars_in_trade() {
+        let mut definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let stop = &mut definition.stop_handlers[0];
+        stop.name = "TimeExitStop".to_string();
+        stop.handler_name = "TimeExitStop".to_string();
+        stop.parameters =
+            StrategyParameterMap::from([("bars".to_string(), StrategyParamValue::Number(3.0))]);
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames = HashMap::from([(
+            timeframe.clone(),
+            create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+        )]);
+
+        let report = BacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .run()
+            .unwrap();
+        let timed: Vec<_> = report
+            .trades
+            .iter()
+            .filter(|trade| trade.exit_reason.as_deref() == Some("stop:TimeExit"))
+            .collect();
+        assert!(!timed.is_empty());
+        for trade in timed {
+            let held = trade.exit_time.unwrap() - trade.entry_time.unwrap();
+            assert_eq!(held, Duration::hours(3));
+        }
+    }
+
+    #[test]
+    fn test_benchmark_tracks_buy_and_hold_over_report_dates() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frame = create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0);
+        let first_close = frame.first().unwrap().close() as f64;
+        let last_close = frame.latest().unwrap().close() as f64;
+        let frames = HashMap::from([(timeframe.clone(), frame)]);
+        let config = BacktestConfig {
+            initial_capital: 10_000.0,
+            ..BacktestConfig::default()
+        };
+
+        let report = BacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .with_config(config)
+            .run()
+            .unwrap();
+        assert_eq!(report.benchmark_curve.len(), report.equity_curve.len());
+        assert_eq!(report.benchmark_curve[0], 10_000.0);
+        let benchmark = report.metrics.benchmark.as_ref().expect("benchmark");
+        assert!(
+            (benchmark.benchmark_return_pct - (last_close / first_close - 1.0) * 100.0).abs()
+                < 1e-6
+        );
+        assert!(
+            (benchmark.excess_return_pct
+                - (benchmark.strategy_return_pct - benchmark.benchmark_return_pct))
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_exact_equity_marks_positions_every_bar() {
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let run = |equity_mode: EquityMode| {
+            let definition = default_strategy_definitions()
+                .into_iter()
+                .nth(1)
+                .expect("preset strategy");
+            let frames = HashMap::from([(
+                timeframe.clone(),
+                create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+            )]);
+            BacktestEngine::from_definition(definition, None, frames)
+                .unwrap()
+                .with_config(BacktestConfig {
+                    initial_capital: 10_000.0,
+                    equity_mode,
+                    ..BacktestConfig::default()
+                })
+                .run()
+                .unwrap()
+        };
+
+        let approximate = run(EquityMode::Approximate);
+        let exact = run(EquityMode::ExactIntrabar);
+        assert!(approximate.intrabar_equity_curve.is_empty());
+        assert_eq!(exact.trades.len(), approximate.trades.len());
+        assert_eq!(exact.intrabar_equity_curve.len(), exact.equity_curve.len());
+        assert!(exact
+            .intrabar_equity_curve
+            .iter()
+            .zip(&exact.equity_curve)
+            .all(|(worst, close)| worst <= close));
+        // Между исполнениями приближенный капитал не меняется, точный следует за ценой
+        let changes = |report: &BacktestReport| {
+            report
+                .equity_curve
+                .windows(2)
+                .filter(|pair| (pair[1] - pair[0]).abs() > 1e-9)
+                .count()
+        };
+        assert!(changes(&exact) > changes(&approximate));
+
+        let drawdown = exact.drawdown_curve();
+        assert_eq!(drawdown.drawdown_pct.len(), exact.equity_curve.len());
+        assert!(drawdown.max_drawdown_pct >= exact.metrics.drawdown_percent.unwrap_or(0.0) - 1e-9);
+        assert!(drawdown
+            .underwater
+            .iter()
+            .all(|period| period.start_time.is_some()));
+    }
+
+    #[test]
+    fn test_take_profit_r_targets_multiple_of_initial_risk() {
+        let mut definition = default_strategy_definitions()
+            .into_iter()
+            .nth(1)
+            .expect("preset strategy");
+        let mut stop = definition.stop_handlers[0].clone();
+        stop.name = "StopLossPct".to_string();
+        stop.handler_name = "StopLossPct".to_string();
+        stop.parameters = StrategyParameterMap::from([(
+            "percentage".to_string(),
+            StrategyParamValue::Number(2.0),
+        )]);
+        definition.stop_handlers = vec![stop.clone()];
+        definition.take_handlers = vec![TakeHandlerSpec {
+            id: "take_r".to_string(),
+            name: "TakeProfitR".to_string(),
+            handler_name: "TakeProfitR".to_string(),
+            timeframe: stop.timeframe.clone(),
+            price_field: stop.price_field.clone(),
+            parameters: StrategyParameterMap::from([(
+                "r_multiple".to_string(),
+                StrategyParamValue::Number(1.0),
+            )]),
+            direction: stop.direction.clone(),
+            priority: stop.priority,
+            tags: vec!["take_profit".to_string()],
+            target_entry_ids: stop.target_entry_ids.clone(),
+        }];
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::Minutes(60);
+        let start_time = Utc::now() - Duration::days(30);
+        let frames = HashMap::from([(
+            timeframe.clone(),
+            create_wave_quote_frame(symbol.clone(), timeframe.clone(), start_time, 400, 0),
+        )]);
+
+        let report = BacktestEngine::from_definition(definition, None, frames)
+            .unwrap()
+            .run()
+            .unwrap();
+        let takes: Vec<_> = report
+            .trades
+            .iter()
+            .filter(|trade| trade.exit_reason.as_deref() == Some("stop:TakeProfit"))
+            .collect();
+        assert!(!takes.is_empty());
+        for trade in takes {
+            let target = trade.entry_price * 1.02;
+            assert!((trade.exit_price - target).abs() / target < 1e-3);
+            assert!(trade.excursion.bars_in_trade > 0);
+            assert!(trade.excursion.mfe_r.unwrap() >= 1.0 - 1e-3);
+            assert!(trade.excursion.efficiency > 0.0);
+        }
+        let excursions = &report.metrics.excursions;
+        assert!(excursions.mfe_pct.is_some());
+        assert!(excursions.average_bars_in_trade.unwrap() > 0.0);
+    }
 }
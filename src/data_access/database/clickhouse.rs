@@ -8,6 +8,8 @@ use crate::data_access::traits::ToSql;
 use crate::data_access::{
     ConnectionInfo, ConnectionStatus, DataAccessError, DataSource, Database, Result, Transaction,
 };
+use crate::data_model::meta::{AssetClass, InstrumentMeta, MetaRegistry};
+use crate::data_model::types::Symbol;
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, Utc};
 use clickhouse::{Client, Row};
@@ -104,6 +106,25 @@ pub struct SymbolInfo {
     pub code: String,
     pub name: String,
     pub exchange: String,
+    pub asset_class: String,
+    pub tick_size: f32,
+    pub lot_size: f32,
+    pub contract_multiplier: f32,
+}
+
+impl SymbolInfo {
+    /// Спецификация инструмента для MetaRegistry
+    pub fn to_instrument_meta(&self) -> InstrumentMeta {
+        InstrumentMeta::builder(Symbol::with_exchange(
+            self.code.as_str(),
+            self.exchange.as_str(),
+        ))
+        .asset_class(AssetClass::from_name(&self.asset_class))
+        .tick_size(self.tick_size)
+        .lot_size(self.lot_size)
+        .contract_multiplier(self.contract_multiplier)
+        .build()
+    }
 }
 
 /// Индикатор
@@ -523,7 +544,8 @@ impl ClickHouseConnector {
         let client = self.get_client()?;
 
         let query = format!(
-            "SELECT code, name, exchange FROM {}.symbol_info 
+            "SELECT code, name, exchange, asset_class, tick_size, lot_size, contract_multiplier
+             FROM {}.symbol_info 
              WHERE code = ? AND exchange = ? 
              ORDER BY updated_at DESC LIMIT 1",
             self.database
@@ -545,7 +567,8 @@ impl ClickHouseConnector {
         let client = self.get_client()?;
 
         let query = format!(
-            "SELECT code, name, exchange FROM {}.symbol_info 
+            "SELECT code, name, exchange, asset_class, tick_size, lot_size, contract_multiplier
+             FROM {}.symbol_info 
              WHERE exchange = ? 
              ORDER BY code",
             self.database
@@ -559,6 +582,20 @@ impl ClickHouseConnector {
             .map_err(|e| DataAccessError::Query(e.to_string()))
     }
 
+    /// Заполнение реестра спецификаций инструментами биржи
+    pub async fn load_meta_registry(
+        &self,
+        exchange: &str,
+        registry: &mut MetaRegistry,
+    ) -> Result<usize> {
+        let symbols = self.get_exchange_symbols(exchange).await?;
+        let count = symbols.len();
+        for info in &symbols {
+            registry.insert(info.to_instrument_meta());
+        }
+        Ok(count)
+    }
+
     /// Вставка/обновление информации о символе
     pub async fn upsert_symbol_info(&self, info: &SymbolInfo) -> Result<u64> {
         let client = self.get_client()?;
@@ -0,0 +1,508 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Ключи пользовательских рядов календаря, доступные условиям стратегии
+pub const MINUTES_TO_CLOSE_SERIES: &str = "minutes_to_close";
+pub const FIRST_BAR_OF_DAY_SERIES: &str = "is_first_bar_of_day";
+pub const DAY_OF_WEEK_SERIES: &str = "day_of_week";
+
+/// Интервал местного времени внутри дня, конец не включается
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    /// Окно по часам и минутам; некорректное время заменяется полуночью
+    pub fn hm(start: (u32, u32), end: (u32, u32)) -> Self {
+        let time = |(hour, minute): (u32, u32)| {
+            NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or(NaiveTime::MIN)
+        };
+        Self::new(time(start), time(end))
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            // Окно через полночь
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradingSession {
+    pub name: String,
+    pub hours: TimeWindow,
+}
+
+impl TradingSession {
+    pub fn new(name: impl Into<String>, hours: TimeWindow) -> Self {
+        Self {
+            name: name.into(),
+            hours,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("unsupported timezone: {0}")]
+pub struct UnsupportedTimezone(pub String);
+
+/// Часовой пояс календаря: фиксированное смещение (`UTC+3`, `+03:00`, `MSK`)
+/// или зона IANA (`America/New_York`) с переходом на летнее время
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum CalendarZone {
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+impl CalendarZone {
+    pub fn utc() -> Self {
+        Self::Named(Tz::UTC)
+    }
+
+    pub fn local(&self, timestamp: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Fixed(offset) => timestamp.with_timezone(offset).naive_local(),
+            Self::Named(zone) => timestamp.with_timezone(zone).naive_local(),
+        }
+    }
+
+    /// Момент UTC для местного времени; при переводе часов назад — более ранний,
+    /// для пропущенного при переводе вперед времени — `None`
+    pub fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let utc = match self {
+            Self::Fixed(offset) => offset
+                .from_local_datetime(&local)
+                .earliest()?
+                .with_timezone(&Utc),
+            Self::Named(zone) => zone
+                .from_local_datetime(&local)
+                .earliest()?
+                .with_timezone(&Utc),
+        };
+        Some(utc)
+    }
+}
+
+impl FromStr for CalendarZone {
+    type Err = UnsupportedTimezone;
+
+    fn from_str(timezone: &str) -> Result<Self, Self::Err> {
+        if let Some(offset) = parse_utc_offset(timezone) {
+            return Ok(Self::Fixed(offset));
+        }
+        timezone
+            .trim()
+            .parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| UnsupportedTimezone(timezone.to_string()))
+    }
+}
+
+impl TryFrom<String> for CalendarZone {
+    type Error = UnsupportedTimezone;
+
+    fn try_from(timezone: String) -> Result<Self, Self::Error> {
+        timezone.parse()
+    }
+}
+
+impl From<CalendarZone> for String {
+    fn from(zone: CalendarZone) -> Self {
+        zone.to_string()
+    }
+}
+
+impl fmt::Display for CalendarZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(offset) => write!(f, "{}", offset),
+            Self::Named(zone) => f.write_str(zone.name()),
+        }
+    }
+}
+
+/// Торговый календарь биржи: сессии и перерывы в местном времени, рабочие дни и праздники.
+///
+/// Ночные сессии учитывает только `session_end`, остальные расчеты считают,
+/// что сессии не переходят через полночь. Неизвестный часовой пояс — ошибка
+/// разбора, а не UTC.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TradingCalendar {
+    pub name: String,
+    pub timezone: CalendarZone,
+    pub sessions: Vec<TradingSession>,
+    /// Перерывы внутри сессий (клиринг), во время которых торгов нет
+    #[serde(default)]
+    pub breaks: Vec<TimeWindow>,
+    #[serde(default = "default_trading_days")]
+    pub trading_days: Vec<Weekday>,
+    #[serde(default)]
+    pub holidays: Vec<NaiveDate>,
+    /// Рабочие выходные дни, перенесенные биржей
+    #[serde(default)]
+    pub extra_trading_days: Vec<NaiveDate>,
+}
+
+fn default_trading_days() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ]
+}
+
+impl TradingCalendar {
+    pub fn new(name: impl Into<String>, timezone: CalendarZone) -> Self {
+        Self {
+            name: name.into(),
+            timezone,
+            sessions: Vec::new(),
+            breaks: Vec::new(),
+            trading_days: default_trading_days(),
+            holidays: Vec::new(),
+            extra_trading_days: Vec::new(),
+        }
+    }
+
+    /// Фондовый рынок Московской биржи: основная и вечерняя сессии
+    pub fn moex_equity() -> Self {
+        Self::new("MOEX", CalendarZone::Named(Tz::Europe__Moscow))
+            .with_session(TradingSession::new(
+                "main",
+                TimeWindow::hm((10, 0), (18, 50)),
+            ))
+            .with_session(TradingSession::new(
+                "evening",
+                TimeWindow::hm((19, 5), (23, 50)),
+            ))
+    }
+
+    /// Срочный рынок Московской биржи с дневным и вечерним клирингом
+    pub fn moex_forts() -> Self {
+        Self::new("FORTS", CalendarZone::Named(Tz::Europe__Moscow))
+            .with_session(TradingSession::new("day", TimeWindow::hm((9, 0), (18, 45))))
+            .with_session(TradingSession::new(
+                "evening",
+                TimeWindow::hm((19, 5), (23, 50)),
+            ))
+            .with_break(TimeWindow::hm((14, 0), (14, 5)))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn with_session(mut self, session: TradingSession) -> Self {
+        self.sessions.push(session);
+        self.sessions.sort_by_key(|session| session.hours.start);
+        self
+    }
+
+    pub fn with_break(mut self, window: TimeWindow) -> Self {
+        self.breaks.push(window);
+        self
+    }
+
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub fn with_timezone(mut self, timezone: CalendarZone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn local(&self, timestamp: DateTime<Utc>) -> NaiveDateTime {
+        self.timezone.local(timestamp)
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        if self.extra_trading_days.contains(&date) {
+            return true;
+        }
+        self.trading_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Следующий торговый день после `date`; поиск ограничен месяцем
+    pub fn next_trading_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (1..=31)
+            .filter_map(|days| date.checked_add_signed(Duration::days(days)))
+            .find(|candidate| self.is_trading_day(*candidate))
+    }
+
+    /// Сессия, в которой идут торги в момент `timestamp`
+    pub fn session_at(&self, timestamp: DateTime<Utc>) -> Option<&TradingSession> {
+        let local = self.local(timestamp);
+        if !self.is_trading_day(local.date()) {
+            return None;
+        }
+        let time = local.time();
+        if self.breaks.iter().any(|window| window.contains(time)) {
+            return None;
+        }
+        self.sessions
+            .iter()
+            .find(|session| session.hours.contains(time))
+    }
+
+    /// Конец сессии, идущей в момент `timestamp`, а вне сессий — ближайшей следующей.
+    /// Ночная сессия (начало позже конца) заканчивается на следующий день.
+    pub fn session_end(&self, timestamp: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = self.local(timestamp);
+        let time = local.time();
+        let date = local.date();
+        let overnight = |session: &&TradingSession| session.hours.start > session.hours.end;
+        if let Some(session) = self
+            .sessions
+            .iter()
+            .filter(overnight)
+            .find(|session| time < session.hours.end)
+        {
+            if date
+                .pred_opt()
+                .is_some_and(|start| self.is_trading_day(start))
+            {
+                return self.timezone.to_utc(date.and_time(session.hours.end));
+            }
+        }
+        let mut day = date;
+        for offset in 0..=31 {
+            if self.is_trading_day(day) {
+                let next = self.sessions.iter().find(|session| {
+                    offset > 0 || session.hours.contains(time) || session.hours.start >= time
+                });
+                if let Some(session) = next {
+                    let end_day = if overnight(&session) {
+                        day.succ_opt()?
+                    } else {
+                        day
+                    };
+                    return self.timezone.to_utc(end_day.and_time(session.hours.end));
+                }
+            }
+            day = day.succ_opt()?;
+        }
+        None
+    }
+
+    /// Минуты до окончания последней сессии торгового дня; `None` вне торгового дня
+    pub fn minutes_to_close(&self, timestamp: DateTime<Utc>) -> Option<f64> {
+        let local = self.local(timestamp);
+        if !self.is_trading_day(local.date()) {
+            return None;
+        }
+        let close = self
+            .sessions
+            .iter()
+            .map(|session| session.hours.end)
+            .max()?;
+        let remaining = local.date().and_time(close) - local;
+        Some(remaining.num_seconds() as f64 / 60.0)
+    }
+
+    /// Ряды календаря для баров с временем открытия `timestamps` (мс) и длительностью `bar`.
+    /// Минуты до закрытия считаются на момент закрытия бара.
+    pub fn bar_series(&self, timestamps: &[i64], bar: Duration) -> CalendarSeries {
+        let mut series = CalendarSeries::with_capacity(timestamps.len());
+        let mut previous_date: Option<NaiveDate> = None;
+        for &millis in timestamps {
+            let Some(open_time) = DateTime::<Utc>::from_timestamp_millis(millis) else {
+                series.push(f32::NAN, false, f32::NAN);
+                continue;
+            };
+            let date = self.local(open_time).date();
+            let minutes = self
+                .minutes_to_close(open_time + bar)
+                .map(|value| value as f32)
+                .unwrap_or(f32::NAN);
+            let first_bar = previous_date != Some(date);
+            previous_date = Some(date);
+            series.push(
+                minutes,
+                first_bar,
+                date.weekday().number_from_monday() as f32,
+            );
+        }
+        series
+    }
+}
+
+/// Значения календаря по барам одного таймфрейма
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CalendarSeries {
+    pub minutes_to_close: Vec<f32>,
+    pub first_bar_of_day: Vec<f32>,
+    /// День недели местного времени: 1 — понедельник, 7 — воскресенье
+    pub day_of_week: Vec<f32>,
+}
+
+impl CalendarSeries {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            minutes_to_close: Vec::with_capacity(capacity),
+            first_bar_of_day: Vec::with_capacity(capacity),
+            day_of_week: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, minutes_to_close: f32, first_bar: bool, day_of_week: f32) {
+        self.minutes_to_close.push(minutes_to_close);
+        self.first_bar_of_day
+            .push(if first_bar { 1.0 } else { 0.0 });
+        self.day_of_week.push(day_of_week);
+    }
+
+    pub fn is_first_bar_of_day(&self, index: usize) -> bool {
+        self.first_bar_of_day
+            .get(index)
+            .is_some_and(|value| *value > 0.5)
+    }
+}
+
+/// Разбирает фиксированное смещение часового пояса: `UTC`, `UTC+3`, `GMT-05:00`,
+/// `+03:00`, `MSK`. Зоны IANA разбирает `CalendarZone`.
+pub fn parse_utc_offset(timezone: &str) -> Option<FixedOffset> {
+    let zone = timezone.trim();
+    let hours = match zone.to_ascii_uppercase().as_str() {
+        "UTC" | "GMT" | "Z" => Some(0),
+        "MSK" => Some(3),
+        "JST" => Some(9),
+        _ => None,
+    };
+    if let Some(hours) = hours {
+        return FixedOffset::east_opt(hours * 3600);
+    }
+    let offset = zone
+        .strip_prefix("UTC")
+        .or_else(|| zone.strip_prefix("GMT"))
+        .unwrap_or(zone);
+    let (sign, rest) = match offset.chars().next()? {
+        '+' => (1, &offset[1..]),
+        '-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?),
+        None if rest.len() == 4 => (rest[..2].parse().ok()?, rest[2..].parse().ok()?),
+        None => (rest.parse().ok()?, 0),
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_offsets_and_named_zones() {
+        let east = |hours: i32| FixedOffset::east_opt(hours * 3600);
+        assert_eq!(parse_utc_offset("MSK"), east(3));
+        assert_eq!(parse_utc_offset("UTC+3"), east(3));
+        assert_eq!(parse_utc_offset("-05:00"), east(-5));
+        assert_eq!(parse_utc_offset("GMT+0530"), FixedOffset::east_opt(19_800));
+        assert_eq!(parse_utc_offset("America/New_York"), None);
+
+        let new_york: CalendarZone = "America/New_York".parse().unwrap();
+        let summer = Utc.with_ymd_and_hms(2024, 7, 1, 14, 0, 0).unwrap();
+        let winter = Utc.with_ymd_and_hms(2024, 1, 2, 14, 0, 0).unwrap();
+        assert_eq!(
+            new_york.local(summer).time(),
+            NaiveTime::from_hms_opt(10, 0, 0).unwrap()
+        );
+        assert_eq!(
+            new_york.local(winter).time(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+        );
+        assert_eq!(
+            "Mars/Olympus".parse::<CalendarZone>(),
+            Err(UnsupportedTimezone("Mars/Olympus".to_string()))
+        );
+        assert!(TradingCalendar::from_json(
+            r#"{"name": "X", "timezone": "Mars/Olympus", "sessions": []}"#
+        )
+        .is_err());
+        let calendar = TradingCalendar::from_json(
+            r#"{"name": "NYSE", "timezone": "America/New_York", "sessions": []}"#,
+        )
+        .unwrap();
+        assert_eq!(calendar.timezone, new_york);
+        assert_eq!(calendar.timezone.to_string(), "America/New_York");
+    }
+
+    #[test]
+    fn forts_calendar_respects_clearing_holidays_and_close() {
+        let holiday = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let calendar = TradingCalendar::moex_forts().with_holidays([holiday]);
+        // 2024-01-03, среда: 14:02 МСК — дневной клиринг
+        let clearing = Utc.with_ymd_and_hms(2024, 1, 3, 11, 2, 0).unwrap();
+        assert!(calendar.session_at(clearing).is_none());
+        let day = Utc.with_ymd_and_hms(2024, 1, 3, 8, 0, 0).unwrap();
+        assert_eq!(calendar.session_at(day).unwrap().name, "day");
+        assert_eq!(calendar.minutes_to_close(day), Some(770.0));
+        let on_holiday = Utc.with_ymd_and_hms(2024, 1, 2, 8, 0, 0).unwrap();
+        assert!(calendar.session_at(on_holiday).is_none());
+        let friday = NaiveDate::from_ymd_opt(2024, 1, 5).unwrap();
+        assert_eq!(
+            calendar.next_trading_day(friday),
+            NaiveDate::from_ymd_opt(2024, 1, 8)
+        );
+
+        // Заявка после полуночи действует до конца дневной сессии пятницы
+        assert_eq!(
+            calendar.session_end(day),
+            Some(Utc.with_ymd_and_hms(2024, 1, 3, 15, 45, 0).unwrap())
+        );
+        let after_close = Utc.with_ymd_and_hms(2024, 1, 4, 21, 0, 0).unwrap();
+        assert_eq!(
+            calendar.session_end(after_close),
+            Some(Utc.with_ymd_and_hms(2024, 1, 5, 15, 45, 0).unwrap())
+        );
+        let overnight =
+            TradingCalendar::new("CME", "America/Chicago".parse().unwrap()).with_session(
+                TradingSession::new("globex", TimeWindow::hm((17, 0), (16, 0))),
+            );
+        let evening = Utc.with_ymd_and_hms(2024, 7, 2, 23, 0, 0).unwrap();
+        let next_afternoon = Utc.with_ymd_and_hms(2024, 7, 3, 21, 0, 0).unwrap();
+        assert_eq!(overnight.session_end(evening), Some(next_afternoon));
+        let morning = Utc.with_ymd_and_hms(2024, 7, 3, 13, 0, 0).unwrap();
+        assert_eq!(overnight.session_end(morning), Some(next_afternoon));
+
+        let timestamps: Vec<i64> = [(20, 45), (20, 50), (6, 0)]
+            .iter()
+            .enumerate()
+            .map(|(day, (hour, minute))| {
+                Utc.with_ymd_and_hms(2024, 1, 4 + day as u32 / 2, *hour, *minute, 0)
+                    .unwrap()
+                    .timestamp_millis()
+            })
+            .collect();
+        let series = calendar.bar_series(&timestamps, Duration::minutes(5));
+        assert_eq!(series.minutes_to_close, vec![0.0, -5.0, 885.0]);
+        assert!(series.is_first_bar_of_day(0));
+        assert!(!series.is_first_bar_of_day(1));
+        assert!(series.is_first_bar_of_day(2));
+        assert_eq!(series.day_of_week, vec![4.0, 4.0, 5.0]);
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// Модель комиссии брокера/биржи за одно исполнение
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum CommissionModel {
+    #[default]
+    None,
+    /// Фиксированная сумма за сделку
+    PerTrade { fee: f64 },
+    /// Процент от оборота (price × quantity), в процентах
+    PercentOfNotional { percent: f64 },
+    /// Фиксированная сумма за контракт/единицу
+    PerContract { fee: f64 },
+}
+
+impl CommissionModel {
+    pub fn commission(&self, price: f64, quantity: f64) -> f64 {
+        let quantity = quantity.abs();
+        if quantity <= f64::EPSILON {
+            return 0.0;
+        }
+        match self {
+            Self::None => 0.0,
+            Self::PerTrade { fee } => fee.max(0.0),
+            Self::PercentOfNotional { percent } => {
+                price.abs() * quantity * percent.max(0.0) / 100.0
+            }
+            Self::PerContract { fee } => fee.max(0.0) * quantity,
+        }
+    }
+}
+
+/// Модель проскальзывания: сдвиг цены исполнения против трейдера
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum SlippageModel {
+    #[default]
+    None,
+    /// Фиксированный сдвиг цены
+    Fixed { amount: f64 },
+    /// Сдвиг в процентах от цены
+    Percent { percent: f64 },
+    /// Сдвиг как доля ATR за `period` баров
+    Atr { period: usize, multiplier: f64 },
+}
+
+impl SlippageModel {
+    /// Величина проскальзывания на единицу для цены `price`; `atr` вызывается
+    /// с периодом только для модели `Atr`
+    pub fn slippage_per_unit(&self, price: f64, atr: impl FnOnce(usize) -> Option<f64>) -> f64 {
+        let value = match self {
+            Self::None => 0.0,
+            Self::Fixed { amount } => *amount,
+            Self::Percent { percent } => price.abs() * percent / 100.0,
+            Self::Atr { period, multiplier } => {
+                atr(*period).map(|atr| atr * multiplier).unwrap_or(0.0)
+            }
+        };
+        value.max(0.0)
+    }
+}
+
+/// Издержки исполнения инструмента: комиссия и проскальзывание
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct CostModel {
+    pub commission: CommissionModel,
+    pub slippage: SlippageModel,
+}
+
+impl CostModel {
+    pub fn new(commission: CommissionModel, slippage: SlippageModel) -> Self {
+        Self {
+            commission,
+            slippage,
+        }
+    }
+
+    pub fn is_free(&self) -> bool {
+        self.commission == CommissionModel::None && self.slippage == SlippageModel::None
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use super::costs::CostModel;
 use super::types::Symbol;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -17,6 +18,31 @@ pub enum AssetClass {
     Custom(String),
 }
 
+impl AssetClass {
+    /// Разбирает класс актива из текстового представления (например, из справочника БД)
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "equity" | "stock" | "share" => AssetClass::Equity,
+            "forex" | "fx" | "currency" => AssetClass::Forex,
+            "futures" | "future" => AssetClass::Futures,
+            "crypto" => AssetClass::Crypto,
+            "commodity" => AssetClass::Commodity,
+            "index" => AssetClass::Index,
+            "bond" => AssetClass::Bond,
+            "" => AssetClass::Custom("unknown".to_string()),
+            other => AssetClass::Custom(other.to_string()),
+        }
+    }
+}
+
+/// Направление округления цены к сетке шага цены
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickRounding {
+    Up,
+    Down,
+    Nearest,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InstrumentMeta {
     symbol: Symbol,
@@ -24,7 +50,12 @@ pub struct InstrumentMeta {
     quote_currency: Option<String>,
     tick_size: f32,
     lot_size: f32,
+    /// Стоимость пункта цены для фьючерсов; для остальных инструментов 1.0
+    #[serde(default = "default_contract_multiplier")]
+    contract_multiplier: f32,
     timezone: Option<String>,
+    #[serde(default)]
+    costs: Option<CostModel>,
     additional: HashMap<String, String>,
 }
 
@@ -53,10 +84,53 @@ impl InstrumentMeta {
         self.lot_size
     }
 
+    pub fn contract_multiplier(&self) -> f32 {
+        self.contract_multiplier
+    }
+
     pub fn timezone(&self) -> Option<&str> {
         self.timezone.as_deref()
     }
 
+    /// Множитель PnL с защитой от незаполненного значения
+    pub fn pnl_multiplier(&self) -> f64 {
+        if self.contract_multiplier > 0.0 {
+            self.contract_multiplier as f64
+        } else {
+            1.0
+        }
+    }
+
+    /// Округляет количество вниз до целого числа лотов; `None`, если меньше одного лота
+    pub fn round_quantity(&self, quantity: f64) -> Option<f64> {
+        let lot = self.lot_size as f64;
+        if lot <= 0.0 {
+            return (quantity > 0.0).then_some(quantity);
+        }
+        let lots = (quantity / lot + 1e-9).floor();
+        (lots >= 1.0).then_some(lots * lot)
+    }
+
+    /// Приводит цену к сетке шага цены в заданном направлении
+    pub fn round_price(&self, price: f64, rounding: TickRounding) -> f64 {
+        let tick = self.tick_size as f64;
+        if tick <= 0.0 || !price.is_finite() {
+            return price;
+        }
+        let steps = price / tick;
+        let rounded = match rounding {
+            TickRounding::Up => (steps - 1e-9).ceil(),
+            TickRounding::Down => (steps + 1e-9).floor(),
+            TickRounding::Nearest => steps.round(),
+        };
+        rounded * tick
+    }
+
+    /// Индивидуальная модель издержек инструмента (перекрывает настройки бэктеста)
+    pub fn costs(&self) -> Option<&CostModel> {
+        self.costs.as_ref()
+    }
+
     pub fn additional(&self) -> &HashMap<String, String> {
         &self.additional
     }
@@ -68,7 +142,9 @@ pub struct InstrumentMetaBuilder {
     quote_currency: Option<String>,
     tick_size: f32,
     lot_size: f32,
+    contract_multiplier: f32,
     timezone: Option<String>,
+    costs: Option<CostModel>,
     additional: HashMap<String, String>,
 }
 
@@ -80,7 +156,9 @@ impl InstrumentMetaBuilder {
             quote_currency: None,
             tick_size: 0.0,
             lot_size: 0.0,
+            contract_multiplier: 1.0,
             timezone: None,
+            costs: None,
             additional: HashMap::new(),
         }
     }
@@ -105,11 +183,21 @@ impl InstrumentMetaBuilder {
         self
     }
 
+    pub fn contract_multiplier(mut self, value: f32) -> Self {
+        self.contract_multiplier = value;
+        self
+    }
+
     pub fn timezone<S: Into<String>>(mut self, timezone: S) -> Self {
         self.timezone = Some(timezone.into());
         self
     }
 
+    pub fn costs(mut self, costs: CostModel) -> Self {
+        self.costs = Some(costs);
+        self
+    }
+
     pub fn with_additional<S: Into<String>>(mut self, key: S, value: S) -> Self {
         self.additional.insert(key.into(), value.into());
         self
@@ -122,12 +210,18 @@ impl InstrumentMetaBuilder {
             quote_currency: self.quote_currency,
             tick_size: self.tick_size,
             lot_size: self.lot_size,
+            contract_multiplier: self.contract_multiplier,
             timezone: self.timezone,
+            costs: self.costs,
             additional: self.additional,
         }
     }
 }
 
+fn default_contract_multiplier() -> f32 {
+    1.0
+}
+
 #[derive(Default)]
 pub struct MetaRegistry {
     entries: HashMap<Arc<str>, InstrumentMeta>,
@@ -163,3 +257,35 @@ impl MetaRegistry {
         self.entries.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn futures_meta() -> InstrumentMeta {
+        InstrumentMeta::builder(Symbol::with_exchange("SiZ5", "FORTS"))
+            .asset_class(AssetClass::Futures)
+            .tick_size(0.5)
+            .lot_size(10.0)
+            .contract_multiplier(2.0)
+            .build()
+    }
+
+    #[test]
+    fn quantity_is_floored_to_whole_lots() {
+        let meta = futures_meta();
+        assert_eq!(meta.round_quantity(37.0), Some(30.0));
+        assert_eq!(meta.round_quantity(10.0), Some(10.0));
+        assert_eq!(meta.round_quantity(9.9), None);
+    }
+
+    #[test]
+    fn price_is_rounded_to_tick_in_requested_direction() {
+        let meta = futures_meta();
+        assert_eq!(meta.round_price(100.2, TickRounding::Up), 100.5);
+        assert_eq!(meta.round_price(100.2, TickRounding::Down), 100.0);
+        assert_eq!(meta.round_price(100.3, TickRounding::Nearest), 100.5);
+        assert_eq!(meta.round_price(100.5, TickRounding::Up), 100.5);
+        assert_eq!(meta.pnl_multiplier(), 2.0);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod adapters;
+pub mod calendar;
+pub mod costs;
 pub mod meta;
 pub mod quote;
 pub mod quote_frame;
@@ -6,6 +8,7 @@ pub mod types;
 pub mod vector;
 pub mod vector_ops;
 
+pub use costs::{CommissionModel, CostModel, SlippageModel};
 pub use meta::*;
 pub use quote::*;
 pub use quote_frame::*;
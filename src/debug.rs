@@ -457,6 +457,19 @@ fn print_stop_values_per_bar(
             "Exit Rule: {}",
             trade.exit_rule_id.as_deref().unwrap_or("N/A")
         );
+        let excursion = &trade.excursion;
+        println!(
+            "MAE: {:.2} ({:.2}%) | MFE: {:.2} ({:.2}%) | R: {} / {} | Баров: {} (до MFE: {}) | Эффективность: {:.2}",
+            excursion.mae,
+            excursion.mae_pct,
+            excursion.mfe,
+            excursion.mfe_pct,
+            excursion.mae_r.map(|r| format!("{:.2}", r)).unwrap_or("N/A".to_string()),
+            excursion.mfe_r.map(|r| format!("{:.2}", r)).unwrap_or("N/A".to_string()),
+            excursion.bars_in_trade,
+            excursion.bars_to_mfe,
+            excursion.efficiency
+        );
         println!("───────────────────────────────────────────────────────────────────────────────");
 
         match (entry_bar_idx, exit_bar_idx) {
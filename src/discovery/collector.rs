@@ -42,7 +42,9 @@ impl IndicatorInfoCollector {
 
         // Собираем информацию о параметрах
         // Получаем все имена параметров через get_current_values
-        let param_names: Vec<String> = parameters.get_current_values().keys().cloned().collect();
+        let mut param_names: Vec<String> =
+            parameters.get_current_values().keys().cloned().collect();
+        param_names.sort();
         let param_infos: Vec<IndicatorParamInfo> = param_names
             .iter()
             .filter_map(|name| {
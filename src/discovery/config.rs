@@ -1,4 +1,5 @@
 use crate::data_model::types::TimeFrame;
+use crate::position::sizing::PositionSizerSpec;
 use serde::{Deserialize, Serialize};
 
 /// Конфигурация для автоматического поиска стратегий
@@ -20,6 +21,10 @@ pub struct StrategyDiscoveryConfig {
     /// Таймфреймы будут генерироваться как кратные базовому до этого значения
     /// По умолчанию 1440 минут (1 день)
     pub max_timeframe_minutes: u32,
+
+    /// Сайзер позиции кандидатов; его параметры оптимизируются вместе с остальными
+    #[serde(default)]
+    pub position_sizer: Option<PositionSizerSpec>,
 }
 
 impl Default for StrategyDiscoveryConfig {
@@ -29,6 +34,7 @@ impl Default for StrategyDiscoveryConfig {
             timeframe_count: 3,
             base_timeframe: TimeFrame::Minutes(60),
             max_timeframe_minutes: 240,
+            position_sizer: None,
         }
     }
 }
@@ -3,6 +3,7 @@ use crate::discovery::config::StrategyDiscoveryConfig;
 use crate::discovery::strategy_converter::{StrategyConversionError, StrategyConverter};
 use crate::discovery::types::{ConditionInfo, IndicatorInfo, NestedIndicator, StopHandlerInfo};
 use crate::strategy::types::StrategyDefinition;
+use serde::{Deserialize, Serialize};
 
 /// Основной генератор стратегий
 pub struct StrategyDiscoveryEngine {
@@ -16,7 +17,7 @@ impl StrategyDiscoveryEngine {
 }
 
 /// Кандидат стратегии для дальнейшей оптимизации
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyCandidate {
     /// Базовые индикаторы (строящиеся по цене)
     pub indicators: Vec<IndicatorInfo>,
@@ -13,6 +13,7 @@ pub fn example_strategy_generation() {
         timeframe_count: 3,
         base_timeframe: TimeFrame::Minutes(60),
         max_timeframe_minutes: 1440,
+        position_sizer: None,
     };
 
     // Доступные индикаторы (упрощенный пример)
@@ -27,7 +27,11 @@ impl ConverterHelpers {
         if let Some(tf) = explicit_timeframe {
             DataSeriesSource::indicator_with_timeframe(alias.to_string(), tf.clone())
         } else if let Some(timeframes) = alias_to_timeframes.get(alias) {
-            if let Some(tf) = timeframes.iter().next() {
+            // Без явного таймфрейма берётся наименьший: порядок обхода HashSet случаен
+            if let Some(tf) = timeframes
+                .iter()
+                .min_by_key(|tf| (tf.total_minutes(), tf.identifier()))
+            {
                 DataSeriesSource::indicator_with_timeframe(alias.to_string(), tf.clone())
             } else {
                 DataSeriesSource::indicator(alias)
@@ -66,7 +66,7 @@ impl StrategyConverter {
         let mut all_condition_bindings = condition_bindings;
         all_condition_bindings.extend(exit_condition_bindings);
 
-        Ok(StrategyDefinition::new(
+        let definition = StrategyDefinition::new(
             metadata,
             parameters,
             indicator_bindings,
@@ -78,7 +78,11 @@ impl StrategyConverter {
             take_handlers,
             defaults,
             BTreeMap::new(),
-        ))
+        );
+        Ok(match &candidate.config.position_sizer {
+            Some(sizer) => definition.with_position_sizer(sizer.clone()),
+            None => definition,
+        })
     }
 
     fn extract_defaults(_candidate: &StrategyCandidate) -> StrategyParameterMap {
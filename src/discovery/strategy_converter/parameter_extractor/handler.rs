@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::discovery::types::StopHandlerInfo;
 use crate::optimization::condition_id::ConditionId;
@@ -73,7 +73,11 @@ pub fn extract_stop_handler_parameters(
             &temp_params,
         ) {
             let handler_params = temp_handler.parameters();
-            for (param_name, param_value) in handler_params.get_current_values() {
+            // Порядок спецификаций не зависит от порядка обхода HashMap,
+            // иначе синхронизация параметров тратит случайные числа по-разному
+            let values: BTreeMap<String, f32> =
+                handler_params.get_current_values().into_iter().collect();
+            for (param_name, param_value) in values {
                 if param_name == "indicator_name" || param_name == "indicator_period" {
                     continue;
                 }
@@ -124,7 +128,9 @@ pub fn extract_take_handler_parameters(
             &temp_params,
         ) {
             let handler_params = temp_handler.parameters();
-            for (param_name, param_value) in handler_params.get_current_values() {
+            let values: BTreeMap<String, f32> =
+                handler_params.get_current_values().into_iter().collect();
+            for (param_name, param_value) in values {
                 if let Some(param_info) = handler_params.get_parameter(&param_name) {
                     let param_key =
                         ConditionId::take_handler_parameter_name(&take_handler.id, &param_name);
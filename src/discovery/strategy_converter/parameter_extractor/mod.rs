@@ -41,6 +41,9 @@ impl ParameterExtractor {
         params.extend(handler::extract_take_handler_parameters(
             &candidate.take_handlers,
         ));
+        if let Some(sizer) = &candidate.config.position_sizer {
+            params.extend(sizer.parameter_specs());
+        }
 
         params
     }
@@ -1,6 +1,7 @@
 use crate::discovery::engine::StrategyCandidate;
 use crate::strategy::types::{
-    ConditionBindingSpec, PositionDirection, RuleLogic, StrategyRuleSpec, StrategySignalType,
+    ConditionBindingSpec, OrderSpec, PositionDirection, RuleLogic, StrategyRuleSpec,
+    StrategySignalType,
 };
 
 use super::main::StrategyConversionError;
@@ -29,6 +30,8 @@ impl RuleBuilder {
             tags: vec!["auto-generated".to_string()],
             position_group: None,
             target_entry_ids: vec![],
+            order: OrderSpec::market(),
+            close_fraction: None,
         }])
     }
 
@@ -54,10 +57,11 @@ impl RuleBuilder {
                 tags: vec!["auto-generated".to_string(), "exit-conditions".to_string()],
                 position_group: None,
                 target_entry_ids: vec![],
+                order: OrderSpec::market(),
+                close_fraction: None,
             });
         }
 
         Ok(exit_rules)
     }
 }
-
@@ -4,7 +4,7 @@ use crate::strategy::types::ConditionOperator;
 use serde::{Deserialize, Serialize};
 
 /// Информация об индикаторе для генерации комбинаций
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndicatorInfo {
     pub name: String,
     pub alias: String,
@@ -19,7 +19,7 @@ pub struct IndicatorInfo {
 }
 
 /// Информация о параметре индикатора
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndicatorParamInfo {
     pub name: String,
     pub param_type: ParameterType,
@@ -33,7 +33,7 @@ pub struct IndicatorParamInfo {
 }
 
 /// Информация об условии для генерации комбинаций
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionInfo {
     pub id: String,
     pub name: String,
@@ -100,7 +100,7 @@ impl ConditionInfo {
 }
 
 /// Информация о параметре условия
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionParamInfo {
     pub name: String,
     pub optimizable: bool,
@@ -111,7 +111,7 @@ pub struct ConditionParamInfo {
 }
 
 /// Информация о стоп-обработчике для генерации комбинаций
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StopHandlerInfo {
     pub id: String,
     pub name: String,
@@ -134,7 +134,7 @@ pub struct IndicatorCombination {
 }
 
 /// Индикатор, строящийся по другому индикатору
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NestedIndicator {
     pub indicator: IndicatorInfo,
     /// Алиас индикатора, по которому строится этот индикатор
@@ -29,7 +29,9 @@ fn parse_date(s: &str) -> chrono::DateTime<Utc> {
         .and_utc()
 }
 
-#[tokio::main]
+// Генератор случайных чисел оптимизации хранится в потоке: эволюция должна
+// выполняться в одном потоке, иначе сид и чекпоинт не воспроизводят запуск
+#[tokio::main(flavor = "current_thread")]
 async fn main() {
     if let Err(err) = run().await {
         eprintln!("{err}");
@@ -104,6 +106,7 @@ async fn run() -> Result<()> {
         initial_capital: 1000.0,
         use_full_capital: true,
         reinvest_profits: false,
+        ..BacktestConfig::default()
     };
     let mut executor = BacktestEngine::from_definition(definition, None, frames)
         .map_err(anyhow::Error::new)?
@@ -215,6 +218,7 @@ async fn run_genetic_optimization(
             min_trades_count: Some(150),
             min_cagr: None,
             min_recovery_factor: None,
+            ..FitnessThresholds::default()
         },
         fitness_weights: FitnessWeights {
             sharpe_ratio_weight: 0.25,
@@ -224,7 +228,9 @@ async fn run_genetic_optimization(
             recovery_factor_weight: 0.20,
             drawdown_penalty: 0.05,
             trades_count_bonus: 0.05,
+            ..FitnessWeights::default()
         },
+        objectives: Vec::new(),
         use_existing_strategies: false,
         decimation_coefficient: 2.0,
         param_variants_per_candidate: 10,
@@ -241,8 +247,33 @@ async fn run_genetic_optimization(
         sds_agents_ratio: 1.0,
         sds_test_threshold: 0.7,
         candidate_builder_config: None,
+        evaluation_threads: num_cpus::get(),
+        evaluation_cache_dir: Some(DEFAULT_EVALUATION_CACHE_DIR.to_string()),
+        checkpoint_interval: 5,
+        checkpoint_path: Some(DEFAULT_CHECKPOINT_PATH.to_string()),
+        seed: None,
     };
 
+    // `--resume [путь]` продолжает эволюцию с сохраненного чекпоинта
+    let args: Vec<String> = std::env::args().collect();
+    let resumed = match args.iter().position(|arg| arg == "--resume") {
+        Some(index) => {
+            let path = args
+                .get(index + 1)
+                .cloned()
+                .or_else(|| config.checkpoint_path.clone())
+                .unwrap_or_else(|| DEFAULT_CHECKPOINT_PATH.to_string());
+            Some(OptimizationCheckpoint::load(&path)?)
+        }
+        None => None,
+    };
+
+    // При продолжении состояние генератора восстанавливается из чекпоинта
+    if resumed.is_none() {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        rng::seed(seed);
+        println!("   Сид генератора: {}", seed);
+    }
     println!("   Размер популяции (μ): {}", config.population_size);
     println!("   Количество потомков (λ): {}", config.lambda_size);
     println!("   Максимум поколений: {}", config.max_generations);
@@ -288,6 +319,7 @@ async fn run_genetic_optimization(
         timeframe_count: 2,
         base_timeframe: base_timeframe.clone(),
         max_timeframe_minutes: 240,
+        position_sizer: None,
     };
 
     println!("🧬 Генерация начальных популяций для островов...");
@@ -307,61 +339,79 @@ async fn run_genetic_optimization(
     };
     let start_time = std::time::Instant::now();
 
-    for island_id in 0..config.islands_count {
-        println!("\n🏝️  Генерация популяции для острова {}...", island_id);
-        let mut population = generator.generate(None).await?;
-        population.island_id = Some(island_id);
-        println!(
-            "   Остров {}: сгенерировано {} особей",
-            island_id,
-            population.individuals.len()
-        );
-        initial_populations.push(population);
-
-        if island_id == 0 {
-            println!("\n⏹️  Остановка после первого острова для профилирования");
-            let elapsed = start_time.elapsed();
-            #[cfg(feature = "profiling")]
-            {
-                if let Ok(report) = _guard.report().build() {
-                    let file_path = "profiling/flamegraph-optimization-pprof.svg";
-                    std::fs::remove_file(file_path).ok();
-                    match std::fs::File::create(file_path) {
-                        Ok(file) => {
-                            if let Err(e) = report.flamegraph(file) {
-                                eprintln!("⚠️  Ошибка при записи flamegraph: {}", e);
-                            } else {
-                                println!("\n✅ Профиль оптимизации сохранен в {}", file_path);
+    if resumed.is_none() {
+        for island_id in 0..config.islands_count {
+            println!("\n🏝️  Генерация популяции для острова {}...", island_id);
+            let mut population = generator.generate(None).await?;
+            population.island_id = Some(island_id);
+            println!(
+                "   Остров {}: сгенерировано {} особей",
+                island_id,
+                population.individuals.len()
+            );
+            initial_populations.push(population);
+
+            if island_id == 0 {
+                println!("\n⏹️  Остановка после первого острова для профилирования");
+                let elapsed = start_time.elapsed();
+                #[cfg(feature = "profiling")]
+                {
+                    if let Ok(report) = _guard.report().build() {
+                        let file_path = "profiling/flamegraph-optimization-pprof.svg";
+                        std::fs::remove_file(file_path).ok();
+                        match std::fs::File::create(file_path) {
+                            Ok(file) => {
+                                if let Err(e) = report.flamegraph(file) {
+                                    eprintln!("⚠️  Ошибка при записи flamegraph: {}", e);
+                                } else {
+                                    println!("\n✅ Профиль оптимизации сохранен в {}", file_path);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("⚠️  Ошибка при создании файла {}: {}", file_path, e);
+                                eprintln!("   Проверьте права доступа к папке profiling/");
                             }
-                        }
-                        Err(e) => {
-                            eprintln!("⚠️  Ошибка при создании файла {}: {}", file_path, e);
-                            eprintln!("   Проверьте права доступа к папке profiling/");
                         }
                     }
                 }
+                println!("\n=== ВРЕМЯ ВЫПОЛНЕНИЯ ОПТИМИЗАЦИИ ===");
+                println!(
+                    "Время выполнения: {:.2} секунд ({:.2} миллисекунд)",
+                    elapsed.as_secs_f64(),
+                    elapsed.as_millis() as f64
+                );
+                println!("\n⏹️  Оптимизация остановлена после первого острова");
+                return Ok(());
             }
-            println!("\n=== ВРЕМЯ ВЫПОЛНЕНИЯ ОПТИМИЗАЦИИ ===");
-            println!(
-                "Время выполнения: {:.2} секунд ({:.2} миллисекунд)",
-                elapsed.as_secs_f64(),
-                elapsed.as_millis() as f64
-            );
-            println!("\n⏹️  Оптимизация остановлена после первого острова");
-            return Ok(());
         }
-    }
 
-    let total_individuals: usize = initial_populations
-        .iter()
-        .map(|p| p.individuals.len())
-        .sum();
-    println!(
-        "\n   ✅ Всего создано {} особей на {} островах (по {} особей на остров)",
-        total_individuals, config.islands_count, config.population_size
-    );
+        let total_individuals: usize = initial_populations
+            .iter()
+            .map(|p| p.individuals.len())
+            .sum();
+        println!(
+            "\n   ✅ Всего создано {} особей на {} островах (по {} особей на остров)",
+            total_individuals, config.islands_count, config.population_size
+        );
+    }
 
-    let mut island_manager = IslandManager::new(config.clone(), initial_populations);
+    let (mut island_manager, mut evolution_manager, first_generation) = match resumed {
+        Some(checkpoint) => {
+            println!(
+                "♻️  Продолжение с поколения {} (чекпоинт от {})",
+                checkpoint.next_generation + 1,
+                checkpoint.saved_at
+            );
+            let first_generation = checkpoint.next_generation;
+            let (island_manager, evolution_manager) = checkpoint.restore(&config);
+            (island_manager, evolution_manager, first_generation)
+        }
+        None => (
+            IslandManager::new(config.clone(), initial_populations),
+            EvolutionManager::new(config.clone()),
+            0,
+        ),
+    };
     println!("   Создано {} островов\n", island_manager.islands_count());
 
     println!("🧬 Создание генетического алгоритма...");
@@ -374,13 +424,12 @@ async fn run_genetic_optimization(
     .with_backtest_config(backtest_config);
 
     println!("📈 Создание менеджеров эволюции...");
-    let mut evolution_manager = EvolutionManager::new(config.clone());
     let migration_system = MigrationSystem::new(config.clone());
     let fresh_blood = FreshBloodSystem::new(config.clone());
 
     println!("\n🚀 Запуск эволюции...\n");
 
-    'evolution: for generation in 0..config.max_generations {
+    'evolution: for generation in first_generation..config.max_generations {
         println!("═══════════════════════════════════════════════════════");
         println!("Поколение {}/{}", generation + 1, config.max_generations);
         println!("═══════════════════════════════════════════════════════");
@@ -451,6 +500,20 @@ async fn run_genetic_optimization(
             evolution_manager.reset_stagnation();
         }
 
+        if checkpoint::should_checkpoint(&config, generation) {
+            if let Some(ref path) = config.checkpoint_path {
+                let checkpoint = OptimizationCheckpoint::capture(
+                    generation + 1,
+                    &island_manager,
+                    &evolution_manager,
+                );
+                match checkpoint.save(path) {
+                    Ok(()) => println!("\n💾 Чекпоинт сохранен: {}", path),
+                    Err(e) => eprintln!("\n⚠️  Не удалось сохранить чекпоинт {}: {:?}", path, e),
+                }
+            }
+        }
+
         println!();
     }
 
@@ -519,6 +582,30 @@ async fn run_genetic_optimization(
         }
     }
 
+    if !config.objectives.is_empty() {
+        let front = pareto::pareto_front(island_manager.get_all_islands(), &config.objectives);
+        println!("═══════════════════════════════════════════════════════");
+        println!("Фронт Парето по всем островам: {} стратегий", front.len());
+        println!("═══════════════════════════════════════════════════════");
+        for (index, member) in front.iter().enumerate() {
+            let values: Vec<String> = config
+                .objectives
+                .iter()
+                .zip(&member.objectives)
+                .map(|(objective, value)| match value {
+                    Some(value) => format!("{:?}={:.4}", objective, value),
+                    None => format!("{:?}=N/A", objective),
+                })
+                .collect();
+            println!("{}. {}", index + 1, values.join(", "));
+            print_backtest_metrics(&member.report);
+            if let Some(ref candidate) = member.strategy.candidate {
+                print_strategy_info(candidate);
+            }
+            println!();
+        }
+    }
+
     Ok(())
 }
 
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use crate::strategy::types::PositionDirection;
+
+use super::backtest::{BacktestMetrics, StrategyTrade};
+
+/// Категория выхода, выделенная из `StrategyTrade::exit_reason`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ExitCategory {
+    RuleExit,
+    StopLoss,
+    TakeProfit,
+    Trailing,
+    Reversal,
+    SessionClose,
+    TimeExit,
+    MarginCall,
+    RiskGuard,
+    Other,
+}
+
+impl ExitCategory {
+    pub fn from_reason(reason: Option<&str>) -> Self {
+        match reason {
+            None | Some("exit") => Self::RuleExit,
+            Some("stop:StopLoss") => Self::StopLoss,
+            Some("stop:TakeProfit") => Self::TakeProfit,
+            Some("stop:Trailing") => Self::Trailing,
+            Some("reversal") => Self::Reversal,
+            Some("stop:SessionClose") => Self::SessionClose,
+            Some("stop:TimeExit") => Self::TimeExit,
+            Some("stop:MarginCall") => Self::MarginCall,
+            Some(reason) if reason.starts_with("risk_guard:") => Self::RiskGuard,
+            Some(_) => Self::Other,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::RuleExit => "rule_exit",
+            Self::StopLoss => "stop_loss",
+            Self::TakeProfit => "take_profit",
+            Self::Trailing => "trailing",
+            Self::Reversal => "reversal",
+            Self::SessionClose => "session_close",
+            Self::TimeExit => "time_exit",
+            Self::MarginCall => "margin_call",
+            Self::RiskGuard => "risk_guard",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Метрики подмножества сделок
+#[derive(Clone, Debug)]
+pub struct AttributionSlice {
+    pub key: String,
+    pub metrics: BacktestMetrics,
+}
+
+/// Разложение результата по сторонам, правилам входа, причинам выхода и таймфреймам.
+/// Метрики каждой группы считаются по кривой капитала из ее закрытых сделок
+/// от полного начального капитала.
+#[derive(Clone, Debug, Default)]
+pub struct PerformanceAttribution {
+    pub by_direction: Vec<AttributionSlice>,
+    pub by_entry_rule: Vec<AttributionSlice>,
+    pub by_exit_reason: Vec<AttributionSlice>,
+    pub by_timeframe: Vec<AttributionSlice>,
+}
+
+impl PerformanceAttribution {
+    /// `metrics` — общие метрики бэктеста: из них берутся капитал, даты и число баров
+    pub fn build(trades: &[StrategyTrade], metrics: &BacktestMetrics) -> Self {
+        Self {
+            by_direction: Self::slices(trades, metrics, |trade| {
+                direction_key(&trade.direction).to_string()
+            }),
+            by_entry_rule: Self::slices(trades, metrics, |trade| {
+                trade
+                    .entry_rule_id
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string())
+            }),
+            by_exit_reason: Self::slices(trades, metrics, |trade| {
+                ExitCategory::from_reason(trade.exit_reason.as_deref())
+                    .as_str()
+                    .to_string()
+            }),
+            by_timeframe: Self::slices(trades, metrics, |trade| trade.timeframe.identifier()),
+        }
+    }
+
+    pub fn direction(&self, direction: &PositionDirection) -> Option<&BacktestMetrics> {
+        let key = direction_key(direction);
+        self.by_direction
+            .iter()
+            .find(|slice| slice.key == key)
+            .map(|slice| &slice.metrics)
+    }
+
+    /// Доля положительной прибыли, которую дает самая прибыльная сторона, от 0.5 до 1;
+    /// `None`, если прибыльной стороны нет
+    pub fn side_profit_share(trades: &[StrategyTrade]) -> Option<f64> {
+        let (long, short) =
+            trades
+                .iter()
+                .fold((0.0, 0.0), |(long, short), trade| match trade.direction {
+                    PositionDirection::Long => (long + trade.pnl, short),
+                    PositionDirection::Short => (long, short + trade.pnl),
+                    _ => (long, short),
+                });
+        let (long, short) = (f64::max(long, 0.0), f64::max(short, 0.0));
+        let total = long + short;
+        (total > 0.0).then(|| long.max(short) / total)
+    }
+
+    fn slices(
+        trades: &[StrategyTrade],
+        metrics: &BacktestMetrics,
+        key: impl Fn(&StrategyTrade) -> String,
+    ) -> Vec<AttributionSlice> {
+        let mut groups: BTreeMap<String, Vec<StrategyTrade>> = BTreeMap::new();
+        for trade in trades {
+            groups.entry(key(trade)).or_default().push(trade.clone());
+        }
+        groups
+            .into_iter()
+            .map(|(key, mut group)| {
+                group.sort_by_key(|trade| trade.exit_time);
+                let mut equity = metrics.initial_capital;
+                let mut equity_curve = Vec::with_capacity(group.len() + 1);
+                equity_curve.push(equity);
+                for trade in &group {
+                    equity += trade.pnl;
+                    equity_curve.push(equity);
+                }
+                let bars_in_positions = group
+                    .iter()
+                    .map(|trade| trade.excursion.bars_in_trade)
+                    .sum::<usize>()
+                    .min(metrics.total_bars);
+                AttributionSlice {
+                    key,
+                    metrics: BacktestMetrics::from_data(
+                        &group,
+                        &equity_curve,
+                        metrics.initial_capital,
+                        metrics.start_date,
+                        metrics.end_date,
+                        metrics.total_bars,
+                        bars_in_positions,
+                        None,
+                    ),
+                }
+            })
+            .collect()
+    }
+}
+
+fn direction_key(direction: &PositionDirection) -> &'static str {
+    match direction {
+        PositionDirection::Long => "long",
+        PositionDirection::Short => "short",
+        PositionDirection::Flat => "flat",
+        PositionDirection::Both => "both",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_reasons_map_to_categories() {
+        assert_eq!(ExitCategory::from_reason(None), ExitCategory::RuleExit);
+        assert_eq!(
+            ExitCategory::from_reason(Some("stop:Trailing")),
+            ExitCategory::Trailing
+        );
+        assert_eq!(
+            ExitCategory::from_reason(Some("risk_guard:daily_loss")),
+            ExitCategory::RiskGuard
+        );
+        assert_eq!(
+            ExitCategory::from_reason(Some("scale_to_flat")),
+            ExitCategory::Other
+        );
+    }
+}
@@ -1,10 +1,22 @@
+use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::{Symbol, TimeFrame};
 use crate::data_model::vector_ops::unsafe_ops;
-use crate::position::{ClosedTrade, ExecutionReport, StopHistoryEntry};
+use crate::metrics::attribution::PerformanceAttribution;
+use crate::metrics::benchmark::{benchmark_curve, BenchmarkComparison};
+use crate::metrics::breakdown::{BreakdownConfig, PeriodicBreakdown};
+use crate::metrics::drawdown::DrawdownCurve;
+use crate::metrics::equity_risk::{EquityRiskConfig, EquityRiskMetrics};
+use crate::metrics::monte_carlo::{MonteCarloConfig, MonteCarloReport};
+use crate::position::{
+    ClosedTrade, ExcursionStats, ExecutionReport, StopHistoryEntry, TradeExcursion,
+};
+use crate::risk::guards::RiskGuardStats;
+use crate::risk::intrabar::IntrabarResolution;
 use crate::strategy::types::PositionDirection;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StrategyTrade {
     pub position_id: String,
     pub symbol: Symbol,
@@ -15,14 +27,26 @@ pub struct StrategyTrade {
     pub exit_price: f64,
     pub entry_time: Option<DateTime<Utc>>,
     pub exit_time: Option<DateTime<Utc>>,
+    /// PnL за вычетом комиссии и проскальзывания
     pub pnl: f64,
+    /// PnL без учета издержек
+    pub gross_pnl: f64,
+    pub commission: f64,
+    pub slippage: f64,
+    /// Плата за заем бумаг и финансирование позиции
+    pub financing: f64,
     pub entry_rule_id: Option<String>,
     pub exit_rule_id: Option<String>,
+    /// Причина выхода: `exit`, `stop:StopLoss`, `risk_guard:daily_loss` и т.п.
+    pub exit_reason: Option<String>,
     pub stop_history: Vec<StopHistoryEntry>,
+    pub intrabar_resolution: Option<IntrabarResolution>,
+    /// MAE/MFE, длительность и эффективность сделки
+    pub excursion: TradeExcursion,
 }
 
 /// Полный набор метрик производительности стратегии
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct BacktestMetrics {
     // ===== БАЗОВЫЕ МЕТРИКИ ПРОИЗВОДИТЕЛЬНОСТИ =====
     /// TOTAL PROFIT = ENDING CAPITAL – INITIAL CAPITAL
@@ -37,6 +61,38 @@ pub struct BacktestMetrics {
     /// CAGR = ((ENDING CAPITAL / INITIAL CAPITAL)^(1 / NUMBER OF YEARS)) - 1
     pub cagr: Option<f64>,
 
+    // ===== ТОРГОВЫЕ ИЗДЕРЖКИ =====
+    /// GROSS TOTAL PROFIT = TOTAL PROFIT + TOTAL COSTS (прибыль без учета издержек)
+    pub gross_total_profit: f64,
+    /// TOTAL COMMISSION = SUM OF TRADE COMMISSIONS
+    pub total_commission: f64,
+    /// TOTAL SLIPPAGE = SUM OF TRADE SLIPPAGE LOSSES
+    pub total_slippage: f64,
+    /// TOTAL FINANCING = SUM OF TRADE BORROW AND FINANCING FEES
+    pub total_financing: f64,
+    /// TOTAL COSTS = TOTAL COMMISSION + TOTAL SLIPPAGE + TOTAL FINANCING
+    pub total_costs: f64,
+
+    // ===== ВНУТРИБАРОВЫЕ КОНФЛИКТЫ СТОП/ТЕЙК =====
+    /// Сделки, у которых стоп и тейк сработали на одном баре
+    pub intrabar_conflicts: usize,
+    /// Из них порядок определен эвристикой пути цены
+    pub intrabar_resolved_by_heuristic: usize,
+    /// Из них порядок определен по фактическим ценам (гэп или младший таймфрейм)
+    pub intrabar_resolved_by_data: usize,
+
+    // ===== ПОРТФЕЛЬНЫЕ ОГРАНИЧЕНИЯ РИСКА =====
+    /// Срабатывания лимитов убытка, экспозиции и kill switch
+    pub risk_guards: RiskGuardStats,
+
+    // ===== ОТКЛОНЕНИЯ ЦЕНЫ В СДЕЛКАХ =====
+    /// Распределения MAE/MFE, эффективность и длительность сделок
+    pub excursions: ExcursionStats,
+
+    // ===== СРАВНЕНИЕ С БЕНЧМАРКОМ =====
+    /// Избыточная доходность, бета, альфа и захват относительно покупки и удержания
+    pub benchmark: Option<BenchmarkComparison>,
+
     // ===== МЕТРИКИ РИСКА И ДОХОДНОСТИ =====
     /// Sharpe Ratio (требует расчета стандартного отклонения доходности)
     pub sharpe_ratio: Option<f64>,
@@ -47,6 +103,36 @@ pub struct BacktestMetrics {
     /// WINNING PERCENTAGE = Percentage of winning trades in all trades
     pub winning_percentage: f64,
 
+    // ===== МЕТРИКИ РИСКА ПО КРИВОЙ КАПИТАЛА =====
+    // Считаются по доходностям баров в `with_equity_risk`, годовые величины в процентах
+    /// SORTINO RATIO = ANNUALIZED MEAN RETURN / ANNUALIZED DOWNSIDE DEVIATION
+    pub sortino_ratio: Option<f64>,
+    /// CALMAR RATIO = ANNUALIZED RETURN / MAX % DRAWDOWN за последние три года
+    pub calmar_ratio: Option<f64>,
+    /// MAR RATIO = ANNUALIZED RETURN / MAX % DRAWDOWN за всю выборку
+    pub mar_ratio: Option<f64>,
+    /// OMEGA RATIO = SUM OF GAINS ABOVE THRESHOLD / SUM OF LOSSES BELOW THRESHOLD
+    pub omega_ratio: Option<f64>,
+    /// ULCER INDEX = SQRT(MEAN(% DRAWDOWN²))
+    pub ulcer_index: Option<f64>,
+    /// ULCER PERFORMANCE INDEX = ANNUALIZED RETURN / ULCER INDEX
+    pub ulcer_performance_index: Option<f64>,
+    /// TAIL RATIO = |95TH PERCENTILE RETURN| / |5TH PERCENTILE RETURN|
+    pub tail_ratio: Option<f64>,
+    /// Асимметрия доходностей баров
+    pub returns_skewness: Option<f64>,
+    /// Эксцесс доходностей баров (0 для нормального распределения)
+    pub returns_kurtosis: Option<f64>,
+    /// Исторический VaR за бар в процентах капитала при `var_confidence`
+    pub value_at_risk: Option<f64>,
+    /// Исторический CVaR (expected shortfall) за бар в процентах капитала
+    pub conditional_value_at_risk: Option<f64>,
+    pub var_confidence: f64,
+    /// Средняя длительность просадки в барах, от пика до нового максимума
+    pub average_drawdown_duration: Option<f64>,
+    /// Максимальная длительность просадки в барах
+    pub max_drawdown_duration: Option<usize>,
+
     // ===== МЕТРИКИ ПРОСАДКИ =====
     /// DRAWDOWN = Max (EQUITY PEAK – FOLLOWING TROUGH)
     pub drawdown: Option<f64>,
@@ -166,6 +252,29 @@ impl BacktestMetrics {
         let ending_capital = equity_curve.last().copied().unwrap_or(initial_capital);
         let total_profit = ending_capital - initial_capital;
 
+        let (total_commission, total_slippage, total_financing) = trades.iter().fold(
+            (0.0, 0.0, 0.0),
+            |(commission, slippage, financing), trade| {
+                (
+                    commission + trade.commission,
+                    slippage + trade.slippage,
+                    financing + trade.financing,
+                )
+            },
+        );
+        let total_costs = total_commission + total_slippage + total_financing;
+        let gross_total_profit = total_profit + total_costs;
+
+        let intrabar_resolved_by_heuristic = trades
+            .iter()
+            .filter(|trade| trade.intrabar_resolution == Some(IntrabarResolution::Heuristic))
+            .count();
+        let intrabar_resolved_by_data = trades
+            .iter()
+            .filter(|trade| trade.intrabar_resolution == Some(IntrabarResolution::RealData))
+            .count();
+        let intrabar_conflicts = intrabar_resolved_by_heuristic + intrabar_resolved_by_data;
+
         let (
             number_of_wins,
             number_of_losses,
@@ -369,12 +478,51 @@ impl BacktestMetrics {
             yearly_avg_percent_return,
             cagr,
 
+            // Торговые издержки
+            gross_total_profit,
+            total_commission,
+            total_slippage,
+            total_financing,
+            total_costs,
+
+            // Внутрибаровые конфликты
+            intrabar_conflicts,
+            intrabar_resolved_by_heuristic,
+            intrabar_resolved_by_data,
+
+            // Портфельные ограничения риска заполняет движок бэктеста
+            risk_guards: RiskGuardStats::default(),
+
+            // Отклонения цены в сделках
+            excursions: ExcursionStats::from_trades(
+                trades.iter().map(|trade| (&trade.excursion, trade.pnl)),
+            ),
+
+            // Бенчмарк строится по ценам инструмента в отчете
+            benchmark: None,
+
             // Метрики риска и доходности
             sharpe_ratio,
             profit_factor,
             return_dd_ratio,
             winning_percentage,
 
+            // Метрики по кривой капитала заполняет with_equity_risk
+            sortino_ratio: None,
+            calmar_ratio: None,
+            mar_ratio: None,
+            omega_ratio: None,
+            ulcer_index: None,
+            ulcer_performance_index: None,
+            tail_ratio: None,
+            returns_skewness: None,
+            returns_kurtosis: None,
+            value_at_risk: None,
+            conditional_value_at_risk: None,
+            var_confidence: 0.0,
+            average_drawdown_duration: None,
+            max_drawdown_duration: None,
+
             // Метрики просадки
             drawdown,
             drawdown_percent,
@@ -430,6 +578,35 @@ impl BacktestMetrics {
         }
     }
 
+    /// Дополняет метрики показателями риска по доходностям баров кривой капитала.
+    /// Без `periods_per_year` в конфигурации число баров в году оценивается по датам выборки.
+    pub fn with_equity_risk(mut self, equity_curve: &[f64], config: &EquityRiskConfig) -> Self {
+        let years = Self::calculate_years(self.start_date, self.end_date);
+        let periods_per_year = config.periods_per_year.unwrap_or_else(|| {
+            if years > 0.0 {
+                equity_curve.len().saturating_sub(1) as f64 / years
+            } else {
+                252.0
+            }
+        });
+        let risk = EquityRiskMetrics::compute(equity_curve, periods_per_year, config);
+        self.sortino_ratio = risk.sortino_ratio;
+        self.calmar_ratio = risk.calmar_ratio;
+        self.mar_ratio = risk.mar_ratio;
+        self.omega_ratio = risk.omega_ratio;
+        self.ulcer_index = risk.ulcer_index;
+        self.ulcer_performance_index = risk.ulcer_performance_index;
+        self.tail_ratio = risk.tail_ratio;
+        self.returns_skewness = risk.skewness;
+        self.returns_kurtosis = risk.kurtosis;
+        self.value_at_risk = risk.value_at_risk;
+        self.conditional_value_at_risk = risk.conditional_value_at_risk;
+        self.var_confidence = config.var_confidence;
+        self.average_drawdown_duration = risk.average_drawdown_duration;
+        self.max_drawdown_duration = risk.max_drawdown_duration;
+        self
+    }
+
     fn calculate_trades_metrics_in_single_pass(
         trades: &[StrategyTrade],
     ) -> (usize, usize, f64, f64, usize, usize, f64) {
@@ -716,11 +893,21 @@ impl BacktestMetrics {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BacktestReport {
     pub trades: Vec<StrategyTrade>,
     pub metrics: BacktestMetrics,
     pub equity_curve: Vec<f64>,
+    /// Начальная маржа открытых позиций по барам, параллельно кривой капитала;
+    /// пусто, если маржинальная модель не задана
+    pub margin_usage: Vec<f64>,
+    /// Время точек кривой капитала; пусто, если движок его не записывал
+    pub equity_timestamps: Vec<DateTime<Utc>>,
+    /// Кривая покупки и удержания бенчмарка, параллельно кривой капитала
+    pub benchmark_curve: Vec<f64>,
+    /// Худший внутрибарный капитал, параллельно кривой капитала;
+    /// пусто вне режима `EquityMode::ExactIntrabar`
+    pub intrabar_equity_curve: Vec<f64>,
 }
 
 impl BacktestReport {
@@ -733,15 +920,95 @@ impl BacktestReport {
             trades,
             metrics,
             equity_curve,
+            margin_usage: Vec::new(),
+            equity_timestamps: Vec::new(),
+            benchmark_curve: Vec::new(),
+            intrabar_equity_curve: Vec::new(),
         }
     }
+
+    pub fn with_margin_usage(mut self, margin_usage: Vec<f64>) -> Self {
+        self.margin_usage = margin_usage;
+        self
+    }
+
+    pub fn with_equity_timestamps(mut self, timestamps: Vec<DateTime<Utc>>) -> Self {
+        self.equity_timestamps = timestamps;
+        self
+    }
+
+    pub fn with_intrabar_equity(mut self, intrabar_equity: Vec<f64>) -> Self {
+        self.intrabar_equity_curve = intrabar_equity;
+        self
+    }
+
+    /// Кривая просадки и периоды ниже предыдущего максимума капитала.
+    /// При наличии внутрибарной кривой глубина считается по худшему капиталу бара.
+    pub fn drawdown_curve(&self) -> DrawdownCurve {
+        let intrabar = (self.intrabar_equity_curve.len() == self.equity_curve.len())
+            .then_some(self.intrabar_equity_curve.as_slice());
+        DrawdownCurve::build(&self.equity_curve, intrabar, &self.equity_timestamps)
+    }
+
+    /// Сравнивает кривую капитала с покупкой и удержанием бенчмарка по тем же датам.
+    /// Без времени точек кривой капитала отчет не меняется.
+    pub fn with_benchmark(mut self, frame: &QuoteFrame, periods_per_year: Option<f64>) -> Self {
+        let initial_capital = self.equity_curve.first().copied().unwrap_or(0.0);
+        let curve = benchmark_curve(frame, &self.equity_timestamps, initial_capital);
+        if curve.is_empty() || curve.len() != self.equity_curve.len() {
+            return self;
+        }
+        let periods_per_year = periods_per_year.unwrap_or_else(|| {
+            let years =
+                BacktestMetrics::calculate_years(self.metrics.start_date, self.metrics.end_date);
+            if years > 0.0 {
+                self.equity_curve.len().saturating_sub(1) as f64 / years
+            } else {
+                252.0
+            }
+        });
+        self.metrics.benchmark = BenchmarkComparison::compute(
+            &self.equity_curve,
+            &curve,
+            periods_per_year,
+            self.metrics.exposure,
+        );
+        self.benchmark_curve = curve;
+        self
+    }
+
+    /// Календарная разбивка: месячные и годовые результаты, сделки по дням недели
+    /// и часам, скользящие метрики по окнам из `config`
+    pub fn periodic_breakdown(&self, config: &BreakdownConfig) -> PeriodicBreakdown {
+        PeriodicBreakdown::build(
+            &self.trades,
+            &self.equity_curve,
+            &self.equity_timestamps,
+            config,
+        )
+    }
+
+    /// Монте-Карло по сделкам отчета: доверительные интервалы капитала, просадки
+    /// и восстановления и итоговая оценка устойчивости
+    pub fn monte_carlo(&self, config: &MonteCarloConfig) -> MonteCarloReport {
+        MonteCarloReport::run(&self.trades, self.metrics.initial_capital, config)
+    }
+
+    /// Метрики по сторонам, правилам входа, причинам выхода и таймфреймам
+    pub fn attribution(&self) -> PerformanceAttribution {
+        PerformanceAttribution::build(&self.trades, &self.metrics)
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct BacktestAnalytics {
     trades: Vec<StrategyTrade>,
     equity_curve: Vec<f64>,
+    margin_usage: Vec<f64>,
+    intrabar_equity: Vec<f64>,
+    equity_timestamps: Vec<DateTime<Utc>>,
     bars_in_positions: usize,
+    equity_risk: EquityRiskConfig,
 }
 
 impl BacktestAnalytics {
@@ -752,9 +1019,20 @@ impl BacktestAnalytics {
     pub fn reset(&mut self) {
         self.trades.clear();
         self.equity_curve.clear();
+        self.margin_usage.clear();
+        self.intrabar_equity.clear();
+        self.equity_timestamps.clear();
         self.bars_in_positions = 0;
     }
 
+    pub fn set_equity_risk_config(&mut self, config: EquityRiskConfig) {
+        self.equity_risk = config;
+    }
+
+    pub fn equity_risk_config(&self) -> &EquityRiskConfig {
+        &self.equity_risk
+    }
+
     /// Увеличивает счетчик баров в позициях, если есть открытые позиции
     pub fn increment_bars_in_positions_if_has_positions(&mut self, has_open_positions: bool) {
         if has_open_positions {
@@ -771,6 +1049,46 @@ impl BacktestAnalytics {
         self.equity_curve.push(equity);
     }
 
+    /// Записывает загрузку маржи для последней точки кривой капитала;
+    /// пропущенные ранее точки (прогрев) заполняются нулями
+    pub fn push_margin_point(&mut self, margin: f64) {
+        let target = self.equity_curve.len().saturating_sub(1);
+        if self.margin_usage.len() < target {
+            self.margin_usage.resize(target, 0.0);
+        }
+        self.margin_usage.push(margin);
+    }
+
+    /// Записывает худший внутрибарный капитал для последней точки кривой капитала;
+    /// пропущенные ранее точки берутся из самой кривой
+    pub fn push_intrabar_equity_point(&mut self, equity: f64) {
+        let target = self.equity_curve.len().saturating_sub(1);
+        let filled = self.intrabar_equity.len();
+        if filled < target {
+            self.intrabar_equity
+                .extend_from_slice(&self.equity_curve[filled..target]);
+        }
+        self.intrabar_equity.push(equity);
+    }
+
+    /// Записывает время последней точки кривой капитала; точки без времени
+    /// (начальная и прогрев) получают время первой записанной точки
+    pub fn push_equity_timestamp(&mut self, timestamp: DateTime<Utc>) {
+        let target = self.equity_curve.len().saturating_sub(1);
+        if self.equity_timestamps.len() < target {
+            self.equity_timestamps.resize(target, timestamp);
+        }
+        self.equity_timestamps.push(timestamp);
+    }
+
+    pub fn equity_timestamps(&self) -> &[DateTime<Utc>] {
+        &self.equity_timestamps
+    }
+
+    pub fn margin_usage(&self) -> &[f64] {
+        &self.margin_usage
+    }
+
     pub fn absorb_execution_report(&mut self, report: &ExecutionReport) {
         for trade in &report.closed_trades {
             self.trades.push(StrategyTrade::from(trade));
@@ -812,9 +1130,13 @@ impl BacktestAnalytics {
             total_bars,
             bars_in_positions,
             pip_value,
-        );
+        )
+        .with_equity_risk(&self.equity_curve, &self.equity_risk);
 
         BacktestReport::new(self.trades.clone(), metrics, self.equity_curve.clone())
+            .with_margin_usage(self.margin_usage.clone())
+            .with_equity_timestamps(self.equity_timestamps.clone())
+            .with_intrabar_equity(self.intrabar_equity.clone())
     }
 
     /// Создает отчет с упрощенными параметрами (для обратной совместимости)
@@ -853,9 +1175,16 @@ impl From<&ClosedTrade> for StrategyTrade {
             entry_time: trade.entry_time.clone(),
             exit_time: trade.exit_time.clone(),
             pnl: trade.pnl,
+            gross_pnl: trade.gross_pnl,
+            commission: trade.commission,
+            slippage: trade.slippage,
+            financing: trade.financing,
             entry_rule_id: trade.entry_rule_id.clone(),
             exit_rule_id: trade.exit_rule_id.clone(),
+            exit_reason: trade.exit_reason.clone(),
             stop_history: trade.stop_history.clone(),
+            intrabar_resolution: trade.intrabar_resolution,
+            excursion: trade.excursion,
         }
     }
 }
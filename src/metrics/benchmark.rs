@@ -0,0 +1,195 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data_model::quote_frame::QuoteFrame;
+
+/// Источник цен для сравнения стратегии с покупкой и удержанием
+#[derive(Clone)]
+pub enum BenchmarkSource {
+    /// Основной таймфрейм торгуемого инструмента
+    PrimaryFrame,
+    /// Произвольный инструмент, например индекс
+    Frame(Arc<QuoteFrame>),
+}
+
+impl fmt::Debug for BenchmarkSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrimaryFrame => write!(f, "PrimaryFrame"),
+            Self::Frame(frame) => write!(
+                f,
+                "Frame({} {}, {} bars)",
+                frame.symbol().descriptor(),
+                frame.timeframe(),
+                frame.len()
+            ),
+        }
+    }
+}
+
+/// Сравнение кривой капитала стратегии с кривой покупки и удержания.
+/// Доходности в процентах, альфа — в процентах годовых.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchmarkComparison {
+    pub benchmark_return_pct: f64,
+    pub strategy_return_pct: f64,
+    pub excess_return_pct: f64,
+    pub beta: Option<f64>,
+    pub alpha: Option<f64>,
+    pub correlation: Option<f64>,
+    pub information_ratio: Option<f64>,
+    /// Доля роста бенчмарка, полученная стратегией на барах роста
+    pub up_capture: Option<f64>,
+    /// Доля падения бенчмарка, полученная стратегией на барах падения
+    pub down_capture: Option<f64>,
+    /// Доходность стратегии, деленная на долю времени в позиции
+    pub exposure_adjusted_return_pct: Option<f64>,
+}
+
+/// Кривая капитала покупки и удержания: начальный капитал вкладывается в бенчмарк
+/// по цене закрытия бара, действующего в момент первой точки кривой стратегии
+pub fn benchmark_curve(
+    frame: &QuoteFrame,
+    timestamps: &[DateTime<Utc>],
+    initial_capital: f64,
+) -> Vec<f64> {
+    let mut closes = Vec::with_capacity(timestamps.len());
+    let mut index = 0usize;
+    let mut last_close: Option<f64> = None;
+    for timestamp in timestamps {
+        // Время точек кривой капитала хранится с точностью до миллисекунд
+        let millis = timestamp.timestamp_millis();
+        while let Some(quote) = frame.get(index) {
+            if quote.timestamp_millis() > millis {
+                break;
+            }
+            last_close = Some(quote.close() as f64);
+            index += 1;
+        }
+        closes.push(last_close);
+    }
+    // Точки до первого бара бенчмарка получают его первую цену
+    let Some(base) = closes
+        .iter()
+        .flatten()
+        .copied()
+        .next()
+        .or_else(|| frame.first().map(|quote| quote.close() as f64))
+        .filter(|price| *price > 0.0)
+    else {
+        return Vec::new();
+    };
+    closes
+        .into_iter()
+        .map(|close| initial_capital * close.unwrap_or(base) / base)
+        .collect()
+}
+
+impl BenchmarkComparison {
+    pub fn compute(
+        strategy_curve: &[f64],
+        benchmark_curve: &[f64],
+        periods_per_year: f64,
+        exposure: Option<f64>,
+    ) -> Option<Self> {
+        if strategy_curve.len() != benchmark_curve.len() || strategy_curve.len() < 2 {
+            return None;
+        }
+        let total_return = |curve: &[f64]| {
+            let first = curve[0];
+            (first > 0.0).then(|| (curve[curve.len() - 1] / first - 1.0) * 100.0)
+        };
+        let strategy_return_pct = total_return(strategy_curve)?;
+        let benchmark_return_pct = total_return(benchmark_curve)?;
+
+        let (strategy, benchmark): (Vec<f64>, Vec<f64>) = strategy_curve
+            .windows(2)
+            .zip(benchmark_curve.windows(2))
+            .filter(|(s, b)| s[0] > 0.0 && b[0] > 0.0)
+            .map(|(s, b)| (s[1] / s[0] - 1.0, b[1] / b[0] - 1.0))
+            .unzip();
+        let n = strategy.len() as f64;
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / n;
+        let (strategy_mean, benchmark_mean) = (mean(&strategy), mean(&benchmark));
+        let (mut covariance, mut strategy_var, mut benchmark_var, mut tracking) =
+            (0.0, 0.0, 0.0, Vec::with_capacity(strategy.len()));
+        for (s, b) in strategy.iter().zip(&benchmark) {
+            covariance += (s - strategy_mean) * (b - benchmark_mean);
+            strategy_var += (s - strategy_mean).powi(2);
+            benchmark_var += (b - benchmark_mean).powi(2);
+            tracking.push(s - b);
+        }
+        let beta = (benchmark_var > 0.0).then(|| covariance / benchmark_var);
+        let correlation = (benchmark_var > 0.0 && strategy_var > 0.0)
+            .then(|| covariance / (strategy_var * benchmark_var).sqrt());
+        let alpha =
+            beta.map(|beta| (strategy_mean - beta * benchmark_mean) * periods_per_year * 100.0);
+
+        let tracking_mean = mean(&tracking);
+        let tracking_error = (tracking
+            .iter()
+            .map(|d| (d - tracking_mean).powi(2))
+            .sum::<f64>()
+            / n)
+            .sqrt();
+        let information_ratio = (tracking_error > 0.0)
+            .then(|| tracking_mean / tracking_error * periods_per_year.sqrt());
+
+        let capture = |select: fn(f64) -> bool| {
+            let (strategy_sum, benchmark_sum) = strategy
+                .iter()
+                .zip(&benchmark)
+                .filter(|(_, b)| select(**b))
+                .fold((0.0, 0.0), |(s_sum, b_sum), (s, b)| (s_sum + s, b_sum + b));
+            (benchmark_sum != 0.0).then(|| strategy_sum / benchmark_sum)
+        };
+
+        Some(Self {
+            benchmark_return_pct,
+            strategy_return_pct,
+            excess_return_pct: strategy_return_pct - benchmark_return_pct,
+            beta,
+            alpha,
+            correlation,
+            information_ratio,
+            up_capture: capture(|b| b > 0.0),
+            down_capture: capture(|b| b < 0.0),
+            exposure_adjusted_return_pct: exposure
+                .filter(|exposure| *exposure > 0.0)
+                .map(|exposure| strategy_return_pct / exposure),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leveraged_copy_of_benchmark_has_beta_two() {
+        let benchmark = [100.0, 110.0, 99.0, 108.9];
+        let mut strategy = vec![100.0];
+        for pair in benchmark.windows(2) {
+            let last = *strategy.last().unwrap();
+            strategy.push(last * (1.0 + 2.0 * (pair[1] / pair[0] - 1.0)));
+        }
+        let comparison =
+            BenchmarkComparison::compute(&strategy, &benchmark, 252.0, Some(0.5)).unwrap();
+
+        assert!((comparison.beta.unwrap() - 2.0).abs() < 1e-9);
+        assert!((comparison.correlation.unwrap() - 1.0).abs() < 1e-9);
+        assert!(comparison.alpha.unwrap().abs() < 1e-6);
+        assert!((comparison.up_capture.unwrap() - 2.0).abs() < 1e-9);
+        assert!((comparison.down_capture.unwrap() - 2.0).abs() < 1e-9);
+        assert!((comparison.benchmark_return_pct - 8.9).abs() < 1e-9);
+        assert!(
+            (comparison.exposure_adjusted_return_pct.unwrap()
+                - comparison.strategy_return_pct * 2.0)
+                .abs()
+                < 1e-9
+        );
+    }
+}
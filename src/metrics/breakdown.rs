@@ -0,0 +1,393 @@
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::backtest::StrategyTrade;
+
+const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 3600.0;
+const DEFAULT_PERIODS_PER_YEAR: f64 = 252.0;
+
+/// Окно скользящих метрик: фиксированное число баров или календарный период
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollingWindow {
+    Bars(usize),
+    Days(i64),
+}
+
+/// Параметры разбивки результатов по периодам
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BreakdownConfig {
+    pub rolling_windows: Vec<RollingWindow>,
+    /// Сколько точек скользящего ряда приходится на длину окна
+    pub rolling_resolution: usize,
+    /// Баров в году для аннуализации Sharpe; `None` — оценка по датам кривой капитала
+    pub periods_per_year: Option<f64>,
+}
+
+impl Default for BreakdownConfig {
+    fn default() -> Self {
+        Self {
+            rolling_windows: vec![RollingWindow::Bars(60), RollingWindow::Days(365)],
+            rolling_resolution: 20,
+            periods_per_year: None,
+        }
+    }
+}
+
+impl BreakdownConfig {
+    pub fn with_rolling_windows(mut self, windows: Vec<RollingWindow>) -> Self {
+        self.rolling_windows = windows;
+        self
+    }
+
+    pub fn with_periods_per_year(mut self, periods_per_year: f64) -> Self {
+        self.periods_per_year = Some(periods_per_year);
+        self
+    }
+}
+
+/// Строка матрицы месячных доходностей; доходности в процентах
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyReturnsRow {
+    pub year: i32,
+    /// Январь — индекс 0; `None` для месяцев вне выборки
+    pub months: [Option<f64>; 12],
+    pub year_return: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct YearlyStats {
+    pub year: i32,
+    pub return_pct: f64,
+    pub max_drawdown_pct: f64,
+    /// Сделки, закрытые в этом году
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub net_profit: f64,
+}
+
+/// Результаты сделок, сгруппированных по времени входа
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeBucket {
+    /// День недели (0 — понедельник) или час суток UTC
+    pub key: u32,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub net_profit: f64,
+    pub average_pnl: f64,
+    pub win_rate: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RollingPoint {
+    /// Время последнего бара окна
+    pub timestamp: DateTime<Utc>,
+    pub return_pct: f64,
+    pub sharpe_ratio: Option<f64>,
+    pub max_drawdown_pct: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RollingSeries {
+    pub window: RollingWindow,
+    pub points: Vec<RollingPoint>,
+}
+
+/// Календарная разбивка результатов бэктеста
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeriodicBreakdown {
+    pub monthly_returns: Vec<MonthlyReturnsRow>,
+    pub yearly: Vec<YearlyStats>,
+    pub day_of_week: Vec<TradeBucket>,
+    pub hour_of_day: Vec<TradeBucket>,
+    pub rolling: Vec<RollingSeries>,
+}
+
+impl PeriodicBreakdown {
+    /// `timestamps` параллельны `equity_curve`; без них строится только разбивка сделок
+    pub fn build(
+        trades: &[StrategyTrade],
+        equity_curve: &[f64],
+        timestamps: &[DateTime<Utc>],
+        config: &BreakdownConfig,
+    ) -> Self {
+        let points: Vec<(DateTime<Utc>, f64)> = if timestamps.len() == equity_curve.len() {
+            timestamps
+                .iter()
+                .copied()
+                .zip(equity_curve.iter().copied())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let periods_per_year = config
+            .periods_per_year
+            .unwrap_or_else(|| estimate_periods_per_year(&points));
+
+        let mut day_of_week = vec![TradeBucket::default(); 7];
+        let mut hour_of_day = vec![TradeBucket::default(); 24];
+        for (key, bucket) in day_of_week.iter_mut().enumerate() {
+            bucket.key = key as u32;
+        }
+        for (key, bucket) in hour_of_day.iter_mut().enumerate() {
+            bucket.key = key as u32;
+        }
+        for trade in trades {
+            let Some(entry_time) = trade.entry_time else {
+                continue;
+            };
+            let weekday = entry_time.weekday().num_days_from_monday() as usize;
+            day_of_week[weekday].add(trade.pnl);
+            hour_of_day[entry_time.hour() as usize].add(trade.pnl);
+        }
+        for bucket in day_of_week.iter_mut().chain(hour_of_day.iter_mut()) {
+            bucket.finish();
+        }
+
+        Self {
+            monthly_returns: monthly_returns(&points),
+            yearly: yearly_stats(&points, trades),
+            day_of_week,
+            hour_of_day,
+            rolling: config
+                .rolling_windows
+                .iter()
+                .map(|window| RollingSeries {
+                    window: *window,
+                    points: rolling_points(
+                        &points,
+                        *window,
+                        config.rolling_resolution,
+                        periods_per_year,
+                    ),
+                })
+                .collect(),
+        }
+    }
+
+    /// Доля прибыльных месяцев в процентах; `None`, если месяцев нет
+    pub fn profitable_months_pct(&self) -> Option<f64> {
+        let months: Vec<f64> = self
+            .monthly_returns
+            .iter()
+            .flat_map(|row| row.months.iter().flatten().copied())
+            .collect();
+        if months.is_empty() {
+            return None;
+        }
+        let profitable = months.iter().filter(|value| **value > 0.0).count();
+        Some(profitable as f64 / months.len() as f64 * 100.0)
+    }
+
+    pub fn losing_years(&self) -> usize {
+        self.yearly
+            .iter()
+            .filter(|year| year.return_pct < 0.0)
+            .count()
+    }
+}
+
+impl TradeBucket {
+    fn add(&mut self, pnl: f64) {
+        self.trades += 1;
+        if pnl > 0.0 {
+            self.winning_trades += 1;
+        }
+        self.net_profit += pnl;
+    }
+
+    fn finish(&mut self) {
+        if self.trades > 0 {
+            self.average_pnl = self.net_profit / self.trades as f64;
+            self.win_rate = self.winning_trades as f64 / self.trades as f64;
+        }
+    }
+}
+
+fn estimate_periods_per_year(points: &[(DateTime<Utc>, f64)]) -> f64 {
+    match (points.first(), points.last()) {
+        (Some((start, _)), Some((end, _))) if end > start => {
+            let years = end.signed_duration_since(*start).num_seconds() as f64 / SECONDS_PER_YEAR;
+            (points.len() - 1) as f64 / years
+        }
+        _ => DEFAULT_PERIODS_PER_YEAR,
+    }
+}
+
+fn percent_change(from: f64, to: f64) -> f64 {
+    if from > 0.0 {
+        (to / from - 1.0) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Отрезки кривой капитала с одинаковым ключом периода: (ключ, индекс начала, индекс конца).
+/// Доходность периода считается от закрытия предыдущего периода.
+fn periods<K: PartialEq + Copy>(
+    points: &[(DateTime<Utc>, f64)],
+    key: impl Fn(&DateTime<Utc>) -> K,
+) -> Vec<(K, usize, usize)> {
+    let mut result: Vec<(K, usize, usize)> = Vec::new();
+    for (index, (timestamp, _)) in points.iter().enumerate() {
+        let current = key(timestamp);
+        match result.last_mut() {
+            Some((last, _, end)) if *last == current => *end = index,
+            _ => result.push((current, index.saturating_sub(1), index)),
+        }
+    }
+    result
+}
+
+fn monthly_returns(points: &[(DateTime<Utc>, f64)]) -> Vec<MonthlyReturnsRow> {
+    let mut rows: Vec<MonthlyReturnsRow> = Vec::new();
+    for ((year, month), start, end) in periods(points, |ts| (ts.year(), ts.month0())) {
+        if rows.last().map(|row| row.year) != Some(year) {
+            rows.push(MonthlyReturnsRow {
+                year,
+                ..Default::default()
+            });
+        }
+        let row = rows.last_mut().expect("row pushed above");
+        row.months[month as usize] = Some(percent_change(points[start].1, points[end].1));
+    }
+    for row in &mut rows {
+        row.year_return = (row
+            .months
+            .iter()
+            .flatten()
+            .fold(1.0, |total, value| total * (1.0 + value / 100.0))
+            - 1.0)
+            * 100.0;
+    }
+    rows
+}
+
+fn yearly_stats(points: &[(DateTime<Utc>, f64)], trades: &[StrategyTrade]) -> Vec<YearlyStats> {
+    periods(points, |ts| ts.year())
+        .into_iter()
+        .map(|(year, start, end)| {
+            let closed: Vec<&StrategyTrade> = trades
+                .iter()
+                .filter(|trade| trade.exit_time.map(|time| time.year()) == Some(year))
+                .collect();
+            YearlyStats {
+                year,
+                return_pct: percent_change(points[start].1, points[end].1),
+                max_drawdown_pct: max_drawdown_pct(&points[start..=end]),
+                trades: closed.len(),
+                winning_trades: closed.iter().filter(|trade| trade.pnl > 0.0).count(),
+                net_profit: closed.iter().map(|trade| trade.pnl).sum(),
+            }
+        })
+        .collect()
+}
+
+fn max_drawdown_pct(points: &[(DateTime<Utc>, f64)]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut max_drawdown: f64 = 0.0;
+    for (_, equity) in points {
+        peak = peak.max(*equity);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - equity) / peak * 100.0);
+        }
+    }
+    max_drawdown
+}
+
+/// Скользящие доходность, Sharpe и просадка. Точки ряда берутся с шагом
+/// `длина окна / resolution`, чтобы стоимость расчета не росла с шириной окна.
+fn rolling_points(
+    points: &[(DateTime<Utc>, f64)],
+    window: RollingWindow,
+    resolution: usize,
+    periods_per_year: f64,
+) -> Vec<RollingPoint> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    // Префиксные суммы доходностей баров для Sharpe за O(1) на окно
+    let mut sums = vec![0.0; points.len()];
+    let mut squares = vec![0.0; points.len()];
+    for index in 1..points.len() {
+        let previous = points[index - 1].1;
+        let value = if previous > 0.0 {
+            points[index].1 / previous - 1.0
+        } else {
+            0.0
+        };
+        sums[index] = sums[index - 1] + value;
+        squares[index] = squares[index - 1] + value * value;
+    }
+
+    let mut result = Vec::new();
+    let mut start = 0usize;
+    let mut next_emit = 0usize;
+    for end in 1..points.len() {
+        match window {
+            RollingWindow::Bars(bars) => {
+                if bars == 0 || end < bars {
+                    continue;
+                }
+                start = end - bars;
+            }
+            RollingWindow::Days(days) => {
+                let from = points[end].0 - Duration::days(days);
+                if days <= 0 || points[0].0 > from {
+                    continue;
+                }
+                while points[start + 1].0 <= from {
+                    start += 1;
+                }
+            }
+        }
+        if end < next_emit {
+            continue;
+        }
+        next_emit = end + ((end - start) / resolution.max(1)).max(1);
+
+        let count = (end - start) as f64;
+        let mean = (sums[end] - sums[start]) / count;
+        let variance = (squares[end] - squares[start]) / count - mean * mean;
+        let sharpe_ratio = (variance > f64::EPSILON * f64::EPSILON)
+            .then(|| mean / variance.sqrt() * periods_per_year.sqrt());
+        result.push(RollingPoint {
+            timestamp: points[end].0,
+            return_pct: percent_change(points[start].1, points[end].1),
+            sharpe_ratio,
+            max_drawdown_pct: max_drawdown_pct(&points[start..=end]),
+        });
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn monthly_matrix_chains_returns_from_previous_month_close() {
+        let timestamps: Vec<DateTime<Utc>> =
+            [(2023, 12, 29), (2024, 1, 15), (2024, 1, 31), (2024, 2, 15)]
+                .iter()
+                .map(|(y, m, d)| Utc.with_ymd_and_hms(*y, *m, *d, 10, 0, 0).unwrap())
+                .collect();
+        let equity = [100.0, 105.0, 110.0, 99.0];
+        let config = BreakdownConfig::default().with_rolling_windows(vec![RollingWindow::Bars(2)]);
+        let breakdown = PeriodicBreakdown::build(&[], &equity, &timestamps, &config);
+
+        assert_eq!(breakdown.monthly_returns.len(), 2);
+        let row_2024 = &breakdown.monthly_returns[1];
+        assert_eq!(row_2024.year, 2024);
+        assert!((row_2024.months[0].unwrap() - 10.0).abs() < 1e-9);
+        assert!((row_2024.months[1].unwrap() + 10.0).abs() < 1e-9);
+        assert!((row_2024.year_return + 1.0).abs() < 1e-9);
+        assert_eq!(breakdown.losing_years(), 1);
+        assert!((breakdown.profitable_months_pct().unwrap() - 100.0 / 3.0).abs() < 1e-9);
+        assert!((breakdown.yearly[1].max_drawdown_pct - 10.0).abs() < 1e-9);
+
+        let rolling = &breakdown.rolling[0].points;
+        assert_eq!(rolling.len(), 2);
+        assert!((rolling[1].return_pct + 5.714285714285714).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Период, когда капитал ниже предыдущего максимума
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnderwaterPeriod {
+    /// Первая точка ниже максимума
+    pub start_index: usize,
+    pub trough_index: usize,
+    /// Точка, в которой капитал вернулся к максимуму; `None`, если просадка не закрыта
+    pub recovery_index: Option<usize>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub trough_time: Option<DateTime<Utc>>,
+    pub recovery_time: Option<DateTime<Utc>>,
+    /// Глубина в процентах от максимума
+    pub depth_pct: f64,
+    /// Длительность в точках кривой до восстановления или до конца выборки
+    pub duration_bars: usize,
+}
+
+/// Кривая просадки в процентах от максимума капитала, параллельно кривой капитала
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrawdownCurve {
+    pub drawdown_pct: Vec<f64>,
+    pub underwater: Vec<UnderwaterPeriod>,
+    pub max_drawdown_pct: f64,
+}
+
+impl DrawdownCurve {
+    /// Максимум ведется по капиталу на закрытии бара, глубина — по `intrabar`,
+    /// если он задан. `timestamps` используются, если параллельны кривой капитала.
+    pub fn build(
+        equity_curve: &[f64],
+        intrabar: Option<&[f64]>,
+        timestamps: &[DateTime<Utc>],
+    ) -> Self {
+        let time =
+            |index: usize| (timestamps.len() == equity_curve.len()).then(|| timestamps[index]);
+        let mut curve = Self {
+            drawdown_pct: Vec::with_capacity(equity_curve.len()),
+            ..Self::default()
+        };
+        let mut peak = f64::MIN;
+        let mut current: Option<UnderwaterPeriod> = None;
+        for (index, &equity) in equity_curve.iter().enumerate() {
+            let low = intrabar
+                .and_then(|values| values.get(index).copied())
+                .map_or(equity, |worst| worst.min(equity));
+            let depth = if peak > 0.0 {
+                ((peak - low) / peak * 100.0).max(0.0)
+            } else {
+                0.0
+            };
+            curve.drawdown_pct.push(depth);
+            curve.max_drawdown_pct = curve.max_drawdown_pct.max(depth);
+
+            if equity >= peak {
+                peak = equity;
+                if let Some(mut period) = current.take() {
+                    period.recovery_index = Some(index);
+                    period.recovery_time = time(index);
+                    period.duration_bars = index - period.start_index;
+                    curve.underwater.push(period);
+                }
+                continue;
+            }
+            let period = current.get_or_insert_with(|| UnderwaterPeriod {
+                start_index: index,
+                trough_index: index,
+                recovery_index: None,
+                start_time: time(index),
+                trough_time: time(index),
+                recovery_time: None,
+                depth_pct: 0.0,
+                duration_bars: 0,
+            });
+            if depth > period.depth_pct {
+                period.depth_pct = depth;
+                period.trough_index = index;
+                period.trough_time = time(index);
+            }
+        }
+        if let Some(mut period) = current {
+            period.duration_bars = equity_curve.len() - period.start_index;
+            curve.underwater.push(period);
+        }
+        curve
+    }
+
+    /// Самый длинный период под водой в точках кривой
+    pub fn longest_underwater(&self) -> Option<&UnderwaterPeriod> {
+        self.underwater
+            .iter()
+            .max_by_key(|period| period.duration_bars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn underwater_periods_use_intrabar_depth() {
+        let equity = [100.0, 110.0, 104.5, 99.0, 112.0, 100.8];
+        let intrabar = [100.0, 108.0, 99.0, 99.0, 110.0, 100.8];
+        let curve = DrawdownCurve::build(&equity, Some(&intrabar), &[]);
+
+        assert_eq!(curve.underwater.len(), 2);
+        let first = &curve.underwater[0];
+        assert_eq!((first.start_index, first.trough_index), (2, 2));
+        assert_eq!(first.recovery_index, Some(4));
+        assert_eq!(first.duration_bars, 2);
+        assert!((first.depth_pct - 10.0).abs() < 1e-9);
+        let open = &curve.underwater[1];
+        assert_eq!(open.recovery_index, None);
+        assert!((open.depth_pct - 10.0).abs() < 1e-9);
+        assert!((curve.drawdown_pct[3] - 10.0).abs() < 1e-9);
+        assert!((curve.max_drawdown_pct - 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_model::types::TimeFrame;
+
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+const WEEKS_PER_YEAR: f64 = 52.0;
+const MONTHS_PER_YEAR: f64 = 12.0;
+/// Окно Calmar Ratio: последние три года кривой капитала
+const CALMAR_WINDOW_YEARS: f64 = 3.0;
+
+/// Параметры расчета метрик риска по кривой капитала
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EquityRiskConfig {
+    /// Доверительный уровень исторических VaR/CVaR, например 0.95
+    pub var_confidence: f64,
+    /// Порог доходности бара для Omega Ratio
+    pub omega_threshold: f64,
+    /// Баров в году для аннуализации; `None` — оценка по датам выборки
+    pub periods_per_year: Option<f64>,
+}
+
+impl Default for EquityRiskConfig {
+    fn default() -> Self {
+        Self {
+            var_confidence: 0.95,
+            omega_threshold: 0.0,
+            periods_per_year: None,
+        }
+    }
+}
+
+impl EquityRiskConfig {
+    pub fn with_var_confidence(mut self, confidence: f64) -> Self {
+        self.var_confidence = confidence;
+        self
+    }
+
+    pub fn with_omega_threshold(mut self, threshold: f64) -> Self {
+        self.omega_threshold = threshold;
+        self
+    }
+
+    /// Аннуализация по таймфрейму бара. Для внутридневных таймфреймов число баров
+    /// в году зависит от длины сессии, поэтому оно оценивается по датам выборки.
+    pub fn with_timeframe(mut self, timeframe: &TimeFrame) -> Self {
+        self.periods_per_year = match timeframe {
+            TimeFrame::Days(days) if *days > 0 => Some(TRADING_DAYS_PER_YEAR / *days as f64),
+            TimeFrame::Weeks(weeks) if *weeks > 0 => Some(WEEKS_PER_YEAR / *weeks as f64),
+            TimeFrame::Months(months) if *months > 0 => Some(MONTHS_PER_YEAR / *months as f64),
+            _ => None,
+        };
+        self
+    }
+}
+
+/// Метрики риска, рассчитанные по доходностям баров кривой капитала
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct EquityRiskMetrics {
+    pub sortino_ratio: Option<f64>,
+    pub calmar_ratio: Option<f64>,
+    pub mar_ratio: Option<f64>,
+    pub omega_ratio: Option<f64>,
+    pub ulcer_index: Option<f64>,
+    pub ulcer_performance_index: Option<f64>,
+    pub tail_ratio: Option<f64>,
+    pub skewness: Option<f64>,
+    pub kurtosis: Option<f64>,
+    pub value_at_risk: Option<f64>,
+    pub conditional_value_at_risk: Option<f64>,
+    pub average_drawdown_duration: Option<f64>,
+    pub max_drawdown_duration: Option<usize>,
+}
+
+impl EquityRiskMetrics {
+    /// `periods_per_year` — уже разрешенное число баров в году
+    pub fn compute(equity_curve: &[f64], periods_per_year: f64, config: &EquityRiskConfig) -> Self {
+        let returns: Vec<f64> = equity_curve
+            .windows(2)
+            .filter(|pair| pair[0] > 0.0)
+            .map(|pair| (pair[1] - pair[0]) / pair[0])
+            .collect();
+        if returns.len() < 2 || periods_per_year <= 0.0 {
+            return Self::default();
+        }
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+
+        // Доходность и риск в годовом выражении
+        let annual_return = annualized_return(equity_curve, periods_per_year);
+        let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+        let sortino_ratio = (downside > 0.0)
+            .then(|| mean * periods_per_year / (downside * periods_per_year.sqrt()));
+
+        let drawdowns = Drawdowns::from_curve(equity_curve);
+        let mar_ratio = ratio(annual_return, drawdowns.max_percent);
+        let window = (CALMAR_WINDOW_YEARS * periods_per_year).ceil() as usize + 1;
+        let recent = &equity_curve[equity_curve.len().saturating_sub(window)..];
+        let calmar_ratio = ratio(
+            annualized_return(recent, periods_per_year),
+            Drawdowns::from_curve(recent).max_percent,
+        );
+        let ulcer_index = drawdowns.ulcer_index;
+        let ulcer_performance_index = ratio(annual_return, ulcer_index);
+
+        let (gains, losses) = returns.iter().fold((0.0, 0.0), |(gains, losses), r| {
+            let excess = r - config.omega_threshold;
+            (gains + excess.max(0.0), losses + (-excess).max(0.0))
+        });
+        let omega_ratio = if losses > 0.0 {
+            Some(gains / losses)
+        } else if gains > 0.0 {
+            Some(f64::INFINITY)
+        } else {
+            None
+        };
+
+        // Моменты распределения доходностей
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n;
+        let (skewness, kurtosis) = if variance > 0.0 {
+            let m3 = returns.iter().map(|r| (r - mean).powi(3)).sum::<f64>() / n;
+            let m4 = returns.iter().map(|r| (r - mean).powi(4)).sum::<f64>() / n;
+            (
+                Some(m3 / variance.powf(1.5)),
+                Some(m4 / variance.powi(2) - 3.0),
+            )
+        } else {
+            (None, None)
+        };
+
+        // Хвосты: VaR/CVaR и tail ratio в процентах капитала за бар
+        let mut sorted = returns.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let confidence = config.var_confidence.clamp(0.5, 0.9999);
+        let tail_index = (((1.0 - confidence) * n).ceil() as usize).clamp(1, sorted.len());
+        let var_return = sorted[tail_index - 1];
+        let cvar_return = sorted[..tail_index].iter().sum::<f64>() / tail_index as f64;
+        let value_at_risk = Some((-var_return).max(0.0) * 100.0);
+        let conditional_value_at_risk = Some((-cvar_return).max(0.0) * 100.0);
+        let right_tail = quantile(&sorted, 0.95).abs();
+        let left_tail = quantile(&sorted, 0.05).abs();
+        let tail_ratio = (left_tail > 0.0).then(|| right_tail / left_tail);
+
+        Self {
+            sortino_ratio,
+            calmar_ratio,
+            mar_ratio,
+            omega_ratio,
+            ulcer_index,
+            ulcer_performance_index,
+            tail_ratio,
+            skewness,
+            kurtosis,
+            value_at_risk,
+            conditional_value_at_risk,
+            average_drawdown_duration: drawdowns.average_duration,
+            max_drawdown_duration: drawdowns.max_duration,
+        }
+    }
+}
+
+/// Годовая доходность кривой капитала в процентах
+fn annualized_return(equity_curve: &[f64], periods_per_year: f64) -> Option<f64> {
+    let first = *equity_curve.first()?;
+    let last = *equity_curve.last()?;
+    let periods = equity_curve.len().saturating_sub(1) as f64;
+    if first <= 0.0 || last <= 0.0 || periods <= 0.0 {
+        return None;
+    }
+    Some(((last / first).powf(periods_per_year / periods) - 1.0) * 100.0)
+}
+
+fn ratio(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    match (numerator, denominator) {
+        (Some(numerator), Some(denominator)) if denominator > 0.0 => Some(numerator / denominator),
+        _ => None,
+    }
+}
+
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    sorted[((sorted.len() - 1) as f64 * q).round() as usize]
+}
+
+/// Просадки кривой капитала: глубина, Ulcer Index и длительность эпизодов в барах
+struct Drawdowns {
+    max_percent: Option<f64>,
+    ulcer_index: Option<f64>,
+    average_duration: Option<f64>,
+    max_duration: Option<usize>,
+}
+
+impl Drawdowns {
+    fn from_curve(equity_curve: &[f64]) -> Self {
+        let mut peak = f64::MIN;
+        let mut max_percent: f64 = 0.0;
+        let mut squares = 0.0;
+        let mut durations = Vec::new();
+        let mut current = 0usize;
+        for &equity in equity_curve {
+            if equity >= peak {
+                peak = equity;
+                if current > 0 {
+                    durations.push(current);
+                    current = 0;
+                }
+                continue;
+            }
+            current += 1;
+            let percent = if peak > 0.0 {
+                (peak - equity) / peak * 100.0
+            } else {
+                0.0
+            };
+            max_percent = max_percent.max(percent);
+            squares += percent * percent;
+        }
+        // Незавершенная просадка учитывается как эпизод
+        if current > 0 {
+            durations.push(current);
+        }
+        let count = equity_curve.len();
+        Self {
+            max_percent: (max_percent > 0.0).then_some(max_percent),
+            ulcer_index: (count > 0).then(|| (squares / count as f64).sqrt()),
+            average_duration: (!durations.is_empty())
+                .then(|| durations.iter().sum::<usize>() as f64 / durations.len() as f64),
+            max_duration: durations.iter().max().copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drawdown_durations_and_tail_metrics() {
+        let curve = [100.0, 110.0, 99.0, 104.5, 115.0, 103.5, 120.0, 126.0];
+        let config = EquityRiskConfig::default().with_var_confidence(0.8);
+        let metrics = EquityRiskMetrics::compute(&curve, 252.0, &config);
+
+        assert_eq!(metrics.max_drawdown_duration, Some(2));
+        assert_eq!(metrics.average_drawdown_duration, Some(1.5));
+        // Худшая доходность бара: 115 -> 103.5 = -10%
+        assert!((metrics.value_at_risk.unwrap() - 10.0).abs() < 1e-9);
+        assert!(metrics.conditional_value_at_risk.unwrap() >= metrics.value_at_risk.unwrap());
+        assert!(metrics.sortino_ratio.unwrap() > 0.0);
+        assert!(metrics.omega_ratio.unwrap() > 1.0);
+        assert!(metrics.ulcer_index.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn annualization_follows_bar_timeframe() {
+        let daily = EquityRiskConfig::default().with_timeframe(&TimeFrame::Days(1));
+        assert_eq!(daily.periods_per_year, Some(252.0));
+        let weekly = EquityRiskConfig::default().with_timeframe(&TimeFrame::Weeks(1));
+        assert_eq!(weekly.periods_per_year, Some(52.0));
+        let hourly = EquityRiskConfig::default().with_timeframe(&TimeFrame::Hours(1));
+        assert_eq!(hourly.periods_per_year, None);
+    }
+}
@@ -1,5 +1,17 @@
+pub mod attribution;
 pub mod backtest;
+pub mod benchmark;
+pub mod breakdown;
+pub mod drawdown;
+pub mod equity_risk;
+pub mod monte_carlo;
 pub mod portfolio;
 
+pub use attribution::{ExitCategory, PerformanceAttribution};
 pub use backtest::{BacktestAnalytics, BacktestMetrics, BacktestReport, StrategyTrade};
+pub use benchmark::{BenchmarkComparison, BenchmarkSource};
+pub use breakdown::{BreakdownConfig, PeriodicBreakdown, RollingWindow};
+pub use drawdown::{DrawdownCurve, UnderwaterPeriod};
+pub use equity_risk::EquityRiskConfig;
+pub use monte_carlo::{MonteCarloConfig, MonteCarloMethod, MonteCarloReport};
 pub use portfolio::PortfolioSnapshot;
@@ -0,0 +1,288 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::backtest::StrategyTrade;
+
+/// Способ возмущения последовательности сделок
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MonteCarloMethod {
+    /// Случайный порядок тех же сделок
+    Reshuffle,
+    /// Выборка сделок с возвращением
+    Bootstrap,
+    /// Каждая сделка пропускается с вероятностью `probability`
+    SkipTrades { probability: f64 },
+    /// Дополнительное проскальзывание до `max_pct` процентов цены на вход и выход
+    Slippage { max_pct: f64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    pub iterations: usize,
+    pub methods: Vec<MonteCarloMethod>,
+    /// Доверительный уровень интервалов, например 0.95
+    pub confidence: f64,
+    /// Зерно генератора для воспроизводимости; `None` — случайное
+    pub seed: Option<u64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            methods: vec![
+                MonteCarloMethod::Reshuffle,
+                MonteCarloMethod::Bootstrap,
+                MonteCarloMethod::SkipTrades { probability: 0.1 },
+                MonteCarloMethod::Slippage { max_pct: 0.05 },
+            ],
+            confidence: 0.95,
+            seed: None,
+        }
+    }
+}
+
+impl MonteCarloConfig {
+    /// Облегченная воспроизводимая конфигурация для отбора стратегий в оптимизаторе
+    pub fn screening() -> Self {
+        Self::default().with_iterations(200).with_seed(0)
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_methods(mut self, methods: Vec<MonteCarloMethod>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Результат одной симуляции
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloSample {
+    pub final_equity: f64,
+    pub max_drawdown_pct: f64,
+    /// Самый долгий выход из просадки в сделках; незакрытая просадка считается до конца
+    pub recovery_trades: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+    pub lower: f64,
+    pub median: f64,
+    pub upper: f64,
+    pub mean: f64,
+}
+
+impl ConfidenceInterval {
+    fn from_values(mut values: Vec<f64>, confidence: f64) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let tail = (1.0 - confidence.clamp(0.0, 1.0)) / 2.0;
+        let quantile = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Self {
+            lower: quantile(tail),
+            median: quantile(0.5),
+            upper: quantile(1.0 - tail),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloDistribution {
+    pub method: MonteCarloMethod,
+    pub final_equity: ConfidenceInterval,
+    pub max_drawdown_pct: ConfidenceInterval,
+    pub recovery_trades: ConfidenceInterval,
+    /// Доля симуляций, закончившихся ниже начального капитала
+    pub probability_of_loss: f64,
+    /// Все симуляции метода, для построения графиков
+    pub samples: Vec<MonteCarloSample>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloReport {
+    pub initial_capital: f64,
+    /// Исходная последовательность сделок
+    pub baseline: MonteCarloSample,
+    pub distributions: Vec<MonteCarloDistribution>,
+    /// От 0 до 1: среднее по методам произведения доли прибыльных симуляций
+    /// и отношения исходной просадки к верхней границе просадки симуляций
+    pub robustness_score: f64,
+}
+
+impl MonteCarloReport {
+    pub fn run(trades: &[StrategyTrade], initial_capital: f64, config: &MonteCarloConfig) -> Self {
+        let pnls: Vec<f64> = trades.iter().map(|trade| trade.pnl).collect();
+        let baseline = simulate(initial_capital, pnls.iter().copied());
+        if pnls.is_empty() || config.iterations == 0 {
+            return Self {
+                initial_capital,
+                baseline,
+                ..Self::default()
+            };
+        }
+        let mut rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let distributions: Vec<MonteCarloDistribution> = config
+            .methods
+            .iter()
+            .map(|method| {
+                let samples: Vec<MonteCarloSample> = (0..config.iterations)
+                    .map(|_| {
+                        let sequence = perturb(trades, &pnls, *method, &mut rng);
+                        simulate(initial_capital, sequence.into_iter())
+                    })
+                    .collect();
+                let collect = |f: fn(&MonteCarloSample) -> f64| {
+                    ConfidenceInterval::from_values(
+                        samples.iter().map(f).collect(),
+                        config.confidence,
+                    )
+                };
+                let losses = samples
+                    .iter()
+                    .filter(|sample| sample.final_equity < initial_capital)
+                    .count();
+                MonteCarloDistribution {
+                    method: *method,
+                    final_equity: collect(|sample| sample.final_equity),
+                    max_drawdown_pct: collect(|sample| sample.max_drawdown_pct),
+                    recovery_trades: collect(|sample| sample.recovery_trades as f64),
+                    probability_of_loss: losses as f64 / samples.len() as f64,
+                    samples,
+                }
+            })
+            .collect();
+
+        let robustness_score = if distributions.is_empty() {
+            0.0
+        } else {
+            distributions
+                .iter()
+                .map(|distribution| {
+                    let drawdown_ratio = if distribution.max_drawdown_pct.upper > 0.0 {
+                        (baseline.max_drawdown_pct / distribution.max_drawdown_pct.upper).min(1.0)
+                    } else {
+                        1.0
+                    };
+                    (1.0 - distribution.probability_of_loss) * drawdown_ratio
+                })
+                .sum::<f64>()
+                / distributions.len() as f64
+        };
+
+        Self {
+            initial_capital,
+            baseline,
+            distributions,
+            robustness_score,
+        }
+    }
+}
+
+fn perturb(
+    trades: &[StrategyTrade],
+    pnls: &[f64],
+    method: MonteCarloMethod,
+    rng: &mut StdRng,
+) -> Vec<f64> {
+    match method {
+        MonteCarloMethod::Reshuffle => {
+            let mut sequence = pnls.to_vec();
+            sequence.shuffle(rng);
+            sequence
+        }
+        MonteCarloMethod::Bootstrap => (0..pnls.len())
+            .map(|_| pnls[rng.gen_range(0..pnls.len())])
+            .collect(),
+        MonteCarloMethod::SkipTrades { probability } => {
+            let probability = probability.clamp(0.0, 1.0);
+            pnls.iter()
+                .copied()
+                .filter(|_| !rng.gen_bool(probability))
+                .collect()
+        }
+        MonteCarloMethod::Slippage { max_pct } => trades
+            .iter()
+            .map(|trade| {
+                let turnover = trade.quantity.abs() * (trade.entry_price + trade.exit_price);
+                trade.pnl - turnover * max_pct.max(0.0) / 100.0 * rng.gen::<f64>()
+            })
+            .collect(),
+    }
+}
+
+fn simulate(initial_capital: f64, pnls: impl Iterator<Item = f64>) -> MonteCarloSample {
+    let mut equity = initial_capital;
+    let mut peak = initial_capital;
+    let mut max_drawdown_pct: f64 = 0.0;
+    let mut underwater = 0usize;
+    let mut recovery_trades = 0usize;
+    for pnl in pnls {
+        equity += pnl;
+        if equity >= peak {
+            peak = equity;
+            underwater = 0;
+            continue;
+        }
+        underwater += 1;
+        recovery_trades = recovery_trades.max(underwater);
+        if peak > 0.0 {
+            max_drawdown_pct = max_drawdown_pct.max((peak - equity) / peak * 100.0);
+        }
+    }
+    MonteCarloSample {
+        final_equity: equity,
+        max_drawdown_pct,
+        recovery_trades,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reshuffle_keeps_final_equity_and_bounds_drawdown() {
+        let pnls = [100.0, -50.0, 80.0, -120.0, 60.0, 40.0];
+        let baseline = simulate(1_000.0, pnls.iter().copied());
+        assert_eq!(baseline.final_equity, 1_110.0);
+        assert_eq!(baseline.recovery_trades, 3);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let sequence = perturb(&[], &pnls, MonteCarloMethod::Reshuffle, &mut rng);
+            let sample = simulate(1_000.0, sequence.into_iter());
+            assert!((sample.final_equity - 1_110.0).abs() < 1e-9);
+            // Худший порядок — все убытки подряд от максимума
+            assert!(sample.max_drawdown_pct <= 170.0 / 1_000.0 * 100.0 + 1e-9);
+        }
+
+        let interval = ConfidenceInterval::from_values((1..=101).map(f64::from).collect(), 0.9);
+        assert_eq!(
+            (interval.lower, interval.median, interval.upper),
+            (6.0, 51.0, 96.0)
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::data_model::types::TimeFrame;
 use crate::discovery::types::{ConditionInfo, IndicatorInfo, NestedIndicator};
 use crate::discovery::StrategyCandidate;
 use crate::optimization::condition_id::ConditionId;
+use crate::optimization::rng::{self, OptimizationRng};
 use crate::strategy::types::ConditionOperator;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -16,11 +17,11 @@ use crate::optimization::utils::ConditionIdGenerator;
 
 pub struct ConditionBuilder<'a> {
     config: &'a CandidateBuilderConfig,
-    rng: &'a mut rand::rngs::ThreadRng,
+    rng: &'a mut OptimizationRng,
 }
 
 impl<'a> ConditionBuilder<'a> {
-    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut rand::rngs::ThreadRng) -> Self {
+    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut OptimizationRng) -> Self {
         Self { config, rng }
     }
 
@@ -807,7 +808,7 @@ impl<'a> ConditionBuilder<'a> {
         is_entry: bool,
         probabilities: &ConditionProbabilities,
     ) -> Option<ConditionInfo> {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
 
         let all_indicators: Vec<&IndicatorInfo> = candidate
             .indicators
@@ -885,7 +886,7 @@ impl<'a> ConditionBuilder<'a> {
         operator: &ConditionOperator,
         probabilities: &ConditionProbabilities,
         is_entry: bool,
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<(
         String,
         String,
@@ -924,7 +925,7 @@ impl<'a> ConditionBuilder<'a> {
         primary_indicator: &IndicatorInfo,
         operator: &ConditionOperator,
         is_entry: bool,
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<(
         String,
         String,
@@ -987,7 +988,7 @@ impl<'a> ConditionBuilder<'a> {
         primary_indicator: &IndicatorInfo,
         operator: &ConditionOperator,
         is_entry: bool,
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<(
         String,
         String,
@@ -1034,7 +1035,7 @@ impl<'a> ConditionBuilder<'a> {
         operator: &ConditionOperator,
         probabilities: &ConditionProbabilities,
         is_entry: bool,
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<(
         String,
         String,
@@ -1103,7 +1104,7 @@ impl<'a> ConditionBuilder<'a> {
         operator: &ConditionOperator,
         probabilities: &ConditionProbabilities,
         is_entry: bool,
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<(
         String,
         String,
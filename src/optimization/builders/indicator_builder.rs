@@ -4,14 +4,15 @@ use rand::Rng;
 use std::collections::HashSet;
 
 use crate::optimization::candidate_builder_config::{CandidateBuilderConfig, ElementProbabilities};
+use crate::optimization::rng::OptimizationRng;
 
 pub struct IndicatorBuilder<'a> {
     config: &'a CandidateBuilderConfig,
-    rng: &'a mut rand::rngs::ThreadRng,
+    rng: &'a mut OptimizationRng,
 }
 
 impl<'a> IndicatorBuilder<'a> {
-    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut rand::rngs::ThreadRng) -> Self {
+    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut OptimizationRng) -> Self {
         Self { config, rng }
     }
 
@@ -4,14 +4,15 @@ use rand::Rng;
 use std::collections::HashSet;
 
 use crate::optimization::candidate_builder_config::CandidateBuilderConfig;
+use crate::optimization::rng::OptimizationRng;
 
 pub struct StopHandlerBuilder<'a> {
     config: &'a CandidateBuilderConfig,
-    rng: &'a mut rand::rngs::ThreadRng,
+    rng: &'a mut OptimizationRng,
 }
 
 impl<'a> StopHandlerBuilder<'a> {
-    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut rand::rngs::ThreadRng) -> Self {
+    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut OptimizationRng) -> Self {
         Self { config, rng }
     }
 
@@ -115,7 +116,7 @@ impl<'a> StopHandlerBuilder<'a> {
     /// Выбирает случайный трендовый индикатор из доступных
     fn select_random_trend_indicator(
         available_indicators: &[IndicatorInfo],
-        rng: &mut rand::rngs::ThreadRng,
+        rng: &mut OptimizationRng,
     ) -> Option<String> {
         // Фильтруем только трендовые индикаторы
         let trend_indicators: Vec<&IndicatorInfo> = available_indicators
@@ -7,14 +7,15 @@ use crate::optimization::builders::ConditionBuilder;
 use crate::optimization::candidate_builder_config::{
     CandidateBuilderConfig, ElementConstraints, TimeframeProbabilities,
 };
+use crate::optimization::rng::OptimizationRng;
 
 pub struct TimeframeBuilder<'a> {
     config: &'a CandidateBuilderConfig,
-    rng: &'a mut rand::rngs::ThreadRng,
+    rng: &'a mut OptimizationRng,
 }
 
 impl<'a> TimeframeBuilder<'a> {
-    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut rand::rngs::ThreadRng) -> Self {
+    pub fn new(config: &'a CandidateBuilderConfig, rng: &'a mut OptimizationRng) -> Self {
         Self { config, rng }
     }
 
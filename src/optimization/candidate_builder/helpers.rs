@@ -3,6 +3,7 @@ use crate::data_model::types::TimeFrame;
 use crate::discovery::types::{
     ConditionInfo, IndicatorInfo, NestedIndicator, StopHandlerConfig, StopHandlerInfo,
 };
+use crate::optimization::rng::OptimizationRng;
 use crate::strategy::types::ConditionOperator;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -12,7 +13,9 @@ use super::super::build_rules_provider::{
     has_percent_of_price_threshold, is_oscillator_like, is_phase_1_allowed,
 };
 use super::super::builders::ConditionBuilder;
-use super::super::candidate_builder_config::{CandidateBuilderConfig, ElementProbabilities};
+use super::super::candidate_builder_config::{
+    CandidateBuilderConfig, ElementProbabilities, TakeHandlerProbabilities,
+};
 
 pub fn make_handler_params(
     config: &StopHandlerConfig,
@@ -43,7 +46,7 @@ pub fn select_single_indicator(
     exclude_aliases: &[String],
     is_phase_1: bool,
     config: &CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<IndicatorInfo> {
     let exclude_set: std::collections::HashSet<&str> =
         exclude_aliases.iter().map(|s| s.as_str()).collect();
@@ -81,7 +84,7 @@ pub fn select_single_stop_handler_required(
     available: &[StopHandlerConfig],
     available_indicators: &[IndicatorInfo],
     config: &CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<StopHandlerInfo> {
     let excluded_stop_handlers: std::collections::HashSet<&str> = config
         .rules
@@ -122,9 +125,38 @@ pub fn select_single_stop_handler_required(
     })
 }
 
+/// Выбирает конфигурацию тейка с учетом весов обработчиков. Вес делится на число
+/// конфигураций обработчика, чтобы многопараметрические тейки не выбирались чаще.
+pub fn select_take_handler_config<'a>(
+    available: &'a [StopHandlerConfig],
+    probabilities: &TakeHandlerProbabilities,
+    rng: &mut OptimizationRng,
+) -> Option<&'a StopHandlerConfig> {
+    let take_configs: Vec<&StopHandlerConfig> = available
+        .iter()
+        .filter(|c| c.stop_type == "take_profit")
+        .collect();
+
+    let mut configs_per_handler: std::collections::HashMap<&str, usize> =
+        std::collections::HashMap::new();
+    for config in &take_configs {
+        *configs_per_handler
+            .entry(config.handler_name.as_str())
+            .or_default() += 1;
+    }
+
+    take_configs
+        .choose_weighted(rng, |config| {
+            let count = configs_per_handler[config.handler_name.as_str()];
+            probabilities.weight(&config.handler_name) / count as f64
+        })
+        .ok()
+        .copied()
+}
+
 pub fn select_random_trend_indicator(
     available_indicators: &[IndicatorInfo],
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<String> {
     let trend_indicators: Vec<&IndicatorInfo> = available_indicators
         .iter()
@@ -144,7 +176,7 @@ pub fn build_condition_with_timeframe(
     is_entry: bool,
     timeframe: Option<TimeFrame>,
     config: &CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<ConditionInfo> {
     let operator = if rng.gen_bool(0.5) {
         ConditionOperator::Above
@@ -185,13 +217,13 @@ pub fn build_condition_with_timeframe(
     })
 }
 
-pub fn should_add(probability: f64, rng: &mut rand::rngs::ThreadRng) -> bool {
+pub fn should_add(probability: f64, rng: &mut OptimizationRng) -> bool {
     rng.gen_bool(probability.clamp(0.0, 1.0))
 }
 
 pub fn weighted_condition_type_choice(
     probabilities: &super::super::candidate_builder_config::ConditionProbabilities,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> &'static str {
     let w_price = probabilities.use_indicator_price_condition;
     let w_indicator = probabilities.use_indicator_indicator_condition;
@@ -215,7 +247,7 @@ pub fn weighted_condition_type_choice(
 
 pub fn weighted_choice_for_oscillator_based(
     probabilities: &super::super::candidate_builder_config::ConditionProbabilities,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> &'static str {
     let w_indicator = probabilities.use_indicator_indicator_condition;
     let w_trend = probabilities.use_trend_condition;
@@ -5,6 +5,7 @@ mod validator;
 
 use crate::data_model::types::TimeFrame;
 use crate::discovery::types::{ConditionInfo, IndicatorInfo, NestedIndicator, StopHandlerConfig};
+use crate::optimization::rng::{self, OptimizationRng};
 use rand::Rng;
 
 use super::builders::ConditionBuilder;
@@ -14,14 +15,14 @@ use super::candidate_builder_config::{
 
 pub struct CandidateBuilder {
     config: CandidateBuilderConfig,
-    rng: rand::rngs::ThreadRng,
+    rng: OptimizationRng,
 }
 
 impl CandidateBuilder {
     pub fn new(config: CandidateBuilderConfig) -> Self {
         Self {
             config,
-            rng: rand::thread_rng(),
+            rng: rng::rng(),
         }
     }
 
@@ -3,6 +3,7 @@ use crate::data_model::types::TimeFrame;
 use crate::discovery::types::{
     ConditionInfo, IndicatorInfo, NestedIndicator, StopHandlerConfig, StopHandlerInfo,
 };
+use crate::optimization::rng::OptimizationRng;
 use crate::strategy::types::ConditionOperator;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -20,7 +21,7 @@ pub fn build_phase_1(
     constraints: &ElementConstraints,
     probabilities: &ElementProbabilities,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     if !available_timeframes.is_empty() {
         candidate.timeframes.push(available_timeframes[0].clone());
@@ -88,12 +89,11 @@ pub fn build_phase_1(
         && candidate.take_handlers.len() < constraints.max_take_handlers
         && helpers::should_add(probabilities.take_handlers.add_take_profit, rng)
     {
-        let take_configs: Vec<&StopHandlerConfig> = available_stop_handlers
-            .iter()
-            .filter(|c| c.stop_type == "take_profit")
-            .collect();
-
-        if let Some(config_item) = take_configs.choose(rng) {
+        if let Some(config_item) = helpers::select_take_handler_config(
+            available_stop_handlers,
+            &probabilities.take_handlers,
+            rng,
+        ) {
             candidate.take_handlers.push(StopHandlerInfo {
                 id: format!("take_{}", rng.gen::<u32>()),
                 name: config_item.handler_name.clone(),
@@ -118,7 +118,7 @@ pub fn build_additional_phase(
     probabilities: &ElementProbabilities,
     _phase: usize,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> bool {
     let all_limits_reached = candidate.indicators.len() >= constraints.max_indicators
         && candidate.entry_conditions.len() >= constraints.max_entry_conditions
@@ -267,7 +267,7 @@ pub fn try_add_nested_indicator(
     candidate: &mut CandidateElements,
     available_indicators: &[IndicatorInfo],
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     let add_nested_prob = config.probabilities.nested_indicators.add_nested_indicator;
     let max_depth = config.probabilities.nested_indicators.max_nesting_depth;
@@ -355,7 +355,7 @@ pub fn build_condition_simple_with_timeframe(
     is_entry: bool,
     timeframe: Option<TimeFrame>,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<ConditionInfo> {
     use super::super::build_rules_provider::{
         has_absolute_threshold, has_percent_of_price_threshold,
@@ -564,7 +564,7 @@ pub fn build_condition(
     probabilities: &super::super::candidate_builder_config::ConditionProbabilities,
     is_entry: bool,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<ConditionInfo> {
     use super::super::build_rules_provider::{
         get_allowed_conditions, has_absolute_threshold, has_percent_of_price_threshold,
@@ -1,4 +1,5 @@
 use crate::discovery::types::{StopHandlerConfig, StopHandlerInfo};
+use crate::optimization::rng::OptimizationRng;
 use rand::Rng;
 
 use super::super::candidate_builder_config::{ElementSelector, RuleAction, RuleCondition};
@@ -10,7 +11,7 @@ pub fn apply_rules(
     available_stop_handlers: &[StopHandlerConfig],
     rules: &super::super::candidate_builder_config::BuildRules,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     for dependency in &rules.dependencies {
         if matches_selector(&dependency.trigger, candidate) {
@@ -100,7 +101,7 @@ pub fn apply_action(
     candidate: &mut CandidateElements,
     available_stop_handlers: &[StopHandlerConfig],
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     match action {
         RuleAction::Require { element, strict } => {
@@ -122,7 +123,7 @@ pub fn add_required_element(
     candidate: &mut CandidateElements,
     available_stop_handlers: &[StopHandlerConfig],
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     match selector {
         ElementSelector::TakeHandler { name } => {
@@ -1,5 +1,5 @@
 use crate::discovery::types::{ConditionInfo, IndicatorInfo, NestedIndicator, StopHandlerConfig, StopHandlerInfo};
-use rand::seq::SliceRandom;
+use crate::optimization::rng::OptimizationRng;
 use rand::Rng;
 
 use super::super::candidate_builder_config::ElementConstraints;
@@ -15,7 +15,7 @@ pub fn ensure_all_indicators_used(
     exit_conditions: &[ConditionInfo],
     constraints: &ElementConstraints,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     let all_indicators: Vec<&IndicatorInfo> = indicators
         .iter()
@@ -55,7 +55,7 @@ fn build_condition_simple(
     indicator: &IndicatorInfo,
     is_entry: bool,
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) -> Option<ConditionInfo> {
     phase_builder::build_condition_simple_with_timeframe(indicator, is_entry, None, config, rng)
 }
@@ -66,7 +66,7 @@ pub fn ensure_minimum_requirements(
     available_stop_handlers: &[StopHandlerConfig],
     available_indicators: &[IndicatorInfo],
     config: &super::super::candidate_builder_config::CandidateBuilderConfig,
-    rng: &mut rand::rngs::ThreadRng,
+    rng: &mut OptimizationRng,
 ) {
     while candidate.stop_handlers.len() < constraints.min_stop_handlers {
         if available_stop_handlers.is_empty() {
@@ -91,12 +91,11 @@ pub fn ensure_minimum_requirements(
     }
 
     while candidate.take_handlers.len() < constraints.min_take_handlers {
-        let take_configs: Vec<&StopHandlerConfig> = available_stop_handlers
-            .iter()
-            .filter(|c| c.stop_type == "take_profit")
-            .collect();
-
-        if let Some(config_item) = take_configs.choose(rng) {
+        if let Some(config_item) = helpers::select_take_handler_config(
+            available_stop_handlers,
+            &config.probabilities.take_handlers,
+            rng,
+        ) {
             candidate.take_handlers.push(StopHandlerInfo {
                 id: format!("take_{}", rng.gen::<u32>()),
                 name: config_item.handler_name.clone(),
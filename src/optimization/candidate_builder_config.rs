@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -110,12 +111,42 @@ impl Default for ConditionProbabilities {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TakeHandlerProbabilities {
     pub add_take_profit: f64,
+    /// Относительные веса выбора тейк-обработчиков по имени;
+    /// отсутствующие в карте обработчики получают вес 1.0
+    #[serde(default = "default_take_handler_weights")]
+    pub handler_weights: HashMap<String, f64>,
+}
+
+impl TakeHandlerProbabilities {
+    pub fn weight(&self, handler_name: &str) -> f64 {
+        self.handler_weights
+            .get(handler_name)
+            .copied()
+            .unwrap_or(1.0)
+            .max(0.0)
+    }
+}
+
+fn default_take_handler_weights() -> HashMap<String, f64> {
+    [
+        ("TakeProfitPct", 1.0),
+        ("TakeProfitLadder", 0.5),
+        ("TakeProfitATR", 1.0),
+        ("TakeProfitR", 1.0),
+        ("BollingerTake", 0.5),
+        ("KeltnerTake", 0.5),
+        ("TrailingTake", 0.75),
+    ]
+    .into_iter()
+    .map(|(name, weight)| (name.to_string(), weight))
+    .collect()
 }
 
 impl Default for TakeHandlerProbabilities {
     fn default() -> Self {
         Self {
             add_take_profit: 0.6,
+            handler_weights: default_take_handler_weights(),
         }
     }
 }
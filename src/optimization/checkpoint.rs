@@ -0,0 +1,350 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::optimization::evolution::{EvolutionManager, EvolutionState};
+use crate::optimization::island::IslandManager;
+use crate::optimization::rng::{self, RngState};
+use crate::optimization::types::{GeneticAlgorithmConfig, Population};
+
+pub const DEFAULT_CHECKPOINT_PATH: &str = "./data/checkpoints/optimization.bson";
+
+/// Снимок островной эволюции между поколениями. `GeneticAlgorithmV3` между
+/// поколениями хранит только кеш оценок, который не влияет на результат,
+/// поэтому в чекпоинт входят острова, детектор застоя и генератор случайных чисел.
+/// Пишется в BSON: числа с плавающей точкой, включая бесконечности, сохраняются точно.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OptimizationCheckpoint {
+    /// Поколение, с которого продолжается эволюция
+    pub next_generation: usize,
+    pub islands: Vec<Population>,
+    pub evolution: EvolutionState,
+    pub rng: RngState,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl OptimizationCheckpoint {
+    pub fn capture(
+        next_generation: usize,
+        island_manager: &IslandManager,
+        evolution_manager: &EvolutionManager,
+    ) -> Self {
+        Self {
+            next_generation,
+            islands: island_manager.get_all_islands().to_vec(),
+            evolution: evolution_manager.state(),
+            rng: rng::state(),
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// Восстанавливает менеджеры и генератор случайных чисел текущего потока
+    pub fn restore(self, config: &GeneticAlgorithmConfig) -> (IslandManager, EvolutionManager) {
+        rng::restore(&self.rng);
+        let mut evolution_manager = EvolutionManager::new(config.clone());
+        evolution_manager.restore_state(self.evolution);
+        (
+            IslandManager::new(config.clone(), self.islands),
+            evolution_manager,
+        )
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Не удалось создать каталог {}", dir.display()))?;
+        }
+        let bytes = mongodb::bson::to_vec(self)?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes =
+            fs::read(path).with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+        mongodb::bson::from_slice(&bytes)
+            .with_context(|| format!("Поврежденный чекпоинт {}", path.display()))
+    }
+}
+
+/// Сохранять ли чекпоинт после поколения `generation` (счет с нуля)
+pub fn should_checkpoint(config: &GeneticAlgorithmConfig, generation: usize) -> bool {
+    config.checkpoint_interval > 0 && (generation + 1).is_multiple_of(config.checkpoint_interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::quote::Quote;
+    use crate::data_model::quote_frame::QuoteFrame;
+    use crate::data_model::types::{Symbol, TimeFrame};
+    use crate::discovery::types::IndicatorInfo;
+    use crate::discovery::{StrategyCandidate, StrategyDiscoveryConfig};
+    use crate::metrics::backtest::{BacktestMetrics, BacktestReport};
+    use crate::optimization::genetic::GeneticAlgorithmV3;
+    use crate::optimization::initial_population::InitialPopulationGenerator;
+    use crate::optimization::migration::MigrationSystem;
+    use crate::optimization::population::{PopulationConfig, PopulationManager};
+    use crate::optimization::types::{EvaluatedStrategy, GeneticIndividual};
+    use crate::strategy::types::{StrategyParamValue, StrategyParameterMap};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn individual(period: f64, fitness: f64) -> GeneticIndividual {
+        let mut parameters = HashMap::new();
+        for name in ["period", "fast", "slow", "threshold", "offset"] {
+            parameters.insert(name.to_string(), StrategyParamValue::Number(period));
+        }
+        let metrics = BacktestMetrics {
+            total_profit: fitness * 100.0,
+            profit_factor: Some(f64::INFINITY),
+            ..BacktestMetrics::default()
+        };
+        GeneticIndividual {
+            strategy: EvaluatedStrategy {
+                candidate: Some(StrategyCandidate {
+                    indicators: vec![IndicatorInfo {
+                        name: "SMA".to_string(),
+                        alias: "sma".to_string(),
+                        parameters: vec![],
+                        can_use_indicator_input: false,
+                        input_type: "price".to_string(),
+                        indicator_type: "trend".to_string(),
+                    }],
+                    nested_indicators: vec![],
+                    conditions: vec![],
+                    exit_conditions: vec![],
+                    stop_handlers: vec![],
+                    take_handlers: vec![],
+                    timeframes: vec![],
+                    config: crate::discovery::config::StrategyDiscoveryConfig::default(),
+                }),
+                parameters,
+                fitness: Some(fitness),
+                backtest_report: Some(BacktestReport::new(vec![], metrics, vec![1_000.0])),
+            },
+            generation: 3,
+            island_id: Some(0),
+        }
+    }
+
+    fn breed(
+        manager: &PopulationManager,
+        population: &Population,
+    ) -> Vec<Option<(StrategyParameterMap, StrategyParameterMap)>> {
+        (0..10)
+            .map(|_| {
+                let parents = manager.select_parents(population, 2);
+                manager.crossover(parents[0], parents[1])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resumed_run_repeats_random_choices() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.bson");
+        let config = GeneticAlgorithmConfig {
+            islands_count: 1,
+            ..GeneticAlgorithmConfig::default()
+        };
+        let population = Population {
+            individuals: vec![individual(10.0, 1.0), individual(20.0, 2.0)],
+            generation: 3,
+            island_id: Some(0),
+        };
+        let manager = PopulationManager::new(PopulationConfig {
+            size: 2,
+            elitism_count: 0,
+            crossover_rate: 1.0,
+            mutation_rate: 0.0,
+        });
+
+        rng::seed(42);
+        let island_manager = IslandManager::new(config.clone(), vec![population]);
+        let mut evolution_manager = EvolutionManager::new(config.clone());
+        evolution_manager.update_fitness_history(2.0);
+        OptimizationCheckpoint::capture(4, &island_manager, &evolution_manager)
+            .save(&path)
+            .unwrap();
+        let expected = breed(&manager, &island_manager.get_all_islands()[0]);
+
+        rng::seed(7);
+        let checkpoint = OptimizationCheckpoint::load(&path).unwrap();
+        assert_eq!(checkpoint.next_generation, 4);
+        let (islands, evolution) = checkpoint.restore(&config);
+        assert_eq!(evolution.state(), evolution_manager.state());
+        let restored = &islands.get_all_islands()[0];
+        let report = restored.individuals[0]
+            .strategy
+            .backtest_report
+            .as_ref()
+            .unwrap();
+        assert_eq!(report.metrics.profit_factor, Some(f64::INFINITY));
+        assert_eq!(breed(&manager, restored), expected);
+    }
+
+    fn frames() -> HashMap<TimeFrame, QuoteFrame> {
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::minutes(60);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for i in 0..300 {
+            let close = 100.0 + 10.0 * (i as f32 / 12.0).sin() + i as f32 * 0.05;
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start + chrono::Duration::hours(i),
+                close - 1.0,
+                close + 1.0,
+                close - 0.5,
+                close,
+                1000.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        HashMap::from([(timeframe, frame)])
+    }
+
+    /// Поколения `generations` основного цикла: эволюция островов и миграция
+    async fn evolve(
+        config: &GeneticAlgorithmConfig,
+        frames: &HashMap<TimeFrame, QuoteFrame>,
+        island_manager: &mut IslandManager,
+        evolution_manager: &mut EvolutionManager,
+        generations: std::ops::Range<usize>,
+    ) {
+        let mut genetic_algorithm = GeneticAlgorithmV3::new(
+            config.clone(),
+            frames.clone(),
+            TimeFrame::minutes(60),
+            StrategyDiscoveryConfig::default(),
+        );
+        let migration_system = MigrationSystem::new(config.clone());
+        for generation in generations {
+            for island in island_manager.get_all_islands_mut().iter_mut() {
+                genetic_algorithm.evolve_generation(island).await.unwrap();
+                let best = island
+                    .individuals
+                    .iter()
+                    .filter_map(|individual| individual.strategy.fitness)
+                    .fold(0.0, f64::max);
+                evolution_manager.update_fitness_history(best);
+            }
+            if (generation + 1) % config.migration_interval == 0 {
+                migration_system
+                    .migrate_between_islands(island_manager.get_all_islands_mut())
+                    .unwrap();
+            }
+        }
+    }
+
+    /// Результат эволюции без отчётов бэктеста: id стратегии в них зависит от времени
+    fn outcome(
+        island_manager: &IslandManager,
+    ) -> Vec<Vec<(StrategyParameterMap, Option<f64>, serde_json::Value, usize)>> {
+        island_manager
+            .get_all_islands()
+            .iter()
+            .map(|population| {
+                population
+                    .individuals
+                    .iter()
+                    .map(|individual| {
+                        (
+                            individual.strategy.parameters.clone(),
+                            individual.strategy.fitness,
+                            serde_json::to_value(&individual.strategy.candidate).unwrap(),
+                            individual.generation,
+                        )
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn resumed_evolution_matches_uninterrupted_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.bson");
+        let config = GeneticAlgorithmConfig {
+            population_size: 6,
+            lambda_size: 6,
+            max_generations: 4,
+            elitism_count: 1,
+            islands_count: 2,
+            migration_interval: 2,
+            migration_rate: 0.2,
+            param_variants_per_candidate: 2,
+            filter_initial_population: false,
+            evaluation_threads: 2,
+            ..GeneticAlgorithmConfig::default()
+        };
+        let frames = frames();
+
+        rng::seed(11);
+        let generator = InitialPopulationGenerator::with_discovery_config(
+            config.clone(),
+            frames.clone(),
+            TimeFrame::minutes(60),
+            StrategyDiscoveryConfig::default(),
+        );
+        let mut islands = Vec::new();
+        for island_id in 0..config.islands_count {
+            let mut population = generator.generate(None).await.unwrap();
+            population.island_id = Some(island_id);
+            islands.push(population);
+        }
+        let initial_state = rng::state();
+
+        let mut island_manager = IslandManager::new(config.clone(), islands.clone());
+        let mut evolution_manager = EvolutionManager::new(config.clone());
+        evolve(
+            &config,
+            &frames,
+            &mut island_manager,
+            &mut evolution_manager,
+            0..4,
+        )
+        .await;
+        let uninterrupted = outcome(&island_manager);
+
+        rng::restore(&initial_state);
+        let mut island_manager = IslandManager::new(config.clone(), islands);
+        let mut evolution_manager = EvolutionManager::new(config.clone());
+        evolve(
+            &config,
+            &frames,
+            &mut island_manager,
+            &mut evolution_manager,
+            0..2,
+        )
+        .await;
+        OptimizationCheckpoint::capture(2, &island_manager, &evolution_manager)
+            .save(&path)
+            .unwrap();
+
+        rng::seed(99);
+        let checkpoint = OptimizationCheckpoint::load(&path).unwrap();
+        let first_generation = checkpoint.next_generation;
+        let (mut island_manager, mut evolution_manager) = checkpoint.restore(&config);
+        evolve(
+            &config,
+            &frames,
+            &mut island_manager,
+            &mut evolution_manager,
+            first_generation..4,
+        )
+        .await;
+        let resumed = outcome(&island_manager);
+
+        assert_eq!(resumed, uninterrupted);
+    }
+}
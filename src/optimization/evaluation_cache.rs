@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use arrow::array::{Array, BinaryArray, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use crate::metrics::backtest::BacktestReport;
+
+pub const DEFAULT_EVALUATION_CACHE_DIR: &str = "./data/evaluation_cache";
+
+/// Кеш оценок стратегий на диске: Parquet-файлы с колонками `key` и `report` (BSON,
+/// чтобы бесконечные метрики вроде profit factor без убытков читались без потерь).
+/// Новые записи копятся в памяти и пишутся отдельным файлом при `flush`;
+/// при открытии несколько файлов сливаются в один.
+pub struct PersistentEvaluationCache {
+    dir: PathBuf,
+    entries: HashMap<String, BacktestReport>,
+    pending: Vec<String>,
+}
+
+impl PersistentEvaluationCache {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Не удалось создать каталог кеша {}", dir.display()))?;
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+            .collect();
+        files.sort();
+
+        let mut cache = Self {
+            dir,
+            entries: HashMap::new(),
+            pending: Vec::new(),
+        };
+        for file in &files {
+            cache
+                .load_file(file)
+                .with_context(|| format!("Не удалось прочитать кеш {}", file.display()))?;
+        }
+
+        if files.len() > 1 {
+            cache.pending = cache.entries.keys().cloned().collect();
+            cache.flush()?;
+            for file in files {
+                fs::remove_file(file)?;
+            }
+        }
+        Ok(cache)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Число записей, еще не сохраненных на диск
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BacktestReport> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, report: BacktestReport) {
+        if self.entries.insert(key.clone(), report).is_none() {
+            self.pending.push(key);
+        }
+    }
+
+    /// Записывает накопленные записи в новый файл и возвращает их число
+    pub fn flush(&mut self) -> Result<usize> {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let mut reports = Vec::with_capacity(self.pending.len());
+        for key in &self.pending {
+            reports.push(mongodb::bson::to_vec(&self.entries[key])?);
+        }
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("key", DataType::Utf8, false),
+            Field::new("report", DataType::Binary, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from_iter_values(&self.pending)),
+                Arc::new(BinaryArray::from_iter_values(&reports)),
+            ],
+        )?;
+
+        let name = format!(
+            "part-{}-{}.parquet",
+            chrono::Utc::now().timestamp_micros(),
+            std::process::id()
+        );
+        let tmp_path = self.dir.join(format!("{}.tmp", name));
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(&tmp_path)?, schema, Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        fs::rename(&tmp_path, self.dir.join(name))?;
+
+        let written = self.pending.len();
+        self.pending.clear();
+        Ok(written)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<()> {
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+        for batch in reader {
+            let batch = batch?;
+            let Some(keys) = batch
+                .column_by_name("key")
+                .and_then(|column| column.as_any().downcast_ref::<StringArray>())
+            else {
+                anyhow::bail!("Нет колонки key");
+            };
+            // Файлы старого формата пропускаются, их записи будут пересчитаны
+            let Some(reports) = batch
+                .column_by_name("report")
+                .and_then(|column| column.as_any().downcast_ref::<BinaryArray>())
+            else {
+                continue;
+            };
+            for row in 0..batch.num_rows() {
+                if keys.is_null(row) || reports.is_null(row) {
+                    continue;
+                }
+                if let Ok(report) = mongodb::bson::from_slice(reports.value(row)) {
+                    self.entries.insert(keys.value(row).to_string(), report);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::backtest::BacktestMetrics;
+
+    #[test]
+    fn reports_survive_reopen_and_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = |profit: f64| {
+            let metrics = BacktestMetrics {
+                total_profit: profit,
+                profit_factor: Some(f64::INFINITY),
+                ..BacktestMetrics::default()
+            };
+            BacktestReport::new(vec![], metrics, vec![1_000.0, 1_000.0 + profit])
+        };
+
+        let mut cache = PersistentEvaluationCache::open(dir.path()).unwrap();
+        cache.insert("a".to_string(), report(10.0));
+        assert_eq!(cache.flush().unwrap(), 1);
+        cache.insert("b".to_string(), report(-5.0));
+        cache.insert("a".to_string(), report(10.0));
+        assert_eq!(cache.flush().unwrap(), 1);
+
+        let reopened = PersistentEvaluationCache::open(dir.path()).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.pending(), 0);
+        assert_eq!(reopened.get("b").unwrap().metrics.total_profit, -5.0);
+        assert_eq!(
+            reopened.get("b").unwrap().metrics.profit_factor,
+            Some(f64::INFINITY)
+        );
+        assert_eq!(
+            reopened.get("a").unwrap().equity_curve,
+            vec![1_000.0, 1_010.0]
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
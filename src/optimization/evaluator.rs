@@ -1,14 +1,17 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 
 use crate::backtest::{BacktestConfig, BacktestEngine};
 use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::discovery::{StrategyCandidate, StrategyConverter};
 use crate::metrics::backtest::BacktestReport;
+use crate::optimization::evaluation_cache::PersistentEvaluationCache;
+use crate::optimization::fingerprint;
 use crate::strategy::types::StrategyParameterMap;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct CacheKey {
@@ -21,44 +24,40 @@ impl CacheKey {
         candidate: &StrategyCandidate,
         parameters: &StrategyParameterMap,
     ) -> Self {
-        let candidate_sig = Self::candidate_signature(candidate);
-        let params_sig = Self::parameters_signature(parameters);
         Self {
-            candidate_signature: candidate_sig,
-            parameters_signature: params_sig,
+            candidate_signature: fingerprint::strategy_fingerprint(candidate),
+            parameters_signature: fingerprint::parameters_fingerprint(parameters),
         }
     }
 
-    fn candidate_signature(candidate: &StrategyCandidate) -> String {
-        let mut parts = Vec::with_capacity(4);
-        parts.push(format!("indicators:{}", candidate.indicators.len()));
-        parts.push(format!("nested:{}", candidate.nested_indicators.len()));
-        parts.push(format!("conditions:{}", candidate.conditions.len()));
-        parts.push(format!("timeframes:{}", candidate.timeframes.len()));
-        parts.sort();
-        parts.join("|")
+    /// Ключ дискового кеша: оценка зависит еще от котировок и настроек backtest
+    fn persistent_key(&self, data_fingerprint: &str, config_fingerprint: &str) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            data_fingerprint,
+            config_fingerprint,
+            self.candidate_signature,
+            self.parameters_signature
+        )
     }
+}
 
-    fn parameters_signature(parameters: &StrategyParameterMap) -> String {
-        let mut pairs: Vec<(String, String)> = parameters
-            .iter()
-            .map(|(k, v)| (k.clone(), format!("{:?}", v)))
-            .collect();
-        pairs.sort_by(|a, b| a.0.cmp(&b.0));
-        pairs
-            .into_iter()
-            .map(|(k, v)| format!("{}:{}", k, v))
-            .collect::<Vec<_>>()
-            .join("|")
-    }
+struct PersistentCache {
+    store: RwLock<PersistentEvaluationCache>,
+    data_fingerprint: String,
 }
 
+/// Сколько новых оценок копится до записи дискового кеша в `evaluate_strategy`
+const PERSISTENT_FLUSH_THRESHOLD: usize = 64;
+
 pub struct StrategyEvaluationRunner {
-    frames: Arc<HashMap<TimeFrame, QuoteFrame>>,
+    frames: Arc<HashMap<TimeFrame, Arc<QuoteFrame>>>,
     base_timeframe: TimeFrame,
     available_higher_timeframes: Vec<TimeFrame>,
     cache: Arc<RwLock<HashMap<CacheKey, BacktestReport>>>,
     backtest_config: BacktestConfig,
+    evaluation_threads: usize,
+    persistent_cache: Option<Arc<PersistentCache>>,
 }
 
 impl StrategyEvaluationRunner {
@@ -81,15 +80,31 @@ impl StrategyEvaluationRunner {
         base_timeframe: TimeFrame,
         available_higher_timeframes: Vec<TimeFrame>,
     ) -> Self {
+        let frames = frames
+            .into_iter()
+            .map(|(timeframe, frame)| (timeframe, Arc::new(frame)))
+            .collect();
         Self {
             frames: Arc::new(frames),
             base_timeframe,
             available_higher_timeframes,
             cache: Arc::new(RwLock::new(HashMap::new())),
             backtest_config: BacktestConfig::default(),
+            evaluation_threads: num_cpus::get(),
+            persistent_cache: None,
         }
     }
 
+    /// Число одновременно выполняемых backtest в `evaluate_batch`
+    pub fn with_evaluation_threads(mut self, threads: usize) -> Self {
+        self.evaluation_threads = threads.max(1);
+        self
+    }
+
+    pub fn evaluation_threads(&self) -> usize {
+        self.evaluation_threads
+    }
+
     pub fn with_backtest_config(mut self, config: BacktestConfig) -> Self {
         self.backtest_config = config;
         self
@@ -99,42 +114,170 @@ impl StrategyEvaluationRunner {
         self.backtest_config = config;
     }
 
+    /// Подключает дисковый кеш оценок в `dir`, общий для повторных запусков
+    /// на тех же котировках
+    pub fn set_persistent_cache(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let store = PersistentEvaluationCache::open(dir)?;
+        let data_fingerprint = fingerprint::data_fingerprint(
+            self.frames
+                .iter()
+                .map(|(timeframe, frame)| (timeframe, frame.as_ref())),
+        );
+        self.persistent_cache = Some(Arc::new(PersistentCache {
+            store: RwLock::new(store),
+            data_fingerprint,
+        }));
+        Ok(())
+    }
+
+    /// Сохраняет на диск оценки, накопленные в дисковом кеше
+    pub async fn flush_persistent_cache(&self) -> Result<usize> {
+        match &self.persistent_cache {
+            Some(persistent) => persistent.store.write().await.flush(),
+            None => Ok(0),
+        }
+    }
+
+    fn config_fingerprint(&self) -> String {
+        fingerprint::config_fingerprint(&self.backtest_config)
+    }
+
     pub async fn evaluate_strategy(
         &self,
         candidate: &StrategyCandidate,
         parameters: StrategyParameterMap,
     ) -> Result<BacktestReport> {
         let cache_key = CacheKey::from_candidate_and_params(candidate, &parameters);
+        let config_fingerprint = self.config_fingerprint();
+
+        if let Some(cached_report) = self.cached(&cache_key, &config_fingerprint).await {
+            return Ok(cached_report);
+        }
 
-        {
-            let cache = self.cache.read().await;
-            if let Some(cached_report) = cache.get(&cache_key) {
-                return Ok(cached_report.clone());
+        let report = self.run_backtest(candidate, parameters)?;
+        self.store(vec![(cache_key, report.clone())], &config_fingerprint)
+            .await;
+        if let Some(persistent) = &self.persistent_cache {
+            let mut store = persistent.store.write().await;
+            if store.pending() >= PERSISTENT_FLUSH_THRESHOLD {
+                store.flush()?;
             }
         }
 
-        let definition =
-            StrategyConverter::candidate_to_definition(candidate, self.base_timeframe.clone())
-                .context("Не удалось конвертировать StrategyCandidate в StrategyDefinition")?;
+        Ok(report)
+    }
 
-        let mut frames_clone = HashMap::with_capacity(self.frames.len());
-        for (k, v) in self.frames.iter() {
-            frames_clone.insert(k.clone(), v.clone());
+    async fn cached(
+        &self,
+        cache_key: &CacheKey,
+        config_fingerprint: &str,
+    ) -> Option<BacktestReport> {
+        if let Some(cached_report) = self.cache.read().await.get(cache_key) {
+            return Some(cached_report.clone());
         }
+        let persistent = self.persistent_cache.as_ref()?;
+        let key = cache_key.persistent_key(&persistent.data_fingerprint, config_fingerprint);
+        let report = persistent.store.read().await.get(&key).cloned()?;
+        self.cache
+            .write()
+            .await
+            .insert(cache_key.clone(), report.clone());
+        Some(report)
+    }
 
-        let mut executor =
-            BacktestEngine::from_definition(definition, Some(parameters.clone()), frames_clone)
-                .context("Не удалось создать BacktestEngine")?
-                .with_config(self.backtest_config.clone());
+    async fn store(&self, reports: Vec<(CacheKey, BacktestReport)>, config_fingerprint: &str) {
+        if let Some(persistent) = &self.persistent_cache {
+            let mut store = persistent.store.write().await;
+            for (cache_key, report) in &reports {
+                store.insert(
+                    cache_key.persistent_key(&persistent.data_fingerprint, config_fingerprint),
+                    report.clone(),
+                );
+            }
+        }
+        let mut cache = self.cache.write().await;
+        for (cache_key, report) in reports {
+            cache.insert(cache_key, report);
+        }
+    }
 
-        let report = executor.run().context("Ошибка выполнения backtest")?;
+    /// Оценивает стратегии параллельно, не более `evaluation_threads` одновременно.
+    /// Результаты возвращаются в порядке `jobs`.
+    pub async fn evaluate_batch(
+        &self,
+        jobs: Vec<(StrategyCandidate, StrategyParameterMap)>,
+    ) -> Vec<Result<BacktestReport>> {
+        let config_fingerprint = self.config_fingerprint();
+        let mut results: Vec<Option<Result<BacktestReport>>> = Vec::with_capacity(jobs.len());
+        let mut pending = Vec::new();
+        for (index, (candidate, parameters)) in jobs.into_iter().enumerate() {
+            let cache_key = CacheKey::from_candidate_and_params(&candidate, &parameters);
+            match self.cached(&cache_key, &config_fingerprint).await {
+                Some(cached_report) => results.push(Some(Ok(cached_report))),
+                None => {
+                    results.push(None);
+                    pending.push((index, cache_key, candidate, parameters));
+                }
+            }
+        }
 
-        {
-            let mut cache = self.cache.write().await;
-            cache.insert(cache_key, report.clone());
+        let semaphore = Arc::new(Semaphore::new(self.evaluation_threads));
+        let tasks = pending
+            .into_iter()
+            .map(|(index, cache_key, candidate, parameters)| {
+                let runner = self.clone();
+                let semaphore = Arc::clone(&semaphore);
+                async move {
+                    let report = match semaphore.acquire_owned().await {
+                        Ok(_permit) => tokio::task::spawn_blocking(move || {
+                            runner.run_backtest(&candidate, parameters)
+                        })
+                        .await
+                        .map_err(|e| anyhow!("Поток оценки стратегии завершился с ошибкой: {}", e))
+                        .and_then(|report| report),
+                        Err(e) => Err(anyhow!("Пул оценки стратегий закрыт: {}", e)),
+                    };
+                    (index, cache_key, report)
+                }
+            });
+        let finished = futures::future::join_all(tasks).await;
+
+        let mut evaluated = Vec::with_capacity(finished.len());
+        for (index, cache_key, report) in finished {
+            if let Ok(report) = &report {
+                evaluated.push((cache_key, report.clone()));
+            }
+            results[index] = Some(report);
+        }
+        self.store(evaluated, &config_fingerprint).await;
+        if let Err(e) = self.flush_persistent_cache().await {
+            eprintln!("      ⚠️  Не удалось сохранить кеш оценок: {:?}", e);
         }
 
-        Ok(report)
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(anyhow!("Стратегия не была оценена"))))
+            .collect()
+    }
+
+    fn run_backtest(
+        &self,
+        candidate: &StrategyCandidate,
+        parameters: StrategyParameterMap,
+    ) -> Result<BacktestReport> {
+        let definition =
+            StrategyConverter::candidate_to_definition(candidate, self.base_timeframe.clone())
+                .context("Не удалось конвертировать StrategyCandidate в StrategyDefinition")?;
+
+        let mut executor = BacktestEngine::from_definition_shared(
+            definition,
+            Some(parameters),
+            self.frames.as_ref().clone(),
+        )
+        .context("Не удалось создать BacktestEngine")?
+        .with_config(self.backtest_config.clone());
+
+        executor.run().context("Ошибка выполнения backtest")
     }
 }
 
@@ -146,6 +289,8 @@ impl Clone for StrategyEvaluationRunner {
             available_higher_timeframes: self.available_higher_timeframes.clone(),
             cache: Arc::clone(&self.cache),
             backtest_config: self.backtest_config.clone(),
+            evaluation_threads: self.evaluation_threads,
+            persistent_cache: self.persistent_cache.clone(),
         }
     }
 }
@@ -187,20 +332,19 @@ mod tests {
     }
 
     #[test]
-    fn test_cache_key_candidate_signature() {
+    fn test_cache_key_parameters_order_independent() {
         let candidate = create_test_candidate();
-        let sig = CacheKey::candidate_signature(&candidate);
-        assert!(sig.contains("indicators:0"));
-        assert!(sig.contains("conditions:0"));
-        assert!(sig.contains("timeframes:0"));
-    }
-
-    #[test]
-    fn test_cache_key_parameters_signature() {
         let params = create_test_parameters();
-        let sig = CacheKey::parameters_signature(&params);
-        assert!(sig.contains("param1"));
-        assert!(sig.contains("param2"));
+        let mut reordered = StrategyParameterMap::new();
+        reordered.insert("param2".to_string(), StrategyParamValue::Number(20.0));
+        reordered.insert("param1".to_string(), StrategyParamValue::Number(10.0));
+        let key1 = CacheKey::from_candidate_and_params(&candidate, &params);
+        let key2 = CacheKey::from_candidate_and_params(&candidate, &reordered);
+        assert_eq!(key1, key2);
+
+        reordered.insert("param2".to_string(), StrategyParamValue::Number(21.0));
+        let key3 = CacheKey::from_candidate_and_params(&candidate, &reordered);
+        assert_ne!(key1, key3);
     }
 
     #[test]
@@ -288,5 +432,73 @@ mod tests {
         let runner1 = StrategyEvaluationRunner::new(frames, base_tf);
         let runner2 = runner1.clone();
         assert_eq!(runner1.base_timeframe, runner2.base_timeframe);
+        assert!(Arc::ptr_eq(&runner1.frames, &runner2.frames));
+    }
+
+    #[tokio::test]
+    async fn test_persistent_cache_survives_new_runner() {
+        let dir = tempfile::tempdir().unwrap();
+        let candidate = create_test_candidate();
+        let params = create_test_parameters();
+
+        let mut runner =
+            StrategyEvaluationRunner::new(create_test_frames(), TimeFrame::from_identifier("60"));
+        runner.set_persistent_cache(dir.path()).unwrap();
+        let report = runner
+            .evaluate_strategy(&candidate, params.clone())
+            .await
+            .unwrap();
+        assert_eq!(runner.flush_persistent_cache().await.unwrap(), 1);
+
+        let mut reopened =
+            StrategyEvaluationRunner::new(create_test_frames(), TimeFrame::from_identifier("60"));
+        reopened.set_persistent_cache(dir.path()).unwrap();
+        let cache_key = CacheKey::from_candidate_and_params(&candidate, &params);
+        let cached = reopened
+            .cached(&cache_key, &reopened.config_fingerprint())
+            .await
+            .unwrap();
+        assert_eq!(cached.metrics.total_bars, report.metrics.total_bars);
+
+        let mut other_config =
+            StrategyEvaluationRunner::new(create_test_frames(), TimeFrame::from_identifier("60"))
+                .with_backtest_config(BacktestConfig {
+                    initial_capital: 5_000.0,
+                    ..BacktestConfig::default()
+                });
+        other_config.set_persistent_cache(dir.path()).unwrap();
+        assert!(other_config
+            .cached(&cache_key, &other_config.config_fingerprint())
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_batch_keeps_job_order() {
+        let runner =
+            StrategyEvaluationRunner::new(create_test_frames(), TimeFrame::from_identifier("60"))
+                .with_evaluation_threads(2);
+        let candidate = create_test_candidate();
+        let cached_params = create_test_parameters();
+        let mut cached_report = BacktestReport::new(vec![], Default::default(), vec![]);
+        cached_report.metrics.total_bars = 42;
+        runner.cache.write().await.insert(
+            CacheKey::from_candidate_and_params(&candidate, &cached_params),
+            cached_report,
+        );
+
+        let results = runner
+            .evaluate_batch(vec![
+                (candidate.clone(), HashMap::new()),
+                (candidate.clone(), cached_params),
+                (candidate, HashMap::new()),
+            ])
+            .await;
+
+        let total_bars: Vec<usize> = results
+            .iter()
+            .map(|result| result.as_ref().unwrap().metrics.total_bars)
+            .collect();
+        assert_eq!(total_bars, vec![0, 42, 0]);
     }
 }
@@ -1,4 +1,12 @@
 use crate::optimization::types::GeneticAlgorithmConfig;
+use serde::{Deserialize, Serialize};
+
+/// Состояние детектора застоя для чекпоинта
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EvolutionState {
+    pub stagnation_counter: usize,
+    pub best_fitness_history: Vec<f64>,
+}
 
 pub struct EvolutionManager {
     config: GeneticAlgorithmConfig,
@@ -49,6 +57,18 @@ impl EvolutionManager {
         self.stagnation_counter = 0;
         self.best_fitness_history.clear();
     }
+
+    pub fn state(&self) -> EvolutionState {
+        EvolutionState {
+            stagnation_counter: self.stagnation_counter,
+            best_fitness_history: self.best_fitness_history.clone(),
+        }
+    }
+
+    pub fn restore_state(&mut self, state: EvolutionState) {
+        self.stagnation_counter = state.stagnation_counter;
+        self.best_fitness_history = state.best_fitness_history;
+    }
 }
 
 #[cfg(test)]
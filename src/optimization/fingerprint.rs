@@ -0,0 +1,308 @@
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+
+use crate::backtest::{BacktestConfig, EquityMode, SessionPolicy};
+use crate::data_model::costs::CostModel;
+use crate::data_model::quote_frame::QuoteFrame;
+use crate::data_model::types::TimeFrame;
+use crate::discovery::types::{
+    ConditionInfo, ConditionParamInfo, IndicatorInfo, NestedIndicator, StopHandlerInfo,
+};
+use crate::discovery::StrategyCandidate;
+use crate::metrics::{BenchmarkSource, EquityRiskConfig};
+use crate::position::{FillPolicy, MarginModel, PositionSizerSpec};
+use crate::risk::{IntrabarConfig, RiskGuardConfig};
+use crate::strategy::types::StrategyParameterMap;
+
+/// Канонический хеш структуры стратегии: не зависит от порядка индикаторов,
+/// условий, обработчиков и таймфреймов, но различает их параметры и операнды
+pub fn strategy_fingerprint(candidate: &StrategyCandidate) -> String {
+    let sections = [
+        sorted(candidate.indicators.iter().map(indicator_part)),
+        sorted(candidate.nested_indicators.iter().map(nested_part)),
+        sorted(candidate.conditions.iter().map(condition_part)),
+        sorted(candidate.exit_conditions.iter().map(condition_part)),
+        sorted(candidate.stop_handlers.iter().map(handler_part)),
+        sorted(candidate.take_handlers.iter().map(handler_part)),
+        sorted(candidate.timeframes.iter().map(TimeFrame::identifier)),
+        vec![serde_json::to_string(&candidate.config).unwrap_or_default()],
+    ];
+    let mut hasher = Sha1::new();
+    for section in sections {
+        hasher.update(section.join(";").as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Хеш значений параметров, не зависящий от порядка ключей
+pub fn parameters_fingerprint(parameters: &StrategyParameterMap) -> String {
+    let pairs = sorted(
+        parameters
+            .iter()
+            .map(|(name, value)| format!("{}={:?}", name, value)),
+    );
+    let mut hasher = Sha1::new();
+    hasher.update(pairs.join("|").as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Хеш котировок: символы, таймфреймы и все бары
+pub fn data_fingerprint<'a>(
+    frames: impl IntoIterator<Item = (&'a TimeFrame, &'a QuoteFrame)>,
+) -> String {
+    let mut frames: Vec<(&TimeFrame, &QuoteFrame)> = frames.into_iter().collect();
+    frames.sort_by_key(|(timeframe, _)| timeframe.identifier());
+    let mut hasher = Sha1::new();
+    for (timeframe, frame) in frames {
+        hasher.update(frame.symbol().descriptor().as_bytes());
+        hasher.update(timeframe.identifier().as_bytes());
+        hasher.update((frame.len() as u64).to_le_bytes());
+        for quote in frame.iter() {
+            hasher.update(quote.timestamp_millis().to_le_bytes());
+            for value in [
+                quote.open(),
+                quote.high(),
+                quote.low(),
+                quote.close(),
+                quote.volume(),
+            ] {
+                hasher.update(value.to_le_bytes());
+            }
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Хеш настроек backtest по их serde-представлению. Сторонний бенчмарк
+/// учитывается хешем своих котировок.
+pub fn config_fingerprint(config: &BacktestConfig) -> String {
+    #[derive(Serialize)]
+    struct CanonicalConfig<'a> {
+        initial_capital: f64,
+        use_full_capital: bool,
+        reinvest_profits: bool,
+        costs: &'a CostModel,
+        fill_policy: FillPolicy,
+        intrabar: &'a IntrabarConfig,
+        position_sizer: Option<&'a PositionSizerSpec>,
+        margin: Option<&'a MarginModel>,
+        session: Option<&'a SessionPolicy>,
+        risk_guards: Option<&'a RiskGuardConfig>,
+        risk_metrics: &'a EquityRiskConfig,
+        benchmark: Option<String>,
+        equity_mode: EquityMode,
+    }
+
+    let benchmark = config.benchmark.as_ref().map(|source| match source {
+        BenchmarkSource::PrimaryFrame => "primary".to_string(),
+        BenchmarkSource::Frame(frame) => data_fingerprint([(frame.timeframe(), frame.as_ref())]),
+    });
+    let canonical = CanonicalConfig {
+        initial_capital: config.initial_capital,
+        use_full_capital: config.use_full_capital,
+        reinvest_profits: config.reinvest_profits,
+        costs: &config.costs,
+        fill_policy: config.fill_policy,
+        intrabar: &config.intrabar,
+        position_sizer: config.position_sizer.as_ref(),
+        margin: config.margin.as_ref(),
+        session: config.session.as_ref(),
+        risk_guards: config.risk_guards.as_ref(),
+        risk_metrics: &config.risk_metrics,
+        benchmark,
+        equity_mode: config.equity_mode,
+    };
+    let mut hasher = Sha1::new();
+    hasher.update(serde_json::to_vec(&canonical).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+fn sorted(parts: impl Iterator<Item = String>) -> Vec<String> {
+    let mut parts: Vec<String> = parts.collect();
+    parts.sort();
+    parts
+}
+
+fn indicator_part(indicator: &IndicatorInfo) -> String {
+    let parameters = sorted(indicator.parameters.iter().map(|param| {
+        format!(
+            "{}:{:?}:{}:{}:{:?}",
+            param.name,
+            param.param_type,
+            param.optimizable,
+            param.mutatable,
+            param.global_param_name
+        )
+    }));
+    format!(
+        "{}|{}|{}|{}|[{}]",
+        indicator.name,
+        indicator.alias,
+        indicator.input_type,
+        indicator.indicator_type,
+        parameters.join(",")
+    )
+}
+
+fn nested_part(nested: &NestedIndicator) -> String {
+    format!(
+        "{}>{}>{}",
+        nested.input_indicator_alias,
+        nested.depth,
+        indicator_part(&nested.indicator)
+    )
+}
+
+fn condition_part(condition: &ConditionInfo) -> String {
+    let timeframe = |timeframe: &Option<TimeFrame>| {
+        timeframe
+            .as_ref()
+            .map(TimeFrame::identifier)
+            .unwrap_or_default()
+    };
+    format!(
+        "{}|{}|{:?}|{}|{}|{:?}|{}|{}|{:?}|{:?}|[{}]",
+        condition.id,
+        condition.name,
+        condition.operator,
+        condition.condition_type,
+        condition.primary_indicator_alias,
+        condition.secondary_indicator_alias,
+        timeframe(&condition.primary_timeframe),
+        timeframe(&condition.secondary_timeframe),
+        condition.price_field,
+        condition.constant_value,
+        params_part(&condition.optimization_params)
+    )
+}
+
+fn handler_part(handler: &StopHandlerInfo) -> String {
+    format!(
+        "{}|{}|{}|{}|{}|[{}]",
+        handler.id,
+        handler.name,
+        handler.handler_name,
+        handler.stop_type,
+        handler.priority,
+        params_part(&handler.optimization_params)
+    )
+}
+
+fn params_part(params: &[ConditionParamInfo]) -> String {
+    sorted(params.iter().map(|param| {
+        format!(
+            "{}:{}:{}:{:?}",
+            param.name, param.optimizable, param.mutatable, param.global_param_name
+        )
+    }))
+    .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::config::StrategyDiscoveryConfig;
+    use crate::indicators::types::ParameterType;
+    use crate::strategy::types::ConditionOperator;
+
+    fn indicator(name: &str, period_type: ParameterType) -> IndicatorInfo {
+        IndicatorInfo {
+            name: name.to_string(),
+            alias: name.to_lowercase(),
+            parameters: vec![crate::discovery::types::IndicatorParamInfo {
+                name: "period".to_string(),
+                param_type: period_type,
+                optimizable: true,
+                mutatable: true,
+                global_param_name: None,
+            }],
+            can_use_indicator_input: false,
+            input_type: "price".to_string(),
+            indicator_type: "trend".to_string(),
+        }
+    }
+
+    fn condition(id: &str, operator: ConditionOperator) -> ConditionInfo {
+        ConditionInfo {
+            id: id.to_string(),
+            name: "SMA vs EMA".to_string(),
+            operator,
+            condition_type: "indicator_indicator".to_string(),
+            optimization_params: vec![],
+            constant_value: None,
+            primary_indicator_alias: "sma".to_string(),
+            secondary_indicator_alias: Some("ema".to_string()),
+            primary_timeframe: None,
+            secondary_timeframe: None,
+            price_field: None,
+        }
+    }
+
+    fn candidate(
+        indicators: Vec<IndicatorInfo>,
+        conditions: Vec<ConditionInfo>,
+    ) -> StrategyCandidate {
+        StrategyCandidate {
+            indicators,
+            nested_indicators: vec![],
+            conditions,
+            exit_conditions: vec![],
+            stop_handlers: vec![],
+            take_handlers: vec![],
+            timeframes: vec![],
+            config: StrategyDiscoveryConfig::default(),
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_order_but_not_structure() {
+        let sma = indicator("SMA", ParameterType::Period);
+        let ema = indicator("EMA", ParameterType::Period);
+        let above = condition("entry_1", ConditionOperator::Above);
+        let base = strategy_fingerprint(&candidate(
+            vec![sma.clone(), ema.clone()],
+            vec![above.clone()],
+        ));
+
+        let reordered = candidate(vec![ema.clone(), sma.clone()], vec![above.clone()]);
+        assert_eq!(strategy_fingerprint(&reordered), base);
+
+        // Та же форма, другой оператор — раньше давала ту же сигнатуру
+        let below = candidate(
+            vec![sma.clone(), ema.clone()],
+            vec![condition("entry_1", ConditionOperator::Below)],
+        );
+        assert_ne!(strategy_fingerprint(&below), base);
+
+        let other_param = candidate(
+            vec![sma, indicator("EMA", ParameterType::Multiplier)],
+            vec![above],
+        );
+        assert_ne!(strategy_fingerprint(&other_param), base);
+    }
+
+    #[test]
+    fn config_fingerprint_tracks_settings_not_debug_output() {
+        let base = BacktestConfig {
+            initial_capital: 10_000.0,
+            ..BacktestConfig::default()
+        };
+        assert_eq!(config_fingerprint(&base), config_fingerprint(&base.clone()));
+
+        let exact = BacktestConfig {
+            equity_mode: EquityMode::Exact,
+            ..base.clone()
+        };
+        assert_ne!(config_fingerprint(&exact), config_fingerprint(&base));
+
+        let without_benchmark = BacktestConfig {
+            benchmark: None,
+            ..base.clone()
+        };
+        assert_ne!(
+            config_fingerprint(&without_benchmark),
+            config_fingerprint(&base)
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use crate::metrics::attribution::PerformanceAttribution;
 use crate::metrics::backtest::BacktestReport;
+use crate::metrics::breakdown::BreakdownConfig;
+use crate::metrics::monte_carlo::MonteCarloConfig;
 
 #[derive(Clone, Debug)]
 pub struct FitnessThresholds {
@@ -9,6 +12,25 @@ pub struct FitnessThresholds {
     pub min_trades_count: Option<usize>,
     pub min_cagr: Option<f64>,
     pub min_recovery_factor: Option<f64>,
+    pub min_sortino_ratio: Option<f64>,
+    pub min_calmar_ratio: Option<f64>,
+    pub min_omega_ratio: Option<f64>,
+    pub max_ulcer_index: Option<f64>,
+    pub min_tail_ratio: Option<f64>,
+    /// Максимальный CVaR за бар в процентах капитала
+    pub max_cvar_pct: Option<f64>,
+    /// Максимальная длительность просадки в барах
+    pub max_drawdown_duration: Option<usize>,
+    /// Минимальная доля прибыльных месяцев в процентах
+    pub min_profitable_months_pct: Option<f64>,
+    pub max_losing_years: Option<usize>,
+    /// Максимальная доля прибыли от одной стороны (лонг или шорт), от 0.5 до 1
+    pub max_side_profit_share: Option<f64>,
+    /// Минимальное превышение доходности покупки и удержания в процентах
+    pub min_excess_return_pct: Option<f64>,
+    pub min_information_ratio: Option<f64>,
+    /// Минимальная оценка устойчивости Монте-Карло, от 0 до 1
+    pub min_robustness_score: Option<f64>,
 }
 
 impl Default for FitnessThresholds {
@@ -21,6 +43,19 @@ impl Default for FitnessThresholds {
             min_trades_count: Some(30),
             min_cagr: Some(10.0),
             min_recovery_factor: Some(1.0),
+            min_sortino_ratio: None,
+            min_calmar_ratio: None,
+            min_omega_ratio: None,
+            max_ulcer_index: None,
+            min_tail_ratio: None,
+            max_cvar_pct: None,
+            max_drawdown_duration: None,
+            min_profitable_months_pct: None,
+            max_losing_years: None,
+            max_side_profit_share: None,
+            min_excess_return_pct: None,
+            min_information_ratio: None,
+            min_robustness_score: None,
         }
     }
 }
@@ -34,6 +69,15 @@ pub struct FitnessWeights {
     pub recovery_factor_weight: f64,
     pub drawdown_penalty: f64,
     pub trades_count_bonus: f64,
+    pub sortino_ratio_weight: f64,
+    pub calmar_ratio_weight: f64,
+    pub omega_ratio_weight: f64,
+    pub ulcer_performance_weight: f64,
+    pub tail_ratio_weight: f64,
+    /// Штраф за CVaR: вычитается, как и штраф за просадку
+    pub cvar_penalty: f64,
+    /// Штраф за прибыль, полученную только на одной стороне
+    pub side_concentration_penalty: f64,
 }
 
 impl Default for FitnessWeights {
@@ -46,6 +90,13 @@ impl Default for FitnessWeights {
             recovery_factor_weight: 0.20,
             drawdown_penalty: 0.05,
             trades_count_bonus: 0.05,
+            sortino_ratio_weight: 0.0,
+            calmar_ratio_weight: 0.0,
+            omega_ratio_weight: 0.0,
+            ulcer_performance_weight: 0.0,
+            tail_ratio_weight: 0.0,
+            cvar_penalty: 0.0,
+            side_concentration_penalty: 0.0,
         }
     }
 }
@@ -121,9 +172,82 @@ impl FitnessFunction {
             }
         }
 
+        if !Self::at_least(metrics.sortino_ratio, thresholds.min_sortino_ratio)
+            || !Self::at_least(metrics.calmar_ratio, thresholds.min_calmar_ratio)
+            || !Self::at_least(metrics.omega_ratio, thresholds.min_omega_ratio)
+            || !Self::at_least(metrics.tail_ratio, thresholds.min_tail_ratio)
+            || !Self::at_most(metrics.ulcer_index, thresholds.max_ulcer_index)
+            || !Self::at_most(metrics.conditional_value_at_risk, thresholds.max_cvar_pct)
+            || !Self::at_most(
+                metrics.max_drawdown_duration.map(|bars| bars as f64),
+                thresholds.max_drawdown_duration.map(|bars| bars as f64),
+            )
+        {
+            return false;
+        }
+
+        let benchmark = metrics.benchmark.as_ref();
+        if !Self::at_least(
+            benchmark.map(|benchmark| benchmark.excess_return_pct),
+            thresholds.min_excess_return_pct,
+        ) || !Self::at_least(
+            benchmark.and_then(|benchmark| benchmark.information_ratio),
+            thresholds.min_information_ratio,
+        ) {
+            return false;
+        }
+
+        if !Self::at_most(
+            PerformanceAttribution::side_profit_share(&report.trades),
+            thresholds.max_side_profit_share,
+        ) {
+            return false;
+        }
+
+        // Календарная разбивка нужна только для критериев стабильности
+        if thresholds.min_profitable_months_pct.is_some() || thresholds.max_losing_years.is_some() {
+            let breakdown = report
+                .periodic_breakdown(&BreakdownConfig::default().with_rolling_windows(Vec::new()));
+            if !Self::at_least(
+                breakdown.profitable_months_pct(),
+                thresholds.min_profitable_months_pct,
+            ) {
+                return false;
+            }
+            if let Some(max_losing_years) = thresholds.max_losing_years {
+                if breakdown.losing_years() > max_losing_years {
+                    return false;
+                }
+            }
+        }
+
+        // Монте-Карло — самая дорогая проверка, поэтому выполняется последней
+        if let Some(min_score) = thresholds.min_robustness_score {
+            let monte_carlo = report.monte_carlo(&MonteCarloConfig::screening());
+            if monte_carlo.robustness_score < min_score {
+                return false;
+            }
+        }
+
         true
     }
 
+    /// Порог не задан или метрика не меньше порога; отсутствующая метрика порог не проходит
+    fn at_least(value: Option<f64>, threshold: Option<f64>) -> bool {
+        match threshold {
+            Some(threshold) => value.is_some_and(|value| value >= threshold),
+            None => true,
+        }
+    }
+
+    /// Порог не задан или метрика не больше порога; отсутствующая метрика порог не проходит
+    fn at_most(value: Option<f64>, threshold: Option<f64>) -> bool {
+        match threshold {
+            Some(threshold) => value.is_some_and(|value| value <= threshold),
+            None => true,
+        }
+    }
+
     pub fn calculate_fitness(report: &BacktestReport, weights: &FitnessWeights) -> f64 {
         let metrics = &report.metrics;
         let trades_count = report.trades.len();
@@ -136,6 +260,17 @@ impl FitnessFunction {
             Self::normalize_recovery_factor(metrics.cagr, metrics.drawdown_percent);
         let drawdown_penalty = Self::calculate_drawdown_penalty(metrics.drawdown_percent);
         let trades_bonus = Self::calculate_trades_bonus(trades_count);
+        let sortino_score = Self::normalize_ratio(metrics.sortino_ratio, 4.0);
+        let calmar_score = Self::normalize_ratio(metrics.calmar_ratio, 5.0);
+        let omega_score = Self::normalize_ratio(metrics.omega_ratio.map(|omega| omega - 1.0), 2.0);
+        let ulcer_performance_score = Self::normalize_ratio(metrics.ulcer_performance_index, 10.0);
+        let tail_ratio_score =
+            Self::normalize_ratio(metrics.tail_ratio.map(|ratio| ratio - 1.0), 1.0);
+        let cvar_penalty = Self::normalize_ratio(metrics.conditional_value_at_risk, 5.0);
+        // 0.5 — прибыль поровну между сторонами, 1.0 — вся прибыль с одной стороны
+        let side_penalty = PerformanceAttribution::side_profit_share(&report.trades)
+            .map(|share| ((share - 0.5) * 2.0).clamp(0.0, 1.0))
+            .unwrap_or(0.0);
 
         let total_weight = weights.sharpe_ratio_weight
             + weights.profit_factor_weight
@@ -143,7 +278,14 @@ impl FitnessFunction {
             + weights.cagr_weight
             + weights.recovery_factor_weight
             + weights.drawdown_penalty
-            + weights.trades_count_bonus;
+            + weights.trades_count_bonus
+            + weights.sortino_ratio_weight
+            + weights.calmar_ratio_weight
+            + weights.omega_ratio_weight
+            + weights.ulcer_performance_weight
+            + weights.tail_ratio_weight
+            + weights.cvar_penalty
+            + weights.side_concentration_penalty;
 
         if total_weight == 0.0 {
             return 0.0;
@@ -155,7 +297,14 @@ impl FitnessFunction {
             + cagr_score * weights.cagr_weight
             + recovery_factor_score * weights.recovery_factor_weight
             - drawdown_penalty * weights.drawdown_penalty
-            + trades_bonus * weights.trades_count_bonus)
+            + trades_bonus * weights.trades_count_bonus
+            + sortino_score * weights.sortino_ratio_weight
+            + calmar_score * weights.calmar_ratio_weight
+            + omega_score * weights.omega_ratio_weight
+            + ulcer_performance_score * weights.ulcer_performance_weight
+            + tail_ratio_score * weights.tail_ratio_weight
+            - cvar_penalty * weights.cvar_penalty
+            - side_penalty * weights.side_concentration_penalty)
             / total_weight;
 
         fitness.max(0.0)
@@ -207,6 +356,14 @@ impl FitnessFunction {
         }
     }
 
+    /// Приводит положительную метрику к [0, 1], `scale` соответствует единице
+    fn normalize_ratio(value: Option<f64>, scale: f64) -> f64 {
+        match value {
+            Some(v) if v > 0.0 => (v / scale).min(1.0),
+            _ => 0.0,
+        }
+    }
+
     fn calculate_drawdown_penalty(dd_pct: Option<f64>) -> f64 {
         match dd_pct {
             Some(dd) if dd > 0.0 => (dd / 50.0).min(1.0),
@@ -268,9 +425,16 @@ mod tests {
             entry_time: Some(Utc::now()),
             exit_time: Some(Utc::now()),
             pnl,
+            gross_pnl: pnl,
+            commission: 0.0,
+            slippage: 0.0,
+            financing: 0.0,
             entry_rule_id: None,
             exit_rule_id: None,
+            exit_reason: None,
+            intrabar_resolution: None,
             stop_history: vec![],
+            excursion: Default::default(),
         }
     }
 
@@ -398,6 +562,7 @@ mod tests {
             recovery_factor_weight: 0.0,
             drawdown_penalty: 0.0,
             trades_count_bonus: 0.0,
+            ..FitnessWeights::default()
         };
 
         let fitness = FitnessFunction::calculate_fitness(&report, &weights);
@@ -433,6 +598,45 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_side_concentration() {
+        let mut short_trade = create_test_trade(-50.0);
+        short_trade.direction = PositionDirection::Short;
+        let mut trades = vec![create_test_trade(100.0); 3];
+        trades.push(short_trade);
+        let report = create_test_report(trades, create_test_metrics());
+
+        assert_eq!(
+            PerformanceAttribution::side_profit_share(&report.trades),
+            Some(1.0)
+        );
+        let thresholds = FitnessThresholds {
+            max_side_profit_share: Some(0.8),
+            ..FitnessThresholds::default()
+        };
+        assert!(!FitnessFunction::passes_thresholds(&report, &thresholds));
+    }
+
+    #[test]
+    fn test_equity_risk_thresholds() {
+        let mut metrics = create_test_metrics();
+        metrics.sortino_ratio = Some(1.2);
+        metrics.conditional_value_at_risk = Some(3.0);
+        let trades = vec![create_test_trade(100.0); 50];
+        let report = create_test_report(trades, metrics);
+        let mut thresholds = FitnessThresholds {
+            min_sortino_ratio: Some(1.0),
+            max_cvar_pct: Some(2.5),
+            ..FitnessThresholds::default()
+        };
+
+        assert!(!FitnessFunction::passes_thresholds(&report, &thresholds));
+        thresholds.max_cvar_pct = Some(4.0);
+        assert!(FitnessFunction::passes_thresholds(&report, &thresholds));
+        thresholds.min_omega_ratio = Some(1.0);
+        assert!(!FitnessFunction::passes_thresholds(&report, &thresholds));
+    }
+
     #[test]
     fn test_normalize_sharpe_ratio() {
         assert_eq!(FitnessFunction::normalize_sharpe_ratio(Some(3.0)), 1.0);
@@ -1,6 +1,7 @@
 use crate::discovery::StrategyCandidate;
 use crate::optimization::candidate_builder::CandidateBuilder;
 use crate::optimization::genetic::helpers;
+use crate::optimization::rng;
 use crate::optimization::types::GeneticAlgorithmConfig;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -12,7 +13,7 @@ pub fn crossover_structure_hybrid(
     fitness2: Option<f64>,
     config: &GeneticAlgorithmConfig,
 ) -> (StrategyCandidate, StrategyCandidate) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     let max_entry = config
         .candidate_builder_config
@@ -160,7 +161,7 @@ pub fn crossover_conditions_hybrid(
     Vec<crate::discovery::ConditionInfo>,
     Vec<crate::discovery::ConditionInfo>,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     let mut all_conditions: Vec<(crate::discovery::ConditionInfo, &StrategyCandidate, f64)> =
         Vec::new();
@@ -247,7 +248,7 @@ fn assign_conditions_weighted(
     weight1: f64,
     weight2: f64,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     for (cond, _parent, weight) in unique_conditions {
         if child1_conditions.len() < max_conditions
@@ -275,7 +276,7 @@ fn assign_conditions_uniform(
     child2_conditions: &mut Vec<crate::discovery::ConditionInfo>,
     max_conditions: usize,
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     for (_i, (cond, _parent, _weight)) in unique_conditions.iter().enumerate() {
         if child1_conditions.len() >= max_conditions && child2_conditions.len() >= max_conditions {
@@ -311,7 +312,7 @@ pub fn sync_indicators_with_conditions(
     parent1: &StrategyCandidate,
     parent2: &StrategyCandidate,
 ) {
-    let mut required_aliases = std::collections::HashSet::new();
+    let mut required_aliases = std::collections::BTreeSet::new();
 
     for cond in child.conditions.iter().chain(child.exit_conditions.iter()) {
         for alias in cond.all_indicator_aliases() {
@@ -385,7 +386,7 @@ pub fn ensure_minimum_conditions(
     min_conditions: usize,
 ) {
     if child.conditions.len() < min_conditions && !fallback_parent.conditions.is_empty() {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         let mut attempts = 0;
         let max_attempts = fallback_parent.conditions.len() * 3;
         let mut child_condition_ids: std::collections::HashSet<String> =
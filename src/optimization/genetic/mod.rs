@@ -1,5 +1,5 @@
 mod crossover;
-mod helpers;
+pub(crate) mod helpers;
 mod mutation;
 mod selection;
 
@@ -8,7 +8,9 @@ use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::discovery::StopHandlerConfig;
 use crate::discovery::StrategyCandidate;
+use crate::metrics::backtest::BacktestReport;
 use crate::optimization::evaluator::StrategyEvaluationRunner;
+use crate::optimization::pareto;
 use crate::optimization::population::PopulationManager;
 use crate::optimization::sds::StochasticDiffusionSearch;
 use crate::optimization::types::{
@@ -60,10 +62,22 @@ impl GeneticAlgorithmV3 {
 
         let stop_handler_configs = vec![];
 
+        let mut evaluator = StrategyEvaluationRunner::new(frames, base_timeframe)
+            .with_evaluation_threads(config.evaluation_threads);
+
+        if let Some(dir) = &config.evaluation_cache_dir {
+            if let Err(e) = evaluator.set_persistent_cache(dir) {
+                eprintln!(
+                    "      ⚠️  Дисковый кеш оценок недоступен ({}): {:?}",
+                    dir, e
+                );
+            }
+        }
+
         Self {
             config,
             population_manager: PopulationManager::new(population_config),
-            evaluator: StrategyEvaluationRunner::new(frames, base_timeframe),
+            evaluator,
             available_indicators,
             price_fields,
             operators,
@@ -80,148 +94,143 @@ impl GeneticAlgorithmV3 {
         &mut self,
         population: &mut Population,
     ) -> Result<(), anyhow::Error> {
-        let elites = selection::select_elites(population, self.config.elitism_count);
+        let multi_objective = !self.config.objectives.is_empty();
         let lambda = self.config.lambda_size;
         let mu = population.individuals.len();
+        let pareto_ranks = if multi_objective {
+            pareto::pareto_ranks(&population.individuals, &self.config.objectives).0
+        } else {
+            Vec::new()
+        };
+        let elites = if multi_objective {
+            Vec::new()
+        } else {
+            selection::select_elites(population, self.config.elitism_count)
+        };
         let mut offspring = Vec::with_capacity(lambda);
         let mut evaluated_count = 0;
 
         while offspring.len() < lambda {
-            let parents = self.population_manager.select_parents(population, 2);
-            if parents.len() < 2 {
-                break;
-            }
-
-            let parent1_candidate = parents[0].strategy.candidate.as_ref();
-            let parent2_candidate = parents[1].strategy.candidate.as_ref();
-
-            if let (Some(cand1), Some(cand2)) = (parent1_candidate, parent2_candidate) {
-                let fitness1 = parents[0].strategy.fitness;
-                let fitness2 = parents[1].strategy.fitness;
-
-                let (mut child1_candidate, mut child2_candidate) =
-                    crossover::crossover_structure_hybrid(
-                        cand1,
-                        cand2,
-                        fitness1,
-                        fitness2,
-                        &self.config,
-                    );
-
-                let (mut child1_params, mut child2_params) = if let Some(params) =
-                    self.population_manager.crossover(parents[0], parents[1])
-                {
-                    params
+            let needed = lambda - offspring.len();
+            let mut children = Vec::with_capacity(needed + 1);
+            while children.len() < needed {
+                let parents = if multi_objective {
+                    pareto::tournament_select(population, &pareto_ranks, 2)
                 } else {
-                    (
-                        parents[0].strategy.parameters.clone(),
-                        parents[1].strategy.parameters.clone(),
-                    )
+                    self.population_manager.select_parents(population, 2)
                 };
+                if parents.len() < 2 {
+                    break;
+                }
 
-                mutation::mutate_structure(
-                    &mut child1_candidate,
-                    &self.config,
-                    &self.available_indicators,
-                    &self.price_fields,
-                    &self.operators,
-                    &self.stop_handler_configs,
-                );
-                mutation::mutate_structure(
-                    &mut child2_candidate,
-                    &self.config,
-                    &self.available_indicators,
-                    &self.price_fields,
-                    &self.operators,
-                    &self.stop_handler_configs,
-                );
+                let parent1_candidate = parents[0].strategy.candidate.as_ref();
+                let parent2_candidate = parents[1].strategy.candidate.as_ref();
 
-                use crate::discovery::strategy_converter::ParameterExtractor;
-                let parameter_specs1 = ParameterExtractor::extract_all(&child1_candidate);
-                let parameter_specs2 = ParameterExtractor::extract_all(&child2_candidate);
+                if let (Some(cand1), Some(cand2)) = (parent1_candidate, parent2_candidate) {
+                    let fitness1 = parents[0].strategy.fitness;
+                    let fitness2 = parents[1].strategy.fitness;
 
-                self.population_manager.sync_parameters_with_structure(
-                    &mut child1_params,
-                    &child1_candidate,
-                    &parameter_specs1,
-                );
-                self.population_manager.sync_parameters_with_structure(
-                    &mut child2_params,
-                    &child2_candidate,
-                    &parameter_specs2,
-                );
+                    let (mut child1_candidate, mut child2_candidate) =
+                        crossover::crossover_structure_hybrid(
+                            cand1,
+                            cand2,
+                            fitness1,
+                            fitness2,
+                            &self.config,
+                        );
 
-                self.population_manager.mutate(
-                    &mut child1_params,
-                    &child1_candidate,
-                    &self.config,
-                    &parameter_specs1,
-                );
-                self.population_manager.mutate(
-                    &mut child2_params,
-                    &child2_candidate,
-                    &self.config,
-                    &parameter_specs2,
-                );
+                    let (mut child1_params, mut child2_params) = if let Some(params) =
+                        self.population_manager.crossover(parents[0], parents[1])
+                    {
+                        params
+                    } else {
+                        (
+                            parents[0].strategy.parameters.clone(),
+                            parents[1].strategy.parameters.clone(),
+                        )
+                    };
 
-                evaluated_count += 1;
-                let progress = (evaluated_count as f64 / lambda as f64) * 100.0;
-                println!(
-                    "      [{}/{}] ({:.1}%) Оценка новой особи...",
-                    evaluated_count, lambda, progress
-                );
+                    mutation::mutate_structure(
+                        &mut child1_candidate,
+                        &self.config,
+                        &self.available_indicators,
+                        &self.price_fields,
+                        &self.operators,
+                        &self.stop_handler_configs,
+                    );
+                    mutation::mutate_structure(
+                        &mut child2_candidate,
+                        &self.config,
+                        &self.available_indicators,
+                        &self.price_fields,
+                        &self.operators,
+                        &self.stop_handler_configs,
+                    );
 
-                helpers::log_strategy_details(&child1_candidate, &child1_params, "Child1");
+                    use crate::discovery::strategy_converter::ParameterExtractor;
+                    let parameter_specs1 = ParameterExtractor::extract_all(&child1_candidate);
+                    let parameter_specs2 = ParameterExtractor::extract_all(&child2_candidate);
 
-                match self
-                    .create_individual(
-                        child1_candidate,
-                        child1_params,
+                    self.population_manager.sync_parameters_with_structure(
+                        &mut child1_params,
+                        &child1_candidate,
+                        &parameter_specs1,
+                    );
+                    self.population_manager.sync_parameters_with_structure(
+                        &mut child2_params,
+                        &child2_candidate,
+                        &parameter_specs2,
+                    );
+
+                    self.population_manager.mutate(
+                        &mut child1_params,
+                        &child1_candidate,
+                        &self.config,
+                        &parameter_specs1,
+                    );
+                    self.population_manager.mutate(
+                        &mut child2_params,
+                        &child2_candidate,
+                        &self.config,
+                        &parameter_specs2,
+                    );
+
+                    helpers::log_strategy_details(&child1_candidate, &child1_params, "Child1");
+                    helpers::log_strategy_details(&child2_candidate, &child2_params, "Child2");
+                    children.push((child1_candidate, child1_params));
+                    children.push((child2_candidate, child2_params));
+                }
+            }
+            if children.is_empty() {
+                break;
+            }
+            children.truncate(needed);
+
+            evaluated_count += children.len();
+            let progress = (evaluated_count as f64 / lambda as f64) * 100.0;
+            println!(
+                "      [{}/{}] ({:.1}%) Оценка {} новых особей...",
+                evaluated_count,
+                lambda,
+                progress,
+                children.len()
+            );
+
+            let reports = self.evaluator.evaluate_batch(children.clone()).await;
+            for ((candidate, parameters), report) in children.into_iter().zip(reports) {
+                match report {
+                    Ok(report) => offspring.push(self.create_individual(
+                        candidate,
+                        parameters,
+                        report,
                         population.generation + 1,
                         population.island_id,
-                    )
-                    .await
-                {
-                    Ok(child1) => {
-                        offspring.push(child1);
-                    }
+                    )),
                     Err(e) => {
                         eprintln!("      ❌ Ошибка оценки особи: {:?}", e);
                         if let Some(source) = e.source() {
                             eprintln!("      Источник ошибки: {:?}", source);
                         }
-                        continue;
-                    }
-                }
-
-                if offspring.len() < lambda {
-                    evaluated_count += 1;
-                    let progress = (evaluated_count as f64 / lambda as f64) * 100.0;
-                    println!(
-                        "      [{}/{}] ({:.1}%) Оценка новой особи...",
-                        evaluated_count, lambda, progress
-                    );
-
-                    helpers::log_strategy_details(&child2_candidate, &child2_params, "Child2");
-
-                    match self
-                        .create_individual(
-                            child2_candidate,
-                            child2_params,
-                            population.generation + 1,
-                            population.island_id,
-                        )
-                        .await
-                    {
-                        Ok(child2) => {
-                            offspring.push(child2);
-                        }
-                        Err(e) => {
-                            eprintln!("      ❌ Ошибка оценки особи: {:?}", e);
-                            if let Some(source) = e.source() {
-                                eprintln!("      Источник ошибки: {:?}", source);
-                            }
-                        }
                     }
                 }
             }
@@ -248,26 +257,26 @@ impl GeneticAlgorithmV3 {
             println!("      [SDS] Диффузионный поиск завершен");
         }
 
-        population.individuals = selection::select_with_diversity(combined_population, mu);
-
-        self.population_manager.apply_elitism(population, elites);
+        if multi_objective {
+            population.individuals =
+                pareto::select_nsga2(combined_population, &self.config.objectives, mu);
+        } else {
+            population.individuals = selection::select_with_diversity(combined_population, mu);
+            self.population_manager.apply_elitism(population, elites);
+        }
         population.generation += 1;
 
         Ok(())
     }
 
-    async fn create_individual(
+    fn create_individual(
         &self,
         candidate: StrategyCandidate,
         parameters: StrategyParameterMap,
+        report: BacktestReport,
         generation: usize,
         island_id: Option<usize>,
-    ) -> Result<GeneticIndividual, anyhow::Error> {
-        let report = self
-            .evaluator
-            .evaluate_strategy(&candidate, parameters.clone())
-            .await?;
-
+    ) -> GeneticIndividual {
         let fitness = Some(
             crate::optimization::fitness::FitnessFunction::calculate_fitness(
                 &report,
@@ -282,10 +291,10 @@ impl GeneticAlgorithmV3 {
             backtest_report: Some(report),
         };
 
-        Ok(GeneticIndividual {
+        GeneticIndividual {
             strategy: evaluated,
             generation,
             island_id,
-        })
+        }
     }
 }
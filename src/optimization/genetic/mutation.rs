@@ -3,6 +3,7 @@ use crate::discovery::StopHandlerConfig;
 use crate::discovery::StrategyCandidate;
 use crate::optimization::candidate_builder::CandidateBuilder;
 use crate::optimization::genetic::helpers;
+use crate::optimization::rng;
 use crate::optimization::types::GeneticAlgorithmConfig;
 use crate::strategy::types::{ConditionOperator, PriceField};
 use rand::Rng;
@@ -15,7 +16,7 @@ pub fn mutate_structure(
     operators: &[ConditionOperator],
     stop_handler_configs: &[StopHandlerConfig],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     if rng.gen::<f64>() < config.mutation_rate {
         mutate_indicators(
@@ -71,7 +72,7 @@ fn mutate_indicators(
     price_fields: &[PriceField],
     operators: &[ConditionOperator],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     if rng.gen::<f64>() < 0.3 && !candidate.indicators.is_empty() {
         let idx = rng.gen_range(0..candidate.indicators.len());
@@ -140,7 +141,7 @@ fn mutate_entry_conditions(
     price_fields: &[PriceField],
     operators: &[ConditionOperator],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     if rng.gen::<f64>() < 0.3 && !candidate.conditions.is_empty() {
         let idx = rng.gen_range(0..candidate.conditions.len());
@@ -184,7 +185,7 @@ fn mutate_exit_conditions(
     price_fields: &[PriceField],
     operators: &[ConditionOperator],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     let has_exit_conditions = !candidate.exit_conditions.is_empty();
     let has_stop_handlers = !candidate.stop_handlers.is_empty();
@@ -237,7 +238,7 @@ fn mutate_stop_handlers(
     candidate: &mut StrategyCandidate,
     stop_handler_configs: &[StopHandlerConfig],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     let has_exit_conditions = !candidate.exit_conditions.is_empty();
     let has_stop_handlers = !candidate.stop_handlers.is_empty();
@@ -272,7 +273,7 @@ fn mutate_nested_indicators(
     candidate: &mut StrategyCandidate,
     available_indicators: &[crate::discovery::IndicatorInfo],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     if rng.gen::<f64>() < 0.2 && !candidate.nested_indicators.is_empty() {
         let idx = rng.gen_range(0..candidate.nested_indicators.len());
@@ -309,7 +310,7 @@ fn mutate_take_handlers(
     candidate: &mut StrategyCandidate,
     stop_handler_configs: &[StopHandlerConfig],
 ) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
 
     let has_exit_conditions = !candidate.exit_conditions.is_empty();
     let has_stop_handlers = !candidate.stop_handlers.is_empty();
@@ -345,7 +346,7 @@ fn mutate_take_handlers(
 }
 
 fn mutate_timeframes(candidate: &mut StrategyCandidate) {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
     let base_tf = &candidate.config.base_timeframe;
     let base_duration = base_tf.duration();
 
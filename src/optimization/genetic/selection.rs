@@ -1,6 +1,6 @@
 use crate::optimization::genetic::helpers;
 use crate::optimization::types::{GeneticIndividual, Population};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub fn select_elites(population: &Population, elitism_count: usize) -> Vec<GeneticIndividual> {
     let mut sorted: Vec<&GeneticIndividual> = population.individuals.iter().collect();
@@ -19,7 +19,7 @@ pub fn select_with_diversity(
     individuals: Vec<GeneticIndividual>,
     target_size: usize,
 ) -> Vec<GeneticIndividual> {
-    let mut strategy_groups: HashMap<String, Vec<GeneticIndividual>> = HashMap::new();
+    let mut strategy_groups: BTreeMap<String, Vec<GeneticIndividual>> = BTreeMap::new();
 
     for individual in individuals {
         let strategy_id = if let Some(ref candidate) = individual.strategy.candidate {
@@ -53,13 +53,25 @@ impl InitialPopulationGenerator {
             .map(TimeFrame::minutes)
             .collect();
 
+        let mut evaluator = StrategyEvaluationRunner::with_higher_timeframes(
+            frames,
+            base_timeframe,
+            higher_timeframes,
+        )
+        .with_evaluation_threads(config.evaluation_threads);
+
+        if let Some(dir) = &config.evaluation_cache_dir {
+            if let Err(e) = evaluator.set_persistent_cache(dir) {
+                eprintln!(
+                    "      ⚠️  Дисковый кеш оценок недоступен ({}): {:?}",
+                    dir, e
+                );
+            }
+        }
+
         Self {
             config,
-            evaluator: StrategyEvaluationRunner::with_higher_timeframes(
-                frames,
-                base_timeframe,
-                higher_timeframes,
-            ),
+            evaluator,
             discovery_config,
             candidate_builder_config,
         }
@@ -126,7 +138,7 @@ impl InitialPopulationGenerator {
         );
 
         let total_strategies = all_strategy_candidates.len() * param_variants_count;
-        let mut individuals = Vec::with_capacity(total_strategies);
+        let mut jobs = Vec::with_capacity(total_strategies);
         let mut current_strategy = 0;
 
         for (candidate_idx, candidate) in all_strategy_candidates.iter().enumerate() {
@@ -300,61 +312,80 @@ impl InitialPopulationGenerator {
                     }
                 }
 
-                let report = match self
-                    .evaluator
-                    .evaluate_strategy(candidate, random_params.clone())
-                    .await
-                {
-                    Ok(report) => report,
-                    Err(e) => {
-                        eprintln!(
-                            "      ❌ Ошибка выполнения backtest для кандидата #{} (вариант #{})",
-                            candidate_idx + 1,
-                            param_variant + 1
-                        );
-                        eprintln!("      Детали ошибки: {:?}", e);
-                        if let Some(source) = e.source() {
-                            eprintln!("      Источник ошибки: {:?}", source);
-                        }
-                        continue;
-                    }
-                };
+                jobs.push((candidate_idx, param_variant, random_params));
+            }
+        }
 
-                if self.config.filter_initial_population {
-                    if !FitnessFunction::passes_thresholds(&report, &self.config.fitness_thresholds)
-                    {
-                        println!(
-                            "      ❌ Стратегия не прошла фильтр пороговых значений (Trades: {}, Profit: {:.2})",
-                            report.metrics.total_trades, report.metrics.total_profit
-                        );
-                        continue;
+        println!(
+            "\n   [Этап 2] Параллельная оценка {} стратегий в {} потоках...",
+            jobs.len(),
+            self.evaluator.evaluation_threads()
+        );
+        let reports = self
+            .evaluator
+            .evaluate_batch(
+                jobs.iter()
+                    .map(|(candidate_idx, _, params)| {
+                        (
+                            all_strategy_candidates[*candidate_idx].clone(),
+                            params.clone(),
+                        )
+                    })
+                    .collect(),
+            )
+            .await;
+
+        let mut individuals = Vec::with_capacity(total_strategies);
+        for ((candidate_idx, param_variant, random_params), report) in jobs.into_iter().zip(reports)
+        {
+            let report = match report {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!(
+                        "      ❌ Ошибка выполнения backtest для кандидата #{} (вариант #{})",
+                        candidate_idx + 1,
+                        param_variant + 1
+                    );
+                    eprintln!("      Детали ошибки: {:?}", e);
+                    if let Some(source) = e.source() {
+                        eprintln!("      Источник ошибки: {:?}", source);
                     }
+                    continue;
                 }
+            };
 
-                let fitness =
-                    FitnessFunction::calculate_fitness(&report, &self.config.fitness_weights);
-
+            if self.config.filter_initial_population
+                && !FitnessFunction::passes_thresholds(&report, &self.config.fitness_thresholds)
+            {
                 println!(
-                    "      ✅ Стратегия прошла тест (Fitness: {:.4}, Trades: {}, Profit: {:.2}, Win Rate: {:.1}%)",
-                    fitness,
-                    report.metrics.total_trades,
-                    report.metrics.total_profit,
-                    report.metrics.winning_percentage * 100.0
+                    "      ❌ Стратегия не прошла фильтр пороговых значений (Trades: {}, Profit: {:.2})",
+                    report.metrics.total_trades, report.metrics.total_profit
                 );
-
-                let evaluated = EvaluatedStrategy {
-                    candidate: Some(candidate.clone()),
-                    parameters: random_params,
-                    fitness: Some(fitness),
-                    backtest_report: Some(report),
-                };
-
-                individuals.push(GeneticIndividual {
-                    strategy: evaluated,
-                    generation: 0,
-                    island_id: None,
-                });
+                continue;
             }
+
+            let fitness = FitnessFunction::calculate_fitness(&report, &self.config.fitness_weights);
+
+            println!(
+                "      ✅ Стратегия прошла тест (Fitness: {:.4}, Trades: {}, Profit: {:.2}, Win Rate: {:.1}%)",
+                fitness,
+                report.metrics.total_trades,
+                report.metrics.total_profit,
+                report.metrics.winning_percentage * 100.0
+            );
+
+            let evaluated = EvaluatedStrategy {
+                candidate: Some(all_strategy_candidates[candidate_idx].clone()),
+                parameters: random_params,
+                fitness: Some(fitness),
+                backtest_report: Some(report),
+            };
+
+            individuals.push(GeneticIndividual {
+                strategy: evaluated,
+                generation: 0,
+                island_id: None,
+            });
         }
 
         let total_tested = all_strategy_candidates.len() * param_variants_count;
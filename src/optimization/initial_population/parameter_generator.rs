@@ -5,6 +5,7 @@ use crate::indicators::registry::IndicatorRegistry;
 use crate::indicators::types::{IndicatorCategory, ParameterType};
 use crate::optimization::condition_id::ConditionId;
 use crate::optimization::candidate_builder_config::CandidateBuilderConfig;
+use crate::optimization::rng;
 use crate::risk::get_stop_optimization_range;
 use crate::risk::utils::stop_handler_requires_indicator;
 use crate::strategy::types::StrategyParameterMap;
@@ -16,7 +17,7 @@ pub fn generate_random_parameters(
     candidate: &StrategyCandidate,
     candidate_builder_config: &CandidateBuilderConfig,
 ) -> StrategyParameterMap {
-    let mut rng = rand::thread_rng();
+    let mut rng = rng::rng();
     let total_params: usize = candidate
         .indicators
         .iter()
@@ -1,5 +1,5 @@
 use crate::optimization::types::GeneticIndividual;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::helpers;
 
@@ -7,7 +7,7 @@ pub fn select_with_diversity(
     individuals: Vec<GeneticIndividual>,
     target_size: usize,
 ) -> Vec<GeneticIndividual> {
-    let mut strategy_groups: HashMap<String, Vec<GeneticIndividual>> = HashMap::new();
+    let mut strategy_groups: BTreeMap<String, Vec<GeneticIndividual>> = BTreeMap::new();
 
     for individual in individuals {
         let strategy_id = if let Some(ref candidate) = individual.strategy.candidate {
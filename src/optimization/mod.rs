@@ -2,23 +2,31 @@ pub mod build_rules_provider;
 pub mod builders;
 pub mod candidate_builder;
 pub mod candidate_builder_config;
+pub mod checkpoint;
 pub mod condition_id;
+pub mod evaluation_cache;
 pub mod evaluator;
 pub mod evolution;
+pub mod fingerprint;
 pub mod fitness;
 pub mod fresh_blood;
 pub mod genetic;
 pub mod initial_population;
 pub mod island;
 pub mod migration;
+pub mod pareto;
 pub mod per_structure_optimizer;
 pub mod population;
+pub mod rng;
 pub mod sds;
 pub mod types;
 pub mod utils;
+pub mod walk_forward;
 
 pub use candidate_builder::CandidateBuilder;
 pub use candidate_builder_config::CandidateBuilderConfig;
+pub use checkpoint::{OptimizationCheckpoint, DEFAULT_CHECKPOINT_PATH};
+pub use evaluation_cache::{PersistentEvaluationCache, DEFAULT_EVALUATION_CACHE_DIR};
 pub use evaluator::StrategyEvaluationRunner;
 pub use evolution::EvolutionManager;
 pub use fitness::{FitnessFunction, FitnessThresholds, FitnessWeights};
@@ -27,9 +35,13 @@ pub use genetic::GeneticAlgorithmV3;
 pub use initial_population::InitialPopulationGenerator;
 pub use island::IslandManager;
 pub use migration::MigrationSystem;
+pub use pareto::{Objective, ParetoMember};
 pub use per_structure_optimizer::{OptimizedStrategyResult, PerStructureOptimizer};
 pub use population::PopulationManager;
 pub use sds::StochasticDiffusionSearch;
+pub use walk_forward::{
+    WalkForwardConfig, WalkForwardOptimizer, WalkForwardReport, WindowMode,
+};
 pub use types::*;
 
 pub mod strategy_saver;
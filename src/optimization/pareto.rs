@@ -0,0 +1,437 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use rand::Rng;
+
+use crate::metrics::backtest::{BacktestMetrics, BacktestReport};
+use crate::optimization::fingerprint;
+use crate::optimization::rng;
+use crate::optimization::types::{EvaluatedStrategy, GeneticIndividual, Population};
+
+/// Цель многокритериальной оптимизации (NSGA-II)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    NetProfit,
+    /// Максимальная просадка в процентах, минимизируется
+    MaxDrawdown,
+    TradesCount,
+    /// R² кривой капитала
+    Stability,
+    SharpeRatio,
+    SortinoRatio,
+    ProfitFactor,
+    WinRate,
+    Cagr,
+    ReturnDdRatio,
+    /// Минимизируется
+    UlcerIndex,
+}
+
+impl Objective {
+    pub fn is_minimized(&self) -> bool {
+        matches!(self, Objective::MaxDrawdown | Objective::UlcerIndex)
+    }
+
+    /// Значение метрики без учета направления; `None`, если она не рассчитана
+    pub fn raw_value(&self, metrics: &BacktestMetrics) -> Option<f64> {
+        match self {
+            Objective::NetProfit => Some(metrics.total_profit),
+            Objective::MaxDrawdown => metrics.drawdown_percent,
+            Objective::TradesCount => Some(metrics.total_trades as f64),
+            Objective::Stability => metrics.stability,
+            Objective::SharpeRatio => metrics.sharpe_ratio,
+            Objective::SortinoRatio => metrics.sortino_ratio,
+            Objective::ProfitFactor => metrics.profit_factor,
+            Objective::WinRate => Some(metrics.winning_percentage),
+            Objective::Cagr => metrics.cagr,
+            Objective::ReturnDdRatio => metrics.return_dd_ratio,
+            Objective::UlcerIndex => metrics.ulcer_index,
+        }
+    }
+
+    /// Значение, приведенное к максимизации; без метрики — худшее возможное
+    pub fn score(&self, metrics: &BacktestMetrics) -> f64 {
+        match self.raw_value(metrics) {
+            Some(value) if value.is_finite() => {
+                if self.is_minimized() {
+                    -value
+                } else {
+                    value
+                }
+            }
+            _ => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Член итогового фронта Парето
+#[derive(Clone, Debug)]
+pub struct ParetoMember {
+    pub strategy: EvaluatedStrategy,
+    /// Значения целей в порядке `objectives`, без приведения к максимизации
+    pub objectives: Vec<Option<f64>>,
+    pub report: BacktestReport,
+    pub island_id: Option<usize>,
+}
+
+pub fn objective_scores(individual: &GeneticIndividual, objectives: &[Objective]) -> Vec<f64> {
+    match &individual.strategy.backtest_report {
+        Some(report) => objectives
+            .iter()
+            .map(|objective| objective.score(&report.metrics))
+            .collect(),
+        None => vec![f64::NEG_INFINITY; objectives.len()],
+    }
+}
+
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut strictly_better = false;
+    for (x, y) in a.iter().zip(b) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            strictly_better = true;
+        }
+    }
+    strictly_better
+}
+
+/// Быстрая недоминируемая сортировка: индексы по фронтам, начиная с лучшего
+pub fn non_dominated_sort(scores: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = scores.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0usize; n];
+    let mut fronts: Vec<Vec<usize>> = vec![Vec::new()];
+
+    for p in 0..n {
+        for q in (p + 1)..n {
+            if dominates(&scores[p], &scores[q]) {
+                dominated_by[p].push(q);
+                domination_count[q] += 1;
+            } else if dominates(&scores[q], &scores[p]) {
+                dominated_by[q].push(p);
+                domination_count[p] += 1;
+            }
+        }
+    }
+    for (p, count) in domination_count.iter().enumerate() {
+        if *count == 0 {
+            fronts[0].push(p);
+        }
+    }
+
+    let mut current = 0;
+    while !fronts[current].is_empty() {
+        let mut next = Vec::new();
+        for &p in &fronts[current] {
+            for &q in &dominated_by[p] {
+                domination_count[q] -= 1;
+                if domination_count[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+        current += 1;
+        fronts.push(next);
+    }
+    fronts.pop();
+    fronts
+}
+
+/// Crowding distance для членов одного фронта, в порядке `front`
+pub fn crowding_distance(scores: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let mut distance = vec![0.0; front.len()];
+    if front.len() <= 2 {
+        return vec![f64::INFINITY; front.len()];
+    }
+    let objectives_count = scores[front[0]].len();
+
+    for objective in 0..objectives_count {
+        let values = objective_column(scores, front, objective);
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| {
+            values[a]
+                .partial_cmp(&values[b])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distance[first] = f64::INFINITY;
+        distance[last] = f64::INFINITY;
+
+        let range = values[last] - values[first];
+        if !range.is_finite() || range <= 0.0 {
+            continue;
+        }
+        for window in order.windows(3) {
+            let gap = values[window[2]] - values[window[0]];
+            if gap.is_finite() {
+                distance[window[1]] += gap / range;
+            }
+        }
+    }
+    distance
+}
+
+fn objective_column(scores: &[Vec<f64>], front: &[usize], objective: usize) -> Vec<f64> {
+    front
+        .iter()
+        .map(|&index| scores[index][objective])
+        .collect()
+}
+
+/// Ранг фронта и crowding distance особи. Используются только отбором NSGA-II
+/// и не заменяют скалярный fitness стратегии.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParetoRank {
+    /// Номер недоминируемого фронта, 0 — лучший
+    pub pareto_rank: usize,
+    pub crowding_distance: f64,
+}
+
+impl ParetoRank {
+    /// Crowded-comparison: `Less` — лучше (меньший ранг, при равенстве — большая
+    /// crowding distance)
+    pub fn crowded_cmp(&self, other: &Self) -> Ordering {
+        self.pareto_rank.cmp(&other.pareto_rank).then_with(|| {
+            other
+                .crowding_distance
+                .partial_cmp(&self.crowding_distance)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+/// Ранги особей в порядке `individuals` и индексы особей по фронтам
+pub fn pareto_ranks(
+    individuals: &[GeneticIndividual],
+    objectives: &[Objective],
+) -> (Vec<ParetoRank>, Vec<Vec<usize>>) {
+    let scores: Vec<Vec<f64>> = individuals
+        .iter()
+        .map(|individual| objective_scores(individual, objectives))
+        .collect();
+    let fronts = non_dominated_sort(&scores);
+    let mut ranks = vec![
+        ParetoRank {
+            pareto_rank: fronts.len(),
+            crowding_distance: 0.0,
+        };
+        individuals.len()
+    ];
+    for (rank, front) in fronts.iter().enumerate() {
+        let distances = crowding_distance(&scores, front);
+        for (&index, crowding_distance) in front.iter().zip(distances) {
+            ranks[index] = ParetoRank {
+                pareto_rank: rank,
+                crowding_distance,
+            };
+        }
+    }
+    (ranks, fronts)
+}
+
+/// Отбор NSGA-II: целые фронты по рангу, последний частично — по crowding distance
+pub fn select_nsga2(
+    individuals: Vec<GeneticIndividual>,
+    objectives: &[Objective],
+    target_size: usize,
+) -> Vec<GeneticIndividual> {
+    let (ranks, fronts) = pareto_ranks(&individuals, objectives);
+
+    let mut selected_indices = Vec::with_capacity(target_size);
+    for front in &fronts {
+        if selected_indices.len() >= target_size {
+            break;
+        }
+        let mut front = front.clone();
+        if selected_indices.len() + front.len() > target_size {
+            front.sort_by(|&a, &b| ranks[a].crowded_cmp(&ranks[b]));
+            front.truncate(target_size - selected_indices.len());
+        }
+        selected_indices.extend(front);
+    }
+    selected_indices.sort_by(|&a, &b| ranks[a].crowded_cmp(&ranks[b]));
+
+    let mut slots: Vec<Option<GeneticIndividual>> = individuals.into_iter().map(Some).collect();
+    selected_indices
+        .into_iter()
+        .filter_map(|index| slots[index].take())
+        .collect()
+}
+
+/// Бинарный турнир по crowded-comparison; `ranks` — в порядке особей популяции
+pub fn tournament_select<'a>(
+    population: &'a Population,
+    ranks: &[ParetoRank],
+    count: usize,
+) -> Vec<&'a GeneticIndividual> {
+    if population.individuals.is_empty() {
+        return Vec::new();
+    }
+    let mut rng = rng::rng();
+    (0..count)
+        .map(|_| {
+            let a = rng.gen_range(0..population.individuals.len());
+            let b = rng.gen_range(0..population.individuals.len());
+            let b_wins = matches!(
+                (ranks.get(a), ranks.get(b)),
+                (Some(rank_a), Some(rank_b)) if rank_b.crowded_cmp(rank_a) == Ordering::Less
+            );
+            &population.individuals[if b_wins { b } else { a }]
+        })
+        .collect()
+}
+
+/// Недоминируемые стратегии по всем островам, без повторов
+pub fn pareto_front(populations: &[Population], objectives: &[Objective]) -> Vec<ParetoMember> {
+    let mut seen = HashSet::new();
+    let individuals: Vec<&GeneticIndividual> = populations
+        .iter()
+        .flat_map(|population| population.individuals.iter())
+        .filter(|individual| individual.strategy.backtest_report.is_some())
+        .filter(|individual| {
+            let structure = individual
+                .strategy
+                .candidate
+                .as_ref()
+                .map(fingerprint::strategy_fingerprint)
+                .unwrap_or_default();
+            seen.insert((
+                structure,
+                fingerprint::parameters_fingerprint(&individual.strategy.parameters),
+            ))
+        })
+        .collect();
+
+    let scores: Vec<Vec<f64>> = individuals
+        .iter()
+        .map(|individual| objective_scores(individual, objectives))
+        .collect();
+    let Some(first_front) = non_dominated_sort(&scores).into_iter().next() else {
+        return Vec::new();
+    };
+
+    first_front
+        .into_iter()
+        .filter_map(|index| {
+            let individual = individuals[index];
+            let report = individual.strategy.backtest_report.clone()?;
+            Some(ParetoMember {
+                objectives: objectives
+                    .iter()
+                    .map(|objective| objective.raw_value(&report.metrics))
+                    .collect(),
+                strategy: individual.strategy.clone(),
+                report,
+                island_id: individual.island_id,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn individual(profit: f64, drawdown: f64) -> GeneticIndividual {
+        let metrics = BacktestMetrics {
+            total_profit: profit,
+            drawdown_percent: Some(drawdown),
+            ..BacktestMetrics::default()
+        };
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            "id".to_string(),
+            crate::strategy::types::StrategyParamValue::Number(profit * 1000.0 + drawdown),
+        );
+        GeneticIndividual {
+            strategy: EvaluatedStrategy {
+                candidate: None,
+                parameters,
+                fitness: Some(7.0),
+                backtest_report: Some(BacktestReport::new(vec![], metrics, vec![])),
+            },
+            generation: 0,
+            island_id: None,
+        }
+    }
+
+    const OBJECTIVES: [Objective; 2] = [Objective::NetProfit, Objective::MaxDrawdown];
+
+    #[test]
+    fn non_dominated_sort_orders_fronts() {
+        let scores = vec![
+            vec![10.0, -5.0],
+            vec![5.0, -2.0],
+            vec![4.0, -6.0],
+            vec![1.0, -10.0],
+        ];
+        assert_eq!(
+            non_dominated_sort(&scores),
+            vec![vec![0, 1], vec![2], vec![3]]
+        );
+    }
+
+    #[test]
+    fn crowding_distance_prefers_boundary_points() {
+        let scores = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![3.0, 1.0],
+            vec![4.0, 0.0],
+        ];
+        let distances = crowding_distance(&scores, &[0, 1, 2, 3]);
+        assert!(distances[0].is_infinite() && distances[3].is_infinite());
+        assert!((distances[1] - 1.5).abs() < 1e-9);
+        assert!((distances[2] - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn select_nsga2_keeps_first_front_and_pareto_front_spans_islands() {
+        let selected = select_nsga2(
+            vec![
+                individual(1.0, 10.0),
+                individual(10.0, 5.0),
+                individual(5.0, 2.0),
+                individual(4.0, 6.0),
+            ],
+            &OBJECTIVES,
+            2,
+        );
+        let profits: Vec<f64> = selected
+            .iter()
+            .map(|ind| {
+                ind.strategy
+                    .backtest_report
+                    .as_ref()
+                    .unwrap()
+                    .metrics
+                    .total_profit
+            })
+            .collect();
+        assert_eq!(profits.len(), 2);
+        assert!(profits.contains(&10.0) && profits.contains(&5.0));
+        // Скалярный fitness не перезаписывается рангом
+        assert!(selected.iter().all(|ind| ind.strategy.fitness == Some(7.0)));
+
+        let islands = vec![
+            Population {
+                individuals: vec![individual(10.0, 5.0), individual(4.0, 6.0)],
+                generation: 0,
+                island_id: Some(0),
+            },
+            Population {
+                individuals: vec![individual(5.0, 2.0), individual(10.0, 5.0)],
+                generation: 0,
+                island_id: Some(1),
+            },
+        ];
+        let front = pareto_front(&islands, &OBJECTIVES);
+        assert_eq!(front.len(), 2);
+        assert!(front
+            .iter()
+            .any(|member| member.objectives == vec![Some(5.0), Some(2.0)]));
+    }
+}
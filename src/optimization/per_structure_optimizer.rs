@@ -1,3 +1,4 @@
+use crate::backtest::BacktestConfig;
 use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::discovery::StrategyCandidate;
@@ -12,6 +13,7 @@ pub struct PerStructureOptimizer {
     frames: HashMap<TimeFrame, QuoteFrame>,
     base_timeframe: TimeFrame,
     discovery_config: crate::discovery::StrategyDiscoveryConfig,
+    backtest_config: BacktestConfig,
 }
 
 pub struct OptimizedStrategyResult {
@@ -33,9 +35,15 @@ impl PerStructureOptimizer {
             frames,
             base_timeframe,
             discovery_config,
+            backtest_config: BacktestConfig::default(),
         }
     }
 
+    pub fn with_backtest_config(mut self, config: BacktestConfig) -> Self {
+        self.backtest_config = config;
+        self
+    }
+
     pub async fn optimize_structure(
         &self,
         candidate: StrategyCandidate,
@@ -61,7 +69,8 @@ impl PerStructureOptimizer {
             self.frames.clone(),
             self.base_timeframe.clone(),
             self.discovery_config.clone(),
-        );
+        )
+        .with_backtest_config(self.backtest_config.clone());
 
         println!(
             "   Начальная популяция: {} стратегий",
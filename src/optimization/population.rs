@@ -1,4 +1,5 @@
 use crate::discovery::StrategyCandidate;
+use crate::optimization::rng;
 use crate::optimization::types::{GeneticIndividual, Population};
 use crate::strategy::types::StrategyParameterMap;
 use rand::Rng;
@@ -37,7 +38,7 @@ impl PopulationManager {
         population: &'a Population,
         count: usize,
     ) -> Vec<&'a GeneticIndividual> {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         let mut selected = Vec::with_capacity(count);
         let total_fitness: f64 = population
             .individuals
@@ -76,7 +77,7 @@ impl PopulationManager {
         parent1: &GeneticIndividual,
         parent2: &GeneticIndividual,
     ) -> Option<(StrategyParameterMap, StrategyParameterMap)> {
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         if rng.gen::<f64>() > self.config.crossover_rate {
             return None;
         }
@@ -92,7 +93,7 @@ impl PopulationManager {
             .keys()
             .chain(params2.keys())
             .cloned()
-            .collect::<std::collections::HashSet<_>>()
+            .collect::<std::collections::BTreeSet<_>>()
             .into_iter()
             .collect();
 
@@ -129,8 +130,9 @@ impl PopulationManager {
     ) {
         use crate::strategy::types::{ParameterKind, StrategyParameterSpec};
 
-        let mut rng = rand::thread_rng();
-        let keys: Vec<String> = parameters.keys().cloned().collect();
+        let mut rng = rng::rng();
+        let mut keys: Vec<String> = parameters.keys().cloned().collect();
+        keys.sort();
 
         for key in keys {
             if rng.gen::<f64>() < self.config.mutation_rate {
@@ -249,7 +251,7 @@ impl PopulationManager {
         max_percent: f64,
     ) {
         use crate::strategy::types::StrategyParamValue;
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
 
         let range_size = (range.end - range.start) as f64;
         let mutation_percent = rng.gen_range(min_percent..=max_percent);
@@ -320,7 +322,7 @@ impl PopulationManager {
 
         let current_idx = discrete_values.iter().position(|v| v == value).unwrap_or(0);
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         let new_idx = if discrete_values.len() > 1 {
             let mut new_idx = rng.gen_range(0..discrete_values.len());
             while new_idx == current_idx && discrete_values.len() > 1 {
@@ -367,7 +369,7 @@ impl PopulationManager {
             .position(|name| name == current_name)
             .unwrap_or(0);
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         let new_idx = if indicator_names.len() > 1 {
             let mut new_idx = rng.gen_range(0..indicator_names.len());
             while new_idx == current_idx && indicator_names.len() > 1 {
@@ -410,7 +412,7 @@ impl PopulationManager {
             .and_then(|op| compatible_operators.iter().position(|o| o == &op))
             .unwrap_or(0);
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
         let new_idx = if compatible_operators.len() > 1 {
             let mut new_idx = rng.gen_range(0..compatible_operators.len());
             while new_idx == current_idx && compatible_operators.len() > 1 {
@@ -452,7 +454,7 @@ impl PopulationManager {
                                         "period",
                                         &ParameterType::Period,
                                     ) {
-                                        let mut rng = rand::thread_rng();
+                                        let mut rng = rng::rng();
                                         let steps =
                                             ((range.end - range.start) / range.step) as usize;
                                         let step_index = rng.gen_range(0..=steps);
@@ -487,7 +489,7 @@ impl PopulationManager {
         use crate::strategy::types::StrategyParamValue;
         use rand::Rng;
 
-        let mut rng = rand::thread_rng();
+        let mut rng = rng::rng();
 
         for spec in parameter_specs {
             if !spec.optimize || !spec.mutatable {
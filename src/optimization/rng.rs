@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+
+use rand::{Error, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+thread_local! {
+    static OPTIMIZATION_RNG: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::from_entropy());
+}
+
+/// Генератор случайных чисел оптимизации. Как и `rand::thread_rng`, состояние
+/// у каждого потока свое, но его можно задать сидом, сохранить в чекпоинт и
+/// восстановить, чтобы продолжить эволюцию с той же последовательностью.
+/// Поэтому эволюция идет в одном потоке, а бэктесты в пуле оценки генератор
+/// не используют.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OptimizationRng;
+
+/// Снимок состояния генератора текущего потока. Позиция в потоке хранится
+/// двумя половинами, так как BSON не поддерживает `u128`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RngState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos_high: u64,
+    word_pos_low: u64,
+}
+
+pub fn rng() -> OptimizationRng {
+    OptimizationRng
+}
+
+pub fn seed(seed: u64) {
+    OPTIMIZATION_RNG.with(|rng| *rng.borrow_mut() = ChaCha8Rng::seed_from_u64(seed));
+}
+
+pub fn state() -> RngState {
+    OPTIMIZATION_RNG.with(|rng| {
+        let rng = rng.borrow();
+        let word_pos = rng.get_word_pos();
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos_high: (word_pos >> 64) as u64,
+            word_pos_low: word_pos as u64,
+        }
+    })
+}
+
+pub fn restore(state: &RngState) {
+    let mut restored = ChaCha8Rng::from_seed(state.seed);
+    restored.set_stream(state.stream);
+    restored.set_word_pos(((state.word_pos_high as u128) << 64) | state.word_pos_low as u128);
+    OPTIMIZATION_RNG.with(|rng| *rng.borrow_mut() = restored);
+}
+
+impl RngCore for OptimizationRng {
+    fn next_u32(&mut self) -> u32 {
+        OPTIMIZATION_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        OPTIMIZATION_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        OPTIMIZATION_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        OPTIMIZATION_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::optimization::rng;
 use crate::optimization::types::{GeneticAlgorithmConfig, GeneticIndividual, Population};
 use rand::Rng;
 use std::collections::HashMap;
@@ -35,7 +36,7 @@ impl StochasticDiffusionSearch {
 
             let mut agent_states: Vec<AgentState> =
                 Vec::with_capacity(population.individuals.len());
-            let mut rng = rand::thread_rng();
+            let mut rng = rng::rng();
 
             for individual in &population.individuals {
                 let hypothesis = individual.strategy.parameters.clone();
@@ -128,25 +129,28 @@ impl StochasticDiffusionSearch {
                 agent_states[idx].hypothesis = new_hypothesis;
             }
 
+            let (agents, jobs): (Vec<usize>, Vec<_>) = agent_states
+                .iter()
+                .enumerate()
+                .filter_map(|(i, state)| {
+                    population.individuals[i]
+                        .strategy
+                        .candidate
+                        .as_ref()
+                        .map(|candidate| (i, (candidate.clone(), state.hypothesis.clone())))
+                })
+                .unzip();
+            let hypotheses: Vec<_> = jobs.iter().map(|(_, params)| params.clone()).collect();
+            let reports = evaluator.evaluate_batch(jobs).await;
+
             let mut fitness_updates = Vec::new();
-            for (i, state) in agent_states.iter().enumerate() {
-                if let Some(candidate) = &population.individuals[i].strategy.candidate {
-                    let updated_params = state.hypothesis.clone();
-                    let current_fitness = state.fitness;
-
-                    let new_fitness = self
-                        .evaluate_hypothesis(
-                            candidate.clone(),
-                            updated_params.clone(),
-                            evaluator,
-                            population.generation,
-                            population.island_id,
-                        )
-                        .await?;
-
-                    if new_fitness > current_fitness {
-                        fitness_updates.push((i, new_fitness, updated_params));
-                    }
+            for ((i, updated_params), report) in agents.into_iter().zip(hypotheses).zip(reports) {
+                let new_fitness = crate::optimization::fitness::FitnessFunction::calculate_fitness(
+                    &report?,
+                    &self.config.fitness_weights,
+                );
+                if new_fitness > agent_states[i].fitness {
+                    fitness_updates.push((i, new_fitness, updated_params));
                 }
             }
 
@@ -218,24 +222,6 @@ impl StochasticDiffusionSearch {
 
         parts.join("|")
     }
-
-    async fn evaluate_hypothesis(
-        &self,
-        candidate: crate::discovery::StrategyCandidate,
-        parameters: crate::strategy::types::StrategyParameterMap,
-        evaluator: &crate::optimization::evaluator::StrategyEvaluationRunner,
-        _generation: usize,
-        _island_id: Option<usize>,
-    ) -> Result<f64, anyhow::Error> {
-        let report = evaluator.evaluate_strategy(&candidate, parameters).await?;
-
-        let fitness = crate::optimization::fitness::FitnessFunction::calculate_fitness(
-            &report,
-            &self.config.fitness_weights,
-        );
-
-        Ok(fitness)
-    }
 }
 
 struct AgentState {
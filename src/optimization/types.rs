@@ -1,9 +1,11 @@
 use crate::discovery::StrategyCandidate;
-use crate::optimization::fitness::{FitnessThresholds, FitnessWeights};
 use crate::optimization::candidate_builder_config::CandidateBuilderConfig;
+use crate::optimization::fitness::{FitnessThresholds, FitnessWeights};
+use crate::optimization::pareto::Objective;
 use crate::strategy::types::StrategyParameterMap;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EvaluatedStrategy {
     pub candidate: Option<StrategyCandidate>,
     pub parameters: StrategyParameterMap,
@@ -11,14 +13,14 @@ pub struct EvaluatedStrategy {
     pub backtest_report: Option<crate::metrics::backtest::BacktestReport>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GeneticIndividual {
     pub strategy: EvaluatedStrategy,
     pub generation: usize,
     pub island_id: Option<usize>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Population {
     pub individuals: Vec<GeneticIndividual>,
     pub generation: usize,
@@ -38,6 +40,8 @@ pub struct GeneticAlgorithmConfig {
     pub migration_rate: f64,
     pub fitness_thresholds: FitnessThresholds,
     pub fitness_weights: FitnessWeights,
+    /// Цели NSGA-II; пустой список — отбор по взвешенной сумме `fitness_weights`
+    pub objectives: Vec<Objective>,
     pub use_existing_strategies: bool,
     pub decimation_coefficient: f64,
     pub param_variants_per_candidate: usize,
@@ -54,6 +58,15 @@ pub struct GeneticAlgorithmConfig {
     pub sds_agents_ratio: f64,
     pub sds_test_threshold: f64,
     pub candidate_builder_config: Option<CandidateBuilderConfig>,
+    /// Число потоков для параллельной оценки стратегий
+    pub evaluation_threads: usize,
+    /// Каталог дискового кеша оценок; `None` — только кеш в памяти
+    pub evaluation_cache_dir: Option<String>,
+    /// Сохранять чекпоинт каждые N поколений; 0 — не сохранять
+    pub checkpoint_interval: usize,
+    pub checkpoint_path: Option<String>,
+    /// Сид генератора случайных чисел оптимизации; `None` — случайный
+    pub seed: Option<u64>,
 }
 
 impl Default for GeneticAlgorithmConfig {
@@ -70,6 +83,7 @@ impl Default for GeneticAlgorithmConfig {
             migration_rate: 0.05,
             fitness_thresholds: FitnessThresholds::default(),
             fitness_weights: FitnessWeights::default(),
+            objectives: Vec::new(),
             use_existing_strategies: false,
             decimation_coefficient: 2.0,
             param_variants_per_candidate: 30,
@@ -86,6 +100,11 @@ impl Default for GeneticAlgorithmConfig {
             sds_agents_ratio: 1.0,
             sds_test_threshold: 0.7,
             candidate_builder_config: Some(CandidateBuilderConfig::default()),
+            evaluation_threads: num_cpus::get(),
+            evaluation_cache_dir: None,
+            checkpoint_interval: 0,
+            checkpoint_path: None,
+            seed: None,
         }
     }
 }
@@ -0,0 +1,716 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sha1::{Digest, Sha1};
+
+use crate::backtest::{BacktestConfig, BacktestEngine};
+use crate::data_access::database::WalkForwardResult;
+use crate::data_model::quote_frame::QuoteFrame;
+use crate::data_model::types::TimeFrame;
+use crate::discovery::{StrategyCandidate, StrategyConverter, StrategyDiscoveryConfig};
+use crate::metrics::backtest::{BacktestMetrics, BacktestReport};
+use crate::metrics::{BenchmarkSource, EquityRiskConfig};
+use crate::optimization::fingerprint;
+use crate::optimization::genetic::GeneticAlgorithmV3;
+use crate::optimization::initial_population::InitialPopulationGenerator;
+use crate::optimization::per_structure_optimizer::{
+    OptimizedStrategyResult, PerStructureOptimizer,
+};
+use crate::optimization::types::GeneticAlgorithmConfig;
+use crate::strategy::types::{StrategyDefinition, StrategyParameterMap};
+
+/// Как сдвигается обучающее окно
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowMode {
+    /// Обучающее окно фиксированной длины сдвигается на шаг
+    Rolling,
+    /// Обучающее окно всегда начинается с начала данных и растет на шаг
+    Anchored,
+}
+
+#[derive(Clone, Debug)]
+pub struct WalkForwardConfig {
+    /// Идентификатор прогона в `WalkForwardResult::wf_id`
+    pub wf_id: String,
+    pub in_sample: Duration,
+    pub out_of_sample: Duration,
+    /// Сдвиг между окнами; `None` — длина тестового окна
+    pub step: Option<Duration>,
+    pub mode: WindowMode,
+    /// Сколько лучших различных стратегий окна проверяется на тестовом окне
+    pub winners_per_window: usize,
+}
+
+impl WalkForwardConfig {
+    pub fn new(wf_id: impl Into<String>, in_sample: Duration, out_of_sample: Duration) -> Self {
+        Self {
+            wf_id: wf_id.into(),
+            in_sample,
+            out_of_sample,
+            step: None,
+            mode: WindowMode::Rolling,
+            winners_per_window: 1,
+        }
+    }
+
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: WindowMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_winners_per_window(mut self, winners: usize) -> Self {
+        self.winners_per_window = winners.max(1);
+        self
+    }
+
+    /// Окна для данных с `first` по `last` включительно. Последнее тестовое окно
+    /// обрезается концом данных.
+    pub fn windows(&self, first: DateTime<Utc>, last: DateTime<Utc>) -> Vec<WalkForwardWindow> {
+        let step = self.step.unwrap_or(self.out_of_sample);
+        if step <= Duration::zero()
+            || self.in_sample <= Duration::zero()
+            || self.out_of_sample <= Duration::zero()
+        {
+            return Vec::new();
+        }
+        let data_end = last + Duration::milliseconds(1);
+        let mut windows = Vec::new();
+        let mut offset = Duration::zero();
+        loop {
+            let in_sample_start = match self.mode {
+                WindowMode::Rolling => first + offset,
+                WindowMode::Anchored => first,
+            };
+            let in_sample_end = first + self.in_sample + offset;
+            if in_sample_end >= data_end {
+                break;
+            }
+            windows.push(WalkForwardWindow {
+                number: windows.len() + 1,
+                in_sample_start,
+                in_sample_end,
+                out_sample_start: in_sample_end,
+                out_sample_end: (in_sample_end + self.out_of_sample).min(data_end),
+            });
+            offset += step;
+        }
+        windows
+    }
+}
+
+/// Пара обучающего и тестового окна, границы полуоткрытые: `[start, end)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalkForwardWindow {
+    /// Номер окна с единицы
+    pub number: usize,
+    pub in_sample_start: DateTime<Utc>,
+    pub in_sample_end: DateTime<Utc>,
+    pub out_sample_start: DateTime<Utc>,
+    pub out_sample_end: DateTime<Utc>,
+}
+
+impl WalkForwardWindow {
+    pub fn in_sample_days(&self) -> f64 {
+        days_between(self.in_sample_start, self.in_sample_end)
+    }
+
+    pub fn out_sample_days(&self) -> f64 {
+        days_between(self.out_sample_start, self.out_sample_end)
+    }
+
+    /// Котировки всех таймфреймов внутри `[from, to)`
+    fn slice(
+        frames: &HashMap<TimeFrame, QuoteFrame>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> HashMap<TimeFrame, QuoteFrame> {
+        frames
+            .iter()
+            .map(|(timeframe, frame)| {
+                let mut frame = frame.clone();
+                frame.truncate_before(from);
+                frame.truncate_after(to - Duration::nanoseconds(1));
+                (timeframe.clone(), frame)
+            })
+            .collect()
+    }
+}
+
+/// Стратегия, отобранная на обучающем окне, и ее результат на тестовом
+#[derive(Clone, Debug)]
+pub struct WalkForwardWinner {
+    pub strategy_id: String,
+    pub candidate: StrategyCandidate,
+    pub parameters: StrategyParameterMap,
+    pub fitness: f64,
+    pub in_sample: BacktestReport,
+    pub out_of_sample: BacktestReport,
+    /// Годовая прибыль на тестовом окне к годовой прибыли на обучающем
+    pub efficiency_ratio: f64,
+    /// От 0 до 1: доля Sharpe обучающего окна, потерянная на тестовом
+    pub overfitting_score: f64,
+}
+
+impl WalkForwardWinner {
+    fn new(
+        window: &WalkForwardWindow,
+        result: OptimizedStrategyResult,
+        strategy_id: String,
+        out_of_sample: BacktestReport,
+    ) -> Self {
+        let in_sample = result.backtest_report;
+        let is_profit_per_day = in_sample.metrics.total_profit / window.in_sample_days();
+        let oos_profit_per_day = out_of_sample.metrics.total_profit / window.out_sample_days();
+        let efficiency_ratio = if is_profit_per_day > 0.0 && oos_profit_per_day.is_finite() {
+            oos_profit_per_day / is_profit_per_day
+        } else {
+            0.0
+        };
+        let overfitting_score = match in_sample.metrics.sharpe_ratio {
+            Some(is_sharpe) if is_sharpe > 0.0 => {
+                let oos_sharpe = out_of_sample.metrics.sharpe_ratio.unwrap_or(0.0);
+                (1.0 - oos_sharpe / is_sharpe).clamp(0.0, 1.0)
+            }
+            // Без положительного Sharpe на обучении переносить нечего
+            _ => 1.0,
+        };
+        Self {
+            strategy_id,
+            candidate: result.candidate,
+            parameters: result.parameters,
+            fitness: result.fitness,
+            in_sample,
+            out_of_sample,
+            efficiency_ratio,
+            overfitting_score,
+        }
+    }
+
+    fn to_row(&self, wf_id: &str, window: &WalkForwardWindow) -> WalkForwardResult {
+        let sharpe = |report: &BacktestReport| report.metrics.sharpe_ratio.unwrap_or(0.0) as f32;
+        let drawdown =
+            |report: &BacktestReport| report.metrics.drawdown_percent.unwrap_or(0.0) as f32;
+        WalkForwardResult {
+            wf_id: wf_id.to_string(),
+            strategy_id: self.strategy_id.clone(),
+            window_number: window.number as i32,
+            in_sample_start: window.in_sample_start.date_naive(),
+            in_sample_end: last_date(window.in_sample_end),
+            out_sample_start: window.out_sample_start.date_naive(),
+            out_sample_end: last_date(window.out_sample_end),
+            is_sharpe: sharpe(&self.in_sample),
+            oos_sharpe: sharpe(&self.out_of_sample),
+            is_profit: self.in_sample.metrics.total_profit as f32,
+            oos_profit: self.out_of_sample.metrics.total_profit as f32,
+            is_drawdown: drawdown(&self.in_sample),
+            oos_drawdown: drawdown(&self.out_of_sample),
+            efficiency_ratio: self.efficiency_ratio as f32,
+            overfitting_score: self.overfitting_score as f32,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalkForwardWindowResult {
+    pub window: WalkForwardWindow,
+    /// По убыванию fitness на обучающем окне
+    pub winners: Vec<WalkForwardWinner>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WalkForwardReport {
+    pub windows: Vec<WalkForwardWindowResult>,
+    /// Строки для `insert_walk_forward_results`, по одной на победителя окна
+    pub results: Vec<WalkForwardResult>,
+    /// Склеенные тестовые окна лучших стратегий
+    pub out_of_sample: BacktestReport,
+}
+
+impl WalkForwardReport {
+    /// Средняя эффективность лучших стратегий по окнам
+    pub fn average_efficiency_ratio(&self) -> Option<f64> {
+        let ratios: Vec<f64> = self
+            .windows
+            .iter()
+            .filter_map(|window| window.winners.first())
+            .map(|winner| winner.efficiency_ratio)
+            .collect();
+        (!ratios.is_empty()).then(|| ratios.iter().sum::<f64>() / ratios.len() as f64)
+    }
+}
+
+/// Walk-forward оптимизация: на каждом обучающем окне запускается генетический
+/// алгоритм, победители проверяются на следующем за ним тестовом окне
+pub struct WalkForwardOptimizer {
+    config: WalkForwardConfig,
+    ga_config: GeneticAlgorithmConfig,
+    frames: HashMap<TimeFrame, QuoteFrame>,
+    base_timeframe: TimeFrame,
+    discovery_config: StrategyDiscoveryConfig,
+    backtest_config: BacktestConfig,
+}
+
+impl WalkForwardOptimizer {
+    pub fn new(
+        config: WalkForwardConfig,
+        ga_config: GeneticAlgorithmConfig,
+        frames: HashMap<TimeFrame, QuoteFrame>,
+        base_timeframe: TimeFrame,
+        discovery_config: StrategyDiscoveryConfig,
+    ) -> Self {
+        Self {
+            config,
+            ga_config,
+            frames,
+            base_timeframe,
+            discovery_config,
+            backtest_config: BacktestConfig::default(),
+        }
+    }
+
+    pub fn with_backtest_config(mut self, config: BacktestConfig) -> Self {
+        self.backtest_config = config;
+        self
+    }
+
+    /// Окна по датам базового таймфрейма
+    pub fn windows(&self) -> Vec<WalkForwardWindow> {
+        let Some(frame) = self.frames.get(&self.base_timeframe) else {
+            return Vec::new();
+        };
+        match (frame.first(), frame.latest()) {
+            (Some(first), Some(last)) => self.config.windows(first.timestamp(), last.timestamp()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Прогон по всем окнам. С `structure` оптимизируются только параметры этой
+    /// структуры через `PerStructureOptimizer`, иначе выполняется полный поиск.
+    pub async fn run(&self, structure: Option<StrategyCandidate>) -> Result<WalkForwardReport> {
+        let windows = self.windows();
+        anyhow::ensure!(
+            !windows.is_empty(),
+            "Недостаточно данных для walk-forward окон"
+        );
+
+        let mut window_results = Vec::with_capacity(windows.len());
+        for window in windows {
+            println!(
+                "\n🪟 Walk-forward окно {}: обучение {} — {}, тест {} — {}",
+                window.number,
+                window.in_sample_start,
+                window.in_sample_end,
+                window.out_sample_start,
+                window.out_sample_end
+            );
+            let in_sample_frames = WalkForwardWindow::slice(
+                &self.frames,
+                window.in_sample_start,
+                window.in_sample_end,
+            );
+
+            let optimized = match &structure {
+                Some(candidate) => {
+                    PerStructureOptimizer::new(
+                        self.ga_config.clone(),
+                        in_sample_frames,
+                        self.base_timeframe.clone(),
+                        self.discovery_config.clone(),
+                    )
+                    .with_backtest_config(self.backtest_config.clone())
+                    .optimize_structure(candidate.clone())
+                    .await?
+                }
+                None => self.optimize(in_sample_frames).await?,
+            };
+
+            let mut winners = Vec::with_capacity(self.config.winners_per_window);
+            for (strategy_id, result) in self.select_winners(optimized) {
+                let out_of_sample = self
+                    .out_of_sample_backtest(&result.candidate, &result.parameters, &window)
+                    .with_context(|| {
+                        format!(
+                            "Ошибка backtest стратегии {} на тестовом окне {}",
+                            strategy_id, window.number
+                        )
+                    })?;
+                winners.push(WalkForwardWinner::new(
+                    &window,
+                    result,
+                    strategy_id,
+                    out_of_sample,
+                ));
+            }
+            window_results.push(WalkForwardWindowResult { window, winners });
+        }
+
+        let results = window_results
+            .iter()
+            .flat_map(|result| {
+                result
+                    .winners
+                    .iter()
+                    .map(|winner| winner.to_row(&self.config.wf_id, &result.window))
+            })
+            .collect();
+        let best_reports: Vec<&BacktestReport> = window_results
+            .iter()
+            .filter_map(|result| result.winners.first())
+            .map(|winner| &winner.out_of_sample)
+            .collect();
+        let mut out_of_sample = stitch_reports(
+            &best_reports,
+            self.backtest_config.initial_capital,
+            &self.backtest_config.risk_metrics,
+        );
+        let benchmark_frame = match &self.backtest_config.benchmark {
+            Some(BenchmarkSource::PrimaryFrame) => self.frames.get(&self.base_timeframe),
+            Some(BenchmarkSource::Frame(frame)) => Some(frame.as_ref()),
+            None => None,
+        };
+        if let Some(frame) = benchmark_frame {
+            out_of_sample = out_of_sample
+                .with_benchmark(frame, self.backtest_config.risk_metrics.periods_per_year);
+        }
+
+        Ok(WalkForwardReport {
+            windows: window_results,
+            results,
+            out_of_sample,
+        })
+    }
+
+    async fn optimize(
+        &self,
+        frames: HashMap<TimeFrame, QuoteFrame>,
+    ) -> Result<Vec<OptimizedStrategyResult>> {
+        let generator = InitialPopulationGenerator::with_discovery_config(
+            self.ga_config.clone(),
+            frames.clone(),
+            self.base_timeframe.clone(),
+            self.discovery_config.clone(),
+        );
+        let mut population = generator.generate(None).await?;
+        let mut genetic_algorithm = GeneticAlgorithmV3::new(
+            self.ga_config.clone(),
+            frames,
+            self.base_timeframe.clone(),
+            self.discovery_config.clone(),
+        )
+        .with_backtest_config(self.backtest_config.clone());
+        for _ in 0..self.ga_config.max_generations {
+            genetic_algorithm.evolve_generation(&mut population).await?;
+        }
+
+        Ok(population
+            .individuals
+            .into_iter()
+            .filter_map(|individual| {
+                let strategy = individual.strategy;
+                Some(OptimizedStrategyResult {
+                    candidate: strategy.candidate?,
+                    parameters: strategy.parameters,
+                    fitness: strategy.fitness.unwrap_or(0.0),
+                    backtest_report: strategy.backtest_report?,
+                })
+            })
+            .collect())
+    }
+
+    /// Лучшие по fitness различные стратегии
+    fn select_winners(
+        &self,
+        mut optimized: Vec<OptimizedStrategyResult>,
+    ) -> Vec<(String, OptimizedStrategyResult)> {
+        optimized.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        let mut seen = HashSet::new();
+        optimized
+            .into_iter()
+            .map(|result| (strategy_id(&result.candidate, &result.parameters), result))
+            .filter(|(id, _)| seen.insert(id.clone()))
+            .take(self.config.winners_per_window)
+            .collect()
+    }
+
+    fn out_of_sample_backtest(
+        &self,
+        candidate: &StrategyCandidate,
+        parameters: &StrategyParameterMap,
+        window: &WalkForwardWindow,
+    ) -> Result<BacktestReport> {
+        let definition =
+            StrategyConverter::candidate_to_definition(candidate, self.base_timeframe.clone())
+                .context("Не удалось конвертировать StrategyCandidate в StrategyDefinition")?;
+        self.backtest_window(definition, parameters, window)
+    }
+
+    /// Backtest на тестовом окне. Котировки начинаются за `warmup_bars` баров до окна,
+    /// чтобы индикаторы были готовы уже на первом баре окна; сделки, капитал
+    /// и число баров считаются только внутри окна.
+    fn backtest_window(
+        &self,
+        definition: StrategyDefinition,
+        parameters: &StrategyParameterMap,
+        window: &WalkForwardWindow,
+    ) -> Result<BacktestReport> {
+        let window_frames =
+            WalkForwardWindow::slice(&self.frames, window.out_sample_start, window.out_sample_end);
+        let warmup_bars = BacktestEngine::from_definition(
+            definition.clone(),
+            Some(parameters.clone()),
+            window_frames,
+        )
+        .context("Не удалось создать BacktestEngine")?
+        .warmup_bars();
+        let frames = WalkForwardWindow::slice(
+            &self.frames,
+            self.history_start(window.out_sample_start, warmup_bars),
+            window.out_sample_end,
+        );
+        let mut engine =
+            BacktestEngine::from_definition(definition, Some(parameters.clone()), frames)
+                .context("Не удалось создать BacktestEngine")?
+                .with_config(self.backtest_config.clone())
+                .with_trading_start(window.out_sample_start);
+        engine.run().context("Ошибка выполнения backtest")
+    }
+
+    /// Время бара базового таймфрейма за `bars` баров до `start`
+    fn history_start(&self, start: DateTime<Utc>, bars: usize) -> DateTime<Utc> {
+        let Some(frame) = self.frames.get(&self.base_timeframe) else {
+            return start;
+        };
+        let timestamps = frame.timestamps();
+        let index = timestamps.partition_point(|time| *time < start);
+        timestamps
+            .get(index.saturating_sub(bars))
+            .copied()
+            .unwrap_or(start)
+    }
+}
+
+/// Склеивает отчеты последовательных окон в один: кривая каждого следующего окна
+/// продолжает предыдущую с учетом ее прибыли, начальная точка окна отбрасывается
+pub fn stitch_reports(
+    reports: &[&BacktestReport],
+    initial_capital: f64,
+    risk_config: &EquityRiskConfig,
+) -> BacktestReport {
+    let mut trades = Vec::new();
+    let mut equity_curve = vec![initial_capital];
+    let mut timestamps = Vec::new();
+    let mut intrabar = vec![initial_capital];
+    let with_timestamps = reports
+        .iter()
+        .all(|report| report.equity_timestamps.len() == report.equity_curve.len());
+    let with_intrabar = reports
+        .iter()
+        .all(|report| report.intrabar_equity_curve.len() == report.equity_curve.len());
+    let (mut total_bars, mut bars_in_positions) = (0, 0);
+
+    for report in reports {
+        let Some(&window_start) = report.equity_curve.first() else {
+            continue;
+        };
+        let offset = equity_curve[equity_curve.len() - 1] - window_start;
+        if with_timestamps {
+            if timestamps.is_empty() {
+                timestamps.push(report.equity_timestamps[0]);
+            }
+            timestamps.extend_from_slice(&report.equity_timestamps[1..]);
+        }
+        if with_intrabar {
+            intrabar.extend(report.intrabar_equity_curve[1..].iter().map(|v| v + offset));
+        }
+        equity_curve.extend(report.equity_curve[1..].iter().map(|v| v + offset));
+        trades.extend(report.trades.iter().cloned());
+        total_bars += report.metrics.total_bars;
+        bars_in_positions += report.metrics.bars_in_positions;
+    }
+
+    let metrics = BacktestMetrics::from_data(
+        &trades,
+        &equity_curve,
+        initial_capital,
+        reports.first().and_then(|report| report.metrics.start_date),
+        reports.last().and_then(|report| report.metrics.end_date),
+        total_bars,
+        bars_in_positions,
+        None,
+    )
+    .with_equity_risk(&equity_curve, risk_config);
+    let mut report = BacktestReport::new(trades, metrics, equity_curve);
+    if with_timestamps && !timestamps.is_empty() {
+        report = report.with_equity_timestamps(timestamps);
+    }
+    if with_intrabar && !reports.is_empty() {
+        report = report.with_intrabar_equity(intrabar);
+    }
+    report
+}
+
+/// Устойчивый идентификатор структуры и параметров стратегии
+pub fn strategy_id(candidate: &StrategyCandidate, parameters: &StrategyParameterMap) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(fingerprint::strategy_fingerprint(candidate).as_bytes());
+    hasher.update(fingerprint::parameters_fingerprint(parameters).as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    digest[..16].to_string()
+}
+
+fn days_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// Последний день, попадающий в полуоткрытое окно
+fn last_date(end: DateTime<Utc>) -> NaiveDate {
+    (end - Duration::milliseconds(1)).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::quote::Quote;
+    use crate::data_model::types::Symbol;
+    use crate::strategy::presets::default_strategy_definitions;
+    use chrono::TimeZone;
+
+    #[test]
+    fn rolling_and_anchored_windows_cover_data() {
+        let first = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let last = Utc.with_ymd_and_hms(2024, 1, 10, 23, 0, 0).unwrap();
+        let config = WalkForwardConfig::new("wf", Duration::days(4), Duration::days(2));
+
+        let rolling = config.windows(first, last);
+        assert_eq!(rolling.len(), 3);
+        assert_eq!(rolling[1].in_sample_start, first + Duration::days(2));
+        assert_eq!(rolling[1].out_sample_start, first + Duration::days(6));
+        assert_eq!(rolling[2].out_sample_end, last + Duration::milliseconds(1));
+        assert_eq!(last_date(rolling[2].out_sample_end), last.date_naive());
+
+        let anchored = config
+            .with_mode(WindowMode::Anchored)
+            .with_step(Duration::days(3))
+            .windows(first, last);
+        assert_eq!(anchored.len(), 2);
+        assert!(anchored
+            .iter()
+            .all(|window| window.in_sample_start == first));
+        assert_eq!(anchored[1].in_sample_end, first + Duration::days(7));
+        assert_eq!(anchored[1].out_sample_end, first + Duration::days(9));
+    }
+
+    #[test]
+    fn out_of_sample_backtest_trades_signal_on_first_window_bar() {
+        let definition = default_strategy_definitions()
+            .into_iter()
+            .next()
+            .expect("preset strategy");
+        let symbol = Symbol::from_descriptor("AAA");
+        let timeframe = TimeFrame::minutes(60);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for i in 0..400 {
+            let close = 100.0 + 10.0 * (i as f32 / 12.0).sin();
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start + Duration::hours(i),
+                close - 1.0,
+                close + 1.0,
+                close - 0.5,
+                close,
+                1000.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let frames = HashMap::from([(timeframe.clone(), frame)]);
+        let backtest_config = BacktestConfig {
+            initial_capital: 10_000.0,
+            ..BacktestConfig::default()
+        };
+
+        let full = BacktestEngine::from_definition(definition.clone(), None, frames.clone())
+            .unwrap()
+            .with_config(backtest_config.clone())
+            .run()
+            .unwrap();
+        // Окно начинается на баре входа, найденного на полной истории
+        let entry_time = full
+            .trades
+            .iter()
+            .filter_map(|trade| trade.entry_time)
+            .find(|time| *time >= start + Duration::hours(100))
+            .expect("entry after warmup");
+        let window = WalkForwardWindow {
+            number: 1,
+            in_sample_start: start,
+            in_sample_end: entry_time,
+            out_sample_start: entry_time,
+            out_sample_end: start + Duration::hours(400),
+        };
+        let optimizer = WalkForwardOptimizer::new(
+            WalkForwardConfig::new("wf", Duration::days(4), Duration::days(2)),
+            GeneticAlgorithmConfig::default(),
+            frames.clone(),
+            timeframe,
+            StrategyDiscoveryConfig::default(),
+        )
+        .with_backtest_config(backtest_config.clone());
+
+        let report = optimizer
+            .backtest_window(definition.clone(), &StrategyParameterMap::new(), &window)
+            .unwrap();
+        let window_bars = (window.out_sample_end - entry_time).num_hours() as usize;
+        assert_eq!(
+            report.trades.first().and_then(|trade| trade.entry_time),
+            Some(entry_time)
+        );
+        assert_eq!(report.metrics.start_date, Some(entry_time));
+        assert_eq!(report.metrics.total_bars, window_bars);
+        assert_eq!(report.equity_curve.len(), window_bars + 1);
+
+        // Без истории индикаторы на первом баре окна еще не рассчитаны
+        let cold = BacktestEngine::from_definition(
+            definition,
+            None,
+            WalkForwardWindow::slice(&frames, window.out_sample_start, window.out_sample_end),
+        )
+        .unwrap()
+        .with_config(backtest_config)
+        .run()
+        .unwrap();
+        assert_ne!(
+            cold.trades.first().and_then(|trade| trade.entry_time),
+            Some(entry_time)
+        );
+    }
+
+    #[test]
+    fn stitched_windows_continue_equity() {
+        let window = |curve: Vec<f64>| {
+            let metrics = BacktestMetrics {
+                total_bars: curve.len(),
+                ..BacktestMetrics::default()
+            };
+            BacktestReport::new(vec![], metrics, curve)
+        };
+        let first = window(vec![1_000.0, 1_050.0, 1_100.0]);
+        let second = window(vec![1_000.0, 950.0, 980.0]);
+        let report = stitch_reports(&[&first, &second], 1_000.0, &EquityRiskConfig::default());
+
+        assert_eq!(
+            report.equity_curve,
+            vec![1_000.0, 1_050.0, 1_100.0, 1_050.0, 1_080.0]
+        );
+        assert_eq!(report.metrics.total_bars, 6);
+        assert!((report.metrics.total_profit - 80.0).abs() < 1e-9);
+        assert!(report.equity_timestamps.is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::data_model::costs::CostModel;
+use crate::strategy::context::TimeframeData;
+use crate::strategy::types::{PositionDirection, PriceField};
+
+/// Сторона исполнения заявки
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    pub fn for_entry(direction: &PositionDirection) -> Self {
+        match direction {
+            PositionDirection::Short => Self::Sell,
+            _ => Self::Buy,
+        }
+    }
+
+    pub fn for_exit(direction: &PositionDirection) -> Self {
+        match direction {
+            PositionDirection::Short => Self::Buy,
+            _ => Self::Sell,
+        }
+    }
+}
+
+/// Результат применения модели издержек к одному исполнению
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fill {
+    /// Цена исполнения с учетом проскальзывания
+    pub price: f64,
+    /// Рыночная цена до проскальзывания
+    pub market_price: f64,
+    /// Комиссия за исполнение
+    pub commission: f64,
+    /// Потери на проскальзывании (|price - raw_price| × quantity)
+    pub slippage: f64,
+}
+
+impl Fill {
+    pub fn at(price: f64) -> Self {
+        Self {
+            price,
+            market_price: price,
+            commission: 0.0,
+            slippage: 0.0,
+        }
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.commission + self.slippage
+    }
+}
+
+/// Рассчитывает цену исполнения и издержки рыночной заявки по модели `cost_model`
+pub fn fill(
+    cost_model: &CostModel,
+    side: TradeSide,
+    raw_price: f64,
+    quantity: f64,
+    data: Option<&TimeframeData>,
+    index: usize,
+) -> Fill {
+    if cost_model.is_free() {
+        return Fill::at(raw_price);
+    }
+    let per_unit = cost_model.slippage.slippage_per_unit(raw_price, |period| {
+        data.and_then(|data| average_true_range(data, index, period))
+    });
+    let price = match side {
+        TradeSide::Buy => raw_price + per_unit,
+        TradeSide::Sell => (raw_price - per_unit).max(0.0),
+    };
+    Fill {
+        price,
+        market_price: raw_price,
+        commission: cost_model.commission.commission(price, quantity),
+        slippage: (price - raw_price).abs() * quantity.abs(),
+    }
+}
+
+pub(crate) fn average_true_range(data: &TimeframeData, index: usize, period: usize) -> Option<f64> {
+    let high = data.price_series_slice(&PriceField::High)?;
+    let low = data.price_series_slice(&PriceField::Low)?;
+    let close = data.price_series_slice(&PriceField::Close)?;
+    let len = high.len().min(low.len()).min(close.len());
+    if len == 0 || period == 0 {
+        return None;
+    }
+    let end = index.min(len - 1);
+    let start = end.saturating_sub(period - 1);
+    let mut sum = 0.0;
+    for i in start..=end {
+        let range = (high[i] - low[i]) as f64;
+        let true_range = if i > 0 {
+            let prev_close = close[i - 1] as f64;
+            range
+                .max((high[i] as f64 - prev_close).abs())
+                .max((low[i] as f64 - prev_close).abs())
+        } else {
+            range
+        };
+        sum += true_range;
+    }
+    Some(sum / (end - start + 1) as f64)
+}
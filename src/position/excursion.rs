@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::risk::utils::entry_bar_index;
+use crate::strategy::context::TimeframeData;
+use crate::strategy::types::{PositionDirection, PriceField};
+
+/// Максимальные неблагоприятное (MAE) и благоприятное (MFE) отклонения цены за сделку.
+/// Отклонения неотрицательны и считаются в единицах цены от цены входа.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeExcursion {
+    pub mae: f64,
+    pub mfe: f64,
+    pub mae_pct: f64,
+    pub mfe_pct: f64,
+    /// Отклонения в единицах начального риска; `None`, если у позиции не было стопа
+    pub mae_r: Option<f64>,
+    pub mfe_r: Option<f64>,
+    pub bars_in_trade: usize,
+    /// Число баров от входа до бара, на котором достигнут MFE
+    pub bars_to_mfe: usize,
+    /// Доля доступного хода (MAE + MFE), реализованная сделкой, от -1 до 1
+    pub efficiency: f64,
+}
+
+impl TradeExcursion {
+    /// Оценивает отклонения по барам, целиком лежащим между барами входа и выхода,
+    /// и по самим ценам входа и выхода. Внутрибарные движения баров входа и выхода
+    /// не учитываются: по OHLC нельзя отделить цены до входа и после выхода.
+    pub fn measure(
+        data: &TimeframeData,
+        direction: &PositionDirection,
+        entry_price: f64,
+        exit_price: f64,
+        opened_at: DateTime<Utc>,
+        closed_at: DateTime<Utc>,
+        initial_stop: Option<f64>,
+    ) -> Self {
+        let sign = match direction {
+            PositionDirection::Long => 1.0,
+            PositionDirection::Short => -1.0,
+            _ => return Self::default(),
+        };
+        let entry_index = entry_bar_index(data, Some(opened_at));
+        let exit_index = entry_bar_index(data, Some(closed_at));
+        let bars_in_trade = match (entry_index, exit_index) {
+            (Some(entry), Some(exit)) => exit.saturating_sub(entry),
+            _ => 0,
+        };
+
+        let mut best = (exit_price - entry_price) * sign;
+        let mut worst = best;
+        let mut bars_to_mfe = bars_in_trade;
+        if let (Some(entry), Some(highs), Some(lows)) = (
+            entry_index,
+            data.price_series_slice(&PriceField::High),
+            data.price_series_slice(&PriceField::Low),
+        ) {
+            let last = (entry + bars_in_trade).min(highs.len()).min(lows.len());
+            for index in entry + 1..last {
+                let (favorable, adverse) = if sign > 0.0 {
+                    (highs[index] as f64, lows[index] as f64)
+                } else {
+                    (lows[index] as f64, highs[index] as f64)
+                };
+                let favorable = (favorable - entry_price) * sign;
+                if favorable > best {
+                    best = favorable;
+                    bars_to_mfe = index - entry;
+                }
+                worst = worst.min((adverse - entry_price) * sign);
+            }
+        }
+
+        let mfe = best.max(0.0);
+        let mae = (-worst).max(0.0);
+        let captured = (exit_price - entry_price) * sign;
+        let available = mfe + mae;
+        let risk = initial_stop
+            .map(|stop| (entry_price - stop).abs())
+            .filter(|risk| *risk > f64::EPSILON);
+        let pct = |value: f64| {
+            if entry_price.abs() > f64::EPSILON {
+                value / entry_price * 100.0
+            } else {
+                0.0
+            }
+        };
+        Self {
+            mae,
+            mfe,
+            mae_pct: pct(mae),
+            mfe_pct: pct(mfe),
+            mae_r: risk.map(|risk| mae / risk),
+            mfe_r: risk.map(|risk| mfe / risk),
+            bars_in_trade,
+            bars_to_mfe: if mfe > 0.0 { bars_to_mfe } else { 0 },
+            efficiency: if available > f64::EPSILON {
+                captured / available
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+/// Распределение величины по сделкам
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExcursionDistribution {
+    pub mean: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl ExcursionDistribution {
+    pub fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        values.retain(|value| value.is_finite());
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let quantile = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Some(Self {
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            median: quantile(0.5),
+            p75: quantile(0.75),
+            p90: quantile(0.9),
+            max: values[values.len() - 1],
+        })
+    }
+}
+
+/// Сводка MAE/MFE по всем сделкам бэктеста
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExcursionStats {
+    pub mae_pct: Option<ExcursionDistribution>,
+    pub mfe_pct: Option<ExcursionDistribution>,
+    /// Только по сделкам с начальным стопом
+    pub mae_r: Option<ExcursionDistribution>,
+    pub mfe_r: Option<ExcursionDistribution>,
+    /// MAE прибыльных сделок: показывает, насколько можно сузить стоп
+    pub winners_mae_pct: Option<ExcursionDistribution>,
+    /// MFE убыточных сделок: показывает, где мог бы стоять тейк
+    pub losers_mfe_pct: Option<ExcursionDistribution>,
+    pub average_efficiency: Option<f64>,
+    pub average_bars_in_trade: Option<f64>,
+    pub average_bars_to_mfe: Option<f64>,
+}
+
+impl ExcursionStats {
+    /// Строит сводку по парам (отклонения сделки, PnL сделки)
+    pub fn from_trades<'a>(trades: impl IntoIterator<Item = (&'a TradeExcursion, f64)>) -> Self {
+        let trades: Vec<(&TradeExcursion, f64)> = trades.into_iter().collect();
+        if trades.is_empty() {
+            return Self::default();
+        }
+        let collect = |f: &dyn Fn(&TradeExcursion, f64) -> Option<f64>| {
+            trades
+                .iter()
+                .filter_map(|(excursion, pnl)| f(excursion, *pnl))
+                .collect::<Vec<f64>>()
+        };
+        let average = |values: Vec<f64>| {
+            ExcursionDistribution::from_values(values).map(|distribution| distribution.mean)
+        };
+        Self {
+            mae_pct: ExcursionDistribution::from_values(collect(&|e, _| Some(e.mae_pct))),
+            mfe_pct: ExcursionDistribution::from_values(collect(&|e, _| Some(e.mfe_pct))),
+            mae_r: ExcursionDistribution::from_values(collect(&|e, _| e.mae_r)),
+            mfe_r: ExcursionDistribution::from_values(collect(&|e, _| e.mfe_r)),
+            winners_mae_pct: ExcursionDistribution::from_values(collect(&|e, pnl| {
+                (pnl > 0.0).then_some(e.mae_pct)
+            })),
+            losers_mfe_pct: ExcursionDistribution::from_values(collect(&|e, pnl| {
+                (pnl < 0.0).then_some(e.mfe_pct)
+            })),
+            average_efficiency: average(collect(&|e, _| Some(e.efficiency))),
+            average_bars_in_trade: average(collect(&|e, _| Some(e.bars_in_trade as f64))),
+            average_bars_to_mfe: average(collect(&|e, _| Some(e.bars_to_mfe as f64))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::quote::Quote;
+    use crate::data_model::quote_frame::QuoteFrame;
+    use crate::data_model::types::{Symbol, TimeFrame};
+    use chrono::Duration;
+
+    #[test]
+    fn measures_excursions_between_entry_and_exit_bars() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let start = Utc::now();
+        let bars = [
+            (100.0, 101.0, 90.0),
+            (100.0, 103.0, 98.0),
+            (102.0, 106.0, 101.0),
+            (100.0, 102.0, 97.0),
+            (104.0, 120.0, 80.0),
+        ];
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        for (i, (close, high, low)) in bars.into_iter().enumerate() {
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start + Duration::minutes(i as i64),
+                close,
+                high,
+                low,
+                close,
+                1.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let data = TimeframeData::with_quote_frame(&frame, bars.len() - 1);
+
+        let excursion = TradeExcursion::measure(
+            &data,
+            &PositionDirection::Long,
+            100.0,
+            104.0,
+            start,
+            start + Duration::minutes(4),
+            Some(96.0),
+        );
+        assert_eq!(excursion.bars_in_trade, 4);
+        assert_eq!(excursion.bars_to_mfe, 2);
+        assert!((excursion.mfe - 6.0).abs() < 1e-9);
+        assert!((excursion.mae - 3.0).abs() < 1e-9);
+        assert!((excursion.mfe_r.unwrap() - 1.5).abs() < 1e-9);
+        assert!((excursion.efficiency - 4.0 / 9.0).abs() < 1e-9);
+
+        let stats = ExcursionStats::from_trades([(&excursion, 4.0)]);
+        assert_eq!(stats.winners_mae_pct.unwrap().max, excursion.mae_pct);
+        assert!(stats.losers_mfe_pct.is_none());
+    }
+}
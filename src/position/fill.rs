@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+use crate::position::costs::TradeSide;
+use crate::strategy::context::TimeframeData;
+use crate::strategy::types::PriceField;
+
+/// Момент и цена исполнения рыночной заявки относительно бара сигнала.
+///
+/// Сигналы считаются по закрытию бара, поэтому исполнение допустимо
+/// либо по цене закрытия этого же бара, либо по ценам следующего бара.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum FillPolicy {
+    /// Close бара сигнала
+    #[default]
+    ThisBarClose,
+    /// Open следующего бара
+    NextBarOpen,
+    /// Приближение VWAP следующего бара: (O + H + L + C) / 4
+    NextBarVwap,
+    /// Худшая для стороны цена следующего бара: High для покупки, Low для продажи
+    NextBarWorst,
+}
+
+/// Цена исполнения и бар, на котором оно произошло
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FillPoint {
+    pub price: f64,
+    pub index: usize,
+}
+
+impl FillPolicy {
+    pub fn uses_next_bar(&self) -> bool {
+        !matches!(self, Self::ThisBarClose)
+    }
+
+    /// Цена исполнения для сигнала на баре `signal_index`.
+    /// Возвращает `None`, если нужного бара (или ценового ряда) нет.
+    pub fn resolve(
+        &self,
+        data: &TimeframeData,
+        signal_index: usize,
+        side: TradeSide,
+    ) -> Option<FillPoint> {
+        let index = if self.uses_next_bar() {
+            signal_index + 1
+        } else {
+            signal_index
+        };
+        let price_at = |field: PriceField| -> Option<f64> {
+            data.price_series_slice(&field)
+                .and_then(|series| series.get(index).copied())
+                .map(f64::from)
+        };
+        let price = match self {
+            Self::ThisBarClose => price_at(PriceField::Close)?,
+            Self::NextBarOpen => price_at(PriceField::Open)?,
+            Self::NextBarVwap => {
+                let open = price_at(PriceField::Open)?;
+                let high = price_at(PriceField::High)?;
+                let low = price_at(PriceField::Low)?;
+                let close = price_at(PriceField::Close)?;
+                (open + high + low + close) / 4.0
+            }
+            Self::NextBarWorst => match side {
+                TradeSide::Buy => price_at(PriceField::High)?,
+                TradeSide::Sell => price_at(PriceField::Low)?,
+            },
+        };
+        Some(FillPoint { price, index })
+    }
+}
@@ -4,15 +4,29 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
 
+use super::costs::{self, Fill, TradeSide};
+use super::excursion::TradeExcursion;
+use super::fill::{FillPoint, FillPolicy};
+use super::margin::{MarginModel, MarginRequirement, MarginStatus};
+use super::sizing::{round_units, PositionSizer, SizingContext, SizingStats};
 use super::view::{ActivePosition, PositionBook, PositionInsights};
+use crate::condition::types::SignalStrength;
+use crate::data_model::calendar::{TradingCalendar, UnsupportedTimezone};
+use crate::data_model::costs::{CommissionModel, CostModel};
+use crate::data_model::meta::{InstrumentMeta, MetaRegistry, TickRounding};
 use crate::data_model::types::{Symbol, TimeFrame};
 use crate::metrics::PortfolioSnapshot;
-use crate::strategy::context::StrategyContext;
+use crate::risk::guards::{
+    AccountRiskGuard, EntryExposure, GuardKind, RiskGuardConfig, RiskGuardStats,
+};
+use crate::risk::intrabar::IntrabarResolution;
+use crate::strategy::context::{StrategyContext, TimeframeData};
 use crate::strategy::types::{
-    PositionDirection, PriceField, StopSignal, StopSignalKind, StrategyDecision, StrategyError,
-    StrategyId, StrategySignal,
+    OrderRequest, OrderType, PositionDirection, PriceField, StopSignal, StopSignalKind,
+    StrategyDecision, StrategyError, StrategyId, StrategySignal, StrategySignalType, TimeInForce,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -105,6 +119,10 @@ pub struct PositionState {
     pub key: PositionKey,
     pub status: PositionStatus,
     pub quantity: f64,
+    /// Наибольший объем позиции с учетом доливок, от него считаются доли частичных выходов
+    pub initial_quantity: f64,
+    /// Число частичных закрытий позиции
+    pub partial_exits: usize,
     pub average_price: f64,
     pub current_price: f64,
     pub realized_pnl: f64,
@@ -113,14 +131,14 @@ pub struct PositionState {
     pub updated_at: DateTime<Utc>,
     pub closed_at: Option<DateTime<Utc>>,
     pub metadata: HashMap<String, String>,
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum OrderType {
-    Market,
-    Limit,
-    Stop,
-    StopLimit,
+    /// Комиссия входа, еще не отнесенная на закрытые сделки
+    pub entry_commission: f64,
+    /// Проскальзывание входа, еще не отнесенное на закрытые сделки
+    pub entry_slippage: f64,
+    /// Начисленная плата за перенос, еще не отнесенная на закрытые сделки
+    pub financing: f64,
+    /// Дата, по которую начислено финансирование
+    pub financed_at: DateTime<Utc>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -149,6 +167,90 @@ pub struct OrderTicket {
     pub metadata: HashMap<String, String>,
 }
 
+/// Отложенная заявка на вход, ожидающая исполнения на следующих барах
+#[derive(Clone, Debug)]
+pub struct PendingOrder {
+    pub ticket: OrderTicket,
+    pub key: PositionKey,
+    pub signal: StrategySignal,
+    pub request: OrderRequest,
+    /// Бар, на котором выставлена заявка
+    pub placed_index: usize,
+    pub placed_at: Option<DateTime<Utc>>,
+    /// Для `TimeInForce::Session`: конец торговой сессии бара сигнала
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Для StopLimit: цена активации достигнута, заявка работает как лимитная
+    pub triggered: bool,
+}
+
+impl PendingOrder {
+    fn is_expired(&self, data: &TimeframeData, index: usize) -> bool {
+        match self.request.time_in_force {
+            TimeInForce::Bars(bars) => index > self.placed_index + bars.max(1),
+            TimeInForce::Session => match (self.expires_at, data.timestamp_at(index)) {
+                (Some(expires_at), Some(current)) => current >= expires_at,
+                _ => false,
+            },
+            TimeInForce::Gtc => false,
+        }
+    }
+
+    /// Цена исполнения на баре `index`, если заявка исполняется.
+    /// При гэпе через уровень исполнение происходит по Open.
+    fn match_bar(&mut self, data: &TimeframeData, index: usize) -> Option<f64> {
+        let bar = |field: PriceField| {
+            data.price_series_slice(&field)
+                .and_then(|series| series.get(index).copied())
+                .map(f64::from)
+        };
+        let (open, high, low) = (
+            bar(PriceField::Open)?,
+            bar(PriceField::High)?,
+            bar(PriceField::Low)?,
+        );
+        let buy = TradeSide::for_entry(&self.key.direction) == TradeSide::Buy;
+        let price = self.request.price;
+        match self.request.order_type {
+            OrderType::Market => Some(open),
+            OrderType::Limit => Self::match_limit(buy, price, open, high, low),
+            OrderType::Stop => Self::match_stop(buy, price, open, high, low),
+            OrderType::StopLimit => {
+                let limit = self.request.limit_price.unwrap_or(price);
+                let reference = if self.triggered {
+                    open
+                } else {
+                    let trigger = Self::match_stop(buy, price, open, high, low)?;
+                    self.triggered = true;
+                    trigger
+                };
+                if buy && low <= limit {
+                    Some(reference.min(limit))
+                } else if !buy && high >= limit {
+                    Some(reference.max(limit))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn match_limit(buy: bool, price: f64, open: f64, high: f64, low: f64) -> Option<f64> {
+        if buy {
+            (low <= price).then(|| open.min(price))
+        } else {
+            (high >= price).then(|| open.max(price))
+        }
+    }
+
+    fn match_stop(buy: bool, price: f64, open: f64, high: f64, low: f64) -> Option<f64> {
+        if buy {
+            (high >= price).then(|| open.max(price))
+        } else {
+            (low <= price).then(|| open.min(price))
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum PositionEvent {
     OrderFilled(OrderTicket),
@@ -157,7 +259,7 @@ pub enum PositionEvent {
     PositionClosed(PositionState),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StopHistoryEntry {
     pub bar_index: usize,
     pub stop_level: f64,
@@ -176,10 +278,25 @@ pub struct ClosedTrade {
     pub exit_price: f64,
     pub entry_time: Option<DateTime<Utc>>,
     pub exit_time: Option<DateTime<Utc>>,
+    /// PnL сделки за вычетом комиссии и проскальзывания
     pub pnl: f64,
+    /// PnL сделки без учета издержек
+    pub gross_pnl: f64,
+    /// Комиссия входа и выхода
+    pub commission: f64,
+    /// Потери на проскальзывании входа и выхода
+    pub slippage: f64,
+    /// Плата за заем бумаг и финансирование позиции
+    pub financing: f64,
     pub entry_rule_id: Option<String>,
     pub exit_rule_id: Option<String>,
+    /// Причина выхода без идентификатора правила, например `exit` или `risk_guard:daily_loss`
+    pub exit_reason: Option<String>,
     pub stop_history: Vec<StopHistoryEntry>,
+    /// Способ выбора между стопом и тейком, сработавшими на одном баре
+    pub intrabar_resolution: Option<IntrabarResolution>,
+    /// MAE/MFE и длительность сделки
+    pub excursion: TradeExcursion,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -205,6 +322,8 @@ pub enum PositionError {
     Persistence(#[source] anyhow::Error),
     #[error("event handler error: {0}")]
     Event(#[source] anyhow::Error),
+    #[error(transparent)]
+    Calendar(#[from] UnsupportedTimezone),
 }
 
 #[async_trait]
@@ -232,6 +351,15 @@ pub struct PositionManager {
     initial_capital: f64,
     use_full_capital: bool,
     reinvest_profits: bool,
+    cost_model: CostModel,
+    fill_policy: FillPolicy,
+    meta_registry: Option<Arc<MetaRegistry>>,
+    pending_orders: Vec<PendingOrder>,
+    position_sizer: Option<Arc<dyn PositionSizer>>,
+    sizing_stats: SizingStats,
+    margin_model: Option<MarginModel>,
+    risk_guard: Option<AccountRiskGuard>,
+    calendar: Option<TradingCalendar>,
 }
 
 impl PositionManager {
@@ -249,6 +377,15 @@ impl PositionManager {
             initial_capital: 0.0,
             use_full_capital: false,
             reinvest_profits: false,
+            cost_model: CostModel::default(),
+            fill_policy: FillPolicy::default(),
+            meta_registry: None,
+            pending_orders: Vec::new(),
+            position_sizer: None,
+            sizing_stats: SizingStats::default(),
+            margin_model: None,
+            risk_guard: None,
+            calendar: None,
         }
     }
 
@@ -279,172 +416,1097 @@ impl PositionManager {
         self.initial_capital
     }
 
-    pub fn reset(&mut self) {
-        self.positions.clear();
-        self.open_index.clear();
-        self.orders.clear();
-        self.event_history.clear();
-        self.sequence = 0;
-        self.portfolio.reset();
+    pub fn with_cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = cost_model;
+        self
     }
 
-    pub fn set_persistence(&mut self, persistence: Option<Arc<dyn PositionPersistence>>) {
-        self.persistence = persistence;
+    pub fn set_cost_model(&mut self, cost_model: CostModel) {
+        self.cost_model = cost_model;
     }
 
-    pub fn register_listener(&mut self, listener: Arc<dyn PositionEventListener>) {
-        self.listeners.push(listener);
+    pub fn cost_model(&self) -> &CostModel {
+        &self.cost_model
     }
 
-    pub fn strategy_id(&self) -> &StrategyId {
-        &self.strategy_id
+    pub fn with_fill_policy(mut self, fill_policy: FillPolicy) -> Self {
+        self.fill_policy = fill_policy;
+        self
     }
 
-    pub fn positions(&self) -> impl Iterator<Item = &PositionState> {
-        self.positions.values()
+    pub fn set_fill_policy(&mut self, fill_policy: FillPolicy) {
+        self.fill_policy = fill_policy;
     }
 
-    pub fn portfolio_snapshot(&self) -> &PortfolioSnapshot {
-        &self.portfolio
+    pub fn fill_policy(&self) -> FillPolicy {
+        self.fill_policy
     }
 
-    pub fn event_history(&self) -> &[PositionEvent] {
-        &self.event_history
+    pub fn with_position_sizer(mut self, sizer: Arc<dyn PositionSizer>) -> Self {
+        self.position_sizer = Some(sizer);
+        self
     }
 
-    pub fn open_position_count(&self) -> usize {
-        self.open_index.len()
+    pub fn set_position_sizer(&mut self, sizer: Option<Arc<dyn PositionSizer>>) {
+        self.position_sizer = sizer;
     }
 
-    pub fn process_decision(
-        &mut self,
-        context: &mut StrategyContext,
-        decision: &StrategyDecision,
-    ) -> Result<ExecutionReport, PositionError> {
-        let mut report = ExecutionReport::default();
-        let has_active_positions = !self.open_index.is_empty();
+    pub fn position_sizer(&self) -> Option<&Arc<dyn PositionSizer>> {
+        self.position_sizer.as_ref()
+    }
 
-        if has_active_positions {
-            let mut stop_ids: HashSet<String> = HashSet::new();
-            let mut stop_signals: Vec<&StopSignal> = decision.stop_signals.iter().collect();
-            stop_signals.sort_unstable_by_key(|signal| signal.priority);
-            for stop in stop_signals {
-                stop_ids.insert(stop.signal.rule_id.clone());
-                let reason = format!("stop:{:?}", stop.kind);
-                self.handle_exit_signal(
-                    context,
-                    &stop.signal,
-                    Some(stop.exit_price),
-                    Some(reason),
-                    &mut report,
-                )?;
-            }
-            for exit in &decision.exits {
-                if stop_ids.contains(&exit.rule_id)
-                    && exit.tags.iter().any(|tag| tag.eq_ignore_ascii_case("stop"))
-                {
-                    continue;
-                }
-                self.handle_exit_signal(context, exit, None, None, &mut report)?;
-            }
+    /// Объем входа по сигналу: явный объем сигнала, затем сайзер,
+    /// затем весь капитал (`use_full_capital`) или одна единица.
+    /// `None` — сайзер не смог рассчитать объем, вход пропускается.
+    pub fn entry_quantity(
+        &self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        price: f64,
+        stop_level: Option<f64>,
+    ) -> Option<f64> {
+        if let Some(quantity) = signal.quantity {
+            return Some(quantity);
         }
-        for entry in &decision.entries {
-            self.handle_entry_signal(context, entry, &mut report)?;
+        let capital = if self.reinvest_profits {
+            self.initial_capital + self.portfolio.total_equity
+        } else {
+            self.initial_capital
+        };
+        let data = context.timeframe(&signal.timeframe).ok();
+        let symbol = data.and_then(|data| data.symbol());
+        let multiplier = symbol
+            .map(|symbol| self.contract_multiplier(symbol))
+            .unwrap_or(1.0);
+        let instrument = symbol.and_then(|symbol| self.instrument(symbol));
+        if let Some(sizer) = &self.position_sizer {
+            return sizer.quantity(&SizingContext {
+                data,
+                index: data.map(|data| data.index()).unwrap_or(0),
+                direction: &signal.direction,
+                entry_price: price,
+                equity: capital,
+                stop_level,
+                contract_multiplier: multiplier,
+                instrument,
+                stats: &self.sizing_stats,
+            });
+        }
+        if self.use_full_capital && price > 0.0 {
+            Some(round_units(instrument, capital / (price * multiplier)).unwrap_or(0.0))
+        } else {
+            Some(
+                data.and_then(|data| data.symbol())
+                    .map(|symbol| self.minimum_quantity(symbol))
+                    .unwrap_or(1.0),
+            )
         }
-        let snapshot = self.snapshot_active_positions_with_context(context);
-        context.set_active_positions(snapshot);
-        Ok(report)
     }
 
-    fn handle_entry_signal(
+    pub fn with_margin_model(mut self, margin_model: MarginModel) -> Self {
+        self.margin_model = Some(margin_model);
+        self
+    }
+
+    pub fn set_margin_model(&mut self, margin_model: Option<MarginModel>) {
+        self.margin_model = margin_model;
+    }
+
+    pub fn margin_model(&self) -> Option<&MarginModel> {
+        self.margin_model.as_ref()
+    }
+
+    pub fn with_risk_guards(mut self, config: RiskGuardConfig) -> Self {
+        self.risk_guard = Some(AccountRiskGuard::new(config));
+        self
+    }
+
+    pub fn set_risk_guards(&mut self, config: Option<RiskGuardConfig>) {
+        self.risk_guard = config.map(AccountRiskGuard::new);
+    }
+
+    /// Календарь, по которому истекают заявки `TimeInForce::Session`;
+    /// без него сессией считаются сутки UTC
+    pub fn with_calendar(mut self, calendar: TradingCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    pub fn set_calendar(&mut self, calendar: Option<TradingCalendar>) {
+        self.calendar = calendar;
+    }
+
+    pub fn risk_guard(&self) -> Option<&AccountRiskGuard> {
+        self.risk_guard.as_ref()
+    }
+
+    pub fn risk_guard_stats(&self) -> RiskGuardStats {
+        self.risk_guard
+            .as_ref()
+            .map(AccountRiskGuard::stats)
+            .unwrap_or_default()
+    }
+
+    pub fn set_meta_registry(&mut self, registry: Option<Arc<MetaRegistry>>) {
+        self.meta_registry = registry;
+    }
+
+    pub fn meta_registry(&self) -> Option<&Arc<MetaRegistry>> {
+        self.meta_registry.as_ref()
+    }
+
+    /// Модель издержек для инструмента: индивидуальная из MetaRegistry или общая
+    pub fn cost_model_for(&self, symbol: &Symbol) -> &CostModel {
+        self.meta_registry
+            .as_ref()
+            .and_then(|registry| registry.get(symbol))
+            .and_then(|meta| meta.costs())
+            .unwrap_or(&self.cost_model)
+    }
+
+    pub fn instrument(&self, symbol: &Symbol) -> Option<&InstrumentMeta> {
+        self.meta_registry
+            .as_ref()
+            .and_then(|registry| registry.get(symbol))
+    }
+
+    /// Множитель контракта инструмента (1.0, если инструмент не описан)
+    pub fn contract_multiplier(&self, symbol: &Symbol) -> f64 {
+        self.instrument(symbol)
+            .map(InstrumentMeta::pnl_multiplier)
+            .unwrap_or(1.0)
+    }
+
+    /// Округляет объем вниз до лота инструмента; `None`, если меньше одного лота
+    pub fn round_to_lot(&self, symbol: &Symbol, quantity: f64) -> Option<f64> {
+        match self.instrument(symbol) {
+            Some(meta) => meta.round_quantity(quantity),
+            None => Some(quantity),
+        }
+    }
+
+    /// Объем выхода по сигналу: частичный выход берет долю позиции, округленную до лота,
+    /// а остаток меньше лота закрывается вместе с ней
+    fn exit_quantity(&self, state: &PositionState, signal: &StrategySignal) -> f64 {
+        let requested = signal.quantity.unwrap_or(state.quantity);
+        let Some(fraction) = signal
+            .close_fraction
+            .filter(|fraction| *fraction > 0.0 && *fraction < 1.0)
+        else {
+            return requested;
+        };
+        let partial = requested.min(state.quantity) * fraction;
+        let Some(meta) = self.instrument(&state.key.symbol) else {
+            return partial;
+        };
+        let rounded = meta
+            .round_quantity(partial)
+            .unwrap_or_else(|| self.minimum_quantity(&state.key.symbol))
+            .min(state.quantity);
+        if meta.round_quantity(state.quantity - rounded).is_none() {
+            state.quantity
+        } else {
+            rounded
+        }
+    }
+
+    /// Минимальный объем заявки: один лот или одна единица
+    fn minimum_quantity(&self, symbol: &Symbol) -> f64 {
+        self.instrument(symbol)
+            .map(|meta| meta.lot_size() as f64)
+            .filter(|lot| *lot > 0.0)
+            .unwrap_or(1.0)
+    }
+
+    /// Приводит цену к шагу цены инструмента
+    pub fn round_to_tick(&self, symbol: &Symbol, price: f64, rounding: TickRounding) -> f64 {
+        match self.instrument(symbol) {
+            Some(meta) => meta.round_price(price, rounding),
+            None => price,
+        }
+    }
+
+    fn margin_requirement(&self, symbol: &Symbol) -> Option<MarginRequirement> {
+        let model = self.margin_model.as_ref()?;
+        Some(model.requirement(self.instrument(symbol).map(InstrumentMeta::asset_class)))
+    }
+
+    /// Цена закрытия текущего бара для позиции, если ее таймфрейм есть в контексте
+    fn mark_price(&self, context: &StrategyContext, state: &PositionState) -> Option<f64> {
+        self.mark_price_at(context, state, &PriceField::Close)
+    }
+
+    fn mark_price_at(
+        &self,
+        context: &StrategyContext,
+        state: &PositionState,
+        field: &PriceField,
+    ) -> Option<f64> {
+        let data = context.timeframe(&state.key.timeframe).ok()?;
+        if !belongs_to_context(data, &state.key.symbol) {
+            return None;
+        }
+        data.price_series_slice(field)
+            .and_then(|series| series.get(data.index()).copied())
+            .map(f64::from)
+    }
+
+    /// Реализованный и нереализованный PnL с переоценкой позиций по текущим барам
+    /// всех контекстов. `worst_case` оценивает лонги по минимуму бара, шорты — по максимуму;
+    /// позиции без бара в контекстах оцениваются по цене последнего исполнения.
+    pub fn marked_pnl(&self, contexts: &[&StrategyContext], worst_case: bool) -> f64 {
+        self.open_positions()
+            .fold(self.portfolio.realized_pnl, |equity, state| {
+                let field = match (worst_case, &state.key.direction) {
+                    (true, PositionDirection::Long) => PriceField::Low,
+                    (true, PositionDirection::Short) => PriceField::High,
+                    _ => PriceField::Close,
+                };
+                equity + self.mark_position(contexts, state, &field).1
+            })
+    }
+
+    /// Капитал и маржа счета по ценам текущего бара; `None` без маржинальной модели
+    pub fn margin_status(&self, context: &StrategyContext) -> Option<MarginStatus> {
+        self.portfolio_margin_status(&[context])
+    }
+
+    /// Капитал и маржа счета по текущим барам всех контекстов портфеля
+    pub fn portfolio_margin_status(&self, contexts: &[&StrategyContext]) -> Option<MarginStatus> {
+        self.margin_status_after(contexts, &Reversal::default())
+    }
+
+    /// Капитал и маржа счета после закрытия позиций разворота
+    fn margin_status_after(
+        &self,
+        contexts: &[&StrategyContext],
+        reversal: &Reversal,
+    ) -> Option<MarginStatus> {
+        self.margin_model.as_ref()?;
+        let mut status = MarginStatus {
+            equity: self.initial_capital + self.portfolio.realized_pnl + reversal.equity_change,
+            ..MarginStatus::default()
+        };
+        for state in self.open_positions() {
+            let requirement = self.margin_requirement(&state.key.symbol)?;
+            let (notional, pnl) = self.mark_position(contexts, state, &PriceField::Close);
+            status.equity += pnl;
+            if reversal.closes(state) {
+                continue;
+            }
+            status.initial_margin += notional * requirement.initial;
+            status.maintenance_margin += notional * requirement.maintenance;
+        }
+        Some(status)
+    }
+
+    /// Стоимость позиции и ее PnL за вычетом комиссии входа и финансирования
+    /// по полю `field` текущего бара первого контекста с этим инструментом
+    fn mark_position(
+        &self,
+        contexts: &[&StrategyContext],
+        state: &PositionState,
+        field: &PriceField,
+    ) -> (f64, f64) {
+        let price = contexts
+            .iter()
+            .find_map(|context| self.mark_price_at(context, state, field))
+            .unwrap_or(state.current_price);
+        let (notional, pnl) = self.position_value(state, price);
+        (notional, pnl - state.entry_commission - state.financing)
+    }
+
+    /// Стоимость позиции и ее ценовой PnL по цене `price`
+    fn position_value(&self, state: &PositionState, price: f64) -> (f64, f64) {
+        let multiplier = self.contract_multiplier(&state.key.symbol);
+        let notional = state.quantity.abs() * price * multiplier;
+        let pnl = match state.key.direction {
+            PositionDirection::Long => (price - state.average_price) * state.quantity,
+            PositionDirection::Short => (state.average_price - price) * state.quantity,
+            PositionDirection::Flat | PositionDirection::Both => 0.0,
+        } * multiplier;
+        (notional, pnl)
+    }
+
+    /// Капитал счета по ценам текущего бара
+    pub fn account_equity(&self, context: &StrategyContext) -> f64 {
+        self.open_positions().fold(
+            self.initial_capital + self.portfolio.realized_pnl,
+            |equity, state| equity + self.mark_position(&[context], state, &PriceField::Close).1,
+        )
+    }
+
+    /// Проверяет вход портфельными ограничениями риска
+    fn check_risk_guard(
         &mut self,
         context: &StrategyContext,
-        signal: &StrategySignal,
-        report: &mut ExecutionReport,
-    ) -> Result<(), PositionError> {
-        let direction = match signal.direction.clone() {
-            PositionDirection::Long => PositionDirection::Long,
-            PositionDirection::Short => PositionDirection::Short,
-            PositionDirection::Flat => return Ok(()),
-            PositionDirection::Both => {
-                return Err(PositionError::UnsupportedDirection(PositionDirection::Both))
+        info: &MarketSnapshot,
+        direction: &PositionDirection,
+        quantity: f64,
+        reversal: &Reversal,
+    ) -> Result<(), GuardKind> {
+        if self.risk_guard.is_none() {
+            return Ok(());
+        }
+        let mut entry = EntryExposure {
+            direction: direction.clone(),
+            notional: quantity * info.price * self.contract_multiplier(&info.symbol),
+            long_notional: 0.0,
+            short_notional: 0.0,
+            positions_in_direction: 0,
+            equity: self.account_equity(context) + reversal.equity_change,
+        };
+        for state in self
+            .open_positions()
+            .filter(|state| !reversal.closes(state))
+        {
+            let (notional, _) = self.mark_position(&[context], state, &PriceField::Close);
+            match state.key.direction {
+                PositionDirection::Long => entry.long_notional += notional,
+                PositionDirection::Short => entry.short_notional += notional,
+                PositionDirection::Flat | PositionDirection::Both => {}
+            }
+            if &state.key.direction == direction {
+                entry.positions_in_direction += 1;
             }
+        }
+        match self.risk_guard.as_mut() {
+            Some(guard) => guard.check_entry(info.timestamp, &entry),
+            None => Ok(()),
+        }
+    }
+
+    /// Сигналы закрытия позиций текущего контекста, если сработал лимит убытка
+    /// или kill switch. Вызывается на каждом баре, в том числе без открытых позиций.
+    pub fn risk_guard_signals(&mut self, context: &StrategyContext) -> Vec<StopSignal> {
+        match self.check_risk_guards(&[context]) {
+            Some((kind, equity)) => self.risk_guard_liquidation(context, kind, equity),
+            None => Vec::new(),
+        }
+    }
+
+    /// Проверяет лимиты убытка и kill switch по капиталу на текущих барах всех
+    /// контекстов; возвращает сработавшее ограничение и капитал.
+    /// Вызывается один раз на бар портфеля.
+    pub fn check_risk_guards(&mut self, contexts: &[&StrategyContext]) -> Option<(GuardKind, f64)> {
+        self.risk_guard.as_ref()?;
+        let time = contexts
+            .iter()
+            .filter_map(|context| {
+                let data = context.timeframe_by_index(0).ok()?;
+                data.timestamp_at(data.index())
+            })
+            .max()?;
+        let equity = self.initial_capital + self.marked_pnl(contexts, false);
+        let kind = self.risk_guard.as_mut()?.on_bar(time, equity)?;
+        Some((kind, equity))
+    }
+
+    /// Сигналы закрытия позиций контекста по сработавшему ограничению риска
+    pub fn risk_guard_liquidation(
+        &self,
+        context: &StrategyContext,
+        kind: GuardKind,
+        equity: f64,
+    ) -> Vec<StopSignal> {
+        let metadata = HashMap::from([("equity".to_string(), equity.to_string())]);
+        self.liquidation_signals(
+            context,
+            StopSignalKind::RiskGuard(kind),
+            kind.as_str(),
+            &metadata,
+        )
+    }
+
+    fn open_positions(&self) -> impl Iterator<Item = &PositionState> {
+        self.open_index
+            .values()
+            .filter_map(|position_id| self.positions.get(position_id))
+    }
+
+    /// Уменьшает объем входа до свободной покупательной способности;
+    /// `None`, если не хватает маржи даже на один лот
+    fn fit_buying_power(
+        &self,
+        context: &StrategyContext,
+        symbol: &Symbol,
+        quantity: f64,
+        price: f64,
+        reversal: &Reversal,
+    ) -> Option<f64> {
+        if self.initial_capital <= 0.0 || price <= 0.0 {
+            return Some(quantity);
+        }
+        let (Some(requirement), Some(status)) = (
+            self.margin_requirement(symbol),
+            self.margin_status_after(&[context], reversal),
+        ) else {
+            return Some(quantity);
         };
-        let info = match Self::resolve_entry_snapshot(context, &signal.timeframe)? {
-            Some(snapshot) => snapshot,
-            None => return Ok(()),
+        let unit_margin = price * self.contract_multiplier(symbol) * requirement.initial;
+        if unit_margin <= 0.0 || quantity * unit_margin <= status.available() {
+            return Some(quantity);
+        }
+        let affordable = (status.available().max(0.0) / unit_margin).min(quantity);
+        round_units(self.instrument(symbol), affordable)
+    }
+
+    /// Начисляет плату за перенос позиций, пересекших границу дня.
+    /// Возвращает `true`, если капитал изменился.
+    pub fn accrue_financing(&mut self, context: &StrategyContext) -> bool {
+        let Some(model) = self.margin_model.as_ref() else {
+            return false;
         };
-        let mut quantity = if self.use_full_capital && signal.quantity.is_none() {
-            let capital = if self.reinvest_profits {
-                self.initial_capital + self.portfolio.total_equity
-            } else {
-                self.initial_capital
+        let mut charges = Vec::new();
+        for (position_id, state) in self
+            .open_index
+            .values()
+            .filter_map(|id| self.positions.get(id).map(|state| (id, state)))
+        {
+            let Some(data) = context
+                .timeframe(&state.key.timeframe)
+                .ok()
+                .filter(|data| belongs_to_context(data, &state.key.symbol))
+            else {
+                continue;
             };
-            if info.price > 0.0 {
-                (capital / info.price).floor()
-            } else {
-                1.0
+            let Some(timestamp) = data.timestamp_at(data.index()) else {
+                continue;
+            };
+            let days = (timestamp.date_naive() - state.financed_at.date_naive()).num_days();
+            if days <= 0 {
+                continue;
             }
-        } else {
-            signal.quantity.unwrap_or(1.0)
-        };
-        if quantity.abs() <= f64::EPSILON {
-            quantity = 1.0;
+            let price = self
+                .mark_price(context, state)
+                .unwrap_or(state.current_price);
+            let notional =
+                state.quantity.abs() * price * self.contract_multiplier(&state.key.symbol);
+            let requirement = model.requirement(
+                self.instrument(&state.key.symbol)
+                    .map(InstrumentMeta::asset_class),
+            );
+            let cost = model.financing_cost(&state.key.direction, notional, days, &requirement);
+            charges.push((position_id.clone(), timestamp, cost));
         }
-        let position_group = signal
-            .position_group
-            .clone()
-            .or_else(|| Some(signal.rule_id.clone()));
-        let entry_rule_value = signal
-            .entry_rule_id
-            .clone()
-            .unwrap_or_else(|| signal.rule_id.clone());
-        let entry_rule_id = Some(entry_rule_value.clone());
-        if let Some(opposite_direction) = opposite_direction(&direction) {
-            let opposite_ids: Vec<String> = self
-                .open_index
-                .iter()
-                .filter_map(|(key, id)| {
-                    if key.matches(&info.symbol, &info.timeframe, &opposite_direction) {
-                        Some(id.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            for opposite_id in opposite_ids {
-                if let Some(state) = self.positions.get(&opposite_id) {
-                    let close_qty = state.quantity;
-                    if close_qty.abs() > f64::EPSILON {
-                        self.close_position(
-                            opposite_id,
-                            info.price,
-                            close_qty,
-                            info.timestamp,
-                            Some("reversal".to_string()),
-                            Some(signal.rule_id.clone()),
-                            report,
-                        )?;
-                    }
-                }
+        if charges.is_empty() {
+            return false;
+        }
+        let mut changed = false;
+        for (position_id, timestamp, cost) in charges {
+            if let Some(state) = self.positions.get_mut(&position_id) {
+                state.financing += cost;
+                state.financed_at = timestamp;
+                changed |= cost > 0.0;
             }
         }
-        let key = PositionKey::new(
-            info.symbol.clone(),
-            info.timeframe.clone(),
-            direction.clone(),
-            position_group,
-            entry_rule_id,
-        );
-        if self.open_index.contains_key(&key) {
-            return Ok(());
+        if changed {
+            self.refresh_portfolio_metrics();
         }
-        self.open_new_position(key, quantity, info.price, info.timestamp, signal, report)?;
-        Ok(())
+        changed
     }
 
-    fn handle_exit_signal(
-        &mut self,
+    /// Сигналы принудительного закрытия позиций текущего контекста,
+    /// если капитал опустился ниже поддерживающей маржи
+    pub fn margin_call_signals(&self, context: &StrategyContext) -> Vec<StopSignal> {
+        match self.margin_call(&[context]) {
+            Some(status) => self.margin_call_liquidation(context, &status),
+            None => Vec::new(),
+        }
+    }
+
+    /// Состояние счета, если капитал по текущим барам всех контекстов
+    /// опустился ниже поддерживающей маржи
+    pub fn margin_call(&self, contexts: &[&StrategyContext]) -> Option<MarginStatus> {
+        self.portfolio_margin_status(contexts)
+            .filter(MarginStatus::is_margin_call)
+    }
+
+    /// Сигналы закрытия позиций контекста по маржин-коллу
+    pub fn margin_call_liquidation(
+        &self,
+        context: &StrategyContext,
+        status: &MarginStatus,
+    ) -> Vec<StopSignal> {
+        let metadata = HashMap::from([
+            ("equity".to_string(), status.equity.to_string()),
+            (
+                "maintenance_margin".to_string(),
+                status.maintenance_margin.to_string(),
+            ),
+        ]);
+        self.liquidation_signals(
+            context,
+            StopSignalKind::MarginCall,
+            "margin_call",
+            &metadata,
+        )
+    }
+
+    /// Сигналы закрытия всех позиций текущего контекста по цене закрытия бара
+    pub fn liquidation_signals(
+        &self,
+        context: &StrategyContext,
+        kind: StopSignalKind,
+        handler_id: &str,
+        metadata: &HashMap<String, String>,
+    ) -> Vec<StopSignal> {
+        self.open_positions()
+            .filter_map(|state| {
+                let exit_price = self.mark_price(context, state)?;
+                let signal = StrategySignal {
+                    rule_id: format!("{}_{}", handler_id, state.id),
+                    signal_type: StrategySignalType::Exit,
+                    direction: state.key.direction.clone(),
+                    timeframe: state.key.timeframe.clone(),
+                    strength: SignalStrength::Strong,
+                    quantity: None,
+                    entry_rule_id: state.key.entry_rule_id.clone(),
+                    tags: vec!["stop".to_string()],
+                    position_group: state.key.position_group.clone(),
+                    target_entry_ids: state.key.entry_rule_id.iter().cloned().collect(),
+                    order: None,
+                    close_fraction: None,
+                };
+                Some(StopSignal {
+                    handler_id: handler_id.to_string(),
+                    signal,
+                    exit_price,
+                    kind: kind.clone(),
+                    priority: 0,
+                    metadata: metadata.clone(),
+                })
+            })
+            .collect()
+    }
+
+    fn fill_at(
+        &self,
+        context: &StrategyContext,
+        snapshot: &MarketSnapshot,
+        side: TradeSide,
+        quantity: f64,
+    ) -> Fill {
+        let data = context.timeframe(&snapshot.timeframe).ok();
+        let cost_model = self.cost_model_for(&snapshot.symbol);
+        let mut fill = costs::fill(
+            cost_model,
+            side,
+            snapshot.price,
+            quantity,
+            data,
+            snapshot.index,
+        );
+        // Проскальзывание и процентная комиссия считаются от стоимости контрактов
+        let multiplier = self.contract_multiplier(&snapshot.symbol);
+        if (multiplier - 1.0).abs() > f64::EPSILON {
+            fill.slippage *= multiplier;
+            if matches!(
+                cost_model.commission,
+                CommissionModel::PercentOfNotional { .. }
+            ) {
+                fill.commission *= multiplier;
+            }
+        }
+        fill
+    }
+
+    pub fn pending_orders(&self) -> &[PendingOrder] {
+        &self.pending_orders
+    }
+
+    pub fn has_pending_orders(&self) -> bool {
+        !self.pending_orders.is_empty()
+    }
+
+    /// Сопоставляет отложенные заявки с текущим баром: исполняет,
+    /// снимает по сроку действия или оставляет ждать
+    pub fn process_pending_orders(
+        &mut self,
+        context: &mut StrategyContext,
+    ) -> Result<ExecutionReport, PositionError> {
+        let mut report = ExecutionReport::default();
+        if self.pending_orders.is_empty() {
+            return Ok(report);
+        }
+        let pending = std::mem::take(&mut self.pending_orders);
+        for mut order in pending {
+            let Ok(data) = context.timeframe(&order.key.timeframe) else {
+                self.pending_orders.push(order);
+                continue;
+            };
+            let index = data.index();
+            if !belongs_to_context(data, &order.key.symbol) || index <= order.placed_index {
+                self.pending_orders.push(order);
+                continue;
+            }
+            if order.is_expired(data, index) {
+                self.finish_pending_order(order, OrderStatus::Cancelled, &mut report);
+                continue;
+            }
+            let Some(price) = order.match_bar(data, index) else {
+                self.pending_orders.push(order);
+                continue;
+            };
+            if self.open_index.contains_key(&order.key) {
+                self.finish_pending_order(order, OrderStatus::Cancelled, &mut report);
+                continue;
+            }
+            let info = self.resolve_market_snapshot(
+                context,
+                &order.key.timeframe,
+                Some(price),
+                TradeSide::for_entry(&order.key.direction),
+            )?;
+            let direction = order.key.direction.clone();
+            let signal = order.signal.clone();
+            self.execute_entry(context, &signal, direction, info, Some(order), &mut report)?;
+        }
+        let snapshot = self.snapshot_active_positions_with_context(context);
+        context.set_active_positions(snapshot);
+        context.set_pending_orders(self.pending_orders.len());
+        Ok(report)
+    }
+
+    fn place_pending_order(
+        &mut self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        direction: PositionDirection,
+        request: OrderRequest,
+        report: &mut ExecutionReport,
+    ) -> Result<(), PositionError> {
+        let data = context
+            .timeframe(&signal.timeframe)
+            .map_err(PositionError::from)?;
+        let symbol = data
+            .symbol()
+            .cloned()
+            .ok_or_else(|| PositionError::MissingSymbol(signal.timeframe.clone()))?;
+        let index = data.index();
+        let placed_at = data.timestamp_at(index);
+        let request = self.round_order_request(&symbol, TradeSide::for_entry(&direction), request);
+        let expires_at = match (request.time_in_force, placed_at) {
+            (TimeInForce::Session, Some(timestamp)) => self.session_end(&symbol, timestamp)?,
+            _ => None,
+        };
+        let key = Self::entry_key(symbol, signal, direction);
+        if self.open_index.contains_key(&key) {
+            return Ok(());
+        }
+        // Новый сигнал того же правила заменяет ранее выставленную заявку
+        let (replaced, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_orders)
+            .into_iter()
+            .partition(|order| order.key == key);
+        self.pending_orders = kept;
+        for order in replaced {
+            self.finish_pending_order(order, OrderStatus::Cancelled, report);
+        }
+        let mut ticket = self.build_order("", &key, signal.quantity.unwrap_or(0.0), request.price);
+        ticket.order_type = request.order_type;
+        ticket.status = OrderStatus::Submitted;
+        if let Some(timestamp) = placed_at {
+            ticket.created_at = timestamp;
+            ticket.updated_at = timestamp;
+        }
+        self.orders.insert(ticket.id.clone(), ticket.clone());
+        report.orders.push(ticket.clone());
+        self.pending_orders.push(PendingOrder {
+            ticket,
+            key,
+            signal: signal.clone(),
+            request,
+            placed_index: index,
+            placed_at,
+            expires_at,
+            triggered: false,
+        });
+        Ok(())
+    }
+
+    /// Конец торговой сессии инструмента для момента `timestamp`.
+    /// Часовой пояс инструмента из справочника заменяет пояс календаря.
+    fn session_end(
+        &self,
+        symbol: &Symbol,
+        timestamp: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, PositionError> {
+        let Some(calendar) = self.calendar.as_ref() else {
+            let next_day = timestamp.date_naive().succ_opt();
+            return Ok(next_day.map(|day| day.and_time(NaiveTime::MIN).and_utc()));
+        };
+        let timezone = match self.instrument(symbol).and_then(InstrumentMeta::timezone) {
+            Some(timezone) => timezone.parse()?,
+            None => calendar.timezone,
+        };
+        let calendar = calendar.clone().with_timezone(timezone);
+        Ok(calendar.session_end(timestamp))
+    }
+
+    fn cancel_pending_orders(
+        &mut self,
+        symbol: &Symbol,
+        timeframe: &TimeFrame,
+        direction: &PositionDirection,
+        target_groups: Option<&HashSet<String>>,
+        report: &mut ExecutionReport,
+    ) {
+        if self.pending_orders.is_empty() {
+            return;
+        }
+        let (cancelled, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_orders)
+            .into_iter()
+            .partition(|order| {
+                order.key.matches(symbol, timeframe, direction)
+                    && target_groups.is_none_or(|groups| key_in_groups(&order.key, groups))
+            });
+        self.pending_orders = kept;
+        for order in cancelled {
+            self.finish_pending_order(order, OrderStatus::Cancelled, report);
+        }
+    }
+
+    /// Отклоняет вход объемом меньше одного лота
+    fn reject_entry(
+        &mut self,
+        key: &PositionKey,
+        quantity: f64,
+        price: f64,
+        pending: Option<PendingOrder>,
+        report: &mut ExecutionReport,
+    ) {
+        if let Some(order) = pending {
+            self.finish_pending_order(order, OrderStatus::Rejected, report);
+            return;
+        }
+        let mut ticket = self.build_order("", key, quantity, price);
+        ticket.status = OrderStatus::Rejected;
+        self.orders.insert(ticket.id.clone(), ticket.clone());
+        report.orders.push(ticket);
+    }
+
+    /// Приводит цены заявки к шагу цены: лимит — в сторону лучшей цены,
+    /// активация стопа — в сторону более позднего срабатывания
+    fn round_order_request(
+        &self,
+        symbol: &Symbol,
+        side: TradeSide,
+        mut request: OrderRequest,
+    ) -> OrderRequest {
+        let (limit, stop) = match side {
+            TradeSide::Buy => (TickRounding::Down, TickRounding::Up),
+            TradeSide::Sell => (TickRounding::Up, TickRounding::Down),
+        };
+        let trigger = match request.order_type {
+            OrderType::Limit => limit,
+            OrderType::Market | OrderType::Stop | OrderType::StopLimit => stop,
+        };
+        request.price = self.round_to_tick(symbol, request.price, trigger);
+        request.limit_price = request
+            .limit_price
+            .map(|price| self.round_to_tick(symbol, price, limit));
+        request
+    }
+
+    fn finish_pending_order(
+        &mut self,
+        order: PendingOrder,
+        status: OrderStatus,
+        report: &mut ExecutionReport,
+    ) {
+        let mut ticket = order.ticket;
+        ticket.status = status;
+        self.orders.insert(ticket.id.clone(), ticket.clone());
+        report.orders.push(ticket);
+    }
+
+    pub fn reset(&mut self) {
+        self.positions.clear();
+        self.open_index.clear();
+        self.orders.clear();
+        self.pending_orders.clear();
+        self.sizing_stats = SizingStats::default();
+        if let Some(guard) = self.risk_guard.as_mut() {
+            guard.reset();
+        }
+        self.event_history.clear();
+        self.sequence = 0;
+        self.portfolio.reset();
+    }
+
+    pub fn set_persistence(&mut self, persistence: Option<Arc<dyn PositionPersistence>>) {
+        self.persistence = persistence;
+    }
+
+    pub fn register_listener(&mut self, listener: Arc<dyn PositionEventListener>) {
+        self.listeners.push(listener);
+    }
+
+    pub fn strategy_id(&self) -> &StrategyId {
+        &self.strategy_id
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &PositionState> {
+        self.positions.values()
+    }
+
+    pub fn portfolio_snapshot(&self) -> &PortfolioSnapshot {
+        &self.portfolio
+    }
+
+    pub fn event_history(&self) -> &[PositionEvent] {
+        &self.event_history
+    }
+
+    pub fn open_position_count(&self) -> usize {
+        self.open_index.len()
+    }
+
+    pub fn process_decision(
+        &mut self,
+        context: &mut StrategyContext,
+        decision: &StrategyDecision,
+    ) -> Result<ExecutionReport, PositionError> {
+        let mut report = ExecutionReport::default();
+        let has_active_positions = !self.open_index.is_empty() || !self.pending_orders.is_empty();
+
+        if has_active_positions {
+            let mut stop_ids: HashSet<String> = HashSet::new();
+            let mut stop_signals: Vec<&StopSignal> = decision.stop_signals.iter().collect();
+            stop_signals.sort_unstable_by_key(|signal| signal.priority);
+            for stop in stop_signals {
+                stop_ids.insert(stop.signal.rule_id.clone());
+                let reason = match &stop.kind {
+                    StopSignalKind::RiskGuard(kind) => format!("risk_guard:{}", kind.as_str()),
+                    kind => format!("stop:{:?}", kind),
+                };
+                let exit_price = self.round_stop_price(context, stop);
+                self.handle_exit_signal(
+                    context,
+                    &stop.signal,
+                    Some(exit_price),
+                    Some(reason),
+                    &mut report,
+                )?;
+            }
+            for exit in &decision.exits {
+                // Сигналы стопов и тейков дублируются в exits; частичный тейк не должен исполниться дважды
+                if stop_ids.contains(&exit.rule_id)
+                    && exit.tags.iter().any(|tag| {
+                        tag.eq_ignore_ascii_case("stop") || tag.eq_ignore_ascii_case("take")
+                    })
+                {
+                    continue;
+                }
+                self.handle_exit_signal(context, exit, None, None, &mut report)?;
+            }
+        }
+        for entry in &decision.entries {
+            self.handle_entry_signal(context, entry, &mut report)?;
+        }
+        let snapshot = self.snapshot_active_positions_with_context(context);
+        context.set_active_positions(snapshot);
+        context.set_pending_orders(self.pending_orders.len());
+        Ok(report)
+    }
+
+    /// Цена выхода по стопу на сетке шага цены: уровень округляется туда,
+    /// где заявка сработала бы позже (стоп на продажу — вниз, лимит на продажу — вверх)
+    fn round_stop_price(&self, context: &StrategyContext, stop: &StopSignal) -> f64 {
+        let Some(symbol) = context
+            .timeframe(&stop.signal.timeframe)
+            .ok()
+            .and_then(|data| data.symbol())
+        else {
+            return stop.exit_price;
+        };
+        let long = matches!(stop.signal.direction, PositionDirection::Long);
+        let take = matches!(stop.kind, StopSignalKind::TakeProfit);
+        let rounding = if take == long {
+            TickRounding::Up
+        } else {
+            TickRounding::Down
+        };
+        self.round_to_tick(symbol, stop.exit_price, rounding)
+    }
+
+    fn handle_entry_signal(
+        &mut self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        report: &mut ExecutionReport,
+    ) -> Result<(), PositionError> {
+        let direction = match signal.direction.clone() {
+            PositionDirection::Long => PositionDirection::Long,
+            PositionDirection::Short => PositionDirection::Short,
+            PositionDirection::Flat => return Ok(()),
+            PositionDirection::Both => {
+                return Err(PositionError::UnsupportedDirection(PositionDirection::Both))
+            }
+        };
+        if let Some(request) = signal
+            .order
+            .as_ref()
+            .filter(|request| request.order_type != OrderType::Market)
+        {
+            return self.place_pending_order(context, signal, direction, request.clone(), report);
+        }
+        let info = match self.resolve_entry_snapshot(
+            context,
+            &signal.timeframe,
+            TradeSide::for_entry(&direction),
+        )? {
+            Some(snapshot) => snapshot,
+            None => return Ok(()),
+        };
+        self.execute_entry(context, signal, direction, info, None, report)
+    }
+
+    fn execute_entry(
+        &mut self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        direction: PositionDirection,
+        info: MarketSnapshot,
+        pending: Option<PendingOrder>,
+        report: &mut ExecutionReport,
+    ) -> Result<(), PositionError> {
+        // Отложенная заявка закрывает встречную позицию по своей цене исполнения
+        let exit_price = pending.as_ref().map(|_| info.price);
+        let Some(mut quantity) = self.entry_quantity(context, signal, info.price, None) else {
+            return Ok(());
+        };
+        if quantity.abs() <= f64::EPSILON {
+            quantity = self.minimum_quantity(&info.symbol);
+        }
+        let Some(quantity) = self.round_to_lot(&info.symbol, quantity) else {
+            let key = Self::entry_key(info.symbol.clone(), signal, direction);
+            self.reject_entry(&key, quantity, info.price, pending, report);
+            return Ok(());
+        };
+        let reversal = self.plan_reversal(context, signal, &direction, &info, exit_price)?;
+        let key = Self::entry_key(info.symbol.clone(), signal, direction.clone());
+        if self.open_index.contains_key(&key) {
+            self.execute_reversal(context, signal, reversal, report)?;
+            if let Some(order) = pending {
+                self.finish_pending_order(order, OrderStatus::Cancelled, report);
+            }
+            return Ok(());
+        }
+        // Проверки идут по счету после разворота: отклоненный вход не закрывает
+        // встречную позицию
+        let Some(quantity) =
+            self.fit_buying_power(context, &info.symbol, quantity, info.price, &reversal)
+        else {
+            self.reject_entry(&key, quantity, info.price, pending, report);
+            return Ok(());
+        };
+        if self
+            .check_risk_guard(context, &info, &direction, quantity, &reversal)
+            .is_err()
+        {
+            self.reject_entry(&key, quantity, info.price, pending, report);
+            return Ok(());
+        }
+        self.execute_reversal(context, signal, reversal, report)?;
+        let entry_fill = self.fill_at(context, &info, TradeSide::for_entry(&direction), quantity);
+        // Исполненная заявка заменяет выставленную ранее
+        if let Some(order) = pending {
+            self.orders.remove(&order.ticket.id);
+        }
+        self.open_new_position(key, quantity, entry_fill, info.timestamp, signal, report)?;
+        Ok(())
+    }
+
+    /// Встречные позиции, которые закроет вход в направлении `direction`
+    fn plan_reversal(
+        &self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        direction: &PositionDirection,
+        info: &MarketSnapshot,
+        exit_price: Option<f64>,
+    ) -> Result<Reversal, PositionError> {
+        let Some(opposite_direction) = opposite_direction(direction) else {
+            return Ok(Reversal::default());
+        };
+        let opposite: Vec<&PositionState> = self
+            .open_positions()
+            .filter(|state| {
+                state
+                    .key
+                    .matches(&info.symbol, &info.timeframe, &opposite_direction)
+                    && state.quantity.abs() > f64::EPSILON
+            })
+            .collect();
+        if opposite.is_empty() {
+            return Ok(Reversal::default());
+        }
+        let exit_side = TradeSide::for_exit(&opposite_direction);
+        let exit_info =
+            self.resolve_market_snapshot(context, &signal.timeframe, exit_price, exit_side)?;
+        let mut reversal = Reversal::default();
+        for state in opposite {
+            let fill = self.fill_at(context, &exit_info, exit_side, state.quantity);
+            let (_, marked_pnl) = self.mark_position(&[context], state, &PriceField::Close);
+            let (_, exit_pnl) = self.position_value(state, fill.price);
+            // Закрытие реализует PnL за вычетом обеих комиссий и финансирования
+            let realized = exit_pnl - fill.commission - state.entry_commission - state.financing;
+            reversal.equity_change += realized - marked_pnl;
+            reversal
+                .exits
+                .push((state.id.clone(), state.quantity, fill));
+        }
+        reversal.timestamp = exit_info.timestamp;
+        Ok(reversal)
+    }
+
+    fn execute_reversal(
+        &mut self,
+        context: &StrategyContext,
+        signal: &StrategySignal,
+        reversal: Reversal,
+        report: &mut ExecutionReport,
+    ) -> Result<(), PositionError> {
+        for (position_id, quantity, fill) in reversal.exits {
+            self.close_position(
+                context,
+                position_id,
+                fill,
+                quantity,
+                reversal.timestamp,
+                Some("reversal".to_string()),
+                Some(signal.rule_id.clone()),
+                report,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn entry_key(
+        symbol: Symbol,
+        signal: &StrategySignal,
+        direction: PositionDirection,
+    ) -> PositionKey {
+        let position_group = signal
+            .position_group
+            .clone()
+            .or_else(|| Some(signal.rule_id.clone()));
+        let entry_rule_id = signal
+            .entry_rule_id
+            .clone()
+            .or_else(|| Some(signal.rule_id.clone()));
+        PositionKey::new(
+            symbol,
+            signal.timeframe.clone(),
+            direction,
+            position_group,
+            entry_rule_id,
+        )
+    }
+
+    fn handle_exit_signal(
+        &mut self,
         context: &StrategyContext,
         signal: &StrategySignal,
         price_hint: Option<f64>,
@@ -459,7 +1521,12 @@ impl PositionManager {
                 return Err(PositionError::UnsupportedDirection(PositionDirection::Both))
             }
         };
-        let info = Self::resolve_market_snapshot(context, &signal.timeframe, price_hint)?;
+        let info = self.resolve_market_snapshot(
+            context,
+            &signal.timeframe,
+            price_hint,
+            TradeSide::for_exit(&direction),
+        )?;
         let target_groups = if signal.target_entry_ids.is_empty() {
             None
         } else {
@@ -468,7 +1535,7 @@ impl PositionManager {
                     .target_entry_ids
                     .iter()
                     .cloned()
-                    .collect::<std::collections::HashSet<_>>(),
+                    .collect::<HashSet<_>>(),
             )
         };
         let targets: Vec<(String, f64)> = self
@@ -479,24 +1546,15 @@ impl PositionManager {
                     return None;
                 }
                 if let Some(groups) = &target_groups {
-                    let matches_target = key
-                        .position_group
-                        .as_ref()
-                        .map_or(false, |group| groups.contains(group))
-                        || key
-                            .entry_rule_id
-                            .as_ref()
-                            .map_or(false, |entry_id| groups.contains(entry_id));
-                    if !matches_target {
+                    if !key_in_groups(key, groups) {
                         return None;
                     }
                 }
-                let quantity = signal.quantity.unwrap_or_else(|| {
-                    self.positions
-                        .get(position_id)
-                        .map(|state| state.quantity)
-                        .unwrap_or(0.0)
-                });
+                let quantity = self
+                    .positions
+                    .get(position_id)
+                    .map(|state| self.exit_quantity(state, signal))
+                    .unwrap_or(0.0);
                 if quantity.abs() > f64::EPSILON {
                     Some((position_id.clone(), quantity))
                 } else {
@@ -504,15 +1562,24 @@ impl PositionManager {
                 }
             })
             .collect();
+        self.cancel_pending_orders(
+            &info.symbol,
+            &info.timeframe,
+            &direction,
+            target_groups.as_ref(),
+            report,
+        );
         if targets.is_empty() {
             return Ok(());
         }
         let reason_label = reason.clone().unwrap_or_else(|| "exit".to_string());
         let reason_with_rule = format!("{} via {}", reason_label, signal.rule_id);
         for (position_id, quantity) in targets {
+            let exit_fill = self.fill_at(context, &info, TradeSide::for_exit(&direction), quantity);
             self.close_position(
+                context,
                 position_id,
-                info.price,
+                exit_fill,
                 quantity,
                 info.timestamp,
                 Some(reason_with_rule.clone()),
@@ -527,11 +1594,12 @@ impl PositionManager {
         &mut self,
         key: PositionKey,
         quantity: f64,
-        price: f64,
+        fill: Fill,
         timestamp: Option<DateTime<Utc>>,
         signal: &StrategySignal,
         report: &mut ExecutionReport,
     ) -> Result<(), PositionError> {
+        let price = fill.price;
         let position_id = self.next_id("pos");
         let event_time = timestamp.unwrap_or_else(Utc::now);
         let mut metadata = HashMap::new();
@@ -545,16 +1613,25 @@ impl PositionManager {
             key: key.clone(),
             status: PositionStatus::Open,
             quantity,
+            initial_quantity: quantity,
+            partial_exits: 0,
             average_price: price,
-            current_price: price,
+            current_price: fill.market_price,
             realized_pnl: 0.0,
             unrealized_pnl: 0.0,
             opened_at: event_time,
             updated_at: event_time,
             closed_at: None,
             metadata,
+            entry_commission: fill.commission,
+            entry_slippage: fill.slippage,
+            financing: 0.0,
+            financed_at: event_time,
         };
-        let order = self.build_order(&position_id, &key, quantity, price);
+        let mut order = self.build_order(&position_id, &key, quantity, price);
+        if let Some(request) = &signal.order {
+            order.order_type = request.order_type;
+        }
         self.positions.insert(position_id.clone(), state.clone());
         self.open_index.insert(key, position_id.clone());
         self.orders.insert(order.id.clone(), order.clone());
@@ -574,6 +1651,7 @@ impl PositionManager {
 
     fn scale_position(
         &mut self,
+        context: &StrategyContext,
         position_id: String,
         quantity: f64,
         price: f64,
@@ -595,8 +1673,9 @@ impl PositionManager {
                 .clone()
                 .or_else(|| Some(signal.rule_id.clone()));
             self.close_position(
+                context,
                 position_id,
-                price,
+                Fill::at(price),
                 quantity.abs(),
                 None,
                 Some("scale_to_flat".to_string()),
@@ -608,6 +1687,7 @@ impl PositionManager {
         state.average_price =
             ((state.average_price * state.quantity) + (price * quantity)) / new_quantity;
         state.quantity = new_quantity;
+        state.initial_quantity = state.initial_quantity.max(new_quantity);
         state.current_price = price;
         state.status = PositionStatus::Open;
         state.updated_at = now;
@@ -632,8 +1712,9 @@ impl PositionManager {
 
     fn close_position(
         &mut self,
+        context: &StrategyContext,
         position_id: String,
-        price: f64,
+        fill: Fill,
         quantity: f64,
         timestamp: Option<DateTime<Utc>>,
         reason: Option<String>,
@@ -647,6 +1728,11 @@ impl PositionManager {
             return Ok(());
         }
         let event_time = timestamp.unwrap_or_else(Utc::now);
+        let multiplier = self
+            .positions
+            .get(&position_id)
+            .map(|state| self.contract_multiplier(&state.key.symbol))
+            .unwrap_or(1.0);
         let state = self.positions.get_mut(&position_id).ok_or_else(|| {
             PositionError::Persistence(anyhow!("position {} missing for close", position_id))
         })?;
@@ -654,13 +1740,29 @@ impl PositionManager {
         if exit_quantity.abs() <= f64::EPSILON {
             return Ok(());
         }
+        let price = fill.price;
         state.current_price = price;
         let direction = state.key.direction.clone();
-        let pnl = match direction {
+        let price_pnl = match direction {
             PositionDirection::Long => (price - state.average_price) * exit_quantity,
             PositionDirection::Short => (state.average_price - price) * exit_quantity,
             PositionDirection::Flat | PositionDirection::Both => 0.0,
+        } * multiplier;
+        let share = if state.quantity > f64::EPSILON {
+            (exit_quantity / state.quantity).min(1.0)
+        } else {
+            1.0
         };
+        let entry_commission = state.entry_commission * share;
+        let entry_slippage = state.entry_slippage * share;
+        let financing = state.financing * share;
+        state.entry_commission -= entry_commission;
+        state.entry_slippage -= entry_slippage;
+        state.financing -= financing;
+        let commission = entry_commission + fill.commission;
+        let slippage = entry_slippage + fill.slippage;
+        let pnl = price_pnl - commission - financing;
+        let gross_pnl = price_pnl + slippage;
         state.quantity -= exit_quantity;
         state.realized_pnl += pnl;
         state.unrealized_pnl = 0.0;
@@ -670,6 +1772,7 @@ impl PositionManager {
             state.closed_at = Some(event_time);
         } else {
             state.status = PositionStatus::Open;
+            state.partial_exits += 1;
         }
         if let Some(reason_value) = reason.clone() {
             state
@@ -688,6 +1791,26 @@ impl PositionManager {
         drop(state);
         self.portfolio.realized_pnl += pnl;
         self.portfolio.update_equity();
+        let excursion = context
+            .timeframe(&snapshot.key.timeframe)
+            .ok()
+            .filter(|data| belongs_to_context(data, &snapshot.key.symbol))
+            .map(|data| {
+                let initial_stop = context
+                    .active_positions()
+                    .get(&position_id)
+                    .and_then(|position| position.initial_stop);
+                TradeExcursion::measure(
+                    data,
+                    &direction,
+                    snapshot.average_price,
+                    price,
+                    snapshot.opened_at,
+                    event_time,
+                    initial_stop,
+                )
+            })
+            .unwrap_or_default();
         let trade = ClosedTrade {
             position_id: position_id.clone(),
             symbol: snapshot.key.symbol.clone(),
@@ -699,9 +1822,18 @@ impl PositionManager {
             entry_time: Some(snapshot.opened_at),
             exit_time: Some(event_time),
             pnl,
+            gross_pnl,
+            commission,
+            slippage,
+            financing,
             entry_rule_id: snapshot.key.entry_rule_id.clone(),
             exit_rule_id: exit_rule_id.clone(),
+            exit_reason: reason
+                .as_deref()
+                .map(|reason| reason.split(" via ").next().unwrap_or(reason).to_string()),
             stop_history: Vec::new(),
+            intrabar_resolution: None,
+            excursion,
         };
         let order = self.build_order(&position_id, &snapshot.key, exit_quantity, price);
         self.orders.insert(order.id.clone(), order.clone());
@@ -720,6 +1852,10 @@ impl PositionManager {
             report.updated_positions.push(snapshot.clone());
         }
         self.refresh_portfolio_metrics();
+        self.sizing_stats.record(trade.pnl);
+        if let Some(guard) = self.risk_guard.as_mut() {
+            guard.record_trade(trade.pnl, trade.exit_time);
+        }
         report.orders.push(order);
         report.closed_trades.push(trade);
         Ok(())
@@ -793,6 +1929,12 @@ impl PositionManager {
             .open_index
             .values()
             .filter_map(|position_id| self.positions.get(position_id))
+            // В портфельном режиме контекст описывает один инструмент из нескольких
+            .filter(|state| {
+                context
+                    .timeframe(&state.key.timeframe)
+                    .is_ok_and(|data| belongs_to_context(data, &state.key.symbol))
+            })
             .map(|state| {
                 let existing = existing_positions.get(&state.id);
 
@@ -803,6 +1945,8 @@ impl PositionManager {
                     direction: state.key.direction.clone(),
                     entry_price: state.average_price,
                     quantity: state.quantity,
+                    initial_quantity: state.initial_quantity,
+                    partial_exits: state.partial_exits,
                     opened_at: Some(state.opened_at),
                     last_price: Some(state.current_price),
                     metadata: state.metadata.clone(),
@@ -812,6 +1956,7 @@ impl PositionManager {
                     max_high_since_entry: existing.and_then(|p| p.max_high_since_entry),
                     min_low_since_entry: existing.and_then(|p| p.min_low_since_entry),
                     current_stop: existing.and_then(|p| p.current_stop),
+                    initial_stop: existing.and_then(|p| p.initial_stop),
                     entry_bar_index: existing.and_then(|p| p.entry_bar_index),
                 }
             })
@@ -823,8 +1968,13 @@ impl PositionManager {
         let mut exposure = 0.0;
         let mut unrealized = 0.0;
         for position_id in self.open_index.values() {
+            let multiplier = self
+                .positions
+                .get(position_id)
+                .map(|state| self.contract_multiplier(&state.key.symbol))
+                .unwrap_or(1.0);
             if let Some(state) = self.positions.get_mut(position_id) {
-                let pos_exposure = state.quantity.abs() * state.current_price;
+                let pos_exposure = state.quantity.abs() * state.current_price * multiplier;
                 exposure += pos_exposure;
                 let pnl = match state.key.direction {
                     PositionDirection::Long => {
@@ -834,7 +1984,9 @@ impl PositionManager {
                         (state.average_price - state.current_price) * state.quantity
                     }
                     PositionDirection::Flat | PositionDirection::Both => 0.0,
-                };
+                } * multiplier
+                    - state.entry_commission
+                    - state.financing;
                 state.unrealized_pnl = pnl;
                 unrealized += pnl;
             }
@@ -845,65 +1997,77 @@ impl PositionManager {
     }
 
     fn resolve_entry_snapshot(
+        &self,
         context: &StrategyContext,
         timeframe: &TimeFrame,
+        side: TradeSide,
     ) -> Result<Option<MarketSnapshot>, PositionError> {
         let data = context.timeframe(timeframe).map_err(PositionError::from)?;
         let symbol = data
             .symbol()
             .cloned()
             .ok_or_else(|| PositionError::MissingSymbol(timeframe.clone()))?;
-        let series = data.price_series_slice(&PriceField::Open).ok_or_else(|| {
-            PositionError::MissingPriceSeries(timeframe.clone(), PriceField::Open)
-        })?;
-        if series.is_empty() {
-            return Err(PositionError::MissingPriceSeries(
-                timeframe.clone(),
-                PriceField::Open,
-            ));
-        }
-        let current_index = data.index().min(series.len().saturating_sub(1));
-        let target_index = current_index;
-        let price = f64::from(series[target_index]);
-        let timestamp = data.timestamp_at(target_index);
+        let len = Self::series_len(data, timeframe)?;
+        let signal_index = data.index().min(len.saturating_sub(1));
+        // Без бара исполнения (последний бар при next-bar политике) вход не совершается
+        let Some(point) = self.fill_policy.resolve(data, signal_index, side) else {
+            return Ok(None);
+        };
         Ok(Some(MarketSnapshot {
             symbol,
             timeframe: timeframe.clone(),
-            price,
-            index: target_index,
-            timestamp,
+            price: point.price,
+            index: point.index,
+            timestamp: data.timestamp_at(point.index),
         }))
     }
 
     fn resolve_market_snapshot(
+        &self,
         context: &StrategyContext,
         timeframe: &TimeFrame,
         price_hint: Option<f64>,
+        side: TradeSide,
     ) -> Result<MarketSnapshot, PositionError> {
         let data = context.timeframe(timeframe).map_err(PositionError::from)?;
         let symbol = data
             .symbol()
             .cloned()
             .ok_or_else(|| PositionError::MissingSymbol(timeframe.clone()))?;
-        let series = data.price_series_slice(&PriceField::Open).ok_or_else(|| {
-            PositionError::MissingPriceSeries(timeframe.clone(), PriceField::Open)
+        let len = Self::series_len(data, timeframe)?;
+        let index = data.index().min(len.saturating_sub(1));
+        // Стоп/тейк исполняются по своему уровню на текущем баре;
+        // если бара исполнения нет, выход происходит по закрытию текущего
+        let point = match price_hint {
+            Some(price) => FillPoint { price, index },
+            None => self
+                .fill_policy
+                .resolve(data, index, side)
+                .or_else(|| FillPolicy::ThisBarClose.resolve(data, index, side))
+                .ok_or_else(|| {
+                    PositionError::MissingPriceSeries(timeframe.clone(), PriceField::Close)
+                })?,
+        };
+        Ok(MarketSnapshot {
+            symbol,
+            timeframe: timeframe.clone(),
+            price: point.price,
+            index: point.index,
+            timestamp: data.timestamp_at(point.index),
+        })
+    }
+
+    fn series_len(data: &TimeframeData, timeframe: &TimeFrame) -> Result<usize, PositionError> {
+        let series = data.price_series_slice(&PriceField::Close).ok_or_else(|| {
+            PositionError::MissingPriceSeries(timeframe.clone(), PriceField::Close)
         })?;
         if series.is_empty() {
             return Err(PositionError::MissingPriceSeries(
                 timeframe.clone(),
-                PriceField::Open,
+                PriceField::Close,
             ));
         }
-        let index = data.index().min(series.len().saturating_sub(1));
-        let price = price_hint.unwrap_or(f64::from(series[index]));
-        let timestamp = data.timestamp_at(index);
-        Ok(MarketSnapshot {
-            symbol,
-            timeframe: timeframe.clone(),
-            price,
-            index,
-            timestamp,
-        })
+        Ok(series.len())
     }
 
     fn next_id(&mut self, prefix: &str) -> String {
@@ -920,6 +2084,35 @@ struct MarketSnapshot {
     timestamp: Option<DateTime<Utc>>,
 }
 
+/// Встречные позиции, закрываемые разворотом, с заранее рассчитанными исполнениями
+#[derive(Default)]
+struct Reversal {
+    exits: Vec<(String, f64, Fill)>,
+    timestamp: Option<DateTime<Utc>>,
+    /// Изменение капитала от закрытия по цене выхода вместо цены бара
+    equity_change: f64,
+}
+
+impl Reversal {
+    fn closes(&self, state: &PositionState) -> bool {
+        self.exits.iter().any(|(id, _, _)| id == &state.id)
+    }
+}
+
+fn belongs_to_context(data: &TimeframeData, symbol: &Symbol) -> bool {
+    data.symbol().is_none_or(|current| current == symbol)
+}
+
+fn key_in_groups(key: &PositionKey, groups: &HashSet<String>) -> bool {
+    key.position_group
+        .as_ref()
+        .is_some_and(|group| groups.contains(group))
+        || key
+            .entry_rule_id
+            .as_ref()
+            .is_some_and(|entry_id| groups.contains(entry_id))
+}
+
 fn opposite_direction(direction: &PositionDirection) -> Option<PositionDirection> {
     match direction {
         PositionDirection::Long => Some(PositionDirection::Short),
@@ -931,11 +2124,14 @@ fn opposite_direction(direction: &PositionDirection) -> Option<PositionDirection
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::condition::types::{SignalStrength, TrendDirection};
+    use crate::condition::types::TrendDirection;
     use crate::data_model::quote::Quote;
     use crate::data_model::quote_frame::QuoteFrame;
+    use crate::position::margin::MarginModel;
     use crate::strategy::context::TimeframeData;
-    use crate::strategy::types::{StrategyDecision, StrategySignal, StrategySignalType};
+    use crate::strategy::types::{
+        OrderRequest, StrategyDecision, StrategySignal, StrategySignalType, TimeInForce,
+    };
     use std::collections::HashMap;
 
     fn build_context(prices: &[f32], symbol: &Symbol, timeframe: &TimeFrame) -> StrategyContext {
@@ -959,6 +2155,41 @@ mod tests {
         context
     }
 
+    fn build_ohlc_context(
+        bars: &[(f32, f32, f32, f32)],
+        symbol: &Symbol,
+        timeframe: &TimeFrame,
+    ) -> StrategyContext {
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        let start = chrono::Utc::now();
+        for (idx, (open, high, low, close)) in bars.iter().enumerate() {
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start + chrono::Duration::minutes(idx as i64),
+                *open,
+                *high,
+                *low,
+                *close,
+                1.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let mut context = StrategyContext::new();
+        context.insert_timeframe(
+            timeframe.clone(),
+            TimeframeData::with_quote_frame(&frame, 0),
+        );
+        context
+    }
+
+    fn set_bar(context: &mut StrategyContext, timeframe: &TimeFrame, index: usize) {
+        context
+            .timeframe_mut(timeframe)
+            .expect("timeframe")
+            .set_index(index);
+    }
+
     fn entry_signal(timeframe: &TimeFrame) -> StrategySignal {
         StrategySignal {
             rule_id: "enter-long".to_string(),
@@ -971,6 +2202,8 @@ mod tests {
             tags: Vec::new(),
             position_group: Some("enter-long".to_string()),
             target_entry_ids: Vec::new(),
+            order: None,
+            close_fraction: None,
         }
     }
 
@@ -986,6 +2219,8 @@ mod tests {
             tags: Vec::new(),
             position_group: None,
             target_entry_ids: vec!["enter-long".to_string()],
+            order: None,
+            close_fraction: None,
         }
     }
 
@@ -1022,6 +2257,128 @@ mod tests {
         assert!((pnl - 5.0).abs() < 1e-6, "expected pnl ≈ 5.0, got {}", pnl);
     }
 
+    #[tokio::test]
+    async fn partial_exits_produce_trades_linked_by_position_id() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_context(&[100.0, 100.0], &symbol, &timeframe);
+        set_bar(&mut context, &timeframe, 0);
+        let mut manager = PositionManager::new("strategy-partial");
+
+        let mut entry = entry_signal(&timeframe);
+        entry.quantity = Some(4.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(entry);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+
+        let mut trades = Vec::new();
+        for (price, fraction) in [(105.0, Some(0.5)), (110.0, Some(0.5)), (120.0, None)] {
+            let mut exit = exit_signal(&timeframe);
+            exit.quantity = None;
+            exit.close_fraction = fraction;
+            let mut exit_decision = StrategyDecision::empty();
+            exit_decision.exits.push(exit);
+            let mut exit_context = build_context(&[price, price], &symbol, &timeframe);
+            let report = manager
+                .process_decision(&mut exit_context, &exit_decision)
+                .expect("exit failed");
+            if let Some(position) = exit_context.active_positions().values().next() {
+                assert_eq!(position.initial_quantity, 4.0);
+                assert_eq!(position.partial_exits, trades.len() + 1);
+            }
+            trades.extend(report.closed_trades);
+        }
+
+        let quantities: Vec<f64> = trades.iter().map(|trade| trade.quantity).collect();
+        assert_eq!(quantities, vec![2.0, 1.0, 1.0]);
+        assert!(trades
+            .iter()
+            .all(|trade| trade.position_id == trades[0].position_id));
+        assert_eq!(manager.open_position_count(), 0);
+        let pnl = manager.portfolio_snapshot().realized_pnl;
+        assert!((pnl - 40.0).abs() < 1e-6, "pnl {}", pnl);
+    }
+
+    #[tokio::test]
+    async fn applies_commission_and_slippage_to_trade() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_context(&[100.0, 100.0], &symbol, &timeframe);
+        context
+            .timeframe_mut(&timeframe)
+            .expect("entry timeframe")
+            .set_index(0);
+        let mut manager = PositionManager::new("strategy-costs").with_cost_model(CostModel::new(
+            crate::data_model::CommissionModel::PerTrade { fee: 1.0 },
+            crate::data_model::SlippageModel::Fixed { amount: 0.5 },
+        ));
+
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(entry_signal(&timeframe));
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        let unrealized = manager.portfolio_snapshot().unrealized_pnl;
+        assert!(
+            (unrealized + 1.5).abs() < 1e-6,
+            "entry commission and slippage must hit equity, got {}",
+            unrealized
+        );
+
+        let mut exit_decision = StrategyDecision::empty();
+        exit_decision.exits.push(exit_signal(&timeframe));
+        let mut exit_context = build_context(&[105.0, 105.0], &symbol, &timeframe);
+        let report = manager
+            .process_decision(&mut exit_context, &exit_decision)
+            .expect("exit failed");
+        let trade = report.closed_trades.first().expect("closed trade");
+        assert!((trade.entry_price - 100.5).abs() < 1e-6);
+        assert!((trade.exit_price - 104.5).abs() < 1e-6);
+        assert!((trade.gross_pnl - 5.0).abs() < 1e-6);
+        assert!((trade.commission - 2.0).abs() < 1e-6);
+        assert!((trade.slippage - 1.0).abs() < 1e-6);
+        assert!((trade.pnl - 2.0).abs() < 1e-6, "net pnl {}", trade.pnl);
+        assert!((manager.portfolio_snapshot().total_equity - 2.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn next_bar_policy_fills_on_following_bar() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut manager =
+            PositionManager::new("strategy-fill").with_fill_policy(FillPolicy::NextBarOpen);
+
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(entry_signal(&timeframe));
+        let mut last_bar_context = build_context(&[100.0, 110.0], &symbol, &timeframe);
+        let report = manager
+            .process_decision(&mut last_bar_context, &decision)
+            .expect("entry on last bar failed");
+        assert!(report.opened_positions.is_empty());
+        assert_eq!(manager.open_position_count(), 0);
+
+        let mut context = build_context(&[100.0, 110.0, 120.0], &symbol, &timeframe);
+        context
+            .timeframe_mut(&timeframe)
+            .expect("entry timeframe")
+            .set_index(0);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+
+        let mut exit_decision = StrategyDecision::empty();
+        exit_decision.exits.push(exit_signal(&timeframe));
+        let mut exit_context = build_context(&[100.0, 110.0, 120.0], &symbol, &timeframe);
+        let report = manager
+            .process_decision(&mut exit_context, &exit_decision)
+            .expect("exit failed");
+        let trade = report.closed_trades.first().expect("closed trade");
+        assert!((trade.entry_price - 110.0).abs() < 1e-6);
+        assert!((trade.exit_price - 120.0).abs() < 1e-6);
+    }
+
     #[tokio::test]
     async fn stop_signal_closes_position_first() {
         let symbol = Symbol::from_descriptor("TEST.TEST");
@@ -1072,4 +2429,398 @@ mod tests {
             .map(|value| value.starts_with("stop:"))
             .unwrap_or(false));
     }
+
+    #[tokio::test]
+    async fn instrument_meta_rounds_lots_ticks_and_scales_pnl() {
+        let symbol = Symbol::from_descriptor("FORTS:SiZ5");
+        let timeframe = TimeFrame::minutes(1);
+        let mut registry = MetaRegistry::new();
+        registry.insert(
+            InstrumentMeta::builder(symbol.clone())
+                .tick_size(0.5)
+                .lot_size(10.0)
+                .contract_multiplier(2.0)
+                .build(),
+        );
+        let mut manager = PositionManager::new("strategy-meta");
+        manager.set_meta_registry(Some(Arc::new(registry)));
+
+        let mut context = build_context(&[100.0, 100.0], &symbol, &timeframe);
+        set_bar(&mut context, &timeframe, 0);
+        let mut small = entry_signal(&timeframe);
+        small.quantity = Some(5.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(small);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        assert!(report.opened_positions.is_empty());
+        assert_eq!(report.orders[0].status, OrderStatus::Rejected);
+
+        let mut signal = entry_signal(&timeframe);
+        signal.quantity = Some(25.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        let opened = report.opened_positions.first().expect("position opened");
+        assert!((opened.quantity - 20.0).abs() < 1e-6);
+        assert!((manager.portfolio_snapshot().exposure - 4000.0).abs() < 1e-6);
+
+        let mut stop_exit = exit_signal(&timeframe);
+        stop_exit.quantity = None;
+        let mut decision = StrategyDecision::empty();
+        decision.stop_signals.push(StopSignal {
+            handler_id: "stop".to_string(),
+            signal: stop_exit,
+            exit_price: 104.8,
+            kind: StopSignalKind::StopLoss,
+            priority: 0,
+            metadata: HashMap::new(),
+        });
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("stop failed");
+        let trade = report.closed_trades.first().expect("closed trade");
+        assert!((trade.exit_price - 104.5).abs() < 1e-6);
+        assert!((trade.pnl - 180.0).abs() < 1e-6, "pnl {}", trade.pnl);
+    }
+
+    #[tokio::test]
+    async fn margin_model_caps_entries_charges_borrow_and_liquidates() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1440);
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        let start = chrono::Utc::now() - chrono::Duration::days(10);
+        for (idx, price) in [100.0, 100.0, 105.0, 120.0].iter().enumerate() {
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                start + chrono::Duration::days(idx as i64),
+                *price,
+                *price,
+                *price,
+                *price,
+                1.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let mut context = StrategyContext::new();
+        context.insert_timeframe(
+            timeframe.clone(),
+            TimeframeData::with_quote_frame(&frame, 0),
+        );
+        let mut manager = PositionManager::new("strategy-margin")
+            .with_capital(1_000.0, false, false)
+            .with_margin_model(
+                MarginModel::default()
+                    .with_leverage(4.0)
+                    .with_financing(36.5, 0.0),
+            );
+
+        let mut signal = entry_signal(&timeframe);
+        signal.direction = PositionDirection::Short;
+        signal.quantity = Some(50.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        let opened = report.opened_positions.first().expect("position opened");
+        assert!((opened.quantity - 40.0).abs() < 1e-6);
+        let status = manager.margin_status(&context).expect("margin status");
+        assert!((status.initial_margin - 1_000.0).abs() < 1e-6);
+
+        set_bar(&mut context, &timeframe, 1);
+        assert!(manager.accrue_financing(&context));
+        assert!(manager.margin_call_signals(&context).is_empty());
+
+        set_bar(&mut context, &timeframe, 2);
+        assert!(manager.margin_call_signals(&context).is_empty());
+
+        set_bar(&mut context, &timeframe, 3);
+        let signals = manager.margin_call_signals(&context);
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].kind, StopSignalKind::MarginCall);
+        let mut decision = StrategyDecision::empty();
+        decision.stop_signals = signals;
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("liquidation failed");
+        let trade = report.closed_trades.first().expect("liquidated");
+        assert!((trade.exit_price - 120.0).abs() < 1e-6);
+        assert!(
+            (trade.financing - 4.0).abs() < 1e-6,
+            "financing {}",
+            trade.financing
+        );
+        assert!((trade.pnl + 804.0).abs() < 1e-6, "pnl {}", trade.pnl);
+        assert_eq!(manager.open_position_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn rejected_reversal_keeps_opposite_position() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_context(&[100.0, 100.0], &symbol, &timeframe);
+        set_bar(&mut context, &timeframe, 0);
+        let mut manager = PositionManager::new("strategy-reversal")
+            .with_capital(1_000.0, false, false)
+            .with_risk_guards(RiskGuardConfig::default().with_exposure(Some(150.0), None));
+
+        let mut signal = entry_signal(&timeframe);
+        signal.quantity = Some(10.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        assert_eq!(manager.open_position_count(), 1);
+
+        let mut reversal = entry_signal(&timeframe);
+        reversal.rule_id = "enter-short".to_string();
+        reversal.entry_rule_id = Some("enter-short".to_string());
+        reversal.position_group = Some("enter-short".to_string());
+        reversal.direction = PositionDirection::Short;
+        reversal.quantity = Some(20.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(reversal.clone());
+        set_bar(&mut context, &timeframe, 1);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("reversal failed");
+        assert!(report.opened_positions.is_empty());
+        assert!(report.closed_trades.is_empty());
+        assert_eq!(report.orders.last().unwrap().status, OrderStatus::Rejected);
+        assert_eq!(manager.open_position_count(), 1);
+
+        // Экспозиция закрываемого лонга не учитывается в проверке разворота
+        reversal.quantity = Some(10.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(reversal);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("reversal failed");
+        assert_eq!(report.closed_trades.len(), 1);
+        assert_eq!(report.opened_positions.len(), 1);
+        assert_eq!(
+            report.opened_positions[0].key.direction,
+            PositionDirection::Short
+        );
+        assert_eq!(manager.open_position_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn reversal_guard_sees_equity_net_of_entry_commission() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_context(&[100.0, 100.0], &symbol, &timeframe);
+        set_bar(&mut context, &timeframe, 0);
+        let mut manager = PositionManager::new("strategy-reversal-costs")
+            .with_capital(1_000.0, false, false)
+            .with_cost_model(CostModel::new(
+                CommissionModel::PerTrade { fee: 50.0 },
+                crate::data_model::SlippageModel::None,
+            ))
+            .with_risk_guards(RiskGuardConfig::default().with_exposure(Some(100.0), None));
+
+        let mut signal = entry_signal(&timeframe);
+        signal.quantity = Some(10.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("entry failed");
+        assert_eq!(manager.open_position_count(), 1);
+
+        // После закрытия лонга с двумя комиссиями по 50 капитал равен 900
+        let mut reversal = entry_signal(&timeframe);
+        reversal.rule_id = "enter-short".to_string();
+        reversal.entry_rule_id = Some("enter-short".to_string());
+        reversal.position_group = Some("enter-short".to_string());
+        reversal.direction = PositionDirection::Short;
+        reversal.quantity = Some(9.5);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(reversal.clone());
+        set_bar(&mut context, &timeframe, 1);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("reversal failed");
+        assert!(report.closed_trades.is_empty());
+        assert_eq!(report.orders.last().unwrap().status, OrderStatus::Rejected);
+
+        reversal.quantity = Some(9.0);
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(reversal);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("reversal failed");
+        let trade = report.closed_trades.first().expect("long closed");
+        assert!((trade.pnl + 100.0).abs() < 1e-6, "pnl {}", trade.pnl);
+        assert_eq!(report.opened_positions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn session_order_lives_until_overnight_session_end() {
+        use crate::data_model::calendar::{TimeWindow, TradingSession};
+        use chrono::TimeZone;
+
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(60);
+        let mut frame = QuoteFrame::new(symbol.clone(), timeframe.clone());
+        // 18:00 CDT, 00:00 CDT следующего дня и 16:00 CDT — конец сессии Globex
+        for (day, hour) in [(2, 23), (3, 5), (3, 21)] {
+            let time = chrono::Utc
+                .with_ymd_and_hms(2024, 7, day, hour, 0, 0)
+                .unwrap();
+            let quote = Quote::from_parts(
+                symbol.clone(),
+                timeframe.clone(),
+                time,
+                100.0,
+                100.0,
+                100.0,
+                100.0,
+                1.0,
+            );
+            frame.push(quote).unwrap();
+        }
+        let mut context = StrategyContext::new();
+        context.insert_timeframe(
+            timeframe.clone(),
+            TimeframeData::with_quote_frame(&frame, 0),
+        );
+        let calendar =
+            TradingCalendar::new("CME", "America/Chicago".parse().unwrap()).with_session(
+                TradingSession::new("globex", TimeWindow::hm((17, 0), (16, 0))),
+            );
+        let mut manager = PositionManager::new("strategy-session").with_calendar(calendar);
+        let mut signal = entry_signal(&timeframe);
+        signal.order = Some(OrderRequest {
+            order_type: OrderType::Limit,
+            price: 90.0,
+            limit_price: None,
+            time_in_force: TimeInForce::Session,
+        });
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("order placement failed");
+
+        set_bar(&mut context, &timeframe, 1);
+        let report = manager
+            .process_pending_orders(&mut context)
+            .expect("bar 1 failed");
+        assert!(report.orders.is_empty());
+        assert!(manager.has_pending_orders());
+
+        set_bar(&mut context, &timeframe, 2);
+        let report = manager
+            .process_pending_orders(&mut context)
+            .expect("bar 2 failed");
+        assert_eq!(report.orders[0].status, OrderStatus::Cancelled);
+        assert!(!manager.has_pending_orders());
+    }
+
+    #[tokio::test]
+    async fn stop_entry_fills_on_later_bar_at_gap_price() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_ohlc_context(
+            &[
+                (100.0, 101.0, 99.0, 100.0),
+                (100.0, 104.0, 99.0, 103.0),
+                (107.0, 108.0, 106.0, 107.0),
+            ],
+            &symbol,
+            &timeframe,
+        );
+        let mut manager = PositionManager::new("strategy-orders");
+        let mut signal = entry_signal(&timeframe);
+        signal.order = Some(OrderRequest {
+            order_type: OrderType::Stop,
+            price: 105.0,
+            limit_price: None,
+            time_in_force: TimeInForce::Gtc,
+        });
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal);
+        let report = manager
+            .process_decision(&mut context, &decision)
+            .expect("order placement failed");
+        assert!(report.opened_positions.is_empty());
+        assert_eq!(report.orders[0].status, OrderStatus::Submitted);
+        assert!(manager.has_pending_orders());
+
+        set_bar(&mut context, &timeframe, 1);
+        let report = manager
+            .process_pending_orders(&mut context)
+            .expect("bar 1 failed");
+        assert!(report.opened_positions.is_empty());
+
+        set_bar(&mut context, &timeframe, 2);
+        let report = manager
+            .process_pending_orders(&mut context)
+            .expect("bar 2 failed");
+        let opened = report.opened_positions.first().expect("stop order filled");
+        assert!((opened.average_price - 107.0).abs() < 1e-6);
+        assert_eq!(report.orders[0].order_type, OrderType::Stop);
+        assert_eq!(report.orders[0].status, OrderStatus::Filled);
+        assert!(!manager.has_pending_orders());
+    }
+
+    #[tokio::test]
+    async fn pending_orders_expire_and_are_cancelled_by_exit_rules() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let timeframe = TimeFrame::minutes(1);
+        let mut context = build_ohlc_context(
+            &[
+                (100.0, 101.0, 99.0, 100.0),
+                (100.0, 101.0, 98.0, 100.0),
+                (100.0, 101.0, 94.0, 95.0),
+            ],
+            &symbol,
+            &timeframe,
+        );
+        let mut manager = PositionManager::new("strategy-orders");
+        let mut signal = entry_signal(&timeframe);
+        signal.order = Some(OrderRequest {
+            order_type: OrderType::Limit,
+            price: 95.0,
+            limit_price: None,
+            time_in_force: TimeInForce::Bars(1),
+        });
+        let mut decision = StrategyDecision::empty();
+        decision.entries.push(signal.clone());
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("order placement failed");
+
+        set_bar(&mut context, &timeframe, 1);
+        manager
+            .process_pending_orders(&mut context)
+            .expect("bar 1 failed");
+        assert!(manager.has_pending_orders());
+        set_bar(&mut context, &timeframe, 2);
+        let report = manager
+            .process_pending_orders(&mut context)
+            .expect("bar 2 failed");
+        assert!(report.opened_positions.is_empty());
+        assert_eq!(report.orders[0].status, OrderStatus::Cancelled);
+        assert!(!manager.has_pending_orders());
+
+        set_bar(&mut context, &timeframe, 0);
+        manager
+            .process_decision(&mut context, &decision)
+            .expect("order placement failed");
+        let mut exit_decision = StrategyDecision::empty();
+        exit_decision.exits.push(exit_signal(&timeframe));
+        let report = manager
+            .process_decision(&mut context, &exit_decision)
+            .expect("exit failed");
+        assert_eq!(report.orders[0].status, OrderStatus::Cancelled);
+        assert!(!manager.has_pending_orders());
+    }
 }
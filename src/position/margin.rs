@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data_model::meta::AssetClass;
+use crate::strategy::types::PositionDirection;
+
+const DAYS_IN_YEAR: f64 = 365.0;
+
+/// Маржинальные требования в долях стоимости позиции
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarginRequirement {
+    pub initial: f64,
+    pub maintenance: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AssetClassMargin {
+    pub asset_class: AssetClass,
+    pub requirement: MarginRequirement,
+}
+
+/// Маржинальная модель счета: плечо, требования по классам активов,
+/// плата за заем бумаг для шортов и финансирование длинных позиций
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MarginModel {
+    /// Плечо счета: без требований для класса актива начальная маржа равна 1/leverage
+    pub leverage: f64,
+    /// Поддерживающая маржа как доля начальной
+    pub maintenance_ratio: f64,
+    #[serde(default)]
+    pub asset_classes: Vec<AssetClassMargin>,
+    /// Годовая ставка займа бумаг для коротких позиций, в процентах от стоимости позиции
+    #[serde(default)]
+    pub short_borrow_rate: f64,
+    /// Годовая ставка финансирования заемной части длинных позиций, в процентах
+    #[serde(default)]
+    pub long_financing_rate: f64,
+}
+
+impl Default for MarginModel {
+    fn default() -> Self {
+        Self {
+            leverage: 1.0,
+            maintenance_ratio: 0.5,
+            asset_classes: Vec::new(),
+            short_borrow_rate: 0.0,
+            long_financing_rate: 0.0,
+        }
+    }
+}
+
+impl MarginModel {
+    pub fn with_leverage(mut self, leverage: f64) -> Self {
+        self.leverage = leverage;
+        self
+    }
+
+    pub fn with_asset_class(
+        mut self,
+        asset_class: AssetClass,
+        requirement: MarginRequirement,
+    ) -> Self {
+        self.asset_classes
+            .retain(|entry| entry.asset_class != asset_class);
+        self.asset_classes.push(AssetClassMargin {
+            asset_class,
+            requirement,
+        });
+        self
+    }
+
+    pub fn with_financing(mut self, short_borrow_rate: f64, long_financing_rate: f64) -> Self {
+        self.short_borrow_rate = short_borrow_rate;
+        self.long_financing_rate = long_financing_rate;
+        self
+    }
+
+    /// Требования для класса актива; инструменты без описания получают требования по плечу
+    pub fn requirement(&self, asset_class: Option<&AssetClass>) -> MarginRequirement {
+        if let Some(entry) = asset_class.and_then(|class| {
+            self.asset_classes
+                .iter()
+                .find(|entry| &entry.asset_class == class)
+        }) {
+            return entry.requirement;
+        }
+        let initial = if self.leverage > 0.0 {
+            1.0 / self.leverage
+        } else {
+            1.0
+        };
+        MarginRequirement {
+            initial,
+            maintenance: initial * self.maintenance_ratio,
+        }
+    }
+
+    /// Плата за перенос позиции стоимостью `notional` через `days` дней
+    pub fn financing_cost(
+        &self,
+        direction: &PositionDirection,
+        notional: f64,
+        days: i64,
+        requirement: &MarginRequirement,
+    ) -> f64 {
+        if days <= 0 {
+            return 0.0;
+        }
+        let (rate, base) = match direction {
+            PositionDirection::Short => (self.short_borrow_rate, notional),
+            PositionDirection::Long => (
+                self.long_financing_rate,
+                notional * (1.0 - requirement.initial).max(0.0),
+            ),
+            PositionDirection::Flat | PositionDirection::Both => return 0.0,
+        };
+        base.abs() * rate / 100.0 * days as f64 / DAYS_IN_YEAR
+    }
+}
+
+/// Состояние маржи счета на текущем баре
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MarginStatus {
+    /// Капитал счета с учетом нереализованного PnL по текущим ценам
+    pub equity: f64,
+    pub initial_margin: f64,
+    pub maintenance_margin: f64,
+}
+
+impl MarginStatus {
+    /// Свободная покупательная способность
+    pub fn available(&self) -> f64 {
+        self.equity - self.initial_margin
+    }
+
+    pub fn is_margin_call(&self) -> bool {
+        self.maintenance_margin > 0.0 && self.equity < self.maintenance_margin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_class_requirement_overrides_leverage() {
+        let model = MarginModel::default().with_leverage(4.0).with_asset_class(
+            AssetClass::Futures,
+            MarginRequirement {
+                initial: 0.1,
+                maintenance: 0.075,
+            },
+        );
+        let equity = model.requirement(Some(&AssetClass::Equity));
+        assert!((equity.initial - 0.25).abs() < 1e-12);
+        assert!((equity.maintenance - 0.125).abs() < 1e-12);
+        let futures = model.requirement(Some(&AssetClass::Futures));
+        assert!((futures.initial - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn financing_charges_shorts_in_full_and_longs_on_borrowed_part() {
+        let model = MarginModel::default()
+            .with_leverage(2.0)
+            .with_financing(3.65, 7.3);
+        let requirement = model.requirement(None);
+        let short = model.financing_cost(&PositionDirection::Short, 10_000.0, 1, &requirement);
+        assert!((short - 1.0).abs() < 1e-9);
+        let long = model.financing_cost(&PositionDirection::Long, 10_000.0, 2, &requirement);
+        assert!((long - 2.0).abs() < 1e-9);
+        assert_eq!(
+            model.financing_cost(&PositionDirection::Long, 10_000.0, 0, &requirement),
+            0.0
+        );
+    }
+}
@@ -1,8 +1,18 @@
+pub mod costs;
+pub mod excursion;
+pub mod fill;
 pub mod manager;
+pub mod margin;
+pub mod sizing;
 pub mod view;
 
+pub use costs::{Fill, TradeSide};
+pub use excursion::{ExcursionDistribution, ExcursionStats, TradeExcursion};
+pub use fill::{FillPoint, FillPolicy};
 pub use manager::{
     ClosedTrade, ExecutionReport, PositionError, PositionEvent, PositionEventListener,
     PositionManager, PositionPersistence, StopHistoryEntry,
 };
+pub use margin::{AssetClassMargin, MarginModel, MarginRequirement, MarginStatus};
+pub use sizing::{PositionSizer, PositionSizerSpec, SizingContext, SizingStats};
 pub use view::{ActivePosition, PositionBook, PositionInsights};
@@ -0,0 +1,499 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::costs::average_true_range;
+use crate::data_model::meta::InstrumentMeta;
+use crate::strategy::context::TimeframeData;
+use crate::strategy::types::{
+    PositionDirection, StrategyParamValue, StrategyParameterMap, StrategyParameterSpec,
+};
+
+/// Префикс параметров сайзера в `StrategyParameterMap`
+pub const SIZER_PARAMETER_PREFIX: &str = "sizer_";
+
+/// Итоги закрытых сделок, нужные сайзерам на основе истории (Kelly)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SizingStats {
+    pub wins: usize,
+    pub losses: usize,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+}
+
+impl SizingStats {
+    pub fn record(&mut self, pnl: f64) {
+        if pnl > 0.0 {
+            self.wins += 1;
+            self.gross_profit += pnl;
+        } else {
+            self.losses += 1;
+            self.gross_loss += pnl.abs();
+        }
+    }
+
+    pub fn trades(&self) -> usize {
+        self.wins + self.losses
+    }
+
+    pub fn win_rate(&self) -> Option<f64> {
+        let trades = self.trades();
+        (trades > 0).then(|| self.wins as f64 / trades as f64)
+    }
+
+    /// Отношение среднего выигрыша к среднему проигрышу
+    pub fn payoff_ratio(&self) -> Option<f64> {
+        if self.wins == 0 || self.losses == 0 || self.gross_loss <= f64::EPSILON {
+            return None;
+        }
+        let average_win = self.gross_profit / self.wins as f64;
+        let average_loss = self.gross_loss / self.losses as f64;
+        Some(average_win / average_loss)
+    }
+}
+
+/// Данные для расчета объема входа
+pub struct SizingContext<'a> {
+    pub data: Option<&'a TimeframeData>,
+    pub index: usize,
+    pub direction: &'a PositionDirection,
+    pub entry_price: f64,
+    /// Капитал, от которого считается объем (с учетом реинвестирования)
+    pub equity: f64,
+    /// Начальный уровень стопа от активного стоп-обработчика
+    pub stop_level: Option<f64>,
+    /// Стоимость пункта цены (множитель контракта из MetaRegistry)
+    pub contract_multiplier: f64,
+    /// Инструмент из MetaRegistry: объем округляется вниз до его лота
+    pub instrument: Option<&'a InstrumentMeta>,
+    pub stats: &'a SizingStats,
+}
+
+impl SizingContext<'_> {
+    /// Стоимость одной единицы объема по цене входа
+    pub fn unit_value(&self) -> f64 {
+        self.entry_price * self.contract_multiplier
+    }
+}
+
+/// Округляет объем вниз до лота инструмента, без справочника — до целых единиц;
+/// `None`, если объем меньше минимального
+pub fn round_units(instrument: Option<&InstrumentMeta>, units: f64) -> Option<f64> {
+    match instrument {
+        Some(meta) => meta.round_quantity(units),
+        None => Some(units.floor()).filter(|units| *units >= 1.0),
+    }
+}
+
+pub trait PositionSizer: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Объем входа; `None`, если данных для расчета недостаточно
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64>;
+}
+
+pub struct FixedQuantitySizer {
+    pub quantity: f64,
+}
+
+impl PositionSizer for FixedQuantitySizer {
+    fn name(&self) -> &str {
+        "FixedQuantity"
+    }
+
+    fn quantity(&self, _ctx: &SizingContext<'_>) -> Option<f64> {
+        (self.quantity > 0.0).then_some(self.quantity)
+    }
+}
+
+pub struct FixedNotionalSizer {
+    pub notional: f64,
+}
+
+impl PositionSizer for FixedNotionalSizer {
+    fn name(&self) -> &str {
+        "FixedNotional"
+    }
+
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64> {
+        units_for_notional(ctx, self.notional)
+    }
+}
+
+/// Фиксированная доля капитала в процентах
+pub struct FixedFractionalSizer {
+    pub percent: f64,
+}
+
+impl PositionSizer for FixedFractionalSizer {
+    fn name(&self) -> &str {
+        "FixedFractional"
+    }
+
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64> {
+        units_for_notional(ctx, ctx.equity * self.percent / 100.0)
+    }
+}
+
+/// Риск на сделку в процентах капитала до начального стопа
+pub struct RiskPerTradeSizer {
+    pub risk_pct: f64,
+}
+
+impl PositionSizer for RiskPerTradeSizer {
+    fn name(&self) -> &str {
+        "RiskPerTrade"
+    }
+
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64> {
+        let distance = (ctx.entry_price - ctx.stop_level?).abs();
+        units_for_risk(ctx, self.risk_pct, distance)
+    }
+}
+
+/// Таргетирование волатильности: риск в процентах капитала на `atr_multiplier` ATR
+pub struct VolatilityTargetSizer {
+    pub risk_pct: f64,
+    pub atr_period: usize,
+    pub atr_multiplier: f64,
+}
+
+impl PositionSizer for VolatilityTargetSizer {
+    fn name(&self) -> &str {
+        "VolatilityTarget"
+    }
+
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64> {
+        let atr = average_true_range(ctx.data?, ctx.index, self.atr_period)?;
+        units_for_risk(ctx, self.risk_pct, atr * self.atr_multiplier)
+    }
+}
+
+/// Доля Келли `fraction` с ограничением `max_pct` процентов капитала.
+/// Пока сделок меньше `min_trades`, берется доля `fraction` от предела.
+pub struct KellySizer {
+    pub fraction: f64,
+    pub max_pct: f64,
+    pub min_trades: usize,
+}
+
+impl PositionSizer for KellySizer {
+    fn name(&self) -> &str {
+        "Kelly"
+    }
+
+    fn quantity(&self, ctx: &SizingContext<'_>) -> Option<f64> {
+        let cap = self.max_pct / 100.0;
+        let share = if ctx.stats.trades() < self.min_trades {
+            cap * self.fraction
+        } else {
+            let win_rate = ctx.stats.win_rate()?;
+            let kelly = match ctx.stats.payoff_ratio() {
+                Some(payoff) => win_rate - (1.0 - win_rate) / payoff,
+                // Нет проигрышей — ограничивает только предел, нет выигрышей — не входим
+                None if ctx.stats.losses == 0 => cap,
+                None => 0.0,
+            };
+            (kelly * self.fraction).clamp(0.0, cap)
+        };
+        units_for_notional(ctx, ctx.equity * share)
+    }
+}
+
+fn units_for_notional(ctx: &SizingContext<'_>, notional: f64) -> Option<f64> {
+    let unit_value = ctx.unit_value();
+    if unit_value <= 0.0 || notional <= 0.0 {
+        return None;
+    }
+    round_units(ctx.instrument, notional / unit_value)
+}
+
+/// Объем с риском `risk_pct` процентов капитала при расстоянии `distance` до стопа,
+/// но не больше всего капитала
+fn units_for_risk(ctx: &SizingContext<'_>, risk_pct: f64, distance: f64) -> Option<f64> {
+    let unit_value = ctx.unit_value();
+    if distance <= f64::EPSILON || unit_value <= 0.0 {
+        return None;
+    }
+    let units = (ctx.equity * risk_pct / 100.0 / (distance * ctx.contract_multiplier))
+        .min(ctx.equity / unit_value);
+    round_units(ctx.instrument, units)
+}
+
+/// Описание сайзера для конфигурации и оптимизации
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PositionSizerSpec {
+    FixedQuantity {
+        quantity: f64,
+    },
+    FixedNotional {
+        notional: f64,
+    },
+    FixedFractional {
+        percent: f64,
+    },
+    RiskPerTrade {
+        risk_pct: f64,
+    },
+    VolatilityTarget {
+        risk_pct: f64,
+        atr_period: usize,
+        atr_multiplier: f64,
+    },
+    Kelly {
+        fraction: f64,
+        max_pct: f64,
+        min_trades: usize,
+    },
+}
+
+impl PositionSizerSpec {
+    pub fn build(&self) -> Arc<dyn PositionSizer> {
+        match self.clone() {
+            Self::FixedQuantity { quantity } => Arc::new(FixedQuantitySizer { quantity }),
+            Self::FixedNotional { notional } => Arc::new(FixedNotionalSizer { notional }),
+            Self::FixedFractional { percent } => Arc::new(FixedFractionalSizer { percent }),
+            Self::RiskPerTrade { risk_pct } => Arc::new(RiskPerTradeSizer { risk_pct }),
+            Self::VolatilityTarget {
+                risk_pct,
+                atr_period,
+                atr_multiplier,
+            } => Arc::new(VolatilityTargetSizer {
+                risk_pct,
+                atr_period,
+                atr_multiplier,
+            }),
+            Self::Kelly {
+                fraction,
+                max_pct,
+                min_trades,
+            } => Arc::new(KellySizer {
+                fraction,
+                max_pct,
+                min_trades,
+            }),
+        }
+    }
+
+    /// Параметры сайзера с диапазонами оптимизации: (имя, значение, min, max, step)
+    fn parameter_ranges(&self) -> Vec<(&'static str, f64, f64, f64, f64)> {
+        match self {
+            Self::FixedQuantity { quantity } => {
+                vec![("quantity", *quantity, 1.0, 100.0, 1.0)]
+            }
+            Self::FixedNotional { notional } => {
+                vec![("notional", *notional, 1_000.0, 100_000.0, 1_000.0)]
+            }
+            Self::FixedFractional { percent } => vec![("percent", *percent, 5.0, 100.0, 5.0)],
+            Self::RiskPerTrade { risk_pct } => vec![("risk_pct", *risk_pct, 0.25, 5.0, 0.25)],
+            Self::VolatilityTarget {
+                risk_pct,
+                atr_period,
+                atr_multiplier,
+            } => vec![
+                ("risk_pct", *risk_pct, 0.25, 5.0, 0.25),
+                ("atr_period", *atr_period as f64, 5.0, 50.0, 1.0),
+                ("atr_multiplier", *atr_multiplier, 0.5, 5.0, 0.5),
+            ],
+            Self::Kelly {
+                fraction,
+                max_pct,
+                min_trades,
+            } => vec![
+                ("fraction", *fraction, 0.1, 1.0, 0.1),
+                ("max_pct", *max_pct, 5.0, 100.0, 5.0),
+                ("min_trades", *min_trades as f64, 5.0, 50.0, 5.0),
+            ],
+        }
+    }
+
+    pub fn parameter_name(param: &str) -> String {
+        format!("{}{}", SIZER_PARAMETER_PREFIX, param)
+    }
+
+    /// Параметры сайзера как оптимизируемые параметры стратегии
+    pub fn parameter_specs(&self) -> Vec<StrategyParameterSpec> {
+        self.parameter_ranges()
+            .into_iter()
+            .map(|(name, value, min, max, step)| {
+                StrategyParameterSpec::new_numeric(
+                    Self::parameter_name(name),
+                    Some(format!("{} parameter for position sizer", name)),
+                    StrategyParamValue::Number(value),
+                    Some(min),
+                    Some(max),
+                    Some(step),
+                    true,
+                    true,
+                )
+            })
+            .collect()
+    }
+
+    /// Копия с примененными значениями параметров вида `sizer_{name}`
+    pub fn with_parameters(&self, parameters: &StrategyParameterMap) -> Self {
+        let value = |name: &str, current: f64| -> f64 {
+            parameters
+                .get(&Self::parameter_name(name))
+                .and_then(StrategyParamValue::as_f64)
+                .unwrap_or(current)
+        };
+        let count = |name: &str, current: usize| -> usize {
+            value(name, current as f64).round().max(1.0) as usize
+        };
+        match self {
+            Self::FixedQuantity { quantity } => Self::FixedQuantity {
+                quantity: value("quantity", *quantity),
+            },
+            Self::FixedNotional { notional } => Self::FixedNotional {
+                notional: value("notional", *notional),
+            },
+            Self::FixedFractional { percent } => Self::FixedFractional {
+                percent: value("percent", *percent),
+            },
+            Self::RiskPerTrade { risk_pct } => Self::RiskPerTrade {
+                risk_pct: value("risk_pct", *risk_pct),
+            },
+            Self::VolatilityTarget {
+                risk_pct,
+                atr_period,
+                atr_multiplier,
+            } => Self::VolatilityTarget {
+                risk_pct: value("risk_pct", *risk_pct),
+                atr_period: count("atr_period", *atr_period),
+                atr_multiplier: value("atr_multiplier", *atr_multiplier),
+            },
+            Self::Kelly {
+                fraction,
+                max_pct,
+                min_trades,
+            } => Self::Kelly {
+                fraction: value("fraction", *fraction),
+                max_pct: value("max_pct", *max_pct),
+                min_trades: count("min_trades", *min_trades),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(
+        direction: &'a PositionDirection,
+        stats: &'a SizingStats,
+        stop_level: Option<f64>,
+    ) -> SizingContext<'a> {
+        SizingContext {
+            data: None,
+            index: 0,
+            direction,
+            entry_price: 100.0,
+            equity: 10_000.0,
+            stop_level,
+            contract_multiplier: 1.0,
+            instrument: None,
+            stats,
+        }
+    }
+
+    #[test]
+    fn risk_per_trade_uses_stop_distance() {
+        let direction = PositionDirection::Long;
+        let stats = SizingStats::default();
+        let sizer = PositionSizerSpec::RiskPerTrade { risk_pct: 1.0 }.build();
+        // 1% от 10_000 = 100 при расстоянии 2 до стопа
+        assert_eq!(
+            sizer.quantity(&context(&direction, &stats, Some(98.0))),
+            Some(50.0)
+        );
+        assert_eq!(sizer.quantity(&context(&direction, &stats, None)), None);
+        // Не больше, чем позволяет капитал
+        assert_eq!(
+            sizer.quantity(&context(&direction, &stats, Some(99.99))),
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn fractional_lots_are_sized_by_lot_step() {
+        let direction = PositionDirection::Long;
+        let stats = SizingStats::default();
+        let btc = InstrumentMeta::builder(crate::data_model::types::Symbol::from_descriptor(
+            "BINANCE.BTCUSDT",
+        ))
+        .lot_size(0.01)
+        .build();
+        let mut ctx = context(&direction, &stats, None);
+        ctx.entry_price = 60_000.0;
+        ctx.instrument = Some(&btc);
+        // 1% от 10_000 = 100 — меньше минимального лота 0.01 BTC (600)
+        let small = PositionSizerSpec::FixedFractional { percent: 1.0 }.build();
+        assert_eq!(small.quantity(&ctx), None);
+        let sizer = PositionSizerSpec::FixedFractional { percent: 50.0 }.build();
+        let quantity = sizer.quantity(&ctx).unwrap();
+        assert!((quantity - 0.08).abs() < 1e-6, "quantity {}", quantity);
+    }
+
+    #[test]
+    fn kelly_is_capped_and_waits_for_history() {
+        let direction = PositionDirection::Long;
+        let spec = PositionSizerSpec::Kelly {
+            fraction: 0.5,
+            max_pct: 20.0,
+            min_trades: 4,
+        };
+        let sizer = spec.build();
+        let mut stats = SizingStats::default();
+        assert_eq!(
+            sizer.quantity(&context(&direction, &stats, None)),
+            Some(10.0)
+        );
+        for pnl in [200.0, 200.0, 200.0, -100.0] {
+            stats.record(pnl);
+        }
+        // W = 0.75, R = 2: Kelly = 0.625, половина = 0.3125 -> предел 20%
+        assert_eq!(
+            sizer.quantity(&context(&direction, &stats, None)),
+            Some(20.0)
+        );
+    }
+
+    #[test]
+    fn parameters_round_trip_through_overrides() {
+        let spec = PositionSizerSpec::VolatilityTarget {
+            risk_pct: 1.0,
+            atr_period: 14,
+            atr_multiplier: 2.0,
+        };
+        let names: Vec<String> = spec
+            .parameter_specs()
+            .into_iter()
+            .map(|param| param.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["sizer_risk_pct", "sizer_atr_period", "sizer_atr_multiplier"]
+        );
+
+        let overrides = StrategyParameterMap::from([
+            (
+                "sizer_risk_pct".to_string(),
+                StrategyParamValue::Number(0.5),
+            ),
+            (
+                "sizer_atr_period".to_string(),
+                StrategyParamValue::Number(20.0),
+            ),
+        ]);
+        assert_eq!(
+            spec.with_parameters(&overrides),
+            PositionSizerSpec::VolatilityTarget {
+                risk_pct: 0.5,
+                atr_period: 20,
+                atr_multiplier: 2.0,
+            }
+        );
+    }
+}
@@ -19,6 +19,10 @@ pub struct ActivePosition {
     pub direction: PositionDirection,
     pub entry_price: f64,
     pub quantity: f64,
+    /// Объем позиции до частичных выходов
+    pub initial_quantity: f64,
+    /// Число уже выполненных частичных выходов
+    pub partial_exits: usize,
     pub opened_at: Option<DateTime<Utc>>,
     pub last_price: Option<f64>,
     pub metadata: HashMap<String, String>,
@@ -31,6 +35,7 @@ pub struct ActivePosition {
 
     // Trailing stop
     pub current_stop: Option<f64>,      // Текущий уровень стопа
+    pub initial_stop: Option<f64>,      // Стоп на момент входа, база для R-множителя
     pub entry_bar_index: Option<usize>, // Индекс бара входа
 }
 
@@ -52,6 +57,8 @@ impl ActivePosition {
             direction,
             entry_price,
             quantity,
+            initial_quantity: quantity,
+            partial_exits: 0,
             opened_at: None,
             last_price: None,
             metadata: HashMap::new(),
@@ -61,6 +68,7 @@ impl ActivePosition {
             max_high_since_entry: None,
             min_low_since_entry: None,
             current_stop: None,
+            initial_stop: None,
             entry_bar_index: None,
         }
     }
@@ -53,6 +53,19 @@ impl AuxiliaryIndicatorSpec {
         }
     }
 
+    /// Линия канала (BBUpper, KCLower и т.п.) с заданными параметрами
+    pub fn channel_band(indicator_name: &str, parameters: &[(&str, f64)]) -> Self {
+        let suffix: Vec<String> = parameters.iter().map(|(_, v)| v.to_string()).collect();
+        Self {
+            indicator_name: indicator_name.to_string(),
+            parameters: parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect(),
+            alias: format!("aux_{}_{}", indicator_name, suffix.join("_")),
+        }
+    }
+
     pub fn maxfor(period: u32) -> Self {
         Self {
             indicator_name: "MAXFOR".to_string(),
@@ -11,12 +11,20 @@ pub struct StopEvaluationContext<'a> {
     pub max_high_since_entry: f64,
     pub min_low_since_entry: f64,
     pub current_stop: Option<f64>,
+    /// Индекс бара входа на таймфрейме обработчика
+    pub entry_index: Option<usize>,
 }
 
 impl<'a> StopEvaluationContext<'a> {
     pub fn price_series(&self) -> Option<&[f32]> {
         self.timeframe_data.price_series_slice(&self.price_field)
     }
+
+    /// Число завершенных баров с момента входа
+    pub fn bars_in_trade(&self) -> Option<usize> {
+        self.entry_index
+            .map(|entry| self.index.saturating_sub(entry))
+    }
 }
 
 pub struct StopValidationContext<'a> {
@@ -47,4 +55,3 @@ impl<'a> TakeEvaluationContext<'a> {
         self.timeframe_data.price_series_slice(&self.price_field)
     }
 }
-
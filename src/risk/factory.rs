@@ -9,10 +9,15 @@ use super::parameter_extractor::{
     ParameterExtractionError,
 };
 use super::stops::{
-    ATRTrailIndicatorStopHandler, ATRTrailStopHandler, HILOTrailingStopHandler,
+    ATRTrailIndicatorStopHandler, ATRTrailStopHandler, BreakEvenStopHandler, BreakEvenTrigger,
+    ChandelierStopHandler, HILOTrailingStopHandler, ParabolicSARStopHandler,
     PercentTrailIndicatorStopHandler, PercentTrailingStopHandler, StopLossPctHandler,
+    TimeExitStopHandler,
+};
+use super::takes::{
+    ChannelKind, ChannelTakeHandler, TakeProfitATRHandler, TakeProfitLadderHandler,
+    TakeProfitPctHandler, TakeProfitRHandler, TrailingTakeHandler,
 };
-use super::takes::TakeProfitPctHandler;
 use super::traits::{StopHandler, TakeHandler};
 
 impl From<ParameterExtractionError> for StopHandlerError {
@@ -78,6 +83,30 @@ impl StopHandlerFactory {
                     StrategyParamValue::Text("SMA".to_string()),
                 );
             }
+            "BREAKEVENSTOP" | "BREAK_EVEN_STOP" | "BREAKEVEN" => {
+                params.insert("after_exits".to_string(), StrategyParamValue::Number(1.0));
+                params.insert("offset_pct".to_string(), StrategyParamValue::Number(0.0));
+            }
+            "BREAKEVENPCTSTOP" | "BREAK_EVEN_PCT_STOP" | "NOLOSSPCT" => {
+                params.insert("trigger_pct".to_string(), StrategyParamValue::Number(1.0));
+                params.insert("offset_pct".to_string(), StrategyParamValue::Number(0.0));
+            }
+            "BREAKEVENATRSTOP" | "BREAK_EVEN_ATR_STOP" => {
+                params.insert("period".to_string(), StrategyParamValue::Number(14.0));
+                params.insert("coeff_atr".to_string(), StrategyParamValue::Number(1.0));
+                params.insert("offset_pct".to_string(), StrategyParamValue::Number(0.0));
+            }
+            "TIMEEXITSTOP" | "TIME_EXIT_STOP" | "ONBARSSTOPLOSS" => {
+                params.insert("bars".to_string(), StrategyParamValue::Number(20.0));
+            }
+            "CHANDELIERSTOP" | "CHANDELIER_STOP" | "CHANDELIER" => {
+                params.insert("period".to_string(), StrategyParamValue::Number(22.0));
+                params.insert("coeff_atr".to_string(), StrategyParamValue::Number(3.0));
+            }
+            "PARABOLICSARSTOP" | "PARABOLIC_SAR_STOP" | "SAR_STOP" => {
+                params.insert("step".to_string(), StrategyParamValue::Number(0.02));
+                params.insert("max_step".to_string(), StrategyParamValue::Number(0.2));
+            }
             _ => {}
         }
 
@@ -220,6 +249,47 @@ impl StopHandlerFactory {
                     indicator_params,
                 )))
             }
+            "BREAKEVENSTOP" | "BREAK_EVEN_STOP" | "BREAKEVEN" => {
+                let after_exits = extract_number(parameters, &["after_exits", "after"], 1.0)?;
+                let offset_pct = extract_number(parameters, &["offset_pct", "offset"], 0.0)?;
+                Ok(Box::new(BreakEvenStopHandler::new(
+                    BreakEvenTrigger::PartialExits(after_exits.max(1.0) as usize),
+                    offset_pct,
+                )))
+            }
+            "BREAKEVENPCTSTOP" | "BREAK_EVEN_PCT_STOP" | "NOLOSSPCT" => {
+                let trigger_pct = extract_number(parameters, &["trigger_pct", "trigger"], 1.0)?;
+                let offset_pct = extract_number(parameters, &["offset_pct", "offset"], 0.0)?;
+                Ok(Box::new(BreakEvenStopHandler::new(
+                    BreakEvenTrigger::Percent(trigger_pct),
+                    offset_pct,
+                )))
+            }
+            "BREAKEVENATRSTOP" | "BREAK_EVEN_ATR_STOP" => {
+                let period = extract_number(parameters, &["period"], 14.0)?;
+                let coeff_atr =
+                    extract_number(parameters, &["coeff_atr", "coeff", "atr_coeff"], 1.0)?;
+                let offset_pct = extract_number(parameters, &["offset_pct", "offset"], 0.0)?;
+                Ok(Box::new(BreakEvenStopHandler::new(
+                    BreakEvenTrigger::Atr { period, coeff_atr },
+                    offset_pct,
+                )))
+            }
+            "TIMEEXITSTOP" | "TIME_EXIT_STOP" | "ONBARSSTOPLOSS" => {
+                let bars = extract_number(parameters, &["bars", "max_bars"], 20.0)?;
+                Ok(Box::new(TimeExitStopHandler::new(bars.max(1.0) as usize)))
+            }
+            "CHANDELIERSTOP" | "CHANDELIER_STOP" | "CHANDELIER" => {
+                let period = extract_number(parameters, &["period"], 22.0)?;
+                let coeff_atr =
+                    extract_number(parameters, &["coeff_atr", "coeff", "atr_coeff"], 3.0)?;
+                Ok(Box::new(ChandelierStopHandler::new(period, coeff_atr)))
+            }
+            "PARABOLICSARSTOP" | "PARABOLIC_SAR_STOP" | "SAR_STOP" => {
+                let step = extract_number(parameters, &["step", "acceleration"], 0.02)?;
+                let max_step = extract_number(parameters, &["max_step", "max_acceleration"], 0.2)?;
+                Ok(Box::new(ParabolicSARStopHandler::new(step, max_step)))
+            }
             other => Err(StopHandlerError::UnknownHandler(other.to_string())),
         }
     }
@@ -236,6 +306,30 @@ impl TakeHandlerFactory {
             "TAKEPROFITPCT" | "TAKE_PROFIT_PCT" => {
                 params.insert("percentage".to_string(), StrategyParamValue::Number(0.4));
             }
+            "TAKEPROFITLADDER" | "TAKE_PROFIT_LADDER" => {
+                params.insert("step_pct".to_string(), StrategyParamValue::Number(1.0));
+                params.insert("targets".to_string(), StrategyParamValue::Number(2.0));
+                params.insert("close_pct".to_string(), StrategyParamValue::Number(50.0));
+            }
+            "TAKEPROFITATR" | "TAKE_PROFIT_ATR" => {
+                params.insert("period".to_string(), StrategyParamValue::Number(14.0));
+                params.insert("coeff_atr".to_string(), StrategyParamValue::Number(3.0));
+            }
+            "TAKEPROFITR" | "TAKE_PROFIT_R" => {
+                params.insert("r_multiple".to_string(), StrategyParamValue::Number(2.0));
+            }
+            "BOLLINGERTAKE" | "BOLLINGER_TAKE" => {
+                params.insert("period".to_string(), StrategyParamValue::Number(20.0));
+                params.insert("deviation".to_string(), StrategyParamValue::Number(2.0));
+            }
+            "KELTNERTAKE" | "KELTNER_TAKE" => {
+                params.insert("period".to_string(), StrategyParamValue::Number(20.0));
+                params.insert("deviation".to_string(), StrategyParamValue::Number(2.0));
+            }
+            "TRAILINGTAKE" | "TRAILING_TAKE" => {
+                params.insert("activation_pct".to_string(), StrategyParamValue::Number(3.0));
+                params.insert("trail_pct".to_string(), StrategyParamValue::Number(1.0));
+            }
             _ => {}
         }
 
@@ -255,6 +349,51 @@ impl TakeHandlerFactory {
                 )?;
                 Ok(Box::new(TakeProfitPctHandler::new(percentage)))
             }
+            "TAKEPROFITLADDER" | "TAKE_PROFIT_LADDER" => {
+                let step_pct =
+                    extract_percentage(parameters, &["step_pct", "step", "percentage"], 1.0)?;
+                let targets = extract_number(parameters, &["targets"], 2.0)?;
+                let close_pct = extract_number(parameters, &["close_pct", "close"], 50.0)?;
+                Ok(Box::new(TakeProfitLadderHandler::new(
+                    step_pct,
+                    targets.max(1.0) as usize,
+                    close_pct,
+                )))
+            }
+            "TAKEPROFITATR" | "TAKE_PROFIT_ATR" => {
+                let period = extract_number(parameters, &["period"], 14.0)?;
+                let coeff_atr =
+                    extract_number(parameters, &["coeff_atr", "coeff", "atr_coeff"], 3.0)?;
+                Ok(Box::new(TakeProfitATRHandler::new(period, coeff_atr)))
+            }
+            "TAKEPROFITR" | "TAKE_PROFIT_R" => {
+                let r_multiple = extract_number(parameters, &["r_multiple", "multiple", "r"], 2.0)?;
+                Ok(Box::new(TakeProfitRHandler::new(r_multiple)))
+            }
+            "BOLLINGERTAKE" | "BOLLINGER_TAKE" => {
+                let period = extract_number(parameters, &["period"], 20.0)?;
+                let deviation = extract_number(parameters, &["deviation", "multiplier"], 2.0)?;
+                Ok(Box::new(ChannelTakeHandler::new(
+                    ChannelKind::Bollinger,
+                    period,
+                    deviation,
+                )))
+            }
+            "KELTNERTAKE" | "KELTNER_TAKE" => {
+                let period = extract_number(parameters, &["period"], 20.0)?;
+                let deviation = extract_number(parameters, &["deviation", "multiplier"], 2.0)?;
+                Ok(Box::new(ChannelTakeHandler::new(
+                    ChannelKind::Keltner,
+                    period,
+                    deviation,
+                )))
+            }
+            "TRAILINGTAKE" | "TRAILING_TAKE" => {
+                let activation_pct =
+                    extract_number(parameters, &["activation_pct", "activation"], 3.0)?;
+                let trail_pct = extract_number(parameters, &["trail_pct", "trail"], 1.0)?;
+                Ok(Box::new(TrailingTakeHandler::new(activation_pct, trail_pct)))
+            }
             other => Err(TakeHandlerError::UnknownHandler(other.to_string())),
         }
     }
@@ -0,0 +1,428 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::strategy::types::PositionDirection;
+
+/// Портфельное ограничение риска
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GuardKind {
+    DailyLoss,
+    WeeklyLoss,
+    LosingStreak,
+    GrossExposure,
+    NetExposure,
+    PositionLimit,
+    KillSwitch,
+}
+
+impl GuardKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GuardKind::DailyLoss => "daily_loss",
+            GuardKind::WeeklyLoss => "weekly_loss",
+            GuardKind::LosingStreak => "losing_streak",
+            GuardKind::GrossExposure => "gross_exposure",
+            GuardKind::NetExposure => "net_exposure",
+            GuardKind::PositionLimit => "position_limit",
+            GuardKind::KillSwitch => "kill_switch",
+        }
+    }
+}
+
+/// Предел убытка за период
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LossLimit {
+    Absolute(f64),
+    /// Процент капитала на начало периода
+    PercentOfEquity(f64),
+}
+
+impl LossLimit {
+    pub fn amount(&self, period_start_equity: f64) -> f64 {
+        match self {
+            LossLimit::Absolute(amount) => amount.abs(),
+            LossLimit::PercentOfEquity(pct) => period_start_equity.abs() * pct.abs() / 100.0,
+        }
+    }
+}
+
+/// Ограничения уровня счета, проверяемые перед каждым входом и на каждом баре.
+/// Периоды (день, неделя) считаются по датам UTC.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskGuardConfig {
+    /// Убыток за день, после которого позиции закрываются, а входы запрещаются до конца дня
+    pub daily_loss: Option<LossLimit>,
+    /// То же для календарной недели
+    pub weekly_loss: Option<LossLimit>,
+    /// Число убыточных сделок подряд, после которого входы запрещаются на `cooldown_minutes`
+    pub max_consecutive_losses: Option<usize>,
+    pub cooldown_minutes: i64,
+    /// Сумма стоимости всех позиций в процентах капитала
+    pub max_gross_exposure_pct: Option<f64>,
+    /// Разница стоимости длинных и коротких позиций в процентах капитала
+    pub max_net_exposure_pct: Option<f64>,
+    pub max_positions_per_direction: Option<usize>,
+    /// Просадка капитала от пика в процентах, после которой все позиции закрываются
+    /// и торговля прекращается до конца прогона
+    pub max_drawdown_pct: Option<f64>,
+}
+
+impl RiskGuardConfig {
+    pub fn with_daily_loss(mut self, limit: LossLimit) -> Self {
+        self.daily_loss = Some(limit);
+        self
+    }
+
+    pub fn with_weekly_loss(mut self, limit: LossLimit) -> Self {
+        self.weekly_loss = Some(limit);
+        self
+    }
+
+    pub fn with_losing_streak(mut self, max_losses: usize, cooldown_minutes: i64) -> Self {
+        self.max_consecutive_losses = Some(max_losses);
+        self.cooldown_minutes = cooldown_minutes;
+        self
+    }
+
+    pub fn with_exposure(mut self, gross_pct: Option<f64>, net_pct: Option<f64>) -> Self {
+        self.max_gross_exposure_pct = gross_pct;
+        self.max_net_exposure_pct = net_pct;
+        self
+    }
+
+    pub fn with_max_positions_per_direction(mut self, max_positions: usize) -> Self {
+        self.max_positions_per_direction = Some(max_positions);
+        self
+    }
+
+    pub fn with_kill_switch(mut self, max_drawdown_pct: f64) -> Self {
+        self.max_drawdown_pct = Some(max_drawdown_pct);
+        self
+    }
+}
+
+/// Срабатывания ограничений за прогон.
+/// Лимиты убытка, серия убытков и kill switch считаются при включении,
+/// ограничения экспозиции и числа позиций — по каждому отклоненному входу.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskGuardStats {
+    pub daily_loss: usize,
+    pub weekly_loss: usize,
+    pub losing_streak: usize,
+    pub gross_exposure: usize,
+    pub net_exposure: usize,
+    pub position_limit: usize,
+    pub kill_switch: usize,
+    /// Все входы, отклоненные любым ограничением
+    pub blocked_entries: usize,
+}
+
+impl RiskGuardStats {
+    fn record(&mut self, kind: GuardKind) {
+        match kind {
+            GuardKind::DailyLoss => self.daily_loss += 1,
+            GuardKind::WeeklyLoss => self.weekly_loss += 1,
+            GuardKind::LosingStreak => self.losing_streak += 1,
+            GuardKind::GrossExposure => self.gross_exposure += 1,
+            GuardKind::NetExposure => self.net_exposure += 1,
+            GuardKind::PositionLimit => self.position_limit += 1,
+            GuardKind::KillSwitch => self.kill_switch += 1,
+        }
+    }
+
+    pub fn triggers(&self) -> usize {
+        self.daily_loss
+            + self.weekly_loss
+            + self.losing_streak
+            + self.gross_exposure
+            + self.net_exposure
+            + self.position_limit
+            + self.kill_switch
+    }
+}
+
+/// Состояние счета для проверки нового входа
+#[derive(Clone, Debug)]
+pub struct EntryExposure {
+    pub direction: PositionDirection,
+    /// Стоимость нового входа
+    pub notional: f64,
+    pub long_notional: f64,
+    pub short_notional: f64,
+    /// Открытые позиции в направлении входа
+    pub positions_in_direction: usize,
+    pub equity: f64,
+}
+
+/// Портфельные ограничения риска и их состояние в течение прогона
+#[derive(Clone, Debug)]
+pub struct AccountRiskGuard {
+    config: RiskGuardConfig,
+    day: Option<NaiveDate>,
+    day_start_equity: f64,
+    day_locked: bool,
+    week: Option<(i32, u32)>,
+    week_start_equity: f64,
+    week_locked: bool,
+    peak_equity: f64,
+    consecutive_losses: usize,
+    cooldown_until: Option<DateTime<Utc>>,
+    killed: bool,
+    stats: RiskGuardStats,
+}
+
+impl AccountRiskGuard {
+    pub fn new(config: RiskGuardConfig) -> Self {
+        Self {
+            config,
+            day: None,
+            day_start_equity: 0.0,
+            day_locked: false,
+            week: None,
+            week_start_equity: 0.0,
+            week_locked: false,
+            peak_equity: f64::MIN,
+            consecutive_losses: 0,
+            cooldown_until: None,
+            killed: false,
+            stats: RiskGuardStats::default(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config.clone());
+    }
+
+    pub fn config(&self) -> &RiskGuardConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> RiskGuardStats {
+        self.stats
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed
+    }
+
+    fn roll_periods(&mut self, time: DateTime<Utc>, equity: f64) {
+        let date = time.date_naive();
+        if self.day != Some(date) {
+            self.day = Some(date);
+            self.day_start_equity = equity;
+            self.day_locked = false;
+        }
+        let week = date.iso_week();
+        let week = (week.year(), week.week());
+        if self.week != Some(week) {
+            self.week = Some(week);
+            self.week_start_equity = equity;
+            self.week_locked = false;
+        }
+    }
+
+    /// Проверяет лимиты убытка и просадки на баре.
+    /// Возвращает ограничение, по которому нужно закрыть открытые позиции.
+    pub fn on_bar(&mut self, time: DateTime<Utc>, equity: f64) -> Option<GuardKind> {
+        self.roll_periods(time, equity);
+        self.peak_equity = self.peak_equity.max(equity);
+        if let Some(limit) = self.config.max_drawdown_pct {
+            if !self.killed
+                && self.peak_equity > 0.0
+                && (self.peak_equity - equity) / self.peak_equity * 100.0 >= limit
+            {
+                self.killed = true;
+                self.stats.record(GuardKind::KillSwitch);
+            }
+        }
+        if self.killed {
+            return Some(GuardKind::KillSwitch);
+        }
+        if let Some(limit) = self.config.daily_loss {
+            if !self.day_locked
+                && self.day_start_equity - equity >= limit.amount(self.day_start_equity)
+            {
+                self.day_locked = true;
+                self.stats.record(GuardKind::DailyLoss);
+            }
+        }
+        if self.day_locked {
+            return Some(GuardKind::DailyLoss);
+        }
+        if let Some(limit) = self.config.weekly_loss {
+            if !self.week_locked
+                && self.week_start_equity - equity >= limit.amount(self.week_start_equity)
+            {
+                self.week_locked = true;
+                self.stats.record(GuardKind::WeeklyLoss);
+            }
+        }
+        self.week_locked.then_some(GuardKind::WeeklyLoss)
+    }
+
+    /// Учитывает закрытую сделку в серии убытков
+    pub fn record_trade(&mut self, pnl: f64, time: Option<DateTime<Utc>>) {
+        if pnl >= 0.0 {
+            self.consecutive_losses = 0;
+            return;
+        }
+        self.consecutive_losses += 1;
+        let Some(max_losses) = self.config.max_consecutive_losses else {
+            return;
+        };
+        if self.consecutive_losses >= max_losses.max(1) {
+            self.consecutive_losses = 0;
+            self.cooldown_until =
+                time.map(|time| time + Duration::minutes(self.config.cooldown_minutes));
+            self.stats.record(GuardKind::LosingStreak);
+        }
+    }
+
+    /// Проверяет новый вход; `Err` — ограничение, запретившее вход
+    pub fn check_entry(
+        &mut self,
+        time: Option<DateTime<Utc>>,
+        entry: &EntryExposure,
+    ) -> Result<(), GuardKind> {
+        let result = self.entry_verdict(time, entry);
+        if let Err(kind) = result {
+            self.stats.blocked_entries += 1;
+            if matches!(
+                kind,
+                GuardKind::GrossExposure | GuardKind::NetExposure | GuardKind::PositionLimit
+            ) {
+                self.stats.record(kind);
+            }
+        }
+        result
+    }
+
+    fn entry_verdict(
+        &mut self,
+        time: Option<DateTime<Utc>>,
+        entry: &EntryExposure,
+    ) -> Result<(), GuardKind> {
+        if let Some(time) = time {
+            self.roll_periods(time, entry.equity);
+        }
+        if self.killed {
+            return Err(GuardKind::KillSwitch);
+        }
+        if self.day_locked {
+            return Err(GuardKind::DailyLoss);
+        }
+        if self.week_locked {
+            return Err(GuardKind::WeeklyLoss);
+        }
+        if let (Some(until), Some(time)) = (self.cooldown_until, time) {
+            if time < until {
+                return Err(GuardKind::LosingStreak);
+            }
+        }
+        if self
+            .config
+            .max_positions_per_direction
+            .is_some_and(|max| entry.positions_in_direction >= max)
+        {
+            return Err(GuardKind::PositionLimit);
+        }
+        let equity = entry.equity.max(0.0);
+        let notional = entry.notional.abs();
+        if let Some(limit) = self.config.max_gross_exposure_pct {
+            let gross = entry.long_notional + entry.short_notional + notional;
+            if gross > equity * limit / 100.0 {
+                return Err(GuardKind::GrossExposure);
+            }
+        }
+        if let Some(limit) = self.config.max_net_exposure_pct {
+            let net = match entry.direction {
+                PositionDirection::Short => entry.long_notional - entry.short_notional - notional,
+                _ => entry.long_notional + notional - entry.short_notional,
+            };
+            if net.abs() > equity * limit / 100.0 {
+                return Err(GuardKind::NetExposure);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn entry(direction: PositionDirection, notional: f64, long: f64, short: f64) -> EntryExposure {
+        EntryExposure {
+            direction,
+            notional,
+            long_notional: long,
+            short_notional: short,
+            positions_in_direction: 0,
+            equity: 10_000.0,
+        }
+    }
+
+    #[test]
+    fn loss_limits_lock_until_next_period_and_kill_switch_is_final() {
+        let config = RiskGuardConfig::default()
+            .with_daily_loss(LossLimit::PercentOfEquity(2.0))
+            .with_kill_switch(10.0);
+        let mut guard = AccountRiskGuard::new(config);
+        let monday = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        assert_eq!(guard.on_bar(monday, 10_000.0), None);
+        assert_eq!(
+            guard.on_bar(monday + Duration::hours(1), 9_790.0),
+            Some(GuardKind::DailyLoss)
+        );
+        let long = entry(PositionDirection::Long, 100.0, 0.0, 0.0);
+        assert_eq!(
+            guard.check_entry(Some(monday + Duration::hours(2)), &long),
+            Err(GuardKind::DailyLoss)
+        );
+        let tuesday = monday + Duration::days(1);
+        assert_eq!(guard.check_entry(Some(tuesday), &long), Ok(()));
+        assert_eq!(guard.on_bar(tuesday, 8_900.0), Some(GuardKind::KillSwitch));
+        assert_eq!(
+            guard.check_entry(Some(tuesday + Duration::days(7)), &long),
+            Err(GuardKind::KillSwitch)
+        );
+        let stats = guard.stats();
+        assert_eq!((stats.daily_loss, stats.kill_switch), (1, 1));
+        assert_eq!(stats.blocked_entries, 2);
+    }
+
+    #[test]
+    fn streak_cooldown_and_exposure_limits_reject_entries() {
+        let config = RiskGuardConfig::default()
+            .with_losing_streak(2, 60)
+            .with_exposure(Some(150.0), Some(100.0));
+        let mut guard = AccountRiskGuard::new(config);
+        let time = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        guard.record_trade(-10.0, Some(time));
+        guard.record_trade(-10.0, Some(time));
+        let small = entry(PositionDirection::Long, 100.0, 0.0, 0.0);
+        assert_eq!(
+            guard.check_entry(Some(time + Duration::minutes(30)), &small),
+            Err(GuardKind::LosingStreak)
+        );
+        let later = Some(time + Duration::minutes(61));
+        assert_eq!(guard.check_entry(later, &small), Ok(()));
+        let net_breach = entry(PositionDirection::Long, 5_000.0, 6_000.0, 0.0);
+        assert_eq!(
+            guard.check_entry(later, &net_breach),
+            Err(GuardKind::NetExposure)
+        );
+        let hedge = entry(PositionDirection::Short, 5_000.0, 6_000.0, 0.0);
+        assert_eq!(guard.check_entry(later, &hedge), Ok(()));
+        let gross_breach = entry(PositionDirection::Short, 5_000.0, 6_000.0, 5_000.0);
+        assert_eq!(
+            guard.check_entry(later, &gross_breach),
+            Err(GuardKind::GrossExposure)
+        );
+        let stats = guard.stats();
+        assert_eq!(stats.losing_streak, 1);
+        assert_eq!((stats.net_exposure, stats.gross_exposure), (1, 1));
+        assert_eq!(stats.triggers(), 3);
+    }
+}
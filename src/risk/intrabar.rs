@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::data_model::quote_frame::QuoteFrame;
+use crate::data_model::types::TimeFrame;
+use crate::strategy::types::PositionDirection;
+
+/// Предположение о пути цены внутри бара, когда стоп и тейк лежат в его диапазоне
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum IntrabarHeuristic {
+    /// Растущий бар: O→L→H→C, падающий: O→H→L→C
+    #[default]
+    BarDirection,
+    /// Всегда O→H→L→C
+    OpenHighLowClose,
+    /// Всегда O→L→H→C
+    OpenLowHighClose,
+    /// Худший для позиции исход: первым срабатывает стоп
+    Pessimistic,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntrabarConfig {
+    pub heuristic: IntrabarHeuristic,
+    /// Уточнять последовательность по младшему таймфрейму, если он загружен
+    pub use_lower_timeframe: bool,
+}
+
+impl Default for IntrabarConfig {
+    fn default() -> Self {
+        Self {
+            heuristic: IntrabarHeuristic::default(),
+            use_lower_timeframe: true,
+        }
+    }
+}
+
+/// Каким способом определен порядок срабатывания стопа и тейка
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntrabarResolution {
+    /// По предположению о пути цены внутри бара
+    Heuristic,
+    /// По фактическим ценам: гэп открытия или бары младшего таймфрейма
+    RealData,
+}
+
+/// Какой уровень сработал первым
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntrabarHit {
+    Stop,
+    Take,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntrabarBar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub start: Option<DateTime<Utc>>,
+    /// Начало следующего бара; если неизвестно — start + длительность таймфрейма
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// Разрешает конфликты стоп/тейк внутри одного бара
+#[derive(Clone, Default)]
+pub struct IntrabarResolver {
+    config: IntrabarConfig,
+    frames: HashMap<TimeFrame, Arc<QuoteFrame>>,
+}
+
+impl IntrabarResolver {
+    pub fn new(config: IntrabarConfig) -> Self {
+        Self {
+            config,
+            frames: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &IntrabarConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: IntrabarConfig) {
+        self.config = config;
+    }
+
+    pub fn set_frames(&mut self, frames: HashMap<TimeFrame, Arc<QuoteFrame>>) {
+        self.frames = frames;
+    }
+
+    /// Определяет, что сработало первым на баре `bar` таймфрейма `timeframe`
+    pub fn resolve(
+        &self,
+        direction: &PositionDirection,
+        stop_level: f64,
+        take_level: f64,
+        timeframe: &TimeFrame,
+        bar: IntrabarBar,
+    ) -> (IntrabarHit, IntrabarResolution) {
+        if let Some(hit) = gap_hit(direction, stop_level, take_level, bar.open) {
+            return (hit, IntrabarResolution::RealData);
+        }
+        if self.config.use_lower_timeframe {
+            if let Some(result) =
+                self.resolve_lower(direction, stop_level, take_level, timeframe, bar)
+            {
+                return result;
+            }
+        }
+        (
+            heuristic_hit(self.config.heuristic, direction, bar),
+            IntrabarResolution::Heuristic,
+        )
+    }
+
+    fn resolve_lower(
+        &self,
+        direction: &PositionDirection,
+        stop_level: f64,
+        take_level: f64,
+        timeframe: &TimeFrame,
+        bar: IntrabarBar,
+    ) -> Option<(IntrabarHit, IntrabarResolution)> {
+        let frame = self.lower_frame(timeframe)?;
+        let start = bar.start?;
+        let end = bar
+            .end
+            .or_else(|| timeframe.duration().map(|duration| start + duration))?;
+        let quotes = frame.slice_by_time(start, end - Duration::milliseconds(1));
+        for quote in quotes {
+            let bar = IntrabarBar {
+                open: f64::from(quote.open()),
+                high: f64::from(quote.high()),
+                low: f64::from(quote.low()),
+                close: f64::from(quote.close()),
+                start: Some(quote.timestamp()),
+                end: None,
+            };
+            let stop_hit = level_hit(direction, stop_level, bar, IntrabarHit::Stop);
+            let take_hit = level_hit(direction, take_level, bar, IntrabarHit::Take);
+            match (stop_hit, take_hit) {
+                (true, true) => {
+                    // Оба уровня внутри младшего бара — последовательность снова неизвестна
+                    let hit = gap_hit(direction, stop_level, take_level, bar.open)
+                        .unwrap_or_else(|| heuristic_hit(self.config.heuristic, direction, bar));
+                    return Some((hit, IntrabarResolution::Heuristic));
+                }
+                (true, false) => return Some((IntrabarHit::Stop, IntrabarResolution::RealData)),
+                (false, true) => return Some((IntrabarHit::Take, IntrabarResolution::RealData)),
+                (false, false) => {}
+            }
+        }
+        None
+    }
+
+    /// Самый мелкий загруженный таймфрейм младше `timeframe`
+    fn lower_frame(&self, timeframe: &TimeFrame) -> Option<&Arc<QuoteFrame>> {
+        let target = timeframe.duration()?;
+        self.frames
+            .iter()
+            .filter_map(|(tf, frame)| tf.duration().map(|duration| (duration, frame)))
+            .filter(|(duration, frame)| *duration < target && !frame.is_empty())
+            .min_by_key(|(duration, _)| *duration)
+            .map(|(_, frame)| frame)
+    }
+}
+
+fn level_hit(
+    direction: &PositionDirection,
+    level: f64,
+    bar: IntrabarBar,
+    kind: IntrabarHit,
+) -> bool {
+    let below = match (direction, kind) {
+        (PositionDirection::Long, IntrabarHit::Stop) => true,
+        (PositionDirection::Long, IntrabarHit::Take) => false,
+        (PositionDirection::Short, IntrabarHit::Stop) => false,
+        (PositionDirection::Short, IntrabarHit::Take) => true,
+        _ => return false,
+    };
+    if below {
+        bar.low <= level
+    } else {
+        bar.high >= level
+    }
+}
+
+/// Открытие бара уже за одним из уровней
+fn gap_hit(
+    direction: &PositionDirection,
+    stop_level: f64,
+    take_level: f64,
+    open: f64,
+) -> Option<IntrabarHit> {
+    match direction {
+        PositionDirection::Long if open <= stop_level => Some(IntrabarHit::Stop),
+        PositionDirection::Long if open >= take_level => Some(IntrabarHit::Take),
+        PositionDirection::Short if open >= stop_level => Some(IntrabarHit::Stop),
+        PositionDirection::Short if open <= take_level => Some(IntrabarHit::Take),
+        _ => None,
+    }
+}
+
+fn heuristic_hit(
+    heuristic: IntrabarHeuristic,
+    direction: &PositionDirection,
+    bar: IntrabarBar,
+) -> IntrabarHit {
+    let high_first = match heuristic {
+        IntrabarHeuristic::Pessimistic => return IntrabarHit::Stop,
+        IntrabarHeuristic::OpenHighLowClose => true,
+        IntrabarHeuristic::OpenLowHighClose => false,
+        IntrabarHeuristic::BarDirection => bar.close < bar.open,
+    };
+    match (direction, high_first) {
+        (PositionDirection::Short, true) | (PositionDirection::Long, false) => IntrabarHit::Stop,
+        _ => IntrabarHit::Take,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::quote::Quote;
+    use crate::data_model::types::Symbol;
+
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> IntrabarBar {
+        IntrabarBar {
+            open,
+            high,
+            low,
+            close,
+            start: None,
+            end: None,
+        }
+    }
+
+    #[test]
+    fn heuristics_follow_bar_direction() {
+        let resolver = IntrabarResolver::new(IntrabarConfig {
+            heuristic: IntrabarHeuristic::BarDirection,
+            use_lower_timeframe: false,
+        });
+        let tf = TimeFrame::minutes(60);
+        let rising = bar(100.0, 110.0, 90.0, 108.0);
+        let falling = bar(100.0, 110.0, 90.0, 92.0);
+        let long = PositionDirection::Long;
+        assert_eq!(
+            resolver.resolve(&long, 95.0, 105.0, &tf, rising),
+            (IntrabarHit::Stop, IntrabarResolution::Heuristic)
+        );
+        assert_eq!(
+            resolver.resolve(&long, 95.0, 105.0, &tf, falling).0,
+            IntrabarHit::Take
+        );
+
+        let pessimistic = IntrabarResolver::new(IntrabarConfig {
+            heuristic: IntrabarHeuristic::Pessimistic,
+            use_lower_timeframe: false,
+        });
+        assert_eq!(
+            pessimistic.resolve(&long, 95.0, 105.0, &tf, falling).0,
+            IntrabarHit::Stop
+        );
+    }
+
+    #[test]
+    fn lower_timeframe_determines_sequence() {
+        let symbol = Symbol::from_descriptor("TEST.TEST");
+        let minute = TimeFrame::minutes(1);
+        let hour = TimeFrame::minutes(60);
+        let start = DateTime::parse_from_rfc3339("2024-01-02T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut frame = QuoteFrame::new(symbol.clone(), minute.clone());
+        for (idx, (open, high, low, close)) in [
+            (100.0, 101.0, 99.0, 100.5),
+            (100.5, 106.0, 100.0, 105.5),
+            (105.5, 106.0, 94.0, 95.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            frame
+                .push(Quote::from_parts(
+                    symbol.clone(),
+                    minute.clone(),
+                    start + Duration::minutes(idx as i64),
+                    open,
+                    high,
+                    low,
+                    close,
+                    1.0,
+                ))
+                .unwrap();
+        }
+        let mut resolver = IntrabarResolver::new(IntrabarConfig {
+            heuristic: IntrabarHeuristic::Pessimistic,
+            use_lower_timeframe: true,
+        });
+        resolver.set_frames(HashMap::from([(minute, Arc::new(frame))]));
+
+        let (hit, resolution) = resolver.resolve(
+            &PositionDirection::Long,
+            95.0,
+            105.0,
+            &hour,
+            IntrabarBar {
+                start: Some(start),
+                ..bar(100.0, 106.0, 94.0, 95.0)
+            },
+        );
+        assert_eq!(hit, IntrabarHit::Take);
+        assert_eq!(resolution, IntrabarResolution::RealData);
+    }
+}
@@ -1,17 +1,24 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::data_model::quote_frame::QuoteFrame;
 use crate::data_model::types::TimeFrame;
 use crate::position::view::ActivePosition;
 use crate::strategy::context::{StrategyContext, TimeframeData};
 use crate::strategy::types::{
-    PositionDirection, PriceField, StopSignal, StopSignalKind, StrategySignal, StrategySignalType,
+    PositionDirection, PriceField, StopSignal, StopSignalKind, StrategyDecision, StrategySignal,
+    StrategySignalType,
 };
 
 use super::context::{StopEvaluationContext, StopValidationContext};
+use super::intrabar::{
+    IntrabarBar, IntrabarConfig, IntrabarHit, IntrabarResolution, IntrabarResolver,
+};
 use super::state::{PositionRiskState, RiskStateBook};
 use super::traits::{StopHandler, StopOutcome};
-use super::utils::{calculate_stop_exit_price, get_price_at_index, is_stop_triggered};
+use super::utils::{
+    calculate_stop_exit_price, entry_bar_index, get_price_at_index, is_stop_triggered,
+};
 
 pub struct StopHandlerEntry {
     pub handler: Arc<dyn StopHandler>,
@@ -24,6 +31,7 @@ pub struct StopHandlerEntry {
 pub struct RiskManager {
     stop_handlers: Vec<StopHandlerEntry>,
     state_book: RiskStateBook,
+    intrabar: IntrabarResolver,
 }
 
 impl RiskManager {
@@ -31,6 +39,7 @@ impl RiskManager {
         Self {
             stop_handlers: Vec::new(),
             state_book: RiskStateBook::new(),
+            intrabar: IntrabarResolver::default(),
         }
     }
 
@@ -38,14 +47,27 @@ impl RiskManager {
         Self {
             stop_handlers: handlers,
             state_book: RiskStateBook::new(),
+            intrabar: IntrabarResolver::default(),
         }
     }
 
+    pub fn set_intrabar_config(&mut self, config: IntrabarConfig) {
+        self.intrabar.set_config(config);
+    }
+
+    /// Кадры котировок для уточнения порядка срабатывания по младшему таймфрейму
+    pub fn set_intrabar_frames(&mut self, frames: HashMap<TimeFrame, Arc<QuoteFrame>>) {
+        self.intrabar.set_frames(frames);
+    }
+
     pub fn add_handler(&mut self, entry: StopHandlerEntry) {
         self.stop_handlers.push(entry);
     }
 
     pub fn reset(&mut self) {
+        for position_id in self.state_book.position_ids() {
+            self.release_position(&position_id);
+        }
         self.state_book.clear();
     }
 
@@ -100,6 +122,37 @@ impl RiskManager {
         None
     }
 
+    /// Уровень начального стопа для входа по цене `entry_price`,
+    /// как его рассчитают стоп-обработчики после открытия позиции
+    pub fn initial_stop_level(
+        &self,
+        context: &StrategyContext,
+        direction: &PositionDirection,
+        entry_price: f64,
+        timeframe: &TimeFrame,
+    ) -> Option<f64> {
+        let symbol = context.timeframe(timeframe).ok()?.symbol()?.clone();
+        let position = ActivePosition::new(
+            "pending_entry",
+            symbol,
+            timeframe.clone(),
+            direction.clone(),
+            entry_price,
+            0.0,
+            None,
+            None,
+        );
+        let (high, low) = self.get_current_high_low(context, timeframe);
+        let state = PositionRiskState::new(
+            position.id.clone(),
+            direction.clone(),
+            entry_price,
+            high.unwrap_or(entry_price),
+            low.unwrap_or(entry_price),
+        );
+        self.compute_initial_stop(&state, &position, context)
+    }
+
     pub fn on_position_opened(&mut self, position: &ActivePosition, context: &StrategyContext) {
         if self.state_book.contains(&position.id) {
             return;
@@ -117,13 +170,25 @@ impl RiskManager {
 
         if let Some(stop_level) = self.compute_initial_stop(&state, position, context) {
             state.update_stop(stop_level);
+            state.initial_stop = Some(stop_level);
         }
+        state.entry_bar_index = context
+            .timeframe(&position.timeframe)
+            .ok()
+            .and_then(|data| entry_bar_index(data, position.opened_at));
 
         self.state_book.insert(state);
     }
 
     pub fn on_position_closed(&mut self, position_id: &str) {
+        self.release_position(position_id);
+    }
+
+    fn release_position(&mut self, position_id: &str) {
         self.state_book.remove(position_id);
+        for handler_entry in &self.stop_handlers {
+            handler_entry.handler.on_position_closed(position_id);
+        }
     }
 
     pub fn take_stop_history(&mut self, position_id: &str) -> Vec<super::state::StopHistoryRecord> {
@@ -138,6 +203,21 @@ impl RiskManager {
         self.update_trailing_stops(context);
     }
 
+    /// Переносит экстремумы и уровни стопов в активные позиции контекста,
+    /// чтобы их видели тейк-обработчики стратегии
+    pub fn publish_state(&self, context: &mut StrategyContext) {
+        for position in context.active_positions_mut().values_mut() {
+            let Some(state) = self.state_book.get(&position.id) else {
+                continue;
+            };
+            position.max_high_since_entry = Some(state.max_high_since_entry);
+            position.min_low_since_entry = Some(state.min_low_since_entry);
+            position.current_stop = state.current_stop;
+            position.initial_stop = state.initial_stop;
+            position.entry_bar_index = state.entry_bar_index;
+        }
+    }
+
     pub fn sync_with_positions(&mut self, context: &StrategyContext) {
         let active_ids: Vec<String> = context
             .active_positions()
@@ -148,7 +228,7 @@ impl RiskManager {
         let state_ids = self.state_book.position_ids();
         for state_id in state_ids {
             if !active_ids.contains(&state_id) {
-                self.state_book.remove(&state_id);
+                self.release_position(&state_id);
             }
         }
 
@@ -253,36 +333,106 @@ impl RiskManager {
     }
 
     pub fn check_stops(&self, context: &StrategyContext) -> Vec<StopSignal> {
-        let mut signals = Vec::new();
+        context
+            .active_positions()
+            .values()
+            .filter_map(|position| self.check_position_stop(position, context))
+            .collect()
+    }
 
-        for position in context.active_positions().values() {
-            let state = match self.state_book.get(&position.id) {
-                Some(s) => s,
-                None => continue,
+    fn check_position_stop(
+        &self,
+        position: &ActivePosition,
+        context: &StrategyContext,
+    ) -> Option<StopSignal> {
+        let state = self.state_book.get(&position.id)?;
+
+        for handler_entry in &self.stop_handlers {
+            if !self.handler_matches_position(handler_entry, position) {
+                continue;
+            }
+
+            let tf_data = match context.timeframe(&handler_entry.timeframe) {
+                Ok(data) => data,
+                Err(_) => continue,
             };
 
-            for handler_entry in &self.stop_handlers {
-                if !self.handler_matches_position(handler_entry, position) {
-                    continue;
+            let eval_ctx =
+                self.build_eval_context(state, position, tf_data, &handler_entry.price_field);
+
+            if handler_entry.handler.evaluates_exit() {
+                if let Some(outcome) = handler_entry.handler.evaluate(&eval_ctx) {
+                    return Some(self.build_stop_signal(position, handler_entry, outcome));
                 }
+                continue;
+            }
 
-                let tf_data = match context.timeframe(&handler_entry.timeframe) {
-                    Ok(data) => data,
-                    Err(_) => continue,
-                };
+            if let Some(outcome) = self.evaluate_stop(&eval_ctx, handler_entry, state) {
+                return Some(self.build_stop_signal(position, handler_entry, outcome));
+            }
+        }
 
-                let eval_ctx =
-                    self.build_eval_context(state, position, tf_data, &handler_entry.price_field);
+        None
+    }
 
-                if let Some(outcome) = self.evaluate_stop(&eval_ctx, handler_entry, state) {
-                    let signal = self.build_stop_signal(position, handler_entry, outcome);
-                    signals.push(signal);
-                    break;
-                }
+    /// Разрешает бары, на которых сработали и тейк, и стоп одной позиции.
+    /// Если первым был стоп, тейк в решении заменяется стоп-сигналом.
+    /// Возвращает способ разрешения для каждой затронутой позиции.
+    pub fn resolve_take_conflicts(
+        &self,
+        context: &StrategyContext,
+        decision: &mut StrategyDecision,
+    ) -> HashMap<String, IntrabarResolution> {
+        let mut resolved = HashMap::new();
+        if decision.stop_signals.is_empty() {
+            return resolved;
+        }
+
+        for position in context.active_positions().values() {
+            let Some(take_index) = decision.stop_signals.iter().position(|signal| {
+                signal.kind == StopSignalKind::TakeProfit
+                    && signal_targets_position(&signal.signal, position)
+            }) else {
+                continue;
+            };
+            let Some(stop) = self.check_position_stop(position, context) else {
+                continue;
+            };
+            let Ok(tf_data) = context.timeframe(&position.timeframe) else {
+                continue;
+            };
+            let index = tf_data.index();
+            let price = |field: PriceField| get_price_at_index(tf_data, &field, index, 0.0);
+            let bar = IntrabarBar {
+                open: price(PriceField::Open),
+                high: price(PriceField::High),
+                low: price(PriceField::Low),
+                close: price(PriceField::Close),
+                start: tf_data.timestamp_at(index),
+                end: tf_data.timestamp_at(index + 1),
+            };
+            let stop_level = self
+                .get_current_stop(&position.id)
+                .unwrap_or(stop.exit_price);
+            let take = &decision.stop_signals[take_index];
+            let (hit, resolution) = self.intrabar.resolve(
+                &position.direction,
+                stop_level,
+                take.exit_price,
+                &position.timeframe,
+                bar,
+            );
+            if hit == IntrabarHit::Stop {
+                let take_rule_id = take.signal.rule_id.clone();
+                decision.exits.retain(|exit| {
+                    exit.rule_id != take_rule_id || !signal_targets_position(exit, position)
+                });
+                decision.stop_signals[take_index] = stop;
             }
+            resolved.insert(position.id.clone(), resolution);
         }
 
-        signals
+        resolved
     }
 
     fn evaluate_stop(
@@ -366,6 +516,7 @@ impl RiskManager {
             max_high_since_entry: state.max_high_since_entry,
             min_low_since_entry: state.min_low_since_entry,
             current_stop: state.current_stop,
+            entry_index: entry_bar_index(tf_data, position.opened_at),
         }
     }
 
@@ -390,6 +541,8 @@ impl RiskManager {
                 .as_ref()
                 .map(|id| vec![id.clone()])
                 .unwrap_or_default(),
+            order: None,
+            close_fraction: None,
         };
 
         StopSignal {
@@ -453,6 +606,15 @@ impl RiskManager {
     }
 }
 
+fn signal_targets_position(signal: &StrategySignal, position: &ActivePosition) -> bool {
+    signal.direction == position.direction
+        && signal.timeframe == position.timeframe
+        && signal.target_entry_ids.iter().any(|id| {
+            position.position_group.as_deref() == Some(id.as_str())
+                || position.entry_rule_id.as_deref() == Some(id.as_str())
+        })
+}
+
 impl Default for RiskManager {
     fn default() -> Self {
         Self::new()
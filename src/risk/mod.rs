@@ -2,6 +2,8 @@ pub mod auxiliary;
 pub mod context;
 pub mod errors;
 pub mod factory;
+pub mod guards;
+pub mod intrabar;
 pub mod manager;
 pub mod parameter_extractor;
 pub mod parameters;
@@ -19,6 +21,13 @@ pub use auxiliary::{
 pub use context::{StopEvaluationContext, StopValidationContext, TakeEvaluationContext};
 pub use errors::{StopHandlerError, TakeHandlerError};
 pub use factory::{StopHandlerFactory, TakeHandlerFactory};
+pub use guards::{
+    AccountRiskGuard, EntryExposure, GuardKind, LossLimit, RiskGuardConfig, RiskGuardStats,
+};
+pub use intrabar::{
+    IntrabarBar, IntrabarConfig, IntrabarHeuristic, IntrabarHit, IntrabarResolution,
+    IntrabarResolver,
+};
 pub use manager::{RiskManager, StopHandlerEntry};
 pub use parameter_extractor::{
     convert_params_from_f64, convert_params_to_f64, extract_bool,
@@ -34,10 +43,15 @@ pub use registry::{
 };
 pub use state::{PositionRiskState, RiskStateBook, StopHistoryRecord};
 pub use stops::{
-    ATRTrailIndicatorStopHandler, ATRTrailStopHandler, HILOTrailingStopHandler,
+    ATRTrailIndicatorStopHandler, ATRTrailStopHandler, BreakEvenStopHandler, BreakEvenTrigger,
+    ChandelierStopHandler, HILOTrailingStopHandler, ParabolicSARStopHandler,
     PercentTrailIndicatorStopHandler, PercentTrailingStopHandler, StopLossPctHandler,
+    TimeExitStopHandler,
+};
+pub use takes::{
+    ChannelKind, ChannelTakeHandler, TakeProfitATRHandler, TakeProfitLadderHandler,
+    TakeProfitPctHandler, TakeProfitRHandler, TrailingTakeHandler,
 };
-pub use takes::TakeProfitPctHandler;
 pub use traits::{StopHandler, StopOutcome, StopValidationResult, TakeHandler, TakeOutcome};
 pub use utils::{
     extract_indicator_from_handler_name, process_stop_handler_indicator,
@@ -56,6 +56,66 @@ impl StopParameterPresets {
         ParameterRange::new(3.0, 8.0, 0.5)
     }
 
+    pub fn ladder_targets() -> ParameterRange {
+        ParameterRange::new(2.0, 4.0, 1.0)
+    }
+
+    pub fn ladder_close_percentage() -> ParameterRange {
+        ParameterRange::new(20.0, 50.0, 10.0)
+    }
+
+    pub fn break_even_after_exits() -> ParameterRange {
+        ParameterRange::new(1.0, 3.0, 1.0)
+    }
+
+    pub fn break_even_offset() -> ParameterRange {
+        ParameterRange::new(0.0, 1.0, 0.25)
+    }
+
+    pub fn break_even_trigger() -> ParameterRange {
+        ParameterRange::new(0.5, 5.0, 0.5)
+    }
+
+    pub fn break_even_atr_coefficient() -> ParameterRange {
+        ParameterRange::new(0.5, 3.0, 0.5)
+    }
+
+    pub fn time_exit_bars() -> ParameterRange {
+        ParameterRange::new(5.0, 100.0, 5.0)
+    }
+
+    pub fn sar_step() -> ParameterRange {
+        ParameterRange::new(0.01, 0.05, 0.01)
+    }
+
+    pub fn sar_max_step() -> ParameterRange {
+        ParameterRange::new(0.1, 0.3, 0.05)
+    }
+
+    pub fn take_atr_coefficient() -> ParameterRange {
+        ParameterRange::new(1.0, 6.0, 0.5)
+    }
+
+    pub fn r_multiple() -> ParameterRange {
+        ParameterRange::new(1.0, 5.0, 0.5)
+    }
+
+    pub fn channel_period() -> ParameterRange {
+        ParameterRange::new(10.0, 50.0, 5.0)
+    }
+
+    pub fn channel_deviation() -> ParameterRange {
+        ParameterRange::new(1.5, 3.0, 0.5)
+    }
+
+    pub fn trailing_take_activation() -> ParameterRange {
+        ParameterRange::new(1.0, 10.0, 1.0)
+    }
+
+    pub fn trailing_take_distance() -> ParameterRange {
+        ParameterRange::new(0.5, 3.0, 0.5)
+    }
+
     pub fn get_range(handler_name: &str, param_name: &str) -> Option<ParameterRange> {
         let handler = handler_name.to_uppercase();
         let param = param_name.to_lowercase();
@@ -65,6 +125,27 @@ impl StopParameterPresets {
                 Self::match_percentage_param(&param)
             }
             "TAKEPROFITPCT" | "TAKE_PROFIT_PCT" => Self::match_take_profit_param(&param),
+            "TAKEPROFITLADDER" | "TAKE_PROFIT_LADDER" => Self::match_ladder_param(&param),
+            "TAKEPROFITATR" | "TAKE_PROFIT_ATR" => Self::match_take_atr_param(&param),
+            "TAKEPROFITR" | "TAKE_PROFIT_R" => Self::match_r_multiple_param(&param),
+            "BOLLINGERTAKE" | "BOLLINGER_TAKE" | "KELTNERTAKE" | "KELTNER_TAKE" => {
+                Self::match_channel_take_param(&param)
+            }
+            "TRAILINGTAKE" | "TRAILING_TAKE" => Self::match_trailing_take_param(&param),
+            "BREAKEVENSTOP" | "BREAK_EVEN_STOP" | "BREAKEVEN" => {
+                Self::match_break_even_param(&param)
+            }
+            "BREAKEVENPCTSTOP" | "BREAK_EVEN_PCT_STOP" | "NOLOSSPCT" => {
+                Self::match_break_even_pct_param(&param)
+            }
+            "BREAKEVENATRSTOP" | "BREAK_EVEN_ATR_STOP" => Self::match_break_even_atr_param(&param),
+            "TIMEEXITSTOP" | "TIME_EXIT_STOP" | "ONBARSSTOPLOSS" => {
+                Self::match_time_exit_param(&param)
+            }
+            "CHANDELIERSTOP" | "CHANDELIER_STOP" | "CHANDELIER" => {
+                Self::match_atr_trail_param(&param)
+            }
+            "PARABOLICSARSTOP" | "PARABOLIC_SAR_STOP" | "SAR_STOP" => Self::match_sar_param(&param),
             "ATRTRAILSTOP" | "ATR_TRAIL_STOP" | "ATR_TRAIL" => Self::match_atr_trail_param(&param),
             "HILOTRAILSTOP" | "HILOTRAILINGSTOP" | "HILO_TRAIL_STOP" | "HILO_TRAIL" => {
                 Self::match_hilo_param(&param)
@@ -100,6 +181,86 @@ impl StopParameterPresets {
         }
     }
 
+    fn match_ladder_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "step_pct" | "step" | "percentage" => Some(Self::take_profit_percentage()),
+            "targets" => Some(Self::ladder_targets()),
+            "close_pct" | "close" => Some(Self::ladder_close_percentage()),
+            _ => None,
+        }
+    }
+
+    fn match_take_atr_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "period" => Some(Self::trailing_period()),
+            "coeff_atr" | "coeff" | "atr_coeff" => Some(Self::take_atr_coefficient()),
+            _ => None,
+        }
+    }
+
+    fn match_r_multiple_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "r_multiple" | "multiple" | "r" => Some(Self::r_multiple()),
+            _ => None,
+        }
+    }
+
+    fn match_channel_take_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "period" => Some(Self::channel_period()),
+            "deviation" | "multiplier" => Some(Self::channel_deviation()),
+            _ => None,
+        }
+    }
+
+    fn match_trailing_take_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "activation_pct" | "activation" => Some(Self::trailing_take_activation()),
+            "trail_pct" | "trail" => Some(Self::trailing_take_distance()),
+            _ => None,
+        }
+    }
+
+    fn match_break_even_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "after_exits" | "after" => Some(Self::break_even_after_exits()),
+            "offset_pct" | "offset" => Some(Self::break_even_offset()),
+            _ => None,
+        }
+    }
+
+    fn match_break_even_pct_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "trigger_pct" | "trigger" => Some(Self::break_even_trigger()),
+            "offset_pct" | "offset" => Some(Self::break_even_offset()),
+            _ => None,
+        }
+    }
+
+    fn match_break_even_atr_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "period" => Some(Self::trailing_period()),
+            "coeff_atr" | "coeff" | "atr_coeff" => Some(Self::break_even_atr_coefficient()),
+            "offset_pct" | "offset" => Some(Self::break_even_offset()),
+            _ => None,
+        }
+    }
+
+    fn match_time_exit_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "bars" | "max_bars" => Some(Self::time_exit_bars()),
+            _ => None,
+        }
+    }
+
+    fn match_sar_param(param: &str) -> Option<ParameterRange> {
+        match param {
+            "step" | "acceleration" => Some(Self::sar_step()),
+            "max_step" | "max_acceleration" => Some(Self::sar_max_step()),
+            _ => None,
+        }
+    }
+
     fn match_atr_trail_param(param: &str) -> Option<ParameterRange> {
         match param {
             "period" => Some(Self::trailing_period()),
@@ -2,9 +2,14 @@ use crate::discovery::types::StopHandlerConfig;
 use crate::indicators::types::ParameterRange;
 use crate::risk::factory::StopHandlerFactory;
 use crate::risk::stops::{
-    ATRTrailStopHandler, HILOTrailingStopHandler, PercentTrailingStopHandler, StopLossPctHandler,
+    ATRTrailStopHandler, BreakEvenStopHandler, BreakEvenTrigger, ChandelierStopHandler,
+    HILOTrailingStopHandler, ParabolicSARStopHandler, PercentTrailingStopHandler,
+    StopLossPctHandler, TimeExitStopHandler,
+};
+use crate::risk::takes::{
+    ChannelKind, ChannelTakeHandler, TakeProfitATRHandler, TakeProfitLadderHandler,
+    TakeProfitPctHandler, TakeProfitRHandler, TrailingTakeHandler,
 };
-use crate::risk::takes::TakeProfitPctHandler;
 use crate::risk::traits::{StopHandler, TakeHandler};
 use std::collections::HashMap;
 use std::sync::{OnceLock, RwLock};
@@ -36,6 +41,20 @@ impl StopHandlerRegistry {
         self.register_stop_handler(Box::new(ATRTrailStopHandler::new(14.0, 5.0)));
         self.register_stop_handler(Box::new(HILOTrailingStopHandler::new(14.0)));
         self.register_stop_handler(Box::new(PercentTrailingStopHandler::new(1.0)));
+        self.register_stop_handler(Box::new(BreakEvenStopHandler::new(
+            BreakEvenTrigger::Percent(1.0),
+            0.0,
+        )));
+        self.register_stop_handler(Box::new(BreakEvenStopHandler::new(
+            BreakEvenTrigger::Atr {
+                period: 14.0,
+                coeff_atr: 1.0,
+            },
+            0.0,
+        )));
+        self.register_stop_handler(Box::new(TimeExitStopHandler::new(20)));
+        self.register_stop_handler(Box::new(ChandelierStopHandler::new(22.0, 3.0)));
+        self.register_stop_handler(Box::new(ParabolicSARStopHandler::new(0.02, 0.2)));
 
         // ATRTrailIndicatorStop требует indicator_name, создаем с дефолтным
         let mut atr_trail_ind_params = HashMap::new();
@@ -83,6 +102,20 @@ impl StopHandlerRegistry {
 
         // Регистрируем Take Handlers
         self.register_take_handler(Box::new(TakeProfitPctHandler::new(10.0)));
+        self.register_take_handler(Box::new(TakeProfitLadderHandler::new(10.0, 2, 50.0)));
+        self.register_take_handler(Box::new(TakeProfitATRHandler::new(14.0, 3.0)));
+        self.register_take_handler(Box::new(TakeProfitRHandler::new(2.0)));
+        self.register_take_handler(Box::new(ChannelTakeHandler::new(
+            ChannelKind::Bollinger,
+            20.0,
+            2.0,
+        )));
+        self.register_take_handler(Box::new(ChannelTakeHandler::new(
+            ChannelKind::Keltner,
+            20.0,
+            2.0,
+        )));
+        self.register_take_handler(Box::new(TrailingTakeHandler::new(3.0, 1.0)));
     }
 
     /// Автоматически регистрирует стоп-обработчик, извлекая информацию из его ParameterSet
@@ -18,6 +18,8 @@ pub struct PositionRiskState {
     pub max_high_since_entry: f64,
     pub min_low_since_entry: f64,
     pub current_stop: Option<f64>,
+    /// Стоп, рассчитанный при открытии позиции
+    pub initial_stop: Option<f64>,
     pub entry_bar_index: Option<usize>,
     pub stop_history: Vec<StopHistoryRecord>,
 }
@@ -37,6 +39,7 @@ impl PositionRiskState {
             max_high_since_entry: initial_high,
             min_low_since_entry: initial_low,
             current_stop: None,
+            initial_stop: None,
             entry_bar_index: None,
             stop_history: Vec::new(),
         }
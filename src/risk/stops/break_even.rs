@@ -0,0 +1,210 @@
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::strategy::types::PositionDirection;
+
+use crate::risk::auxiliary::AuxiliaryIndicatorSpec;
+use crate::risk::context::StopEvaluationContext;
+use crate::risk::parameters::{create_stop_period_parameter, StopParameterPresets};
+use crate::risk::traits::{StopHandler, StopOutcome};
+use crate::risk::utils::{evaluate_stop_level, get_atr_value};
+
+/// Условие переноса стопа в безубыток
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BreakEvenTrigger {
+    /// После заданного числа частичных выходов
+    PartialExits(usize),
+    /// Когда цена прошла заданный процент от цены входа в сторону прибыли
+    /// (аналог NoLossPct из TSLab)
+    Percent(f64),
+    /// Когда цена прошла coeff_atr·ATR(period) в сторону прибыли
+    Atr { period: f64, coeff_atr: f64 },
+}
+
+/// Перенос стопа в безубыток при выполнении условия `trigger`.
+/// offset_pct сдвигает уровень от цены входа в сторону прибыли.
+pub struct BreakEvenStopHandler {
+    pub trigger: BreakEvenTrigger,
+    pub offset_pct: f64,
+    parameters: ParameterSet,
+}
+
+impl BreakEvenStopHandler {
+    pub fn new(trigger: BreakEvenTrigger, offset_pct: f64) -> Self {
+        let trigger = match trigger {
+            BreakEvenTrigger::PartialExits(after_exits) => {
+                BreakEvenTrigger::PartialExits(after_exits.max(1))
+            }
+            other => other,
+        };
+        let mut params = ParameterSet::new();
+        match trigger {
+            BreakEvenTrigger::PartialExits(after_exits) => {
+                params.add_parameter_unchecked(IndicatorParameter::new(
+                    "after_exits",
+                    after_exits as f32,
+                    StopParameterPresets::break_even_after_exits(),
+                    "Число частичных выходов до переноса стопа",
+                    ParameterType::Custom,
+                ));
+            }
+            BreakEvenTrigger::Percent(trigger_pct) => {
+                params.add_parameter_unchecked(IndicatorParameter::new(
+                    "trigger_pct",
+                    trigger_pct as f32,
+                    StopParameterPresets::break_even_trigger(),
+                    "Прибыль в процентах, после которой стоп переносится в безубыток",
+                    ParameterType::Threshold,
+                ));
+            }
+            BreakEvenTrigger::Atr { period, coeff_atr } => {
+                params.add_parameter_unchecked(create_stop_period_parameter(
+                    "period",
+                    period as f32,
+                    "Период для расчета ATR",
+                ));
+                params.add_parameter_unchecked(IndicatorParameter::new(
+                    "coeff_atr",
+                    coeff_atr as f32,
+                    StopParameterPresets::break_even_atr_coefficient(),
+                    "Прибыль в ATR, после которой стоп переносится в безубыток",
+                    ParameterType::Coefficient,
+                ));
+            }
+        }
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "offset_pct",
+            offset_pct as f32,
+            StopParameterPresets::break_even_offset(),
+            "Сдвиг уровня безубытка в процентах",
+            ParameterType::Threshold,
+        ));
+        Self {
+            trigger,
+            offset_pct,
+            parameters: params,
+        }
+    }
+
+    fn triggered(&self, ctx: &StopEvaluationContext<'_>) -> bool {
+        let entry = ctx.position.entry_price;
+        let profit_move = match ctx.position.direction {
+            PositionDirection::Long => ctx.max_high_since_entry - entry,
+            PositionDirection::Short => entry - ctx.min_low_since_entry,
+            _ => return false,
+        };
+        match self.trigger {
+            BreakEvenTrigger::PartialExits(after_exits) => {
+                ctx.position.partial_exits >= after_exits
+            }
+            BreakEvenTrigger::Percent(trigger_pct) => profit_move >= entry * trigger_pct / 100.0,
+            BreakEvenTrigger::Atr { period, coeff_atr } => {
+                get_atr_value(ctx.timeframe_data, period, ctx.index)
+                    .is_some_and(|atr| profit_move >= atr as f64 * coeff_atr)
+            }
+        }
+    }
+
+    fn level(&self, ctx: &StopEvaluationContext<'_>) -> Option<f64> {
+        if !self.triggered(ctx) {
+            return None;
+        }
+        let entry = ctx.position.entry_price;
+        let offset = entry * self.offset_pct / 100.0;
+        match ctx.position.direction {
+            PositionDirection::Long => Some(entry + offset),
+            PositionDirection::Short => Some(entry - offset),
+            _ => None,
+        }
+    }
+}
+
+impl StopHandler for BreakEvenStopHandler {
+    fn name(&self) -> &str {
+        match self.trigger {
+            BreakEvenTrigger::PartialExits(_) => "BreakEvenStop",
+            BreakEvenTrigger::Percent(_) => "BreakEvenPctStop",
+            BreakEvenTrigger::Atr { .. } => "BreakEvenATRStop",
+        }
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn required_auxiliary_indicators(&self) -> Vec<AuxiliaryIndicatorSpec> {
+        match self.trigger {
+            BreakEvenTrigger::Atr { period, .. } => {
+                vec![AuxiliaryIndicatorSpec::atr(period as u32)]
+            }
+            _ => vec![],
+        }
+    }
+
+    fn compute_stop_level(&self, ctx: &StopEvaluationContext<'_>) -> Option<f64> {
+        self.level(ctx)
+    }
+
+    fn evaluate(&self, ctx: &StopEvaluationContext<'_>) -> Option<StopOutcome> {
+        evaluate_stop_level(ctx, self.level(ctx)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::types::{Symbol, TimeFrame};
+    use crate::position::view::ActivePosition;
+    use crate::strategy::context::TimeframeData;
+    use crate::strategy::types::PriceField;
+
+    #[test]
+    fn level_moves_to_entry_once_trigger_is_reached() {
+        let timeframe = TimeFrame::minutes(1);
+        let data = TimeframeData::new(timeframe.clone());
+        let mut position = ActivePosition::new(
+            "p1",
+            Symbol::from_descriptor("TEST.TEST"),
+            timeframe,
+            PositionDirection::Long,
+            100.0,
+            1.0,
+            None,
+            None,
+        );
+        let level = |handler: &BreakEvenStopHandler, position: &ActivePosition, high: f64| {
+            let ctx = StopEvaluationContext {
+                position,
+                timeframe_data: &data,
+                price_field: PriceField::Close,
+                index: 0,
+                current_price: high,
+                max_high_since_entry: high,
+                min_low_since_entry: 100.0,
+                current_stop: None,
+                entry_index: Some(0),
+            };
+            handler.compute_stop_level(&ctx)
+        };
+
+        let pct = BreakEvenStopHandler::new(BreakEvenTrigger::Percent(2.0), 0.5);
+        assert_eq!(pct.name(), "BreakEvenPctStop");
+        assert_eq!(level(&pct, &position, 101.0), None);
+        assert_eq!(level(&pct, &position, 102.0), Some(100.5));
+
+        let exits = BreakEvenStopHandler::new(BreakEvenTrigger::PartialExits(0), 0.0);
+        assert_eq!(exits.trigger, BreakEvenTrigger::PartialExits(1));
+        assert_eq!(level(&exits, &position, 110.0), None);
+        position.partial_exits = 1;
+        assert_eq!(level(&exits, &position, 100.0), Some(100.0));
+
+        let atr = BreakEvenStopHandler::new(
+            BreakEvenTrigger::Atr {
+                period: 14.0,
+                coeff_atr: 1.0,
+            },
+            0.0,
+        );
+        assert_eq!(atr.required_auxiliary_indicators().len(), 1);
+        // Без значения ATR уровень не переносится
+        assert_eq!(level(&atr, &position, 150.0), None);
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use crate::indicators::types::ParameterSet;
+use crate::strategy::types::{PositionDirection, PriceField, StopSignalKind};
+
+use crate::risk::auxiliary::AuxiliaryIndicatorSpec;
+use crate::risk::context::StopEvaluationContext;
+use crate::risk::parameters::{create_atr_coefficient_parameter, create_stop_period_parameter};
+use crate::risk::traits::{StopHandler, StopOutcome};
+use crate::risk::utils::{get_atr_value, get_price_at_index};
+
+/// Chandelier exit: максимум с момента входа минус coeff_atr·ATR (для шорта — минимум плюс).
+/// В отличие от ATRTrailStop срабатывает по закрытию бара за уровнем, а не по касанию,
+/// и уровень следует за текущим ATR без фиксации.
+pub struct ChandelierStopHandler {
+    pub period: f64,
+    pub coeff_atr: f64,
+    parameters: ParameterSet,
+}
+
+impl ChandelierStopHandler {
+    pub fn new(period: f64, coeff_atr: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(create_stop_period_parameter(
+            "period",
+            period as f32,
+            "Период для расчета ATR",
+        ));
+        params.add_parameter_unchecked(create_atr_coefficient_parameter(
+            "coeff_atr",
+            coeff_atr as f32,
+            "Коэффициент умножения ATR",
+        ));
+        Self {
+            period,
+            coeff_atr,
+            parameters: params,
+        }
+    }
+
+    fn level(&self, ctx: &StopEvaluationContext<'_>) -> Option<f64> {
+        let offset =
+            get_atr_value(ctx.timeframe_data, self.period, ctx.index)? as f64 * self.coeff_atr;
+        match ctx.position.direction {
+            PositionDirection::Long => Some(ctx.max_high_since_entry - offset),
+            PositionDirection::Short => Some(ctx.min_low_since_entry + offset),
+            _ => None,
+        }
+    }
+}
+
+impl StopHandler for ChandelierStopHandler {
+    fn name(&self) -> &str {
+        "ChandelierStop"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn required_auxiliary_indicators(&self) -> Vec<AuxiliaryIndicatorSpec> {
+        vec![AuxiliaryIndicatorSpec::atr(self.period as u32)]
+    }
+
+    fn evaluates_exit(&self) -> bool {
+        true
+    }
+
+    fn evaluate(&self, ctx: &StopEvaluationContext<'_>) -> Option<StopOutcome> {
+        let level = self.level(ctx)?;
+        let close = get_price_at_index(
+            ctx.timeframe_data,
+            &PriceField::Close,
+            ctx.index,
+            ctx.current_price,
+        );
+        let triggered = match ctx.position.direction {
+            PositionDirection::Long => close <= level,
+            PositionDirection::Short => close >= level,
+            _ => false,
+        };
+        if !triggered {
+            return None;
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert("level".to_string(), level.to_string());
+        metadata.insert("triggered_price".to_string(), close.to_string());
+        Some(StopOutcome {
+            exit_price: close,
+            kind: StopSignalKind::Trailing,
+            metadata,
+        })
+    }
+}
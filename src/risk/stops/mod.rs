@@ -4,6 +4,10 @@ mod hilo_trailing;
 mod percent_trailing;
 mod atr_trail_indicator;
 mod percent_trail_indicator;
+mod break_even;
+mod time_exit;
+mod chandelier;
+mod parabolic_sar;
 
 pub use stop_loss_pct::StopLossPctHandler;
 pub use atr_trail::ATRTrailStopHandler;
@@ -11,6 +15,10 @@ pub use hilo_trailing::HILOTrailingStopHandler;
 pub use percent_trailing::PercentTrailingStopHandler;
 pub use atr_trail_indicator::ATRTrailIndicatorStopHandler;
 pub use percent_trail_indicator::PercentTrailIndicatorStopHandler;
+pub use break_even::{BreakEvenStopHandler, BreakEvenTrigger};
+pub use time_exit::TimeExitStopHandler;
+pub use chandelier::ChandelierStopHandler;
+pub use parabolic_sar::ParabolicSARStopHandler;
 
 
 
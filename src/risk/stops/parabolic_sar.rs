@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::strategy::types::{PositionDirection, PriceField};
+
+use crate::risk::context::StopEvaluationContext;
+use crate::risk::parameters::StopParameterPresets;
+use crate::risk::traits::{StopHandler, StopOutcome};
+use crate::risk::utils::evaluate_stop_level;
+
+/// Стоп по Parabolic SAR, запущенному от бара входа: начальный уровень —
+/// минимум (для шорта — максимум) бара входа, ускорение растет на step
+/// с каждым новым экстремумом до max_step.
+/// Состояние SAR хранится по позициям и продлевается с последнего рассчитанного бара.
+pub struct ParabolicSARStopHandler {
+    pub step: f64,
+    pub max_step: f64,
+    parameters: ParameterSet,
+    states: Mutex<HashMap<String, SarState>>,
+}
+
+/// SAR позиции после бара `index`
+#[derive(Clone, Copy, Debug)]
+struct SarState {
+    entry: usize,
+    index: usize,
+    sar: f64,
+    extreme: f64,
+    acceleration: f64,
+}
+
+impl ParabolicSARStopHandler {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "step",
+            step as f32,
+            StopParameterPresets::sar_step(),
+            "Шаг ускорения SAR",
+            ParameterType::Coefficient,
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "max_step",
+            max_step as f32,
+            StopParameterPresets::sar_max_step(),
+            "Максимальное ускорение SAR",
+            ParameterType::Coefficient,
+        ));
+        Self {
+            step,
+            max_step: max_step.max(step),
+            parameters: params,
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Уровень SAR для бара `index` по данным до предыдущего бара включительно
+    fn level(&self, ctx: &StopEvaluationContext<'_>) -> Option<f64> {
+        let entry = ctx.entry_index?;
+        if ctx.index <= entry {
+            return None;
+        }
+        let highs = ctx.timeframe_data.price_series_slice(&PriceField::High)?;
+        let lows = ctx.timeframe_data.price_series_slice(&PriceField::Low)?;
+        if ctx.index > highs.len().min(lows.len()) {
+            return None;
+        }
+        let high = |i: usize| highs[i] as f64;
+        let low = |i: usize| lows[i] as f64;
+        let long = match ctx.position.direction {
+            PositionDirection::Long => true,
+            PositionDirection::Short => false,
+            _ => return None,
+        };
+
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        // Кэш сбрасывается при новом входе и при возврате к более раннему бару
+        let mut state = states
+            .get(&ctx.position.id)
+            .copied()
+            .filter(|state| state.entry == entry && state.index < ctx.index)
+            .unwrap_or_else(|| {
+                let (sar, extreme) = if long {
+                    (low(entry), high(entry))
+                } else {
+                    (high(entry), low(entry))
+                };
+                SarState {
+                    entry,
+                    index: entry,
+                    sar,
+                    extreme,
+                    acceleration: self.step,
+                }
+            });
+        for i in state.index + 1..ctx.index {
+            let new_extreme = if long {
+                high(i) > state.extreme
+            } else {
+                low(i) < state.extreme
+            };
+            if new_extreme {
+                state.extreme = if long { high(i) } else { low(i) };
+                state.acceleration = (state.acceleration + self.step).min(self.max_step);
+            }
+            state.sar += state.acceleration * (state.extreme - state.sar);
+            // SAR не заходит за экстремумы двух последних баров
+            state.sar = if long {
+                state.sar.min(low(i)).min(low(i - 1))
+            } else {
+                state.sar.max(high(i)).max(high(i - 1))
+            };
+            state.index = i;
+        }
+        states.insert(ctx.position.id.clone(), state);
+        Some(state.sar)
+    }
+}
+
+impl StopHandler for ParabolicSARStopHandler {
+    fn name(&self) -> &str {
+        "ParabolicSARStop"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn compute_stop_level(&self, ctx: &StopEvaluationContext<'_>) -> Option<f64> {
+        self.level(ctx)
+    }
+
+    fn evaluate(&self, ctx: &StopEvaluationContext<'_>) -> Option<StopOutcome> {
+        evaluate_stop_level(ctx, self.level(ctx)?)
+    }
+
+    fn on_position_closed(&self, position_id: &str) {
+        self.states
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(position_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::types::{Symbol, TimeFrame};
+    use crate::position::view::ActivePosition;
+    use crate::strategy::context::TimeframeData;
+
+    #[test]
+    fn sar_accelerates_with_new_highs_and_stays_below_recent_lows() {
+        let timeframe = TimeFrame::minutes(1);
+        let mut data = TimeframeData::new(timeframe.clone());
+        data.insert_price_series(PriceField::High, vec![10.0, 11.0, 12.0, 13.0, 14.0]);
+        data.insert_price_series(PriceField::Low, vec![9.0, 10.0, 11.0, 12.0, 13.0]);
+        let position = ActivePosition::new(
+            "p1",
+            Symbol::from_descriptor("TEST.TEST"),
+            timeframe,
+            PositionDirection::Long,
+            10.0,
+            1.0,
+            None,
+            None,
+        );
+        let handler = ParabolicSARStopHandler::new(0.02, 0.2);
+        let level_at = |index: usize| {
+            let ctx = StopEvaluationContext {
+                position: &position,
+                timeframe_data: &data,
+                price_field: PriceField::Close,
+                index,
+                current_price: 0.0,
+                max_high_since_entry: 0.0,
+                min_low_since_entry: 0.0,
+                current_stop: None,
+                entry_index: Some(0),
+            };
+            handler.compute_stop_level(&ctx)
+        };
+
+        assert_eq!(level_at(0), None);
+        assert!((level_at(1).unwrap() - 9.0).abs() < 1e-9);
+        assert!((level_at(2).unwrap() - 9.0).abs() < 1e-9);
+        assert!((level_at(3).unwrap() - 9.18).abs() < 1e-9);
+        assert!((level_at(4).unwrap() - 9.4856).abs() < 1e-9);
+        // Возврат к раннему бару пересчитывает SAR от входа
+        assert!((level_at(3).unwrap() - 9.18).abs() < 1e-9);
+        handler.on_position_closed("p1");
+        assert!((level_at(4).unwrap() - 9.4856).abs() < 1e-9);
+    }
+}
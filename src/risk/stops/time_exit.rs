@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::strategy::types::StopSignalKind;
+
+use crate::risk::context::StopEvaluationContext;
+use crate::risk::parameters::StopParameterPresets;
+use crate::risk::traits::{StopHandler, StopOutcome};
+
+/// Выход по закрытию бара, когда позиция удерживается `bars` баров
+/// (аналог OnBarsStopLoss из TSLab)
+pub struct TimeExitStopHandler {
+    pub bars: usize,
+    parameters: ParameterSet,
+}
+
+impl TimeExitStopHandler {
+    pub fn new(bars: usize) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "bars",
+            bars as f32,
+            StopParameterPresets::time_exit_bars(),
+            "Число баров в сделке до принудительного выхода",
+            ParameterType::Period,
+        ));
+        Self {
+            bars: bars.max(1),
+            parameters: params,
+        }
+    }
+}
+
+impl StopHandler for TimeExitStopHandler {
+    fn name(&self) -> &str {
+        "TimeExitStop"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn evaluates_exit(&self) -> bool {
+        true
+    }
+
+    fn evaluate(&self, ctx: &StopEvaluationContext<'_>) -> Option<StopOutcome> {
+        let bars_in_trade = ctx.bars_in_trade()?;
+        if bars_in_trade < self.bars {
+            return None;
+        }
+        let mut metadata = HashMap::new();
+        metadata.insert("bars_in_trade".to_string(), bars_in_trade.to_string());
+        Some(StopOutcome {
+            exit_price: ctx.current_price,
+            kind: StopSignalKind::TimeExit,
+            metadata,
+        })
+    }
+}
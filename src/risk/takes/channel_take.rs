@@ -0,0 +1,105 @@
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::strategy::types::PositionDirection;
+
+use crate::risk::auxiliary::AuxiliaryIndicatorSpec;
+use crate::risk::context::TakeEvaluationContext;
+use crate::risk::parameters::StopParameterPresets;
+use crate::risk::traits::{TakeHandler, TakeOutcome};
+use crate::risk::utils::evaluate_take_level;
+
+/// Период ATR канала Кельтнера, как у KCUpper/KCLower по умолчанию
+const KELTNER_ATR_PERIOD: f64 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelKind {
+    Bollinger,
+    Keltner,
+}
+
+/// Выход при касании противоположной границы канала:
+/// лонг — верхней линии, шорт — нижней
+pub struct ChannelTakeHandler {
+    pub kind: ChannelKind,
+    pub period: f64,
+    /// Отклонение для Боллинджера, множитель ATR для Кельтнера
+    pub deviation: f64,
+    parameters: ParameterSet,
+}
+
+impl ChannelTakeHandler {
+    pub fn new(kind: ChannelKind, period: f64, deviation: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "period",
+            period as f32,
+            StopParameterPresets::channel_period(),
+            "Период канала",
+            ParameterType::Period,
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "deviation",
+            deviation as f32,
+            StopParameterPresets::channel_deviation(),
+            "Ширина канала",
+            ParameterType::Multiplier,
+        ));
+        Self {
+            kind,
+            period,
+            deviation,
+            parameters: params,
+        }
+    }
+
+    fn band(&self, upper: bool) -> AuxiliaryIndicatorSpec {
+        match (self.kind, upper) {
+            (ChannelKind::Bollinger, upper) => AuxiliaryIndicatorSpec::channel_band(
+                if upper { "BBUpper" } else { "BBLower" },
+                &[("period", self.period), ("deviation", self.deviation)],
+            ),
+            (ChannelKind::Keltner, upper) => AuxiliaryIndicatorSpec::channel_band(
+                if upper { "KCUpper" } else { "KCLower" },
+                &[
+                    ("period", self.period),
+                    ("atr_period", KELTNER_ATR_PERIOD),
+                    ("atr_multiplier", self.deviation),
+                ],
+            ),
+        }
+    }
+}
+
+impl TakeHandler for ChannelTakeHandler {
+    fn name(&self) -> &str {
+        match self.kind {
+            ChannelKind::Bollinger => "BollingerTake",
+            ChannelKind::Keltner => "KeltnerTake",
+        }
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn required_auxiliary_indicators(&self) -> Vec<AuxiliaryIndicatorSpec> {
+        vec![self.band(true), self.band(false)]
+    }
+
+    fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome> {
+        let band = match ctx.position.direction {
+            PositionDirection::Long => self.band(true),
+            PositionDirection::Short => self.band(false),
+            _ => return None,
+        };
+        let level = ctx
+            .timeframe_data
+            .auxiliary_value_at(&band.alias, ctx.index)
+            .map(f64::from)
+            .filter(|level| level.is_finite() && *level > 0.0)?;
+        let mut outcome = evaluate_take_level(ctx, level)?;
+        outcome
+            .metadata
+            .insert("band".to_string(), band.indicator_name);
+        Some(outcome)
+    }
+}
@@ -1,10 +1,13 @@
 mod take_profit_pct;
+mod take_profit_ladder;
+mod take_profit_atr;
+mod take_profit_r;
+mod channel_take;
+mod trailing_take;
 
 pub use take_profit_pct::TakeProfitPctHandler;
-
-
-
-
-
-
-
+pub use take_profit_ladder::TakeProfitLadderHandler;
+pub use take_profit_atr::TakeProfitATRHandler;
+pub use take_profit_r::TakeProfitRHandler;
+pub use channel_take::{ChannelKind, ChannelTakeHandler};
+pub use trailing_take::TrailingTakeHandler;
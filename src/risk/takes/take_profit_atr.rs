@@ -0,0 +1,75 @@
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::strategy::types::PositionDirection;
+
+use crate::risk::auxiliary::AuxiliaryIndicatorSpec;
+use crate::risk::context::TakeEvaluationContext;
+use crate::risk::parameters::{create_stop_period_parameter, StopParameterPresets};
+use crate::risk::traits::{TakeHandler, TakeOutcome};
+use crate::risk::utils::{entry_bar_index, evaluate_take_level, get_atr_value};
+
+/// Тейк на расстоянии coeff_atr·ATR от цены входа; ATR берется на баре входа,
+/// чтобы цель не сдвигалась вместе с волатильностью
+pub struct TakeProfitATRHandler {
+    pub period: f64,
+    pub coeff_atr: f64,
+    parameters: ParameterSet,
+}
+
+impl TakeProfitATRHandler {
+    pub fn new(period: f64, coeff_atr: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(create_stop_period_parameter(
+            "period",
+            period as f32,
+            "Период для расчета ATR",
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "coeff_atr",
+            coeff_atr as f32,
+            StopParameterPresets::take_atr_coefficient(),
+            "Расстояние до тейка в ATR",
+            ParameterType::Coefficient,
+        ));
+        Self {
+            period,
+            coeff_atr,
+            parameters: params,
+        }
+    }
+
+    fn level(&self, ctx: &TakeEvaluationContext<'_>) -> Option<f64> {
+        let index = ctx
+            .position
+            .entry_bar_index
+            .or_else(|| entry_bar_index(ctx.timeframe_data, ctx.position.opened_at))
+            .unwrap_or(ctx.index);
+        let atr = get_atr_value(ctx.timeframe_data, self.period, index)? as f64;
+        if !atr.is_finite() || atr <= 0.0 {
+            return None;
+        }
+        let offset = atr * self.coeff_atr;
+        match ctx.position.direction {
+            PositionDirection::Long => Some(ctx.position.entry_price + offset),
+            PositionDirection::Short => Some(ctx.position.entry_price - offset),
+            _ => None,
+        }
+    }
+}
+
+impl TakeHandler for TakeProfitATRHandler {
+    fn name(&self) -> &str {
+        "TakeProfitATR"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn required_auxiliary_indicators(&self) -> Vec<AuxiliaryIndicatorSpec> {
+        vec![AuxiliaryIndicatorSpec::atr(self.period as u32)]
+    }
+
+    fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome> {
+        evaluate_take_level(ctx, self.level(ctx)?)
+    }
+}
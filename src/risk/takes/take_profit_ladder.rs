@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::position::view::ActivePosition;
+use crate::strategy::types::{PositionDirection, PriceField, StopSignalKind};
+
+use crate::risk::context::TakeEvaluationContext;
+use crate::risk::parameters::{create_take_percentage_parameter, StopParameterPresets};
+use crate::risk::traits::{TakeHandler, TakeOutcome};
+use crate::risk::utils::get_price_at_index;
+
+/// Лестница тейк-профитов: цель k находится на k * step_pct от входа
+/// и закрывает close_pct процентов исходного объема позиции.
+/// После последней цели остаток позиции сопровождают стопы.
+pub struct TakeProfitLadderHandler {
+    pub step_pct: f64,
+    pub targets: usize,
+    pub close_pct: f64,
+    parameters: ParameterSet,
+}
+
+impl TakeProfitLadderHandler {
+    pub fn new(step_pct: f64, targets: usize, close_pct: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(create_take_percentage_parameter(
+            "step_pct",
+            step_pct as f32,
+            "Расстояние между целями в процентах",
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "targets",
+            targets as f32,
+            StopParameterPresets::ladder_targets(),
+            "Количество целей",
+            ParameterType::Custom,
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "close_pct",
+            close_pct as f32,
+            StopParameterPresets::ladder_close_percentage(),
+            "Процент исходного объема, закрываемый на каждой цели",
+            ParameterType::Threshold,
+        ));
+        Self {
+            step_pct,
+            targets: targets.max(1),
+            close_pct,
+            parameters: params,
+        }
+    }
+
+    fn close_share(&self) -> f64 {
+        (self.close_pct / 100.0).clamp(f64::EPSILON, 1.0)
+    }
+
+    /// Номер следующей цели по уже закрытой доле позиции
+    fn next_target(&self, position: &ActivePosition) -> usize {
+        let initial = position.initial_quantity.max(position.quantity);
+        if initial <= f64::EPSILON {
+            return 1;
+        }
+        let filled = (1.0 - position.quantity / initial).max(0.0);
+        (filled / self.close_share()).round() as usize + 1
+    }
+
+    fn level(&self, position: &ActivePosition, target: usize) -> Option<f64> {
+        let ratio = self.step_pct * target as f64 / 100.0;
+        match position.direction {
+            PositionDirection::Long => Some(position.entry_price * (1.0 + ratio)),
+            PositionDirection::Short => Some(position.entry_price * (1.0 - ratio)),
+            _ => None,
+        }
+    }
+
+    /// Доля текущего объема, закрываемая на цели; `None` — остаток закрывается целиком
+    fn fraction(&self, position: &ActivePosition) -> Option<f64> {
+        let initial = position.initial_quantity.max(position.quantity);
+        let fraction = self.close_share() * initial / position.quantity.max(f64::EPSILON);
+        (fraction < 1.0 - f64::EPSILON).then_some(fraction)
+    }
+}
+
+impl TakeHandler for TakeProfitLadderHandler {
+    fn name(&self) -> &str {
+        "TakeProfitLadder"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome> {
+        let target = self.next_target(ctx.position);
+        if target > self.targets {
+            return None;
+        }
+        let level = self.level(ctx.position, target)?;
+
+        let high_price = get_price_at_index(
+            ctx.timeframe_data,
+            &PriceField::High,
+            ctx.index,
+            ctx.current_price,
+        );
+
+        let low_price = get_price_at_index(
+            ctx.timeframe_data,
+            &PriceField::Low,
+            ctx.index,
+            ctx.current_price,
+        );
+
+        let triggered = match ctx.position.direction {
+            PositionDirection::Long => high_price >= level,
+            PositionDirection::Short => low_price <= level,
+            _ => false,
+        };
+
+        if triggered {
+            let mut metadata = HashMap::new();
+            metadata.insert("level".to_string(), level.to_string());
+            metadata.insert("target".to_string(), target.to_string());
+            return Some(TakeOutcome {
+                exit_price: level,
+                kind: StopSignalKind::TakeProfit,
+                fraction: self.fraction(ctx.position),
+                metadata,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::types::{Symbol, TimeFrame};
+
+    fn position(quantity: f64) -> ActivePosition {
+        let mut position = ActivePosition::new(
+            "p1",
+            Symbol::from_descriptor("TEST.TEST"),
+            TimeFrame::minutes(1),
+            PositionDirection::Long,
+            100.0,
+            4.0,
+            None,
+            None,
+        );
+        position.quantity = quantity;
+        position
+    }
+
+    #[test]
+    fn ladder_advances_targets_and_leaves_remainder_for_trailing() {
+        let handler = TakeProfitLadderHandler::new(1.0, 3, 25.0);
+        let first = position(4.0);
+        assert_eq!(handler.next_target(&first), 1);
+        assert!((handler.level(&first, 1).unwrap() - 101.0).abs() < 1e-9);
+        assert!((handler.fraction(&first).unwrap() - 0.25).abs() < 1e-9);
+
+        let third = position(2.0);
+        assert_eq!(handler.next_target(&third), 3);
+        assert!((handler.level(&third, 3).unwrap() - 103.0).abs() < 1e-9);
+        assert!((handler.fraction(&third).unwrap() - 0.5).abs() < 1e-9);
+
+        assert_eq!(handler.next_target(&position(1.0)), 4);
+
+        let halves = TakeProfitLadderHandler::new(1.0, 2, 50.0);
+        assert_eq!(halves.fraction(&position(2.0)), None);
+    }
+}
@@ -82,6 +82,7 @@ impl TakeHandler for TakeProfitPctHandler {
             return Some(TakeOutcome {
                 exit_price: level,
                 kind: StopSignalKind::TakeProfit,
+                fraction: None,
                 metadata,
             });
         }
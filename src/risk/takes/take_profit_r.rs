@@ -0,0 +1,59 @@
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::position::view::ActivePosition;
+use crate::strategy::types::PositionDirection;
+
+use crate::risk::context::TakeEvaluationContext;
+use crate::risk::parameters::StopParameterPresets;
+use crate::risk::traits::{TakeHandler, TakeOutcome};
+use crate::risk::utils::evaluate_take_level;
+
+/// Тейк на r_multiple начальных рисков: расстояние от входа до стопа,
+/// рассчитанного при открытии позиции. Без стоп-обработчика не срабатывает.
+pub struct TakeProfitRHandler {
+    pub r_multiple: f64,
+    parameters: ParameterSet,
+}
+
+impl TakeProfitRHandler {
+    pub fn new(r_multiple: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "r_multiple",
+            r_multiple as f32,
+            StopParameterPresets::r_multiple(),
+            "Цель в единицах начального риска",
+            ParameterType::Multiplier,
+        ));
+        Self {
+            r_multiple,
+            parameters: params,
+        }
+    }
+
+    fn level(&self, position: &ActivePosition) -> Option<f64> {
+        let risk = (position.entry_price - position.initial_stop?).abs();
+        if risk <= f64::EPSILON {
+            return None;
+        }
+        let offset = risk * self.r_multiple;
+        match position.direction {
+            PositionDirection::Long => Some(position.entry_price + offset),
+            PositionDirection::Short => Some(position.entry_price - offset),
+            _ => None,
+        }
+    }
+}
+
+impl TakeHandler for TakeProfitRHandler {
+    fn name(&self) -> &str {
+        "TakeProfitR"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome> {
+        evaluate_take_level(ctx, self.level(ctx.position)?)
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::indicators::types::{IndicatorParameter, ParameterSet, ParameterType};
+use crate::position::view::ActivePosition;
+use crate::strategy::types::{PositionDirection, PriceField, StopSignalKind};
+
+use crate::risk::context::TakeEvaluationContext;
+use crate::risk::parameters::StopParameterPresets;
+use crate::risk::traits::{TakeHandler, TakeOutcome};
+use crate::risk::utils::{calculate_stop_exit_price, get_price_at_index, is_stop_triggered};
+
+/// Скользящий тейк: после прибыли activation_pct фиксирует результат,
+/// когда цена откатывается на trail_pct от лучшей цены с момента входа
+pub struct TrailingTakeHandler {
+    pub activation_pct: f64,
+    pub trail_pct: f64,
+    parameters: ParameterSet,
+}
+
+impl TrailingTakeHandler {
+    pub fn new(activation_pct: f64, trail_pct: f64) -> Self {
+        let mut params = ParameterSet::new();
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "activation_pct",
+            activation_pct as f32,
+            StopParameterPresets::trailing_take_activation(),
+            "Прибыль в процентах для активации",
+            ParameterType::Threshold,
+        ));
+        params.add_parameter_unchecked(IndicatorParameter::new(
+            "trail_pct",
+            trail_pct as f32,
+            StopParameterPresets::trailing_take_distance(),
+            "Допустимый откат от лучшей цены в процентах",
+            ParameterType::Threshold,
+        ));
+        Self {
+            activation_pct,
+            trail_pct,
+            parameters: params,
+        }
+    }
+
+    fn level(&self, position: &ActivePosition) -> Option<f64> {
+        let entry = position.entry_price;
+        match position.direction {
+            PositionDirection::Long => {
+                let best = position.max_high_since_entry?;
+                (best >= entry * (1.0 + self.activation_pct / 100.0))
+                    .then(|| best * (1.0 - self.trail_pct / 100.0))
+            }
+            PositionDirection::Short => {
+                let best = position.min_low_since_entry?;
+                (best <= entry * (1.0 - self.activation_pct / 100.0))
+                    .then(|| best * (1.0 + self.trail_pct / 100.0))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl TakeHandler for TrailingTakeHandler {
+    fn name(&self) -> &str {
+        "TrailingTake"
+    }
+
+    fn parameters(&self) -> &ParameterSet {
+        &self.parameters
+    }
+
+    fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome> {
+        let level = self.level(ctx.position)?;
+        let price = |field: PriceField| {
+            get_price_at_index(ctx.timeframe_data, &field, ctx.index, ctx.current_price)
+        };
+        let direction = &ctx.position.direction;
+        if !is_stop_triggered(
+            direction,
+            price(PriceField::Low),
+            price(PriceField::High),
+            level,
+        ) {
+            return None;
+        }
+        let exit_price =
+            calculate_stop_exit_price(direction, level, price(PriceField::Open), ctx.current_price);
+
+        let mut metadata = HashMap::new();
+        metadata.insert("level".to_string(), level.to_string());
+        metadata.insert("triggered_price".to_string(), exit_price.to_string());
+        Some(TakeOutcome {
+            exit_price,
+            kind: StopSignalKind::TakeProfit,
+            fraction: None,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_model::types::{Symbol, TimeFrame};
+
+    #[test]
+    fn trailing_take_activates_after_threshold_and_trails_best_price() {
+        let handler = TrailingTakeHandler::new(2.0, 1.0);
+        let mut position = ActivePosition::new(
+            "p1",
+            Symbol::from_descriptor("TEST.TEST"),
+            TimeFrame::minutes(1),
+            PositionDirection::Long,
+            100.0,
+            1.0,
+            None,
+            None,
+        );
+        position.max_high_since_entry = Some(101.5);
+        assert_eq!(handler.level(&position), None);
+        position.max_high_since_entry = Some(110.0);
+        assert!((handler.level(&position).unwrap() - 108.9).abs() < 1e-9);
+
+        position.direction = PositionDirection::Short;
+        position.min_low_since_entry = Some(90.0);
+        assert!((handler.level(&position).unwrap() - 90.9).abs() < 1e-9);
+    }
+}
@@ -49,11 +49,22 @@ pub trait StopHandler: Send + Sync {
     fn compute_stop_level(&self, _ctx: &StopEvaluationContext<'_>) -> Option<f64> {
         None
     }
+
+    /// Выход определяется самим `evaluate`, а не пересечением общего уровня стопа
+    /// (выход по времени, подтверждение закрытием бара)
+    fn evaluates_exit(&self) -> bool {
+        false
+    }
+
+    /// Освобождает состояние, накопленное обработчиком по закрытой позиции
+    fn on_position_closed(&self, _position_id: &str) {}
 }
 
 pub struct TakeOutcome {
     pub exit_price: f64,
     pub kind: StopSignalKind,
+    /// Доля позиции для частичного закрытия; `None` — закрыть целиком
+    pub fraction: Option<f64>,
     pub metadata: HashMap<String, String>,
 }
 
@@ -68,4 +79,8 @@ pub trait TakeHandler: Send + Sync {
     }
 
     fn evaluate(&self, ctx: &TakeEvaluationContext<'_>) -> Option<TakeOutcome>;
+
+    fn required_auxiliary_indicators(&self) -> Vec<AuxiliaryIndicatorSpec> {
+        vec![]
+    }
 }
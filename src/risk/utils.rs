@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
-use crate::risk::context::StopValidationContext;
-use crate::risk::traits::StopValidationResult;
+use crate::risk::context::{StopEvaluationContext, StopValidationContext, TakeEvaluationContext};
+use crate::risk::traits::{StopOutcome, StopValidationResult, TakeOutcome};
 use crate::strategy::context::TimeframeData;
-use crate::strategy::types::{PositionDirection, PriceField, StrategyParamValue};
+use crate::strategy::types::{PositionDirection, PriceField, StopSignalKind, StrategyParamValue};
 
 // Функции извлечения параметров перенесены в parameter_extractor.rs
 // Используйте: crate::risk::extract_number, extract_string, etc.
@@ -60,6 +60,99 @@ pub fn calculate_stop_exit_price(
     }
 }
 
+/// Срабатывание стопа с уровнем `level` на текущем баре: при гэпе выход по открытию
+pub fn evaluate_stop_level(ctx: &StopEvaluationContext<'_>, level: f64) -> Option<StopOutcome> {
+    let low_price = get_price_at_index(
+        ctx.timeframe_data,
+        &PriceField::Low,
+        ctx.index,
+        ctx.current_price,
+    );
+    let high_price = get_price_at_index(
+        ctx.timeframe_data,
+        &PriceField::High,
+        ctx.index,
+        ctx.current_price,
+    );
+    if !is_stop_triggered(&ctx.position.direction, low_price, high_price, level) {
+        return None;
+    }
+    let open_price = get_price_at_index(
+        ctx.timeframe_data,
+        &PriceField::Open,
+        ctx.index,
+        ctx.current_price,
+    );
+    let exit_price = calculate_stop_exit_price(
+        &ctx.position.direction,
+        level,
+        open_price,
+        ctx.current_price,
+    );
+
+    let mut metadata = HashMap::new();
+    metadata.insert("level".to_string(), level.to_string());
+    metadata.insert("triggered_price".to_string(), exit_price.to_string());
+    Some(StopOutcome {
+        exit_price,
+        kind: StopSignalKind::StopLoss,
+        metadata,
+    })
+}
+
+/// Срабатывание тейка с уровнем `level` на текущем баре; выход по уровню
+pub fn evaluate_take_level(ctx: &TakeEvaluationContext<'_>, level: f64) -> Option<TakeOutcome> {
+    let triggered_price = match ctx.position.direction {
+        PositionDirection::Long => {
+            get_price_at_index(ctx.timeframe_data, &PriceField::High, ctx.index, ctx.current_price)
+        }
+        PositionDirection::Short => {
+            get_price_at_index(ctx.timeframe_data, &PriceField::Low, ctx.index, ctx.current_price)
+        }
+        _ => return None,
+    };
+    let triggered = match ctx.position.direction {
+        PositionDirection::Long => triggered_price >= level,
+        _ => triggered_price <= level,
+    };
+    if !triggered {
+        return None;
+    }
+    let mut metadata = HashMap::new();
+    metadata.insert("level".to_string(), level.to_string());
+    metadata.insert("triggered_price".to_string(), triggered_price.to_string());
+    Some(TakeOutcome {
+        exit_price: level,
+        kind: StopSignalKind::TakeProfit,
+        fraction: None,
+        metadata,
+    })
+}
+
+/// Значение ATR(period) на баре `index`: вспомогательный индикатор риска,
+/// а при его отсутствии — индикатор стратегии с тем же периодом
+pub fn get_atr_value(timeframe_data: &TimeframeData, period: f64, index: usize) -> Option<f32> {
+    let aux_alias = format!("aux_ATR_{}", period as u32);
+    timeframe_data
+        .auxiliary_value_at(&aux_alias, index)
+        .or_else(|| {
+            let atr_alias = format!("ATR_{}", period as u32);
+            timeframe_data.indicator_value_at(&atr_alias, index)
+        })
+}
+
+/// Индекс бара, внутри которого открыта позиция
+pub fn entry_bar_index(
+    timeframe_data: &TimeframeData,
+    opened_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Option<usize> {
+    let opened = opened_at?.timestamp_millis();
+    let timestamps = timeframe_data.timestamps_slice()?;
+    timestamps
+        .partition_point(|&timestamp| timestamp <= opened)
+        .checked_sub(1)
+}
+
 pub fn validate_indicator_before_entry(
     ctx: &StopValidationContext<'_>,
     indicator_value: f64,
@@ -3,6 +3,7 @@ use super::types::{
     IndicatorBindingSpec, PreparedCondition, StopHandlerSpec, StrategyDecision, StrategyError,
     StrategyId, StrategyMetadata, StrategyParameterMap, StrategyRuleSpec, TimeframeRequirement,
 };
+use crate::position::sizing::PositionSizerSpec;
 use crate::risk::AuxiliaryIndicatorSpec;
 
 pub trait Strategy: Send + Sync {
@@ -24,4 +25,8 @@ pub trait Strategy: Send + Sync {
     fn stop_handler_specs(&self) -> &[StopHandlerSpec] {
         &[]
     }
+
+    fn position_sizer(&self) -> Option<&PositionSizerSpec> {
+        None
+    }
 }
@@ -7,17 +7,18 @@ static BUILD_COUNTER: AtomicUsize = AtomicUsize::new(0);
 use crate::condition::factory::ConditionFactory;
 use crate::condition::types::{ConditionError, SignalStrength};
 use crate::indicators::formula::FormulaDefinition;
+use crate::position::sizing::PositionSizerSpec;
 
 use super::base::Strategy;
 use super::context::StrategyContext;
 use super::types::{
     ConditionBindingSpec, ConditionDeclarativeSpec, ConditionEvaluation, ConditionInputSpec,
     ConditionOperator, DataSeriesSource, IndicatorBindingSpec, IndicatorSourceSpec,
-    PositionDirection, PreparedCondition, PreparedStopHandler, PreparedTakeHandler, PriceField,
-    RuleLogic, StopHandlerSpec, StopSignal, StrategyDecision, StrategyDefinition, StrategyError,
-    StrategyId, StrategyMetadata, StrategyParamValue, StrategyParameterMap, StrategyRuleSpec,
-    StrategySignal, StrategySignalType, StrategyUserInput, TimeframeRequirement,
-    UserFormulaMetadata,
+    OrderPriceSource, OrderRequest, OrderSpec, OrderType, PositionDirection, PreparedCondition,
+    PreparedStopHandler, PreparedTakeHandler, PriceField, RuleLogic, StopHandlerSpec, StopSignal,
+    StrategyDecision, StrategyDefinition, StrategyError, StrategyId, StrategyMetadata,
+    StrategyParamValue, StrategyParameterMap, StrategyRuleSpec, StrategySignal, StrategySignalType,
+    StrategyUserInput, TimeframeRequirement, UserFormulaMetadata,
 };
 use crate::risk::{
     get_auxiliary_specs_from_handler_spec, AuxiliaryIndicatorSpec, StopEvaluationContext,
@@ -247,14 +248,24 @@ impl DynamicStrategy {
             tags: rule.tags.clone(),
             position_group: None,
             target_entry_ids: Vec::with_capacity(rule.target_entry_ids.len()),
+            order: None,
+            close_fraction: None,
         };
         match signal.signal_type {
             StrategySignalType::Entry => {
                 signal.position_group = Some(rule.position_group_key());
                 signal.entry_rule_id = Some(rule.id.clone());
+                if !rule.order.is_market() {
+                    // Без цены заявку выставить нельзя — сигнал пропускается
+                    match self.resolve_order_request(context, &rule.order, &signal.timeframe)? {
+                        Some(request) => signal.order = Some(request),
+                        None => return Ok(None),
+                    }
+                }
             }
             StrategySignalType::Exit => {
                 signal.target_entry_ids = rule.target_entry_ids.clone();
+                signal.close_fraction = rule.close_fraction;
             }
             StrategySignalType::Custom(_) => {}
         }
@@ -266,6 +277,40 @@ impl DynamicStrategy {
         Ok(Some(signal))
     }
 
+    fn resolve_order_request(
+        &self,
+        context: &StrategyContext,
+        spec: &OrderSpec,
+        timeframe: &crate::data_model::types::TimeFrame,
+    ) -> Result<Option<OrderRequest>, StrategyError> {
+        let data = context.timeframe(timeframe)?;
+        let index = data.index();
+        let base = match &spec.price_source {
+            Some(OrderPriceSource::Indicator(alias)) => data.indicator_value_at(alias, index),
+            Some(OrderPriceSource::Price(field)) => data
+                .price_series_slice(field)
+                .and_then(|series| series.get(index).copied()),
+            None => data
+                .price_series_slice(&PriceField::Close)
+                .and_then(|series| series.get(index).copied()),
+        };
+        let Some(base) = base
+            .map(f64::from)
+            .filter(|value| value.is_finite() && *value > 0.0)
+        else {
+            return Ok(None);
+        };
+        let price = base * (1.0 + spec.offset_pct / 100.0);
+        let limit_price = (spec.order_type == OrderType::StopLimit)
+            .then(|| price * (1.0 + spec.limit_offset_pct / 100.0));
+        Ok(Some(OrderRequest {
+            order_type: spec.order_type,
+            price,
+            limit_price,
+            time_in_force: spec.time_in_force,
+        }))
+    }
+
     fn determine_strength(
         &self,
         average_score: f32,
@@ -356,6 +401,8 @@ impl DynamicStrategy {
                         tags: handler.tags.clone(),
                         position_group: None,
                         target_entry_ids: Vec::with_capacity(handler.target_entry_ids.len() + 1),
+                        order: None,
+                        close_fraction: outcome.fraction,
                     };
                     if let Some(group) = position.position_group.as_ref() {
                         signal.target_entry_ids.push(group.clone());
@@ -494,7 +541,8 @@ impl Strategy for DynamicStrategy {
 
         let mut has_exit_rule_signals = false;
 
-        if has_active_positions {
+        // Правила выхода также снимают отложенные заявки на вход
+        if has_active_positions || context.pending_orders() > 0 {
             for optimized_rule in &self.exit_rules {
                 if let Some(signal) = self.evaluate_rule(
                     &optimized_rule.rule,
@@ -546,6 +594,10 @@ impl Strategy for DynamicStrategy {
     fn stop_handler_specs(&self) -> &[StopHandlerSpec] {
         &self.stop_handler_specs
     }
+
+    fn position_sizer(&self) -> Option<&PositionSizerSpec> {
+        self.definition.position_sizer.as_ref()
+    }
 }
 
 pub struct StrategyBuilder {
@@ -743,6 +795,11 @@ impl StrategyBuilder {
             }
             let instance = TakeHandlerFactory::create(&handler.handler_name, &normalized_params)
                 .map_err(|err| map_take_error(&handler.handler_name, err))?;
+            for spec in instance.required_auxiliary_indicators() {
+                if seen_auxiliary_aliases.insert(spec.alias.clone()) {
+                    auxiliary_specs_collector.push(spec);
+                }
+            }
             prepared_take_handlers.push(PreparedTakeHandler {
                 id: handler.id.clone(),
                 name: handler.name.clone(),
@@ -761,13 +818,16 @@ impl StrategyBuilder {
             parameters.insert(key.clone(), value.clone());
         }
 
-        // auxiliary_specs уже собраны при обработке stop_handlers с учетом parameter_overrides
+        // auxiliary_specs уже собраны при обработке stop и take handlers с учетом parameter_overrides
         let auxiliary_specs = auxiliary_specs_collector;
 
         let build_number = BUILD_COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
 
         let mut final_definition = self.definition.clone();
         final_definition.indicator_bindings = indicator_bindings.clone();
+        final_definition.position_sizer = final_definition
+            .position_sizer
+            .map(|sizer| sizer.with_parameters(&parameter_overrides_clone));
 
         for binding in &mut final_definition.condition_bindings {
             let condition_prefix = format!("{}_", binding.id);
@@ -975,6 +1035,8 @@ impl StrategyBuilder {
                 tags: action.tags.clone(),
                 position_group: None,
                 target_entry_ids: Vec::new(),
+                order: action.order.clone(),
+                close_fraction: action.close_fraction,
             };
             match action.signal {
                 StrategySignalType::Entry => entry_rules.push(rule),
@@ -1003,6 +1065,7 @@ impl StrategyBuilder {
             timeframe_requirements,
             defaults,
             optimizer_hints: BTreeMap::new(),
+            position_sizer: None,
         };
         Ok(Self::new(definition))
     }
@@ -395,6 +395,7 @@ pub struct StrategyContext {
     pub metadata: HashMap<String, String>,
     pub runtime_parameters: StrategyParameterMap,
     active_positions: HashMap<String, ActivePosition>,
+    pending_orders: usize,
 }
 
 impl StrategyContext {
@@ -406,6 +407,7 @@ impl StrategyContext {
             metadata: HashMap::with_capacity(8),
             runtime_parameters: HashMap::with_capacity(16),
             active_positions: HashMap::with_capacity(8),
+            pending_orders: 0,
         }
     }
 
@@ -426,6 +428,7 @@ impl StrategyContext {
             metadata: HashMap::with_capacity(8),
             runtime_parameters: HashMap::with_capacity(16),
             active_positions: HashMap::with_capacity(timeframes_len.max(8)),
+            pending_orders: 0,
         }
     }
 
@@ -451,6 +454,7 @@ impl StrategyContext {
             metadata: HashMap::with_capacity(8),
             runtime_parameters: HashMap::with_capacity(16),
             active_positions: HashMap::with_capacity(timeframes_len.max(8)),
+            pending_orders: 0,
         }
     }
 
@@ -702,6 +706,15 @@ impl StrategyContext {
             .collect::<HashMap<_, _>>();
     }
 
+    /// Количество отложенных заявок, ожидающих исполнения
+    pub fn pending_orders(&self) -> usize {
+        self.pending_orders
+    }
+
+    pub fn set_pending_orders(&mut self, count: usize) {
+        self.pending_orders = count;
+    }
+
     pub fn upsert_active_position(&mut self, position: ActivePosition) {
         self.active_positions.insert(position.id.clone(), position);
     }
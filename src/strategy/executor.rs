@@ -748,14 +748,12 @@ impl BacktestExecutor {
             return Ok(());
         }
 
-        // Получаем первый доступный таймфрейм для OHLC данных
-        // (служебные индикаторы вычисляются на базовом таймфрейме)
+        // Служебные индикаторы вычисляются на базовом таймфрейме
         let timeframe = self
             .feed
-            .frames
-            .keys()
-            .next()
-            .cloned()
+            .primary_timeframe
+            .clone()
+            .or_else(|| self.feed.frames.keys().next().cloned())
             .ok_or_else(|| StrategyExecutionError::Feed("No frames available".to_string()))?;
 
         let frame = self.feed.frames.get(&timeframe).ok_or_else(|| {
@@ -1062,6 +1060,7 @@ impl BacktestExecutor {
     fn update_trailing_stops(&mut self) {
         self.risk_manager.sync_with_positions(&self.context);
         self.risk_manager.on_new_bar(&self.context);
+        self.risk_manager.publish_state(&mut self.context);
     }
 
     fn process_immediate_stop_checks(&mut self) -> Result<(), StrategyExecutionError> {
@@ -1530,6 +1529,8 @@ mod tests {
                     tags: Vec::new(),
                     position_group: Some("enter".to_string()),
                     target_entry_ids: Vec::new(),
+                    order: None,
+                    close_fraction: None,
                 };
                 decision.entries.push(signal);
             } else if series_len > 0 && idx + 1 == series_len {
@@ -1544,6 +1545,8 @@ mod tests {
                     tags: Vec::new(),
                     position_group: None,
                     target_entry_ids: vec!["enter".to_string()],
+                    order: None,
+                    close_fraction: None,
                 };
                 decision.exits.push(signal);
             }
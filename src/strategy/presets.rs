@@ -4,8 +4,8 @@ use crate::data_model::types::TimeFrame;
 
 use super::types::{
     ConditionBindingSpec, ConditionDeclarativeSpec, ConditionInputSpec, ConditionOperator,
-    DataSeriesSource, IndicatorBindingSpec, IndicatorSourceSpec, PositionDirection, PriceField,
-    RuleLogic, StopHandlerSpec, StrategyCategory, StrategyDefinition, StrategyMetadata,
+    DataSeriesSource, IndicatorBindingSpec, IndicatorSourceSpec, OrderSpec, PositionDirection,
+    PriceField, RuleLogic, StopHandlerSpec, StrategyCategory, StrategyDefinition, StrategyMetadata,
     StrategyParamValue, StrategyParameterMap, StrategyParameterSpec, StrategyRuleSpec,
     StrategySignalType, TakeHandlerSpec, UserFormulaMetadata,
 };
@@ -98,6 +98,8 @@ fn sma_crossover_long_definition() -> StrategyDefinition {
         tags: vec!["entry".to_string()],
         position_group: None,
         target_entry_ids: Vec::new(),
+        order: OrderSpec::market(),
+        close_fraction: None,
     }];
 
     let exit_rules = vec![StrategyRuleSpec {
@@ -111,6 +113,8 @@ fn sma_crossover_long_definition() -> StrategyDefinition {
         tags: vec!["exit".to_string()],
         position_group: None,
         target_entry_ids: vec!["enter_long".to_string()],
+        order: OrderSpec::market(),
+        close_fraction: None,
     }];
 
     StrategyDefinition::new(
@@ -185,6 +189,8 @@ fn sinewma_rising_trend_definition() -> StrategyDefinition {
         tags: vec!["auto-generated".to_string()],
         position_group: None,
         target_entry_ids: Vec::new(),
+        order: OrderSpec::market(),
+        close_fraction: None,
     }];
 
     let exit_rules = vec![];
@@ -325,6 +331,8 @@ fn amma_rising_trend_definition() -> StrategyDefinition {
         tags: vec!["auto-generated".to_string()],
         position_group: None,
         target_entry_ids: Vec::new(),
+        order: OrderSpec::market(),
+        close_fraction: None,
     }];
 
     let exit_rules = vec![];
@@ -464,6 +472,8 @@ fn vtrand_falling_trend_definition() -> StrategyDefinition {
         tags: vec!["auto-generated".to_string()],
         position_group: None,
         target_entry_ids: Vec::new(),
+        order: OrderSpec::market(),
+        close_fraction: None,
     }];
 
     let exit_rules = vec![];
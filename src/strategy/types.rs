@@ -6,6 +6,8 @@ use crate::condition::types::{
     ConditionCategory, ConditionConfig, ConditionError, ConditionResultData, SignalStrength,
 };
 use crate::data_model::types::TimeFrame;
+use crate::position::sizing::PositionSizerSpec;
+use crate::risk::guards::GuardKind;
 use crate::risk::{StopHandler, TakeHandler};
 use serde::{Deserialize, Serialize};
 
@@ -23,7 +25,7 @@ pub enum StrategyCategory {
     Custom(String),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum StrategyParamValue {
     Number(f64),
     Integer(i64),
@@ -579,6 +581,10 @@ pub struct StrategyRuleSpec {
     pub tags: Vec<String>,
     pub position_group: Option<String>,
     pub target_entry_ids: Vec<String>,
+    /// Заявка, выставляемая по сигналу входа
+    pub order: OrderSpec,
+    /// Доля позиции, закрываемая правилом выхода
+    pub close_fraction: Option<f64>,
 }
 
 impl StrategyRuleSpec {
@@ -589,6 +595,98 @@ impl StrategyRuleSpec {
     }
 }
 
+/// Источник цены для отложенной заявки
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OrderPriceSource {
+    Price(PriceField),
+    /// Значение индикатора по alias (например, MAXFOR или граница канала)
+    Indicator(String),
+}
+
+/// Тип заявки на вход
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OrderType {
+    #[default]
+    Market,
+    Limit,
+    Stop,
+    StopLimit,
+}
+
+/// Срок действия отложенной заявки
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Действует N баров после бара сигнала
+    Bars(usize),
+    /// Действует до конца торгового дня бара сигнала
+    Session,
+    /// Действует до отмены
+    Gtc,
+}
+
+impl Default for TimeInForce {
+    fn default() -> Self {
+        Self::Bars(1)
+    }
+}
+
+/// Описание заявки в правиле стратегии
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct OrderSpec {
+    pub order_type: OrderType,
+    /// Источник цены; по умолчанию Close бара сигнала
+    pub price_source: Option<OrderPriceSource>,
+    /// Сдвиг цены от источника, в процентах
+    pub offset_pct: f64,
+    /// Для StopLimit: цена лимита относительно цены активации, в процентах
+    pub limit_offset_pct: f64,
+    pub time_in_force: TimeInForce,
+}
+
+impl OrderSpec {
+    pub fn market() -> Self {
+        Self::default()
+    }
+
+    pub fn pending(order_type: OrderType, price_source: OrderPriceSource) -> Self {
+        Self {
+            order_type,
+            price_source: Some(price_source),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_offset_pct(mut self, offset_pct: f64) -> Self {
+        self.offset_pct = offset_pct;
+        self
+    }
+
+    pub fn with_limit_offset_pct(mut self, limit_offset_pct: f64) -> Self {
+        self.limit_offset_pct = limit_offset_pct;
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn is_market(&self) -> bool {
+        self.order_type == OrderType::Market
+    }
+}
+
+/// Отложенная заявка с ценами, рассчитанными на баре сигнала
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderRequest {
+    pub order_type: OrderType,
+    /// Цена лимита для Limit, цена активации для Stop/StopLimit
+    pub price: f64,
+    /// Цена лимита для StopLimit
+    pub limit_price: Option<f64>,
+    pub time_in_force: TimeInForce,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TimeframeRequirement {
     pub alias: String,
@@ -638,6 +736,8 @@ pub struct StrategyDefinition {
     pub timeframe_requirements: Vec<TimeframeRequirement>,
     pub defaults: StrategyParameterMap,
     pub optimizer_hints: BTreeMap<String, StrategyParamValue>,
+    /// Расчет объема входа; `None` — по настройкам бэктеста
+    pub position_sizer: Option<PositionSizerSpec>,
 }
 
 impl StrategyDefinition {
@@ -669,9 +769,21 @@ impl StrategyDefinition {
             timeframe_requirements,
             defaults,
             optimizer_hints,
+            position_sizer: None,
         }
     }
 
+    /// Задает сайзер и добавляет его параметры в оптимизируемые
+    pub fn with_position_sizer(mut self, sizer: PositionSizerSpec) -> Self {
+        for spec in sizer.parameter_specs() {
+            if !self.parameters.iter().any(|param| param.name == spec.name) {
+                self.parameters.push(spec);
+            }
+        }
+        self.position_sizer = Some(sizer);
+        self
+    }
+
     pub fn all_timeframes(&self) -> HashSet<TimeFrame> {
         let mut set = HashSet::new();
         for req in &self.timeframe_requirements {
@@ -718,10 +830,18 @@ pub enum StopSignalKind {
     StopLoss,
     TakeProfit,
     Trailing,
+    /// Принудительное закрытие при нехватке поддерживающей маржи
+    MarginCall,
+    /// Закрытие позиций перед окончанием торгового дня или выходными
+    SessionClose,
+    /// Закрытие позиций по портфельному ограничению риска
+    RiskGuard(GuardKind),
+    /// Выход по истечении заданного числа баров в сделке
+    TimeExit,
     Custom(String),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PositionDirection {
     Long,
     Short,
@@ -741,6 +861,10 @@ pub struct StrategySignal {
     pub tags: Vec<String>,
     pub position_group: Option<String>,
     pub target_entry_ids: Vec<String>,
+    /// Отложенная заявка; `None` — рыночное исполнение
+    pub order: Option<OrderRequest>,
+    /// Доля позиции, закрываемая выходом; `None` — позиция закрывается целиком
+    pub close_fraction: Option<f64>,
 }
 
 #[derive(Clone, Debug)]
@@ -846,6 +970,9 @@ pub struct UserActionStep {
     pub direction: PositionDirection,
     pub quantity: Option<f64>,
     pub tags: Vec<String>,
+    pub order: OrderSpec,
+    /// Доля позиции для частичного выхода
+    pub close_fraction: Option<f64>,
 }
 
 /// Полный пользовательский ввод для создания стратегии
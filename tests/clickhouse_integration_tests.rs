@@ -156,6 +156,10 @@ mod clickhouse_tests {
             code: "TESTBTC".to_string(),
             name: "Test Bitcoin".to_string(),
             exchange: "TEST_EXCHANGE".to_string(),
+            asset_class: "crypto".to_string(),
+            tick_size: 0.01,
+            lot_size: 0.001,
+            contract_multiplier: 1.0,
         };
 
         let upsert_result = connector.upsert_symbol_info(&symbol_info).await;